@@ -0,0 +1,151 @@
+//! Library entry points: non-interactive scaffolding that reports [`Event`]s while it runs.
+//!
+//! The human-readable log is still printed to stdout; callers that show their own progress can
+//! ignore it.
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use crate::config::NetworkConfig;
+use crate::net;
+use crate::progress::{self, Event};
+use crate::{normalize_repo_url, run_scaffold, BootstrapMode, ScaffoldOptions};
+
+/// What to scaffold. Runs as with `--yes`: defaults are used instead of prompts.
+#[derive(Debug, Clone)]
+pub struct ScaffoldRequest {
+    /// Template repository URL (HTTPS, SSH or SCP-like).
+    pub repo_url: String,
+    pub name: String,
+    /// Name the template uses, replaced by `name` in all case variants (default `acme-app`).
+    pub template_base: String,
+    /// Template variables, as with `--var`.
+    pub vars: Vec<(String, String)>,
+    /// Directory for the new project; defaults to `./<name>`.
+    pub output_dir: Option<PathBuf>,
+    /// Merge into this existing directory instead, as with `--into`.
+    pub into_dir: Option<PathBuf>,
+    /// Exact template commit, as with `--commit`.
+    pub commit: Option<String>,
+    pub dry_run: bool,
+    /// Same output on every run and machine, as with `--reproducible`.
+    pub reproducible: bool,
+    /// Run the template's bootstrap commands (e.g. `npm install`).
+    pub bootstrap: bool,
+    /// Proxy for registry fetches and git; `HTTPS_PROXY`/`HTTP_PROXY` otherwise.
+    pub proxy: Option<String>,
+    /// Extra PEM CA certificates to trust (`LISCAF_CA_BUNDLE` takes precedence).
+    pub ca_bundle: Option<PathBuf>,
+}
+
+impl ScaffoldRequest {
+    pub fn new(repo_url: impl Into<String>, name: impl Into<String>) -> Self {
+        ScaffoldRequest {
+            repo_url: repo_url.into(),
+            name: name.into(),
+            template_base: "acme-app".to_string(),
+            vars: Vec::new(),
+            output_dir: None,
+            into_dir: None,
+            commit: None,
+            dry_run: false,
+            reproducible: false,
+            bootstrap: false,
+            proxy: None,
+            ca_bundle: None,
+        }
+    }
+}
+
+/// Scaffolds on the current thread, passing each event to `on_event`. Returns the project
+/// directory, or `None` for dry runs.
+pub fn scaffold(
+    request: &ScaffoldRequest,
+    on_event: impl FnMut(&Event) + 'static,
+) -> anyhow::Result<Option<PathBuf>> {
+    let network = NetworkConfig {
+        ca_bundle: request.ca_bundle.clone(),
+        ..Default::default()
+    };
+    let options = ScaffoldOptions {
+        dry_run: request.dry_run,
+        assume_yes: true,
+        into_dir: request.into_dir.clone(),
+        output_dir: request.output_dir.clone(),
+        workspace_aware: true,
+        vars: request.vars.clone(),
+        commit: request.commit.clone(),
+        reproducible: request.reproducible,
+        bootstrap: if request.bootstrap {
+            BootstrapMode::Always
+        } else {
+            BootstrapMode::Never
+        },
+        network: Some(Arc::new(net::Settings::new(request.proxy.as_deref(), &network)?)),
+        ..Default::default()
+    };
+    let repo_url = normalize_repo_url(&request.repo_url);
+    progress::with_sink(on_event, || {
+        run_scaffold(&repo_url, &request.name, &request.template_base, &options)
+    })
+}
+
+/// Scaffolds on a background thread. Events arrive on [`ScaffoldTask::events`] as they happen;
+/// awaiting the task yields the result. The task needs no particular async runtime.
+pub fn scaffold_async(request: ScaffoldRequest) -> ScaffoldTask {
+    let (sender, events) = mpsc::channel();
+    let shared = Arc::new(Mutex::new(Shared::default()));
+    let thread_shared = Arc::clone(&shared);
+    std::thread::spawn(move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            scaffold(&request, move |event| {
+                let _ = sender.send(event.clone());
+            })
+        }))
+        .unwrap_or_else(|_| Err(anyhow::anyhow!("scaffolding panicked")));
+        let mut shared = thread_shared.lock().unwrap_or_else(|e| e.into_inner());
+        shared.result = Some(result);
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    });
+    ScaffoldTask { events, shared }
+}
+
+/// A scaffold running in the background; see [`scaffold_async`].
+pub struct ScaffoldTask {
+    events: Receiver<Event>,
+    shared: Arc<Mutex<Shared>>,
+}
+
+#[derive(Default)]
+struct Shared {
+    result: Option<anyhow::Result<Option<PathBuf>>>,
+    waker: Option<Waker>,
+}
+
+impl ScaffoldTask {
+    /// Progress events; the channel closes when the scaffold ends.
+    pub fn events(&self) -> &Receiver<Event> {
+        &self.events
+    }
+}
+
+impl Future for ScaffoldTask {
+    type Output = anyhow::Result<Option<PathBuf>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+        match shared.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -0,0 +1,307 @@
+//! `--archive out.tar.gz|out.zip`: the rendered project is written as an archive instead of a
+//! directory, e.g. to hand a starter bundle to someone or attach it to a ticket. Entries sit below
+//! a top-level directory named after the project, as in release tarballs, and keep their modes;
+//! symlinks stay symlinks. With `--no-git` the archive holds no `.git`.
+//!
+//! tar is written here: ustar with PAX headers for long paths, gzip-compressed. zip entries are
+//! deflated and written with [`crate::zip`] (no Zip64, so at most 65535 entries and 4 GiB).
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use walkdir::WalkDir;
+
+use crate::zip;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    TarGz,
+    Zip,
+}
+
+impl Format {
+    pub fn of(path: &Path) -> Option<Format> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Format::TarGz)
+        } else if name.ends_with(".zip") {
+            Some(Format::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// Value parser for `--archive`.
+pub fn parse_path(raw: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(raw);
+    match Format::of(&path) {
+        Some(_) => Ok(path),
+        None => Err(format!("'{}' is not a .tar.gz, .tgz or .zip file", raw)),
+    }
+}
+
+struct Item {
+    /// Path inside the archive, with `/` separators.
+    name: String,
+    kind: Kind,
+    mode: u32,
+    mtime: DateTime<Utc>,
+}
+
+enum Kind {
+    Dir,
+    File(PathBuf),
+    Symlink(String),
+}
+
+/// Writes everything below `root` to `out` under the directory `top`; `mtime` replaces the
+/// files' own times (`--reproducible`). Returns the number of entries.
+pub fn write(root: &Path, top: &str, out: &Path, mtime: Option<DateTime<Utc>>) -> anyhow::Result<usize> {
+    let format = Format::of(out)
+        .ok_or_else(|| anyhow::anyhow!("Unsupported archive type: {}", out.display()))?;
+    let items = collect(root, top, mtime)?;
+    if let Some(parent) = out.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let file = BufWriter::new(File::create(out)?);
+    let written = match format {
+        Format::TarGz => write_tar_gz(file, &items),
+        Format::Zip => write_zip(file, &items),
+    };
+    if let Err(e) = written {
+        let _ = fs::remove_file(out);
+        return Err(anyhow::anyhow!("Failed to write {}: {}", out.display(), e));
+    }
+    Ok(items.len())
+}
+
+fn collect(root: &Path, top: &str, mtime: Option<DateTime<Utc>>) -> anyhow::Result<Vec<Item>> {
+    let mut items = Vec::new();
+    for entry in WalkDir::new(root).sort_by_file_name() {
+        let entry = entry?;
+        let rel = entry.path().strip_prefix(root)?;
+        let mut name = top.to_string();
+        for component in rel.components() {
+            name.push('/');
+            name.push_str(&component.as_os_str().to_string_lossy());
+        }
+        let metadata = entry.path().symlink_metadata()?;
+        let file_type = metadata.file_type();
+        let kind = if file_type.is_symlink() {
+            Kind::Symlink(fs::read_link(entry.path())?.to_string_lossy().replace('\\', "/"))
+        } else if file_type.is_dir() {
+            Kind::Dir
+        } else {
+            Kind::File(entry.path().to_path_buf())
+        };
+        let modified = metadata.modified().map(DateTime::<Utc>::from).unwrap_or_else(|_| Utc::now());
+        items.push(Item {
+            name,
+            mode: mode_of(&metadata, &kind),
+            kind,
+            mtime: mtime.unwrap_or(modified),
+        });
+    }
+    Ok(items)
+}
+
+#[cfg(unix)]
+fn mode_of(metadata: &fs::Metadata, _kind: &Kind) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o7777
+}
+
+#[cfg(not(unix))]
+fn mode_of(_metadata: &fs::Metadata, kind: &Kind) -> u32 {
+    match kind {
+        Kind::File(_) => crate::permissions::mode_for(false, false),
+        _ => 0o777,
+    }
+}
+
+// --- tar ---
+
+const BLOCK: usize = 512;
+
+fn write_tar_gz(out: impl Write, items: &[Item]) -> io::Result<()> {
+    let mut gz = GzEncoder::new(out, Compression::default());
+    for item in items {
+        let (type_flag, link, data) = match &item.kind {
+            Kind::Dir => (b'5', "", Vec::new()),
+            Kind::Symlink(target) => (b'2', target.as_str(), Vec::new()),
+            Kind::File(path) => (b'0', "", fs::read(path)?),
+        };
+        let name = match item.kind {
+            Kind::Dir => format!("{}/", item.name),
+            _ => item.name.clone(),
+        };
+        let mut pax = String::new();
+        if name.len() > 100 {
+            pax.push_str(&pax_record("path", &name));
+        }
+        if link.len() > 100 {
+            pax.push_str(&pax_record("linkpath", link));
+        }
+        if !pax.is_empty() {
+            let header = tar_header("././@PaxHeader", b'x', pax.len() as u64, 0o644, item.mtime, "");
+            gz.write_all(&header)?;
+            write_padded(&mut gz, pax.as_bytes())?;
+        }
+        let header = tar_header(&name, type_flag, data.len() as u64, item.mode, item.mtime, link);
+        gz.write_all(&header)?;
+        write_padded(&mut gz, &data)?;
+    }
+    gz.write_all(&[0; BLOCK * 2])?;
+    gz.finish()?.flush()
+}
+
+/// `"<length> <key>=<value>\n"`, where the length counts the whole record including itself.
+fn pax_record(key: &str, value: &str) -> String {
+    let body = format!(" {}={}\n", key, value);
+    let mut len = body.len() + 1;
+    while (len.to_string().len() + body.len()) != len {
+        len += 1;
+    }
+    format!("{}{}", len, body)
+}
+
+fn tar_header(name: &str, type_flag: u8, size: u64, mode: u32, mtime: DateTime<Utc>, link: &str) -> [u8; BLOCK] {
+    let mut header = [0u8; BLOCK];
+    let put = |header: &mut [u8; BLOCK], at: usize, len: usize, value: &[u8]| {
+        let n = value.len().min(len);
+        header[at..at + n].copy_from_slice(&value[..n]);
+    };
+    let octal = |value: u64, width: usize| format!("{:0w$o}\0", value, w = width - 1).into_bytes();
+    // Long names went into a PAX header; ustar's prefix field holds the rest of shorter ones
+    let (prefix, short) = match name.len() {
+        0..=100 => ("", name),
+        _ => split_ustar(name),
+    };
+    put(&mut header, 0, 100, short.as_bytes());
+    put(&mut header, 100, 8, &octal(u64::from(mode), 8));
+    put(&mut header, 108, 8, &octal(0, 8));
+    put(&mut header, 116, 8, &octal(0, 8));
+    put(&mut header, 124, 12, &octal(size, 12));
+    put(&mut header, 136, 12, &octal(mtime.timestamp().max(0) as u64, 12));
+    header[148..156].fill(b' ');
+    header[156] = type_flag;
+    put(&mut header, 157, 100, link.as_bytes());
+    put(&mut header, 257, 6, b"ustar\0");
+    put(&mut header, 263, 2, b"00");
+    put(&mut header, 345, 155, prefix.as_bytes());
+    let checksum: u32 = header.iter().map(|&b| u32::from(b)).sum();
+    put(&mut header, 148, 8, format!("{:06o}\0 ", checksum).as_bytes());
+    header
+}
+
+/// Splits `name` at a `/` into a prefix of at most 155 and a name of at most 100 bytes, as far
+/// as possible (readers prefer the PAX path anyway).
+fn split_ustar(name: &str) -> (&str, &str) {
+    name.char_indices()
+        .filter(|&(i, c)| c == '/' && i <= 155 && name.len() - i - 1 <= 100)
+        .map(|(i, _)| (&name[..i], &name[i + 1..]))
+        .next()
+        .unwrap_or(("", name))
+}
+
+fn write_padded(out: &mut impl Write, data: &[u8]) -> io::Result<()> {
+    out.write_all(data)?;
+    let rest = data.len() % BLOCK;
+    if rest != 0 {
+        out.write_all(&[0; BLOCK][..BLOCK - rest])?;
+    }
+    Ok(())
+}
+
+// --- zip ---
+
+/// Version 2.0, made by a Unix host so readers apply the modes in the external attributes.
+const ZIP_MADE_BY: u16 = (3 << 8) | 20;
+
+fn write_zip(out: impl Write, items: &[Item]) -> io::Result<()> {
+    let mut writer = zip::Writer::new(out);
+    for item in items {
+        let (name, data, file_type) = match &item.kind {
+            Kind::Dir => (format!("{}/", item.name), Vec::new(), 0o040000),
+            Kind::Symlink(target) => (item.name.clone(), target.clone().into_bytes(), 0o120000),
+            Kind::File(path) => (item.name.clone(), fs::read(path)?, 0o100000),
+        };
+        let deflate = !matches!(item.kind, Kind::Symlink(_));
+        let attributes = (file_type | item.mode) << 16;
+        writer.add(&zip::Entry::new(&name, &data, deflate, dos_time(item.mtime), ZIP_MADE_BY, attributes)?)?;
+    }
+    writer.finish(&[])?;
+    Ok(())
+}
+
+/// MS-DOS time and date fields, in UTC so that reproducible archives do not depend on the time
+/// zone; zip cannot express anything before 1980.
+fn dos_time(time: DateTime<Utc>) -> (u16, u16) {
+    if time.year() < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    let dos_time = (time.hour() << 11) | (time.minute() << 5) | (time.second() / 2);
+    let dos_date = ((time.year() as u32 - 1980) << 9) | (time.month() << 5) | time.day();
+    (dos_time as u16, dos_date as u16)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+    use std::process::Command;
+
+    use super::*;
+
+    #[test]
+    fn tar_archives_round_trip_through_tar() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("zeta");
+        let long_dir = format!("src/{}/{}", "nested".repeat(12), "module".repeat(10));
+        fs::create_dir_all(root.join(&long_dir)).unwrap();
+        fs::create_dir_all(root.join("empty")).unwrap();
+        fs::write(root.join(&long_dir).join("handler.rs"), "fn handle() {}\n").unwrap();
+        fs::write(root.join("run.sh"), "#!/bin/sh\n").unwrap();
+        fs::set_permissions(root.join("run.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+        fs::write(root.join("README.md"), "# zeta\n").unwrap();
+        fs::set_permissions(root.join("README.md"), fs::Permissions::from_mode(0o644)).unwrap();
+        std::os::unix::fs::symlink("README.md", root.join("README")).unwrap();
+
+        let out = dir.path().join("zeta.tar.gz");
+        let entries = write(&root, "zeta", &out, None).unwrap();
+        let listing = Command::new("tar").arg("-tzf").arg(&out).output().unwrap();
+        assert!(listing.status.success(), "{}", String::from_utf8_lossy(&listing.stderr));
+        let listing = String::from_utf8(listing.stdout).unwrap();
+        let names: Vec<&str> = listing.lines().collect();
+        assert_eq!(names.len(), entries);
+        let long_name = format!("zeta/{}/handler.rs", long_dir);
+        assert!(long_name.len() > 100);
+        for name in ["zeta/", "zeta/empty/", "zeta/run.sh", "zeta/README", long_name.as_str()] {
+            assert!(names.contains(&name), "{} missing from {:?}", name, names);
+        }
+
+        let unpacked = dir.path().join("unpacked");
+        fs::create_dir_all(&unpacked).unwrap();
+        let status = Command::new("tar").arg("-xzf").arg(&out).arg("-C").arg(&unpacked).status().unwrap();
+        assert!(status.success());
+        let unpacked = unpacked.join("zeta");
+        let mode = |rel: &str| fs::metadata(unpacked.join(rel)).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode("run.sh"), 0o755);
+        assert_eq!(mode("README.md"), 0o644);
+        assert_eq!(fs::read_to_string(unpacked.join(&long_dir).join("handler.rs")).unwrap(), "fn handle() {}\n");
+        assert_eq!(fs::read_link(unpacked.join("README")).unwrap(), Path::new("README.md"));
+        assert_eq!(fs::read_dir(unpacked.join("empty")).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn pax_record_lengths_count_themselves() {
+        for value in ["a", &"x".repeat(93), &"x".repeat(94), &"x".repeat(990)] {
+            let record = pax_record("path", value);
+            let (len, _) = record.split_once(' ').unwrap();
+            assert_eq!(len.parse::<usize>().unwrap(), record.len(), "{:?}", record);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! `liscaf batch <plan.toml>`: scaffolds many projects in one run and reports the outcome of
+//! each. Runs are non-interactive; a failing project does not stop the others.
+//!
+//! ```toml
+//! [defaults]
+//! template = "https://github.com/acme/rust-api"
+//!
+//! [[projects]]
+//! name = "payments-service"
+//! destination = "services/payments"
+//! variables = { database = "postgres" }
+//! ```
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{normalize_repo_url, run_scaffold, ScaffoldOptions};
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+struct Plan {
+    defaults: PlanDefaults,
+    projects: Vec<PlanProject>,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+struct PlanDefaults {
+    template: Option<String>,
+    template_base: Option<String>,
+    variables: BTreeMap<String, String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct PlanProject {
+    name: String,
+    template: Option<String>,
+    template_base: Option<String>,
+    /// Output directory, relative to the plan file; defaults to `<name>` next to it.
+    destination: Option<PathBuf>,
+    #[serde(default)]
+    variables: BTreeMap<String, String>,
+}
+
+enum Outcome {
+    Created(PathBuf),
+    DryRun,
+    Failed(String),
+}
+
+pub fn run_batch(plan_path: &Path, dry_run: bool, tmpdir: Option<&Path>) -> anyhow::Result<()> {
+    let content = fs::read_to_string(plan_path)
+        .map_err(|e| anyhow::anyhow!("Failed to read plan {}: {}", plan_path.display(), e))?;
+    let plan: Plan = toml::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse plan {}: {}", plan_path.display(), e))?;
+    if plan.projects.is_empty() {
+        anyhow::bail!("Plan {} lists no [[projects]]", plan_path.display());
+    }
+    let base_dir = plan_path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+        .unwrap_or(std::env::current_dir()?);
+
+    let mut results = Vec::new();
+    for (i, project) in plan.projects.iter().enumerate() {
+        println!(
+            "\n=== [{}/{}] {} ===",
+            i + 1,
+            plan.projects.len(),
+            project.name
+        );
+        let outcome = match scaffold_project(project, &plan.defaults, &base_dir, dry_run, tmpdir) {
+            Ok(Some(dest)) => Outcome::Created(dest),
+            Ok(None) => Outcome::DryRun,
+            Err(e) => {
+                println!("ERROR: {}", e);
+                Outcome::Failed(e.to_string())
+            }
+        };
+        results.push((project.name.as_str(), outcome));
+    }
+
+    let failed = results
+        .iter()
+        .filter(|(_, o)| matches!(o, Outcome::Failed(_)))
+        .count();
+    println!(
+        "\nBatch summary: {} succeeded, {} failed",
+        results.len() - failed,
+        failed
+    );
+    let width = results.iter().map(|(n, _)| n.len()).max().unwrap_or(0);
+    for (name, outcome) in &results {
+        match outcome {
+            Outcome::Created(dest) => {
+                println!("  OK      {:width$}  {}", name, dest.display(), width = width)
+            }
+            Outcome::DryRun => println!("  DRY     {:width$}", name, width = width),
+            Outcome::Failed(e) => println!("  FAILED  {:width$}  {}", name, e, width = width),
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{} of {} projects failed", failed, results.len());
+    }
+    Ok(())
+}
+
+fn scaffold_project(
+    project: &PlanProject,
+    defaults: &PlanDefaults,
+    base_dir: &Path,
+    dry_run: bool,
+    tmpdir: Option<&Path>,
+) -> anyhow::Result<Option<PathBuf>> {
+    let template = project
+        .template
+        .as_ref()
+        .or(defaults.template.as_ref())
+        .ok_or_else(|| anyhow::anyhow!("No template for project '{}'", project.name))?;
+    let template_base = project
+        .template_base
+        .as_ref()
+        .or(defaults.template_base.as_ref())
+        .map(String::as_str)
+        .unwrap_or("acme-app");
+    let destination = base_dir.join(
+        project
+            .destination
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(&project.name)),
+    );
+
+    let mut vars: BTreeMap<String, String> = defaults.variables.clone();
+    vars.extend(project.variables.clone());
+
+    let options = ScaffoldOptions {
+        dry_run,
+        assume_yes: true,
+        output_dir: Some(destination),
+        workspace_aware: true,
+        vars: vars.into_iter().collect(),
+        tmpdir: tmpdir.map(Path::to_path_buf),
+        notify: true,
+        ..Default::default()
+    };
+    run_scaffold(
+        &normalize_repo_url(template),
+        &project.name,
+        template_base,
+        &options,
+    )
+}
@@ -0,0 +1,744 @@
+//! Local cache of template clones: repeated scaffolds from the same template only fetch what
+//! changed, and still work offline from the last fetched state.
+//!
+//! Layout: `<cache dir>/store/<url key>.git` is a bare repository holding the git objects of
+//! every ref fetched from one template URL, so the default branch and each pinned commit share
+//! the blobs they have in common. `<cache dir>/templates/<key>/entry.json` records one ref: its
+//! URL, commit, size and last-used time; the store keeps the commit under `refs/liscaf/...`.
+//! Checkouts are written straight from the store. `<cache dir>/config/<key>.toml` is a fetched
+//! shared configuration.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use crate::diskspace::dir_size;
+
+const ENTRY_FILE: &str = "entry.json";
+const DEFAULT_REF: &str = "HEAD";
+const DEFAULT_MAX_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+
+/// The size budget; `None` for unlimited.
+static MAX_SIZE: OnceLock<Option<u64>> = OnceLock::new();
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CacheEntry {
+    pub url: String,
+    pub git_ref: String,
+    pub commit: Option<String>,
+    pub fetched_at: String,
+    pub last_used: String,
+    /// Size of the checked-out files.
+    #[serde(default)]
+    pub size: Option<u64>,
+}
+
+/// The template commit a checkout came from.
+#[derive(Debug, Clone)]
+pub struct Revision {
+    pub commit: String,
+    /// Committer date (RFC 3339).
+    pub committed_at: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct CachedTemplate {
+    pub dir: PathBuf,
+    pub entry: CacheEntry,
+    /// Size of the template's files; refs of one URL share most of it on disk.
+    pub size: u64,
+}
+
+/// `LISCAF_CACHE_DIR`, else the platform cache directory (`$XDG_CACHE_HOME/liscaf`,
+/// `~/.cache/liscaf`, `%LOCALAPPDATA%\liscaf`).
+pub fn cache_dir() -> PathBuf {
+    let from_env = |key: &str| std::env::var_os(key).filter(|v| !v.is_empty()).map(PathBuf::from);
+    if let Some(dir) = from_env("LISCAF_CACHE_DIR") {
+        return dir;
+    }
+    if let Some(dir) = from_env("XDG_CACHE_HOME") {
+        return dir.join("liscaf");
+    }
+    if cfg!(windows) {
+        if let Some(dir) = from_env("LOCALAPPDATA") {
+            return dir.join("liscaf");
+        }
+    }
+    if let Some(home) = from_env("HOME") {
+        return home.join(".cache").join("liscaf");
+    }
+    std::env::temp_dir().join("liscaf-cache")
+}
+
+/// Sets the size budget from `LISCAF_CACHE_MAX_SIZE`, else the configuration's
+/// `[cache] max_size`; call once at startup.
+pub fn configure(config: &crate::config::CacheConfig) -> anyhow::Result<()> {
+    let raw = std::env::var("LISCAF_CACHE_MAX_SIZE")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .or_else(|| config.max_size.clone());
+    let max_size = match raw.as_deref() {
+        None => Some(DEFAULT_MAX_SIZE),
+        Some(v) if v.eq_ignore_ascii_case("unlimited") => None,
+        Some(v) => Some(
+            crate::filter::parse_size(v)
+                .map_err(|e| anyhow::anyhow!("Invalid cache max_size: {}", e))?,
+        ),
+    };
+    let _ = MAX_SIZE.set(max_size);
+    Ok(())
+}
+
+/// Where the shared configuration fetched from `url` (`LISCAF_CONFIG=https://...`) is kept.
+pub fn remote_config_path(url: &str) -> PathBuf {
+    cache_dir()
+        .join("config")
+        .join(format!("{}.toml", entry_key(url.trim_end_matches(".toml"), DEFAULT_REF)))
+}
+
+fn templates_dir(root: &Path) -> PathBuf {
+    root.join("templates")
+}
+
+fn stores_dir(root: &Path) -> PathBuf {
+    root.join("store")
+}
+
+/// The object store shared by all refs of `url`.
+fn store_dir(root: &Path, url: &str) -> PathBuf {
+    stores_dir(root).join(format!("{}.git", entry_key(url, DEFAULT_REF)))
+}
+
+/// The store ref that keeps `git_ref`'s commit (and its objects) alive.
+fn store_ref(git_ref: &str) -> String {
+    if git_ref == DEFAULT_REF {
+        "refs/liscaf/head".to_string()
+    } else {
+        format!("refs/liscaf/pinned/{}", git_ref)
+    }
+}
+
+/// Directory name for a template URL, e.g. `github.com-owner-repo`.
+fn entry_key(url: &str, git_ref: &str) -> String {
+    let without_scheme = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let mut key = String::new();
+    for c in without_scheme.trim_end_matches(".git").chars() {
+        if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+            key.push(c.to_ascii_lowercase());
+        } else if !key.ends_with('-') {
+            key.push('-');
+        }
+    }
+    let mut key = key.trim_matches('-').to_string();
+    key.truncate(100);
+    if git_ref != DEFAULT_REF {
+        key.push('@');
+        key.push_str(&git_ref.replace(['/', '\\'], "-"));
+    }
+    key
+}
+
+/// Places the files of `url` (without `.git`) in `dest`, from the cache when possible, and
+/// returns the revision they came from. With `use_cache` false the template is cloned directly.
+///
+/// A pinned `commit` is fetched by SHA instead of the default branch; if the remote no longer
+/// has it, the checkout fails rather than silently using something else.
+pub fn checkout(
+    url: &str,
+    commit: Option<&str>,
+    dest: &Path,
+    use_cache: bool,
+) -> anyhow::Result<Option<Revision>> {
+    let commit = commit.map(str::to_ascii_lowercase);
+    if let Some(sha) = commit.as_deref() {
+        validate_commit(sha)?;
+    }
+    checkout_ref(url, commit.as_deref(), dest, use_cache)
+}
+
+/// Like [`checkout`], for a branch, tag or full commit SHA; `None` is the default branch.
+/// Branches and tags are fetched again on every run, like the default branch.
+pub fn checkout_ref(
+    url: &str,
+    git_ref: Option<&str>,
+    dest: &Path,
+    use_cache: bool,
+) -> anyhow::Result<Option<Revision>> {
+    let preferred = crate::protocol::rewrite(url);
+    if preferred != url {
+        println!("Using {} (protocol preference)", preferred);
+    }
+    let url = preferred.as_str();
+    let commit = git_ref.filter(|r| validate_commit(r).is_ok());
+    crate::progress::emit(crate::progress::Event::CloneStarted {
+        url: url.to_string(),
+    });
+    if !use_cache {
+        match git_ref {
+            Some(rev) => fetch_rev(url, rev, commit.is_some(), dest)?,
+            None => {
+                crate::git_clone_shallow(url, dest)?;
+                println!("git clone succeeded");
+            }
+        }
+        crate::lfs::pull(dest, &[])?;
+        let head = revision(dest, "HEAD");
+        let git_dir = dest.join(".git");
+        if git_dir.exists() {
+            println!("Removing .git to unlink original repository");
+            if let Err(e) = fs::remove_dir_all(&git_dir) {
+                println!("Warning: failed to remove .git: {}", e);
+            }
+        }
+        return Ok(head);
+    }
+
+    checkout_cached(&cache_dir(), url, git_ref.unwrap_or(DEFAULT_REF), commit, dest)
+}
+
+/// Fetches `git_ref` into the store below the cache directory `root` (falling back to what it
+/// already has when offline) and writes its files to `dest`.
+fn checkout_cached(
+    root: &Path,
+    url: &str,
+    git_ref: &str,
+    commit: Option<&str>,
+    dest: &Path,
+) -> anyhow::Result<Option<Revision>> {
+    let key_dir = templates_dir(root).join(entry_key(url, git_ref));
+    let store = store_dir(root, url);
+    let local_ref = store_ref(git_ref);
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut entry = read_entry(&key_dir);
+
+    // Clones from before the shared store
+    let legacy = key_dir.join("repo");
+    if legacy.exists() {
+        let _ = fs::remove_dir_all(&legacy);
+    }
+    fs::create_dir_all(&key_dir)?;
+    open_store(&store, url)?;
+    let cached = resolve(&store, &local_ref);
+    match (commit, &cached) {
+        (Some(sha), _) => println!("Fetching pinned commit {} from {}", sha, url),
+        (None, _) if git_ref != DEFAULT_REF => println!("Fetching {} of {}", git_ref, url),
+        (None, Some(_)) => println!("Updating cached template in {}", store.display()),
+        (None, None) => println!("Fetching into cache: {}", store.display()),
+    }
+    match fetch_into_store(&store, url, git_ref, commit.is_some(), &local_ref) {
+        Ok(()) => {
+            if let Some(entry) = &mut entry {
+                entry.fetched_at = now.clone();
+            }
+        }
+        Err(e) if cached.is_none() || e.is::<CommitUnavailable>() => {
+            let _ = fs::remove_dir_all(&key_dir);
+            return Err(e);
+        }
+        Err(e) => match &entry {
+            Some(entry) => println!(
+                "Warning: could not update cached template ({}); using the copy fetched at {}",
+                e, entry.fetched_at
+            ),
+            None => println!("Warning: could not update cached template ({})", e),
+        },
+    }
+    let head = resolve(&store, &local_ref)
+        .ok_or_else(|| anyhow::anyhow!("Cached template has no commit for {}", git_ref))?;
+
+    let size = tree_size(&store, &head);
+    let mut entry = entry.unwrap_or_else(|| CacheEntry {
+        url: url.to_string(),
+        git_ref: git_ref.to_string(),
+        commit: None,
+        fetched_at: now.clone(),
+        last_used: now.clone(),
+        size: None,
+    });
+    entry.commit = Some(head.clone());
+    entry.last_used = now;
+    entry.size = size;
+    write_entry(&key_dir, &entry)?;
+    if let Some(max_size) = MAX_SIZE.get().copied().unwrap_or(Some(DEFAULT_MAX_SIZE)) {
+        if let Err(e) = evict(root, max_size, Some(&key_dir)) {
+            println!("Warning: could not shrink the template cache: {}", e);
+        }
+    }
+
+    // The working copy, plus as much again for rewritten files and the final move
+    crate::diskspace::ensure(dest, size.unwrap_or(0).saturating_mul(2), "template working copy")?;
+    let index = tempfile::tempdir()?;
+    let git_env = [
+        ("GIT_DIR", store.as_path()),
+        ("GIT_WORK_TREE", dest),
+        ("GIT_INDEX_FILE", &index.path().join("index")),
+    ];
+    fs::create_dir_all(dest)?;
+    git_with(dest, &git_env, &["read-tree", &head])?;
+    git_with(dest, &git_env, &["checkout-index", "--all", "--force"])?;
+    crate::lfs::pull(dest, &git_env)?;
+    Ok(revision(&store, &head))
+}
+
+/// Creates the bare store for `url` if needed and points its `origin` at `url`.
+fn open_store(store: &Path, url: &str) -> anyhow::Result<()> {
+    if !store.join("HEAD").is_file() {
+        fs::create_dir_all(store)?;
+        git(store, &["init", "--bare", "--quiet", "--template="])?;
+    }
+    if git_output(store, &["remote", "get-url", "origin"]).as_deref() != Some(url) {
+        let _ = git(store, &["remote", "remove", "origin"]);
+        git(store, &["remote", "add", "origin", url])?;
+    }
+    Ok(())
+}
+
+/// Fetches `git_ref` (the default branch, a branch or tag, or a full commit SHA) into the store
+/// and points `local_ref` at it. Objects the store already has are not transferred again.
+fn fetch_into_store(
+    store: &Path,
+    url: &str,
+    git_ref: &str,
+    is_commit: bool,
+    local_ref: &str,
+) -> anyhow::Result<()> {
+    fetch_shallow(store, url, git_ref)?;
+    git(store, &["update-ref", local_ref, "FETCH_HEAD"])?;
+    if is_commit && resolve(store, local_ref).as_deref() != Some(git_ref) {
+        anyhow::bail!("Fetched commit does not match the pinned commit {}", git_ref);
+    }
+    Ok(())
+}
+
+/// The commit `rev` names in `repo`.
+fn resolve(repo: &Path, rev: &str) -> Option<String> {
+    git_output(repo, &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", rev)])
+}
+
+/// Total size of the files in `commit`.
+fn tree_size(repo: &Path, commit: &str) -> Option<u64> {
+    let listing = git_output(repo, &["ls-tree", "-r", "-l", commit])?;
+    Some(
+        listing
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(3)?.parse::<u64>().ok())
+            .sum(),
+    )
+}
+
+/// Pinned commits must be full SHA-1 or SHA-256 object names; servers only hand out
+/// unadvertised commits by their full name.
+pub fn validate_commit(sha: &str) -> anyhow::Result<()> {
+    if !matches!(sha.len(), 40 | 64) || !sha.chars().all(|c| c.is_ascii_hexdigit()) {
+        anyhow::bail!("Pinned commit must be a full 40-character commit SHA: {}", sha);
+    }
+    Ok(())
+}
+
+/// Fetches exactly `sha` from `url` into the git repository at `repo` (created if needed) and
+/// checks it out.
+fn fetch_rev(url: &str, rev: &str, is_commit: bool, repo: &Path) -> anyhow::Result<()> {
+    if !repo.join(".git").is_dir() {
+        fs::create_dir_all(repo)?;
+        git(repo, &["init", "--quiet"])?;
+        git(repo, &["remote", "add", "origin", url])?;
+    }
+    if is_commit {
+        println!("Fetching pinned commit {} from {}", rev, url);
+    } else {
+        println!("Fetching {} of {}", rev, url);
+    }
+    fetch_shallow(repo, url, rev)?;
+    git(repo, &["checkout", "--quiet", "--force", "--detach", "FETCH_HEAD"])?;
+    if is_commit && git_output(repo, &["rev-parse", "HEAD"]).as_deref() != Some(rev) {
+        anyhow::bail!("Checked out commit does not match the pinned commit {}", rev);
+    }
+    Ok(())
+}
+
+/// `git fetch --depth 1 origin <what>` in `repo`, with retries; a commit the remote does not
+/// have fails with [`CommitUnavailable`].
+fn fetch_shallow(repo: &Path, url: &str, what: &str) -> anyhow::Result<()> {
+    crate::net::with_retries(&format!("Fetching {}", what), || {
+        let output = crate::net::git()
+            .args(["fetch", "--depth", "1", "origin", what])
+            .current_dir(repo)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .map_err(|e| crate::net::Failure::Permanent(anyhow::anyhow!("Failed to run git: {}", e)))?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if what != DEFAULT_REF && is_missing_object_error(&stderr) {
+            return Err(crate::net::Failure::Permanent(
+                CommitUnavailable {
+                    sha: what.to_string(),
+                    url: url.to_string(),
+                }
+                .into(),
+            ));
+        }
+        Err(crate::git_failure("git fetch", &output))
+    })
+}
+
+/// The remote answered but does not have the pinned commit (force-pushed away or deleted).
+#[derive(Debug)]
+struct CommitUnavailable {
+    sha: String,
+    url: String,
+}
+
+impl std::fmt::Display for CommitUnavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if validate_commit(&self.sha).is_err() {
+            return write!(f, "{} is no branch or tag of {}", self.sha, self.url);
+        }
+        write!(
+            f,
+            "Pinned commit {} is no longer available from {}; refusing to scaffold from anything else",
+            self.sha, self.url
+        )
+    }
+}
+
+impl std::error::Error for CommitUnavailable {}
+
+/// How servers report a `want` for a commit they do not (or no longer) have.
+fn is_missing_object_error(stderr: &str) -> bool {
+    const MISSING: &[&str] = &[
+        "not our ref",
+        "couldn't find remote ref",
+        "no such remote ref",
+        "upload-pack: not our ref",
+        "unadvertised object",
+    ];
+    let lower = stderr.to_lowercase();
+    MISSING.iter().any(|m| lower.contains(m))
+}
+
+fn git(repo: &Path, args: &[&str]) -> anyhow::Result<()> {
+    git_with(repo, &[], args)
+}
+
+/// Runs git in `dir` with extra environment variables (`GIT_DIR` and friends).
+fn git_with(dir: &Path, env: &[(&str, &Path)], args: &[&str]) -> anyhow::Result<()> {
+    let status = crate::net::git()
+        .args(args)
+        .envs(env.iter().copied())
+        .current_dir(dir)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| anyhow::anyhow!("Failed to run git: {}", e))?;
+    if !status.success() {
+        anyhow::bail!("git {} failed with code: {}", args[0], status.code().unwrap_or(-1));
+    }
+    Ok(())
+}
+
+fn revision(repo: &Path, rev: &str) -> Option<Revision> {
+    let out = git_output(repo, &["log", "-1", "--format=%H %cI", rev])?;
+    let (commit, committed_at) = out.split_once(' ')?;
+    Some(Revision {
+        commit: commit.to_string(),
+        committed_at: committed_at.to_string(),
+    })
+}
+
+fn git_output(repo: &Path, args: &[&str]) -> Option<String> {
+    let out = Command::new("git").args(args).current_dir(repo).output().ok()?;
+    out.status
+        .success()
+        .then(|| String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+fn read_entry(key_dir: &Path) -> Option<CacheEntry> {
+    let content = fs::read_to_string(key_dir.join(ENTRY_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_entry(key_dir: &Path, entry: &CacheEntry) -> anyhow::Result<()> {
+    fs::write(key_dir.join(ENTRY_FILE), serde_json::to_string_pretty(entry)?)?;
+    Ok(())
+}
+
+/// Size of the template files (without `.git`) of a cached checkout, if there is one.
+pub fn cached_size(url: &str, commit: Option<&str>) -> Option<u64> {
+    let commit = commit.map(str::to_ascii_lowercase);
+    let git_ref = commit.as_deref().unwrap_or(DEFAULT_REF);
+    read_entry(&templates_dir(&cache_dir()).join(entry_key(url, git_ref)))?.size
+}
+
+/// What the cache takes on disk: entries and the shared stores.
+pub fn disk_usage() -> u64 {
+    usage(&cache_dir())
+}
+
+fn usage(root: &Path) -> u64 {
+    dir_size(&templates_dir(root)) + dir_size(&stores_dir(root))
+}
+
+/// Cached templates, least recently used first.
+pub fn entries() -> anyhow::Result<Vec<CachedTemplate>> {
+    entries_in(&cache_dir())
+}
+
+fn entries_in(root: &Path) -> anyhow::Result<Vec<CachedTemplate>> {
+    let dir = templates_dir(root);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut entries = Vec::new();
+    for item in fs::read_dir(&dir)? {
+        let path = item?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let Some(entry) = read_entry(&path) else {
+            println!("WARN: Ignoring cache directory without {}: {}", ENTRY_FILE, path.display());
+            continue;
+        };
+        let size = entry.size.unwrap_or_else(|| dir_size(&path));
+        entries.push(CachedTemplate {
+            dir: path,
+            entry,
+            size,
+        });
+    }
+    entries.sort_by(|a, b| a.entry.last_used.cmp(&b.entry.last_used));
+    Ok(entries)
+}
+
+fn format_time(rfc3339: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(rfc3339)
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| rfc3339.to_string())
+}
+
+fn short_ref(entry: &CacheEntry) -> String {
+    match &entry.commit {
+        Some(commit) if *commit == entry.git_ref => format!("pinned@{}", &commit[..8]),
+        Some(commit) => format!("{}@{}", entry.git_ref, &commit[..commit.len().min(8)]),
+        None => entry.git_ref.clone(),
+    }
+}
+
+pub fn list() -> anyhow::Result<()> {
+    let entries = entries()?;
+    println!("Cache directory: {}", cache_dir().display());
+    if entries.is_empty() {
+        println!("No cached templates");
+        return Ok(());
+    }
+    let url_width = entries.iter().map(|e| e.entry.url.len()).max().unwrap_or(3).max(3);
+    println!(
+        "{:url_width$}  {:16}  {:>9}  LAST USED",
+        "URL",
+        "REF",
+        "SIZE",
+        url_width = url_width
+    );
+    let mut total = 0;
+    for cached in entries.iter().rev() {
+        total += cached.size;
+        println!(
+            "{:url_width$}  {:16}  {:>9}  {}",
+            cached.entry.url,
+            short_ref(&cached.entry),
+            crate::filter::format_size(cached.size),
+            format_time(&cached.entry.last_used),
+            url_width = url_width
+        );
+    }
+    println!(
+        "{} templates, {} ({} on disk)",
+        entries.len(),
+        crate::filter::format_size(total),
+        crate::filter::format_size(disk_usage())
+    );
+    Ok(())
+}
+
+/// Removes a cached template; objects no other ref of the same URL uses go with it.
+fn remove(root: &Path, cached: &CachedTemplate) -> anyhow::Result<()> {
+    fs::remove_dir_all(&cached.dir)?;
+    let store = store_dir(root, &cached.entry.url);
+    if !store.is_dir() {
+        return Ok(());
+    }
+    let _ = git(&store, &["update-ref", "-d", &store_ref(&cached.entry.git_ref)]);
+    let remaining = git_output(&store, &["for-each-ref", "--count=1", "refs/liscaf/"]).unwrap_or_default();
+    if remaining.is_empty() {
+        fs::remove_dir_all(&store)?;
+    } else {
+        git(&store, &["gc", "--prune=now", "--quiet"])?;
+    }
+    Ok(())
+}
+
+/// Removes every cached template, or only those whose URL contains `filter`.
+pub fn clean(filter: Option<&str>) -> anyhow::Result<()> {
+    let root = cache_dir();
+    let mut removed = 0;
+    for cached in entries_in(&root)? {
+        if filter.is_some_and(|f| !cached.entry.url.contains(f)) {
+            continue;
+        }
+        remove(&root, &cached)?;
+        println!("REMOVE: {} ({})", cached.entry.url, crate::filter::format_size(cached.size));
+        removed += 1;
+    }
+    println!("Removed {} cached templates", removed);
+    Ok(())
+}
+
+/// Removes least recently used templates until the cache is at most `max_size` bytes.
+pub fn prune(max_size: u64) -> anyhow::Result<()> {
+    let (removed, total) = evict(&cache_dir(), max_size, None)?;
+    println!(
+        "Removed {} cached templates; cache is now {}",
+        removed,
+        crate::filter::format_size(total)
+    );
+    Ok(())
+}
+
+/// Removes least recently used templates below `root`, never `keep`, while the cache is larger
+/// than `max_size`; returns how many were removed and the size left.
+fn evict(root: &Path, max_size: u64, keep: Option<&Path>) -> anyhow::Result<(usize, u64)> {
+    let mut total = usage(root);
+    if total <= max_size {
+        return Ok((0, total));
+    }
+    let mut removed = 0;
+    for cached in entries_in(root)? {
+        if total <= max_size {
+            break;
+        }
+        if keep == Some(cached.dir.as_path()) {
+            continue;
+        }
+        remove(root, &cached)?;
+        println!(
+            "REMOVE: {} ({}, last used {})",
+            cached.entry.url,
+            crate::filter::format_size(cached.size),
+            format_time(&cached.entry.last_used)
+        );
+        total = usage(root);
+        removed += 1;
+    }
+    if keep.is_some() && removed > 0 {
+        println!(
+            "Evicted {} least recently used templates to keep the cache under {}",
+            removed,
+            crate::filter::format_size(max_size)
+        );
+    }
+    Ok((removed, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=liscaf", "-c", "user.email=liscaf@localhost"])
+            .args(args)
+            .current_dir(dir)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?}", args);
+    }
+
+    /// A template repository with a `v1` tag one commit behind the default branch.
+    fn template_repo(dir: &Path) -> String {
+        let repo = dir.join("acme-app");
+        fs::create_dir_all(&repo).unwrap();
+        run_git(&repo, &["init", "--quiet"]);
+        fs::write(repo.join("README.md"), "v1\n").unwrap();
+        run_git(&repo, &["add", "."]);
+        run_git(&repo, &["commit", "--quiet", "-m", "v1"]);
+        run_git(&repo, &["tag", "v1"]);
+        fs::write(repo.join("README.md"), "v2\n").unwrap();
+        run_git(&repo, &["commit", "--quiet", "-am", "v2"]);
+        format!("file://{}", repo.display())
+    }
+
+    fn entry(root: &Path, url: &str, git_ref: &str) -> CachedTemplate {
+        entries_in(root)
+            .unwrap()
+            .into_iter()
+            .find(|c| c.entry.url == url && c.entry.git_ref == git_ref)
+            .unwrap()
+    }
+
+    #[test]
+    fn refs_of_one_url_share_a_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("cache");
+        let url = template_repo(dir.path());
+
+        checkout_cached(&root, &url, DEFAULT_REF, None, &dir.path().join("head")).unwrap();
+        checkout_cached(&root, &url, "v1", None, &dir.path().join("tag")).unwrap();
+        assert_eq!(fs::read_to_string(dir.path().join("head/README.md")).unwrap(), "v2\n");
+        assert_eq!(fs::read_to_string(dir.path().join("tag/README.md")).unwrap(), "v1\n");
+        assert_eq!(entries_in(&root).unwrap().len(), 2);
+        assert_eq!(fs::read_dir(stores_dir(&root)).unwrap().count(), 1);
+
+        // The store goes with the last ref that uses it
+        let store = store_dir(&root, &url);
+        remove(&root, &entry(&root, &url, "v1")).unwrap();
+        assert!(resolve(&store, &store_ref(DEFAULT_REF)).is_some());
+        assert!(resolve(&store, &store_ref("v1")).is_none());
+        remove(&root, &entry(&root, &url, DEFAULT_REF)).unwrap();
+        assert!(!store.exists());
+    }
+
+    /// A cached entry without a store, holding `size` bytes.
+    fn fake_entry(root: &Path, name: &str, last_used: &str, size: usize) -> PathBuf {
+        let key_dir = templates_dir(root).join(name);
+        fs::create_dir_all(&key_dir).unwrap();
+        fs::write(key_dir.join("payload"), vec![0u8; size]).unwrap();
+        let entry = CacheEntry {
+            url: format!("https://example.com/{}", name),
+            git_ref: DEFAULT_REF.to_string(),
+            commit: None,
+            fetched_at: last_used.to_string(),
+            last_used: last_used.to_string(),
+            size: Some(size as u64),
+        };
+        write_entry(&key_dir, &entry).unwrap();
+        key_dir
+    }
+
+    #[test]
+    fn eviction_removes_the_least_recently_used_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fake_entry(root, "a-newest", "2026-03-01T00:00:00+00:00", 4096);
+        fake_entry(root, "b-oldest", "2026-01-01T00:00:00+00:00", 4096);
+        let middle = fake_entry(root, "c-middle", "2026-02-01T00:00:00+00:00", 4096);
+        let names = |root: &Path| -> Vec<String> {
+            entries_in(root).unwrap().into_iter().map(|c| c.entry.url.replace("https://example.com/", "")).collect()
+        };
+        assert_eq!(names(root), ["b-oldest", "c-middle", "a-newest"]);
+
+        let (removed, total) = evict(root, usage(root) - 1, None).unwrap();
+        assert_eq!(removed, 1);
+        assert_eq!(total, usage(root));
+        assert_eq!(names(root), ["c-middle", "a-newest"]);
+
+        // The template being used is kept even when it alone exceeds the budget
+        let (removed, _) = evict(root, 0, Some(&middle)).unwrap();
+        assert_eq!(removed, 1);
+        assert_eq!(names(root), ["c-middle"]);
+        assert_eq!(evict(root, u64::MAX, None).unwrap(), (0, usage(root)));
+    }
+}
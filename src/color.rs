@@ -0,0 +1,123 @@
+//! `--color auto|always|never`: colored status lines (`ADD`, `MERGE`, `RENAME`, conflicts, ...)
+//! and `--diff` output, so big scaffolds are easier to scan. `auto` colors when stdout is a
+//! terminal and `NO_COLOR` is unset or empty; `NO_COLOR` also turns off the colors of prompts.
+//! Library users get plain text unless they call [`configure`].
+use std::borrow::Cow;
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+pub fn parse(raw: &str) -> Result<ColorChoice, String> {
+    match raw.to_ascii_lowercase().as_str() {
+        "auto" => Ok(ColorChoice::Auto),
+        "always" => Ok(ColorChoice::Always),
+        "never" => Ok(ColorChoice::Never),
+        other => Err(format!("unknown color mode '{}' (use auto, always or never)", other)),
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const BOLD_RED: &str = "\x1b[1;31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const CYAN: &str = "\x1b[36m";
+
+/// Status tags and their colors; longer tags first where one is a suffix of another.
+const TAGS: &[(&str, &str)] = &[
+    ("ADD", GREEN),
+    ("MERGE", YELLOW),
+    ("BIN CONFLICT", BOLD_RED),
+    ("CONFLICT", BOLD_RED),
+    ("RENAME", CYAN),
+    ("MOVE", CYAN),
+    ("WORKSPACE", GREEN),
+    ("DROP", RED),
+    ("DELETE", RED),
+];
+
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// Applies `--color`; call once at startup, after stdout has been set up.
+pub fn configure(choice: ColorChoice) {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let enabled = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            !no_color && std::io::stdout().is_terminal() && std::env::var("TERM").map_or(true, |t| t != "dumb")
+        }
+    };
+    let _ = ENABLED.set(enabled);
+    // Prompts go to the terminal even when stdout is piped, so only an explicit request turns
+    // their colors off
+    if choice == ColorChoice::Never || (choice == ColorChoice::Auto && no_color) {
+        inquire::set_global_render_config(inquire::ui::RenderConfig::empty());
+    }
+}
+
+pub fn enabled() -> bool {
+    ENABLED.get().copied().unwrap_or(false)
+}
+
+/// `line` with its status tag colored, after an optional dimmed `DRY` prefix.
+pub fn paint_status(line: &str) -> Cow<'_, str> {
+    if !enabled() {
+        return Cow::Borrowed(line);
+    }
+    let (dry, rest) = match line.strip_prefix("DRY ") {
+        Some(rest) => (true, rest),
+        None => (false, line),
+    };
+    for (tag, color) in TAGS {
+        if let Some(tail) = rest.strip_prefix(tag).filter(|t| t.starts_with(':')) {
+            let dry = if dry { format!("{}DRY{} ", DIM, RESET) } else { String::new() };
+            return Cow::Owned(format!("{}{}{}{}{}", dry, color, tag, RESET, tail));
+        }
+    }
+    Cow::Borrowed(line)
+}
+
+/// A unified diff with headers, hunk markers, additions and removals colored.
+pub fn paint_diff(diff: &str) -> Cow<'_, str> {
+    if !enabled() {
+        return Cow::Borrowed(diff);
+    }
+    let mut painted = String::with_capacity(diff.len() * 2);
+    for line in diff.split_inclusive('\n') {
+        let color = if line.starts_with("+++ ") || line.starts_with("--- ") {
+            BOLD
+        } else if line.starts_with("@@") {
+            CYAN
+        } else if line.starts_with('+') {
+            GREEN
+        } else if line.starts_with('-') {
+            RED
+        } else {
+            painted.push_str(line);
+            continue;
+        };
+        let (text, newline) = match line.strip_suffix('\n') {
+            Some(text) => (text, "\n"),
+            None => (line, ""),
+        };
+        painted.push_str(&format!("{}{}{}{}", color, text, RESET, newline));
+    }
+    Cow::Owned(painted)
+}
+
+/// `println!` for status lines, coloring the tag when colors are on.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        println!("{}", $crate::color::paint_status(&format!($($arg)*)))
+    };
+}
+pub(crate) use status;
@@ -0,0 +1,271 @@
+//! Conditions on template variables (`ask_if = "database == 'postgres'"`).
+//!
+//! Grammar: comparisons `a == 'x'` / `a != 'x'`, combined with `&&` / `||` (or `and` / `or`),
+//! negated with `!` / `not`, grouped with parentheses. A bare operand is true unless it is empty,
+//! `false`, `no`, `off`, `n` or `0`, so `ask_if = "use_docker"` works for yes/no answers. Unknown
+//! variables are empty.
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Literal(String),
+    Eq,
+    Ne,
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+#[derive(Debug)]
+enum Expr {
+    Value(Operand),
+    Compare(Operand, bool, Operand),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug)]
+enum Operand {
+    Var(String),
+    Literal(String),
+}
+
+fn tokenize(input: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = input.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::Open);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                i += 1;
+            }
+            '=' if next == Some('=') => {
+                tokens.push(Token::Eq);
+                i += 2;
+            }
+            '!' if next == Some('=') => {
+                tokens.push(Token::Ne);
+                i += 2;
+            }
+            '!' => {
+                tokens.push(Token::Not);
+                i += 1;
+            }
+            '&' if next == Some('&') => {
+                tokens.push(Token::And);
+                i += 2;
+            }
+            '|' if next == Some('|') => {
+                tokens.push(Token::Or);
+                i += 2;
+            }
+            '\'' | '"' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&q| q == c)
+                    .ok_or_else(|| anyhow::anyhow!("unterminated string"))?;
+                tokens.push(Token::Literal(chars[i + 1..i + 1 + end].iter().collect()));
+                i += end + 2;
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '-' || c == '.' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '-' | '.'))
+                {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                tokens.push(match word.as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    "true" | "false" => Token::Literal(word),
+                    w if w.starts_with(|c: char| c.is_ascii_digit()) => Token::Literal(word),
+                    _ => Token::Ident(word),
+                });
+            }
+            c => anyhow::bail!("unexpected '{}'", c),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> anyhow::Result<Expr> {
+        let mut left = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> anyhow::Result<Expr> {
+        let mut left = self.not()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            left = Expr::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> anyhow::Result<Expr> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> anyhow::Result<Expr> {
+        if self.peek() == Some(&Token::Open) {
+            self.pos += 1;
+            let inner = self.or()?;
+            if self.next() != Some(Token::Close) {
+                anyhow::bail!("expected ')'");
+            }
+            return Ok(inner);
+        }
+        let left = self.operand()?;
+        let equal = match self.peek() {
+            Some(Token::Eq) => true,
+            Some(Token::Ne) => false,
+            _ => return Ok(Expr::Value(left)),
+        };
+        self.pos += 1;
+        Ok(Expr::Compare(left, equal, self.operand()?))
+    }
+
+    fn operand(&mut self) -> anyhow::Result<Operand> {
+        match self.next() {
+            Some(Token::Ident(name)) => Ok(Operand::Var(name)),
+            Some(Token::Literal(value)) => Ok(Operand::Literal(value)),
+            Some(token) => anyhow::bail!("unexpected {:?}", token),
+            None => anyhow::bail!("unexpected end of condition"),
+        }
+    }
+}
+
+fn parse(input: &str) -> anyhow::Result<Expr> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        pos: 0,
+    };
+    let expr = parser.or()?;
+    if let Some(token) = parser.peek() {
+        anyhow::bail!("unexpected {:?}", token);
+    }
+    Ok(expr)
+}
+
+/// Fails with a description of the first syntax error.
+pub fn check(input: &str) -> anyhow::Result<()> {
+    parse(input).map(|_| ())
+}
+
+pub fn evaluate(input: &str, vars: &BTreeMap<String, String>) -> anyhow::Result<bool> {
+    Ok(eval(&parse(input)?, vars))
+}
+
+fn eval(expr: &Expr, vars: &BTreeMap<String, String>) -> bool {
+    let value = |operand: &Operand| match operand {
+        Operand::Var(name) => vars.get(name).cloned().unwrap_or_default(),
+        Operand::Literal(value) => value.clone(),
+    };
+    match expr {
+        Expr::Value(operand) => is_truthy(&value(operand)),
+        Expr::Compare(left, equal, right) => (value(left) == value(right)) == *equal,
+        Expr::Not(inner) => !eval(inner, vars),
+        Expr::And(left, right) => eval(left, vars) && eval(right, vars),
+        Expr::Or(left, right) => eval(left, vars) || eval(right, vars),
+    }
+}
+
+fn is_truthy(value: &str) -> bool {
+    let value = value.trim().to_lowercase();
+    !matches!(value.as_str(), "" | "false" | "no" | "off" | "n" | "0")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval_with(input: &str, vars: &[(&str, &str)]) -> bool {
+        let vars = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        evaluate(input, &vars).unwrap()
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let vars = [("a", "yes"), ("b", "no"), ("c", "no")];
+        assert!(eval_with("a || b && c", &vars));
+        assert!(!eval_with("(a || b) && c", &vars));
+        assert!(eval_with("b and c or a", &vars));
+    }
+
+    #[test]
+    fn not_applies_to_the_next_operand_or_group() {
+        let vars = [("docker", "yes"), ("database", "sqlite")];
+        assert!(!eval_with("!docker", &vars));
+        assert!(eval_with("not docker || database == 'sqlite'", &vars));
+        assert!(!eval_with("not (docker || database == 'sqlite')", &vars));
+        assert!(eval_with("!!docker", &vars));
+        assert!(eval_with("database != 'postgres'", &vars));
+    }
+
+    #[test]
+    fn quoted_operators_are_plain_text() {
+        let vars = [("title", "a && b || (c)"), ("op", "!=")];
+        assert!(eval_with("title == 'a && b || (c)'", &vars));
+        assert!(eval_with("op == \"!=\"", &vars));
+        assert!(!eval_with("title == \"and\"", &vars));
+    }
+
+    #[test]
+    fn bare_operands_are_truthy_unless_empty_or_negative() {
+        for falsy in ["", "false", "No", " off ", "n", "0"] {
+            assert!(!eval_with("flag", &[("flag", falsy)]), "{:?}", falsy);
+        }
+        for truthy in ["yes", "true", "1", "postgres"] {
+            assert!(eval_with("flag", &[("flag", truthy)]), "{:?}", truthy);
+        }
+        assert!(!eval_with("unknown", &[]));
+        assert!(eval_with("unknown == ''", &[]));
+        assert!(!eval_with("false", &[]) && eval_with("true", &[]));
+    }
+
+    #[test]
+    fn malformed_conditions_are_errors() {
+        for input in ["", "a ==", "(a", "a)", "a == 'x", "a = 'x'", "a b", "&& a", "a == == b", "a # b"] {
+            assert!(check(input).is_err(), "{:?}", input);
+        }
+        assert!(check("(a == 'x') && !b").is_ok());
+    }
+}
@@ -0,0 +1,209 @@
+//! Whole directory trees kept or dropped depending on the answers, for templates that carry
+//! alternative implementations side by side:
+//!
+//! ```toml
+//! [[conditional]]
+//! paths = ["web-react"]
+//! when = "frontend == 'react'"
+//! rename_to = "web"
+//!
+//! [[conditional]]
+//! paths = ["web-vue", "docs/vue-*.md"]
+//! when = "frontend == 'vue'"
+//! rename_to = "web"   # renames the first path, which must not be a pattern
+//! ```
+//!
+//! Paths are globs relative to the template root; a matching directory goes with everything in
+//! it. Conditions use the `ask_if` syntax. Every rule whose condition fails is applied before any
+//! survivor is renamed, so alternatives can share the target name.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use walkdir::WalkDir;
+
+use crate::color::status;
+use crate::manifest::ConditionalSpec;
+
+/// Characters that make a path a pattern rather than a single path.
+const GLOB_CHARS: &[char] = &['*', '?', '[', '{'];
+
+/// Checks a rule when the manifest is loaded.
+pub fn check(spec: &ConditionalSpec) -> anyhow::Result<()> {
+    if spec.paths.is_empty() {
+        anyhow::bail!("needs at least one path");
+    }
+    crate::condition::check(&spec.when)?;
+    for path in spec.paths.iter().chain(&spec.rename_to) {
+        if Path::new(path).is_absolute() || path.split('/').any(|c| c == "..") {
+            anyhow::bail!("'{}' is outside the template", path);
+        }
+    }
+    globs(&spec.paths)?;
+    if spec.rename_to.is_some() && spec.paths[0].contains(GLOB_CHARS) {
+        anyhow::bail!("rename_to needs a first path without wildcards");
+    }
+    Ok(())
+}
+
+fn globs(patterns: &[String]) -> anyhow::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Invalid glob '{}': {}", pattern, e))?;
+        builder.add(glob);
+    }
+    Ok(builder.build()?)
+}
+
+/// Drops the paths of rules whose condition fails on `vars`, then renames the survivors.
+pub fn apply(
+    root: &Path,
+    rules: &[ConditionalSpec],
+    vars: &BTreeMap<String, String>,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let mut renames: Vec<(&str, &str)> = Vec::new();
+    let mut dropped = Vec::new();
+    for rule in rules {
+        if crate::condition::evaluate(&rule.when, vars)? {
+            if let Some(ref target) = rule.rename_to {
+                let from = rule.paths[0].trim_start_matches("./").trim_end_matches('/');
+                renames.push((from, target.trim_start_matches("./").trim_end_matches('/')));
+            }
+        } else {
+            dropped.extend(rule.paths.iter().cloned());
+        }
+    }
+    if !dropped.is_empty() {
+        drop_matching(root, &globs(&dropped)?, dry_run)?;
+    }
+    for (from, to) in renames {
+        let (source, target) = (root.join(from), root.join(to));
+        if !source.exists() {
+            continue;
+        }
+        if dry_run {
+            status!("DRY MOVE: {} -> {}", from, to);
+            continue;
+        }
+        if target.exists() {
+            anyhow::bail!("Cannot rename {} to {}: {} already exists", from, to, to);
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(&source, &target)?;
+        status!("MOVE: {} -> {}", from, to);
+    }
+    Ok(())
+}
+
+fn drop_matching(root: &Path, globs: &GlobSet, dry_run: bool) -> anyhow::Result<()> {
+    let mut matched = Vec::new();
+    let mut walker = WalkDir::new(root).min_depth(1).into_iter();
+    while let Some(entry) = walker.next() {
+        let entry = entry?;
+        if entry.file_name() == ".git" {
+            walker.skip_current_dir();
+            continue;
+        }
+        let rel = entry.path().strip_prefix(root)?;
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        if globs.is_match(&rel) {
+            if entry.file_type().is_dir() {
+                walker.skip_current_dir();
+            }
+            matched.push((entry.into_path(), rel));
+        }
+    }
+    for (path, rel) in matched {
+        if dry_run {
+            status!("DRY DROP: {}", rel);
+            continue;
+        }
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        } else {
+            fs::remove_file(&path)?;
+        }
+        status!("DROP: {}", rel);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(paths: &[&str], when: &str, rename_to: Option<&str>) -> ConditionalSpec {
+        ConditionalSpec {
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+            when: when.to_string(),
+            rename_to: rename_to.map(str::to_string),
+        }
+    }
+
+    fn template(files: &[&str]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for file in files {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, file).unwrap();
+        }
+        dir
+    }
+
+    fn vars(frontend: &str) -> BTreeMap<String, String> {
+        BTreeMap::from([("frontend".to_string(), frontend.to_string())])
+    }
+
+    #[test]
+    fn failed_conditions_drop_directories_and_globbed_files() {
+        let dir = template(&["web-vue/src/main.ts", "docs/vue-setup.md", "docs/react-setup.md", "README.md"]);
+        let rules = [rule(&["web-vue/", "./docs/vue-*.md"], "frontend == 'vue'", None)];
+        apply(dir.path(), &rules, &vars("react"), false).unwrap();
+        assert!(!dir.path().join("web-vue").exists());
+        assert!(!dir.path().join("docs/vue-setup.md").exists());
+        assert!(dir.path().join("docs/react-setup.md").exists());
+        assert!(dir.path().join("README.md").exists());
+    }
+
+    #[test]
+    fn alternatives_are_dropped_before_the_survivor_takes_the_shared_name() {
+        let dir = template(&["web-vue/index.html", "web-react/index.html"]);
+        // The surviving rule comes first, so renaming before dropping would hit `web`
+        let rules = [
+            rule(&["web-react"], "frontend == 'react'", Some("web")),
+            rule(&["web-vue"], "frontend == 'vue'", Some("web")),
+        ];
+        apply(dir.path(), &rules, &vars("react"), false).unwrap();
+        assert_eq!(fs::read_to_string(dir.path().join("web/index.html")).unwrap(), "web-react/index.html");
+        assert!(!dir.path().join("web-vue").exists() && !dir.path().join("web-react").exists());
+    }
+
+    #[test]
+    fn renaming_onto_an_existing_path_fails() {
+        let dir = template(&["web-react/index.html", "web/index.html"]);
+        let rules = [rule(&["web-react"], "frontend == 'react'", Some("web"))];
+        let error = apply(dir.path(), &rules, &vars("react"), false).unwrap_err();
+        assert!(error.to_string().contains("web already exists"), "{}", error);
+        assert!(dir.path().join("web-react/index.html").exists());
+    }
+
+    #[test]
+    fn dry_runs_leave_the_tree_alone() {
+        let dir = template(&["web-vue/index.html", "web-react/index.html"]);
+        let rules = [
+            rule(&["web-react"], "frontend == 'react'", Some("web")),
+            rule(&["web-vue"], "frontend == 'vue'", None),
+        ];
+        apply(dir.path(), &rules, &vars("react"), true).unwrap();
+        assert!(dir.path().join("web-vue").exists() && dir.path().join("web-react").exists());
+        assert!(!dir.path().join("web").exists());
+    }
+}
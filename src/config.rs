@@ -0,0 +1,292 @@
+//! User configuration: `$XDG_CONFIG_HOME/liscaf/config.toml` (`~/.config/liscaf/config.toml`,
+//! `%APPDATA%\liscaf\config.toml`), or the file named by `LISCAF_CONFIG`.
+//!
+//! `LISCAF_CONFIG` may also be an HTTP(S) URL of a team configuration. It is fetched at most once
+//! per `LISCAF_CONFIG_TTL` seconds (default an hour) and kept in the cache directory; the local
+//! file still applies on top, except for the team's `[defaults]`, which are enforced.
+//!
+//! ```toml
+//! [network]
+//! ca_bundle = "/etc/ssl/corp-root.pem"
+//! connect_timeout = 10   # seconds
+//! read_timeout = 60      # seconds without progress
+//! retries = 2
+//!
+//! [merge]               # conflict hunks in files merged with --into / update
+//! ours_label = "EXISTING"
+//! theirs_label = "TEMPLATE"
+//!
+//! [cache]
+//! max_size = "5G"        # least recently used templates are evicted beyond this; "unlimited"
+//!
+//! [prompts]
+//! timeout = 300          # seconds a prompt waits for an answer
+//! on_timeout = "default" # take the prompt's default, or "abort"
+//!
+//! [[registries]]
+//! name = "internal"
+//! source = "https://templates.example.com/"
+//! priority = 10          # higher wins when registries list the same template name
+//! token_env = "TEMPLATES_TOKEN"  # bearer token; or username + password_env for basic auth
+//!
+//! [[webhooks]]           # notified after each scaffold
+//! url = "https://platform.example.com/hooks/liscaf"
+//! token_env = "PLATFORM_HOOK_TOKEN"
+//!
+//! [[favorites]]          # managed with `liscaf fav add|remove`
+//! name = "rust-api"
+//! url = "https://github.com/acme/rust-api-template"
+//!
+//! [aliases]              # short names for `liscaf new <alias> <name>`
+//! api = "rust-api"       # a registry template or a repository URL
+//!
+//! [defaults]
+//! default_branch = "main"
+//! ```
+//!
+//! Top-level keys (before any table): `editor = "code"` for `--open`; `protocol = "ssh"` (or
+//! `"https"`) to clone templates over that protocol, limited to `protocol_hosts = ["github.com"]`
+//! if given.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Seconds a fetched team configuration is used before it is fetched again.
+const DEFAULT_REMOTE_TTL: u64 = 3600;
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub network: NetworkConfig,
+    pub cache: CacheConfig,
+    pub merge: MergeConfig,
+    pub prompts: PromptsConfig,
+    /// Template registries consulted in addition to `--templates`.
+    pub registries: Vec<RegistryConfig>,
+    /// Endpoints POSTed a JSON payload when a scaffold completes.
+    pub webhooks: Vec<WebhookConfig>,
+    /// Favorite templates, listed first in the picker and usable by name.
+    pub favorites: Vec<FavoriteConfig>,
+    /// Short names for templates: alias → registry template name or repository URL.
+    pub aliases: BTreeMap<String, String>,
+    /// Command that `--open` runs on a new project, e.g. `code` or `idea`; `VISUAL` / `EDITOR`
+    /// if unset.
+    pub editor: Option<String>,
+    /// Clone templates over `ssh` or `https`, rewriting their URLs as needed.
+    pub protocol: Option<String>,
+    /// Hosts the protocol preference applies to; all when empty.
+    pub protocol_hosts: Vec<String>,
+    pub defaults: DefaultsConfig,
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct DefaultsConfig {
+    /// Branch of the initial commit in new projects, e.g. `main`.
+    pub default_branch: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct FavoriteConfig {
+    /// Short name, e.g. `rust-api`.
+    pub name: String,
+    pub url: String,
+    /// Exact commit, when the template was pinned where it was added from.
+    pub commit: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct RegistryConfig {
+    pub name: String,
+    /// Folder, git repo, HTTP base URL or `gh-org:` spec, as accepted by `--templates`.
+    pub source: String,
+    /// Templates from registries with a higher priority shadow those with the same name; the
+    /// `--templates` source has priority 0.
+    pub priority: i32,
+    /// Bearer token sent when fetching the registry over HTTP(S); prefer `token_env`.
+    pub token: Option<String>,
+    /// Environment variable holding the bearer token.
+    pub token_env: Option<String>,
+    /// Basic auth user, with `password` or `password_env`.
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub password_env: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Environment variable holding a bearer token for the webhook.
+    pub token_env: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Extra PEM CA certificates trusted for HTTPS (overridden by `LISCAF_CA_BUNDLE`).
+    pub ca_bundle: Option<PathBuf>,
+    /// Seconds to wait for a connection (default 15).
+    pub connect_timeout: Option<u64>,
+    /// Seconds a download may stall before it is aborted (default 60).
+    pub read_timeout: Option<u64>,
+    /// Additional attempts for failed fetches and clones (default 2).
+    pub retries: Option<u32>,
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Size the template cache is kept under, e.g. `5G` (default), or `unlimited`
+    /// (overridden by `LISCAF_CACHE_MAX_SIZE`).
+    pub max_size: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct PromptsConfig {
+    /// Seconds a prompt waits for an answer (overridden by `--prompt-timeout`); no limit if unset.
+    pub timeout: Option<u64>,
+    /// `abort` (default) or `default`, to take the prompt's default answer when it times out.
+    pub on_timeout: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct MergeConfig {
+    /// Label of the destination's side of a conflict (default `EXISTING`).
+    pub ours_label: Option<String>,
+    /// Label of the template's side (default `TEMPLATE`).
+    pub theirs_label: Option<String>,
+}
+
+/// The team configuration URL, when `LISCAF_CONFIG` is one.
+fn remote_url() -> Option<String> {
+    let value = std::env::var("LISCAF_CONFIG").ok()?;
+    let lowered = value.to_lowercase();
+    (lowered.starts_with("https://") || lowered.starts_with("http://")).then_some(value)
+}
+
+/// The local configuration file; the default location when `LISCAF_CONFIG` is a URL.
+pub fn config_path() -> Option<PathBuf> {
+    let from_env = |key: &str| std::env::var_os(key).filter(|v| !v.is_empty()).map(PathBuf::from);
+    if let Some(path) = from_env("LISCAF_CONFIG").filter(|_| remote_url().is_none()) {
+        return Some(path);
+    }
+    if let Some(dir) = from_env("XDG_CONFIG_HOME") {
+        return Some(dir.join("liscaf").join("config.toml"));
+    }
+    if cfg!(windows) {
+        if let Some(dir) = from_env("APPDATA") {
+            return Some(dir.join("liscaf").join("config.toml"));
+        }
+    }
+    from_env("HOME").map(|home| home.join(".config").join("liscaf").join("config.toml"))
+}
+
+/// Directory for files liscaf keeps next to the configuration, such as the recent templates.
+pub fn config_dir() -> Option<PathBuf> {
+    config_path()?.parent().map(PathBuf::from)
+}
+
+/// The user configuration, or defaults when there is no config file.
+pub fn load() -> anyhow::Result<Config> {
+    let Some(path) = config_path().filter(|p| p.is_file()) else {
+        return Ok(Config::default());
+    };
+    let content = fs::read_to_string(&path)?;
+    parse(&content, &path.display().to_string())
+}
+
+/// Adds the team configuration when `LISCAF_CONFIG` is a URL: its registries, favorites and
+/// aliases come first and its defaults replace the local ones. Network settings stay local, since
+/// they are needed to fetch it, and so does the cache size.
+pub fn apply_remote(config: &mut Config) -> anyhow::Result<()> {
+    let Some(url) = remote_url() else {
+        return Ok(());
+    };
+    let remote = parse(&fetch_remote(&url)?, &url)?;
+    config.registries.splice(0..0, remote.registries);
+    config.webhooks.splice(0..0, remote.webhooks);
+    config.favorites.splice(0..0, remote.favorites);
+    for (alias, template) in remote.aliases {
+        config.aliases.entry(alias).or_insert(template);
+    }
+    if config.editor.is_none() {
+        config.editor = remote.editor;
+    }
+    if config.protocol.is_none() {
+        config.protocol = remote.protocol;
+        config.protocol_hosts = remote.protocol_hosts;
+    }
+    let merge = &mut config.merge;
+    merge.ours_label = merge.ours_label.take().or(remote.merge.ours_label);
+    merge.theirs_label = merge.theirs_label.take().or(remote.merge.theirs_label);
+    if remote.defaults.default_branch.is_some() {
+        config.defaults.default_branch = remote.defaults.default_branch;
+    }
+    Ok(())
+}
+
+/// The cached copy while it is younger than the TTL, else a fresh one; a stale copy when the
+/// fetch fails.
+fn fetch_remote(url: &str) -> anyhow::Result<String> {
+    let cached = crate::cache::remote_config_path(url);
+    let ttl = std::env::var("LISCAF_CONFIG_TTL")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_REMOTE_TTL);
+    let age = fs::metadata(&cached)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok());
+    if age.is_some_and(|age| age < Duration::from_secs(ttl)) {
+        if let Ok(content) = fs::read_to_string(&cached) {
+            return Ok(content);
+        }
+    }
+    let auth = std::env::var("LISCAF_CONFIG_TOKEN")
+        .ok()
+        .filter(|t| !t.is_empty())
+        .map(crate::net::Auth::Bearer);
+    match crate::net::get_text(url, auth.as_ref()) {
+        Ok(content) => {
+            if let Some(parent) = cached.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&cached, &content)?;
+            Ok(content)
+        }
+        Err(e) => match fs::read_to_string(&cached) {
+            Ok(content) => {
+                println!("Warning: using the cached team configuration ({})", e);
+                Ok(content)
+            }
+            Err(_) => Err(e),
+        },
+    }
+}
+
+fn parse(content: &str, path: &str) -> anyhow::Result<Config> {
+    let config: Config = toml::from_str(content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path, e))?;
+    for registry in &config.registries {
+        if registry.name.is_empty() || registry.source.is_empty() {
+            anyhow::bail!("Every [[registries]] entry in {} needs a name and a source", path);
+        }
+    }
+    for favorite in &config.favorites {
+        if favorite.name.is_empty() || favorite.url.is_empty() {
+            anyhow::bail!("Every [[favorites]] entry in {} needs a name and a url", path);
+        }
+    }
+    if let Some(ref protocol) = config.protocol {
+        if protocol != "ssh" && protocol != "https" {
+            anyhow::bail!("`protocol` in {} must be \"ssh\" or \"https\", not \"{}\"", path, protocol);
+        }
+    }
+    Ok(config)
+}
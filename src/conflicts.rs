@@ -0,0 +1,173 @@
+//! Conflict hunks for merges into existing files, in the format `git merge-file` writes, so
+//! editors and `git mergetool` recognize them. liscaf merges without a common ancestor, like
+//! git does for a file both sides added: every differing region becomes a hunk of its own, with
+//! the lines both sides share (also when they only differ in a missing final newline) kept out
+//! of it. Without a base there is no `|||||||` section, whatever `merge.conflictStyle` says.
+//!
+//! The labels after the markers can be configured:
+//!
+//! ```toml
+//! [merge]
+//! ours_label = "EXISTING"    # the file in the destination
+//! theirs_label = "TEMPLATE"  # the freshly scaffolded file
+//! ```
+use std::path::Path;
+use std::sync::OnceLock;
+
+use similar::{ChangeTag, TextDiff};
+
+use crate::config::MergeConfig;
+
+const MARKER_LEN: usize = 7;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Style {
+    pub ours: String,
+    pub theirs: String,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            ours: "EXISTING".to_string(),
+            theirs: "TEMPLATE".to_string(),
+        }
+    }
+}
+
+static CONFIG: OnceLock<MergeConfig> = OnceLock::new();
+
+/// Applies the `[merge]` configuration; call once at startup.
+pub fn configure(config: &MergeConfig) {
+    let _ = CONFIG.set(config.clone());
+}
+
+/// The configured labels, else the defaults.
+pub fn style() -> Style {
+    let config = CONFIG.get().cloned().unwrap_or_default();
+    let defaults = Style::default();
+    Style {
+        ours: config.ours_label.unwrap_or(defaults.ours),
+        theirs: config.theirs_label.unwrap_or(defaults.theirs),
+    }
+}
+
+enum Segment {
+    Same(String),
+    Conflict { ours: String, theirs: String },
+}
+
+/// `ours` with a conflict hunk wherever `theirs` differs.
+pub fn merge(ours: &str, theirs: &str, style: &Style) -> String {
+    let diff = TextDiff::from_lines(ours, theirs);
+    let mut segments: Vec<Segment> = Vec::new();
+    for change in diff.iter_all_changes() {
+        let value = change.value();
+        match (change.tag(), segments.last_mut()) {
+            (ChangeTag::Equal, Some(Segment::Same(text))) => text.push_str(value),
+            (ChangeTag::Equal, _) => segments.push(Segment::Same(value.to_string())),
+            (ChangeTag::Delete, Some(Segment::Conflict { ours, .. })) => ours.push_str(value),
+            (ChangeTag::Insert, Some(Segment::Conflict { theirs, .. })) => theirs.push_str(value),
+            (ChangeTag::Delete, _) => segments.push(Segment::Conflict {
+                ours: value.to_string(),
+                theirs: String::new(),
+            }),
+            (ChangeTag::Insert, _) => segments.push(Segment::Conflict {
+                ours: String::new(),
+                theirs: value.to_string(),
+            }),
+        }
+    }
+
+    let mut out = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Same(text) => out.push_str(&text),
+            Segment::Conflict { ours, theirs } => write_conflict(&mut out, &ours, &theirs, style),
+        }
+    }
+    out
+}
+
+/// Writes a differing region as a hunk, leaving the lines it starts and ends with on both sides
+/// outside of it (in `ours`' version).
+fn write_conflict(out: &mut String, ours: &str, theirs: &str, style: &Style) {
+    let ours: Vec<&str> = ours.split_inclusive('\n').collect();
+    let theirs: Vec<&str> = theirs.split_inclusive('\n').collect();
+    let same = |a: &&str, b: &&str| a.strip_suffix('\n').unwrap_or(a) == b.strip_suffix('\n').unwrap_or(b);
+    let prefix = ours.iter().zip(&theirs).take_while(|(a, b)| same(a, b)).count();
+    let suffix = ours[prefix..]
+        .iter()
+        .rev()
+        .zip(theirs[prefix..].iter().rev())
+        .take_while(|(a, b)| same(a, b))
+        .count();
+    out.extend(ours[..prefix].iter().copied());
+    let (ours_rest, theirs_rest) = (&ours[prefix..ours.len() - suffix], &theirs[prefix..theirs.len() - suffix]);
+    if !ours_rest.is_empty() || !theirs_rest.is_empty() {
+        write_hunk(out, &ours_rest.concat(), &theirs_rest.concat(), style);
+    }
+    out.extend(ours[ours.len() - suffix..].iter().copied());
+}
+
+fn write_hunk(out: &mut String, ours: &str, theirs: &str, style: &Style) {
+    let marker = |c: char, label: &str| format!("{} {}\n", c.to_string().repeat(MARKER_LEN), label);
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(&marker('<', &style.ours));
+    push_lines(out, ours);
+    out.push_str(&"=".repeat(MARKER_LEN));
+    out.push('\n');
+    push_lines(out, theirs);
+    out.push_str(&marker('>', &style.theirs));
+}
+
+/// Appends `text`, ending it with a newline so the next marker starts its own line.
+fn push_lines(out: &mut String, text: &str) {
+    out.push_str(text);
+    if !text.is_empty() && !text.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// The note written next to a binary file that could not be merged.
+pub fn binary_note(existing: &Path, incoming: &Path, style: &Style) -> String {
+    let mut note = String::new();
+    write_hunk(
+        &mut note,
+        &format!("(binary file kept at {})", existing.display()),
+        &format!("(binary incoming saved at {})", incoming.display()),
+        style,
+    );
+    note
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conflicts_are_git_hunks_with_configured_labels() {
+        let style = Style {
+            ours: "ours".to_string(),
+            ..Default::default()
+        };
+        let merged = merge("a\nmine\n}\nx\nend", "a\ntheirs\n}\ny\nend\n", &style);
+        assert_eq!(
+            merged,
+            "a\n<<<<<<< ours\nmine\n=======\ntheirs\n>>>>>>> TEMPLATE\n}\n<<<<<<< ours\nx\n=======\ny\n>>>>>>> TEMPLATE\nend"
+        );
+        let plain = merge("a\nmine\nb\n", "a\ntheirs\nb\n", &Style::default());
+        assert_eq!(plain, "a\n<<<<<<< EXISTING\nmine\n=======\ntheirs\n>>>>>>> TEMPLATE\nb\n");
+    }
+
+    #[test]
+    fn lines_differing_only_in_the_final_newline_stay_out_of_the_hunk() {
+        assert_eq!(merge("a\nb", "a\nb\n", &Style::default()), "a\nb");
+        assert_eq!(
+            merge("a\nb", "a\nb\nc\n", &Style::default()),
+            "a\nb\n<<<<<<< EXISTING\n=======\nc\n>>>>>>> TEMPLATE\n"
+        );
+    }
+}
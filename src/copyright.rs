@@ -0,0 +1,98 @@
+//! Refreshes copyright notices (`Copyright (c) 2019-2023 Acme Corp.`) in the generated project:
+//! the years become the current `year` and the holder the `copyright_holder` variable, else the
+//! `author`. Without either only the years change.
+//!
+//! Notices inside code (string literals, markup) are left alone. The manifest's
+//! `[copyright] holders` limits this to notices naming the template's own holders,
+//! so third-party notices in bundled code stay as they are.
+use std::path::Path;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::filter::PathFilter;
+use crate::scaffold_fs::ScaffoldFs;
+
+fn notice() -> &'static Regex {
+    static NOTICE: OnceLock<Regex> = OnceLock::new();
+    NOTICE.get_or_init(|| {
+        Regex::new(
+            r#"(?mi)^(?P<pre>[^\n]*?\bcopyright(?:[ \t]*(?:\(c\)|©))?[ \t]+)(?P<years>\d{4}(?:[ \t]*(?:-|–|,)[ \t]*(?:\d{4}|present))*)(?P<sep>,?[ \t]+)(?P<holder>(?:[^\n`"<>{}\[\];]|<[^<>\n]*@[^<>\n]*>)*?)(?P<post>\.?(?:[ \t]*all rights reserved\.?)?[ \t]*(?:\*/|-->)?[ \t\r]*)$"#,
+        )
+        .expect("valid copyright pattern")
+    })
+}
+
+/// `text` with its copyright notices refreshed, or `None` if none changed. With `holders` set
+/// only notices whose holder contains one of them (ignoring case) are touched.
+pub fn refresh_text(text: &str, year: &str, holder: Option<&str>, holders: &[String]) -> Option<String> {
+    let mut changed = false;
+    let refreshed = notice().replace_all(text, |caps: &regex::Captures| {
+        let old_holder = &caps["holder"];
+        let matches_template = holders.is_empty()
+            || holders
+                .iter()
+                .any(|h| old_holder.to_lowercase().contains(&h.to_lowercase()));
+        if !matches_template || old_holder.trim().is_empty() {
+            return caps[0].to_string();
+        }
+        let line = format!(
+            "{}{}{}{}{}",
+            &caps["pre"],
+            year,
+            &caps["sep"],
+            holder.unwrap_or(old_holder),
+            &caps["post"]
+        );
+        changed |= line != caps[0];
+        line
+    });
+    changed.then(|| refreshed.into_owned())
+}
+
+/// Refreshes the notices in the text files below `root`; returns the number of files changed.
+/// Writes go to `fs` in dry runs too.
+pub fn refresh(
+    fs: &dyn ScaffoldFs,
+    root: &Path,
+    filter: &PathFilter,
+    vars: &std::collections::BTreeMap<String, String>,
+    holders: &[String],
+    dry_run: bool,
+) -> anyhow::Result<usize> {
+    let Some(year) = vars.get("year") else {
+        return Ok(0);
+    };
+    let holder = vars
+        .get("copyright_holder")
+        .or_else(|| vars.get("author"))
+        .map(String::as_str)
+        .filter(|h| !h.is_empty());
+    let mut updated = 0;
+    let entries = fs.walk(root, &Default::default(), &mut |e| {
+        e.depth == 0 || !e.path.file_name().is_some_and(|n| n == ".git" || filter.skips_dir(n))
+    });
+    for entry in entries {
+        let path = entry.path.as_path();
+        if !entry.is_file || path.file_name() == Some(".git".as_ref()) || !filter.allows(root, path) {
+            continue;
+        }
+        if filter.exceeds_size_limit(entry.len) {
+            continue;
+        }
+        let Ok(content) = fs.read_to_string(path) else {
+            continue;
+        };
+        let Some(refreshed) = refresh_text(&content, year, holder, holders) else {
+            continue;
+        };
+        fs.write(path, refreshed.as_bytes())?;
+        if dry_run {
+            println!("DRY COPYRIGHT: Would refresh notice in {}", path.display());
+        } else {
+            println!("COPYRIGHT: Refreshed notice in {}", path.display());
+        }
+        updated += 1;
+    }
+    Ok(updated)
+}
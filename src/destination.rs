@@ -0,0 +1,117 @@
+//! The destination prompt of interactive scaffolds: where the new project goes, defaulting to
+//! `./<name>`, with tab completion of directories. The answer must name a directory that does not
+//! exist yet, in a parent that does.
+use std::path::{Path, PathBuf};
+
+use inquire::autocompletion::{Autocomplete, Replacement};
+use inquire::validator::Validation;
+use inquire::{CustomUserError, Text};
+
+use crate::i18n::tr;
+use crate::prompt::Ask;
+
+/// Directories below the part of the input typed so far.
+#[derive(Clone, Default)]
+struct DirCompleter;
+
+impl DirCompleter {
+    fn candidates(input: &str) -> Vec<String> {
+        let (dir, prefix) = match input.rfind(std::path::MAIN_SEPARATOR).or_else(|| input.rfind('/')) {
+            Some(at) => (&input[..=at], &input[at + 1..]),
+            None => ("", input),
+        };
+        let listed = if dir.is_empty() { Path::new(".") } else { Path::new(dir) };
+        let Ok(entries) = std::fs::read_dir(expand_home(listed)) else {
+            return Vec::new();
+        };
+        let mut found: Vec<String> = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_dir())
+            .filter_map(|e| e.file_name().to_str().map(str::to_string))
+            // Hidden directories only when asked for
+            .filter(|name| name.starts_with(prefix) && (prefix.starts_with('.') || !name.starts_with('.')))
+            .map(|name| format!("{}{}/", dir, name))
+            .collect();
+        found.sort();
+        found
+    }
+}
+
+impl Autocomplete for DirCompleter {
+    fn get_suggestions(&mut self, input: &str) -> Result<Vec<String>, CustomUserError> {
+        Ok(Self::candidates(input))
+    }
+
+    fn get_completion(
+        &mut self,
+        input: &str,
+        highlighted_suggestion: Option<String>,
+    ) -> Result<Replacement, CustomUserError> {
+        if highlighted_suggestion.is_some() {
+            return Ok(highlighted_suggestion);
+        }
+        // Complete to the longest prefix all candidates share
+        let candidates = Self::candidates(input);
+        let Some(first) = candidates.first() else {
+            return Ok(None);
+        };
+        let common = candidates.iter().fold(first.as_str(), |common, c| {
+            let len = common
+                .char_indices()
+                .zip(c.chars())
+                .take_while(|((_, a), b)| a == b)
+                .last()
+                .map_or(0, |((i, a), _)| i + a.len_utf8());
+            &common[..len]
+        });
+        Ok((common.len() > input.len()).then(|| common.to_string()))
+    }
+}
+
+/// `~/x` with the home directory spelled out.
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Why `path` cannot be the destination, if it cannot.
+fn problem(path: &Path) -> Option<String> {
+    if path.as_os_str().is_empty() {
+        return Some(tr!("Enter a directory").to_string());
+    }
+    if path.exists() {
+        return Some(tr!("{} already exists", path.display()));
+    }
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    if !parent.is_dir() {
+        return Some(tr!("{} is not an existing directory", parent.display()));
+    }
+    None
+}
+
+/// Asks where to create the project `new_name`.
+pub fn prompt(new_name: &str) -> anyhow::Result<PathBuf> {
+    let default = format!("./{}", new_name);
+    let answer = Text::new("Destination directory:")
+        .with_default(&default)
+        .with_autocomplete(DirCompleter)
+        .with_help_message("tab completes directories")
+        .with_validator(|input: &str| {
+            Ok(match problem(&expand_home(Path::new(input.trim()))) {
+                Some(problem) => Validation::Invalid(problem.into()),
+                None => Validation::Valid,
+            })
+        })
+        .ask()?;
+    let dest = expand_home(Path::new(answer.trim()));
+    Ok(if dest.is_absolute() {
+        dest
+    } else {
+        std::env::current_dir()?.join(dest)
+    })
+}
@@ -0,0 +1,78 @@
+//! Free-space checks before large copies, so a full disk stops a scaffold with a clear message
+//! before anything is written instead of halfway through a copy.
+use std::path::Path;
+
+use walkdir::WalkDir;
+
+/// Total size of the files below `dir`.
+pub fn dir_size(dir: &Path) -> u64 {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Fails if the volume that holds (or will hold) `path` has less than `needed` bytes available.
+/// `what` names the data in the message. Volumes whose free space cannot be determined pass.
+pub fn ensure(path: &Path, needed: u64, what: &str) -> anyhow::Result<()> {
+    let Some(existing) = path.ancestors().find(|p| p.exists()) else {
+        return Ok(());
+    };
+    let Some(available) = available(existing) else {
+        return Ok(());
+    };
+    if available < needed {
+        anyhow::bail!(
+            "Not enough disk space for the {} on {}: about {} needed, {} available",
+            what,
+            existing.display(),
+            crate::filter::format_size(needed),
+            crate::filter::format_size(available)
+        );
+    }
+    Ok(())
+}
+
+/// Whether `a` and `b` (or their nearest existing ancestors) are on the same volume, so moving
+/// between them is a rename that needs no space.
+#[cfg(unix)]
+pub fn same_volume(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let device = |path: &Path| {
+        let existing = path.ancestors().find(|p| p.exists())?;
+        std::fs::metadata(existing).ok().map(|m| m.dev())
+    };
+    matches!((device(a), device(b)), (Some(x), Some(y)) if x == y)
+}
+
+#[cfg(not(unix))]
+pub fn same_volume(_a: &Path, _b: &Path) -> bool {
+    false
+}
+
+/// Bytes available to unprivileged users on the volume holding `path`.
+#[cfg(unix)]
+fn available(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: `path` is NUL-terminated and `stat` is a plain struct statvfs fills in.
+    let stat = unsafe {
+        let mut stat: libc::statvfs = std::mem::zeroed();
+        if libc::statvfs(path.as_ptr(), &mut stat) != 0 {
+            return None;
+        }
+        stat
+    };
+    #[allow(clippy::unnecessary_cast)] // the field types differ between platforms
+    Some((stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64))
+}
+
+#[cfg(not(unix))]
+fn available(_path: &Path) -> Option<u64> {
+    None
+}
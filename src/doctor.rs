@@ -0,0 +1,260 @@
+//! `liscaf doctor`: checks the environment liscaf depends on and prints a fix for every problem.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::registry::Registry;
+use crate::TemplateEntry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+struct Report {
+    problems: usize,
+}
+
+impl Report {
+    fn line(&mut self, status: Status, check: &str, detail: &str, fix: Option<&str>) {
+        let label = match status {
+            Status::Ok => "OK  ",
+            Status::Warn => "WARN",
+            Status::Fail => "FAIL",
+        };
+        println!("{}  {}: {}", label, check, detail);
+        if let Some(fix) = fix.filter(|_| status != Status::Ok) {
+            println!("      fix: {}", fix);
+        }
+        if status == Status::Fail {
+            self.problems += 1;
+        }
+    }
+}
+
+pub fn run_doctor(registries: &[Registry], tmpdir: Option<&Path>) -> anyhow::Result<()> {
+    let mut report = Report { problems: 0 };
+
+    let git_ok = check_git(&mut report);
+    if git_ok {
+        check_git_identity(&mut report);
+    }
+    check_temp_dir(&mut report, tmpdir);
+    check_cache_dir(&mut report);
+    match crate::net::proxy_in_use() {
+        Some(proxy) => {
+            let no_proxy = std::env::var("NO_PROXY")
+                .or_else(|_| std::env::var("no_proxy"))
+                .unwrap_or_default();
+            report.line(Status::Ok, "proxy", &format!("{} (NO_PROXY: {})", proxy, no_proxy), None);
+        }
+        None => report.line(Status::Ok, "proxy", "none", None),
+    }
+    if let Some((path, count)) = crate::net::ca_bundle() {
+        report.line(
+            Status::Ok,
+            "CA bundle",
+            &format!("{} ({} certificates)", path.display(), count),
+            None,
+        );
+    }
+    let mut entries = Vec::new();
+    for registry in registries {
+        entries.extend(check_templates_source(&mut report, registry));
+    }
+    if git_ok {
+        check_credentials(&mut report, &entries);
+    }
+
+    if report.problems > 0 {
+        anyhow::bail!("{} problem(s) found", report.problems);
+    }
+    println!("No problems found");
+    Ok(())
+}
+
+fn check_git(report: &mut Report) -> bool {
+    match Command::new("git").arg("--version").output() {
+        Ok(out) if out.status.success() => {
+            let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+            report.line(Status::Ok, "git", &version, None);
+            true
+        }
+        _ => {
+            report.line(
+                Status::Fail,
+                "git",
+                "not found in PATH",
+                Some("install git (https://git-scm.com/downloads) and make sure it is on PATH"),
+            );
+            false
+        }
+    }
+}
+
+fn check_git_identity(report: &mut Report) {
+    for key in ["user.name", "user.email"] {
+        match crate::variables::git_config_value(key) {
+            Some(value) => report.line(Status::Ok, key, &value, None),
+            None => report.line(
+                Status::Warn,
+                key,
+                "not set; the initial commit of a new project will fail",
+                Some(&format!("git config --global {} <value>", key)),
+            ),
+        }
+    }
+}
+
+fn check_temp_dir(report: &mut Report, tmpdir: Option<&Path>) {
+    let dir = tmpdir.map(Path::to_path_buf).unwrap_or_else(std::env::temp_dir);
+    let check = format!("temp directory {}", dir.display());
+    match crate::make_tempdir("liscaf-doctor-", Some(&dir)) {
+        Ok(probe) => {
+            let writable = fs::write(probe.path().join("probe"), b"ok").is_ok();
+            if writable {
+                report.line(Status::Ok, &check, "writable", None);
+            } else {
+                report.line(
+                    Status::Fail,
+                    &check,
+                    "not writable",
+                    Some("point --tmpdir / LISCAF_TMPDIR at a writable directory"),
+                );
+            }
+        }
+        Err(e) => report.line(
+            Status::Fail,
+            &check,
+            &e.to_string(),
+            Some("point --tmpdir / LISCAF_TMPDIR at a writable directory"),
+        ),
+    }
+}
+
+fn check_cache_dir(report: &mut Report) {
+    let dir = crate::cache::cache_dir();
+    let check = format!("template cache {}", dir.display());
+    let fix = "set LISCAF_CACHE_DIR to a writable directory, or run `liscaf cache clean`";
+    if let Err(e) = fs::create_dir_all(&dir) {
+        report.line(Status::Fail, &check, &e.to_string(), Some(fix));
+        return;
+    }
+    let probe = dir.join(".doctor-probe");
+    if let Err(e) = fs::write(&probe, b"ok") {
+        report.line(Status::Fail, &check, &format!("not writable: {}", e), Some(fix));
+        return;
+    }
+    let _ = fs::remove_file(&probe);
+    match crate::cache::entries() {
+        Ok(entries) => {
+            report.line(
+                Status::Ok,
+                &check,
+                &format!(
+                    "{} templates, {} on disk",
+                    entries.len(),
+                    crate::filter::format_size(crate::cache::disk_usage())
+                ),
+                None,
+            );
+        }
+        Err(e) => report.line(Status::Fail, &check, &e.to_string(), Some(fix)),
+    }
+}
+
+fn check_templates_source(report: &mut Report, registry: &Registry) -> Vec<TemplateEntry> {
+    let check = if registry.name == crate::registry::DEFAULT_NAME {
+        format!("templates source {}", registry.source)
+    } else {
+        format!("registry {} ({})", registry.name, registry.source)
+    };
+    match crate::load_template_entries(&registry.source, registry.auth.as_ref()) {
+        Ok(entries) if entries.is_empty() => {
+            report.line(
+                Status::Warn,
+                &check,
+                "reachable but lists no templates",
+                Some("add entries to repositories.yaml"),
+            );
+            entries
+        }
+        Ok(entries) => {
+            report.line(Status::Ok, &check, &format!("{} templates", entries.len()), None);
+            entries
+        }
+        Err(e) => {
+            let message = e.to_string();
+            let fix = if message.contains("certificate") {
+                "trust the issuing CA with LISCAF_CA_BUNDLE or `ca_bundle` under [network] in the \
+                 liscaf config"
+            } else if message.contains("401") || message.contains("403") {
+                "set `token_env` (or `username` and `password_env`) for this registry in the \
+                 liscaf config, or LISCAF_REGISTRY_<NAME>_TOKEN"
+            } else {
+                "check network access (proxy, VPN) or set --templates / LISCAF_TEMPLATES to a \
+                 reachable folder, repo or URL"
+            };
+            report.line(Status::Fail, &check, &message, Some(fix));
+            Vec::new()
+        }
+    }
+}
+
+/// Probes one template per host with `git ls-remote`, without any interactive prompts.
+fn check_credentials(report: &mut Report, entries: &[TemplateEntry]) {
+    let mut by_host: BTreeMap<String, &TemplateEntry> = BTreeMap::new();
+    for entry in entries {
+        if let Some(host) = repo_host(&entry.url) {
+            by_host.entry(host).or_insert(entry);
+        }
+    }
+    for (host, entry) in by_host {
+        let check = format!("access to {}", host);
+        let url = crate::protocol::rewrite(&entry.url);
+        let ssh = !url.starts_with("http://") && !url.starts_with("https://");
+        let status = crate::net::git()
+            .args(["ls-remote", "--heads", &url])
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        match status {
+            Ok(s) if s.success() => report.line(Status::Ok, &check, &url, None),
+            _ if ssh => report.line(
+                Status::Fail,
+                &check,
+                &format!("cannot read {} over SSH", url),
+                Some("load a key into ssh-agent (ssh-add) and register it with the host"),
+            ),
+            _ => report.line(
+                Status::Fail,
+                &check,
+                &format!("cannot read {}", url),
+                Some(
+                    "for private repositories configure a git credential helper \
+                     (git config --global credential.helper ...) or log in with the host's CLI",
+                ),
+            ),
+        }
+    }
+}
+
+/// Host of an https, ssh:// or SCP-like (`git@host:owner/repo`) URL.
+fn repo_host(url: &str) -> Option<String> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .or_else(|| url.strip_prefix("ssh://"));
+    let authority = match rest {
+        Some(rest) => rest.split('/').next()?,
+        None => url.split(':').next()?,
+    };
+    let host = authority.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+    (!host.is_empty()).then(|| host.to_lowercase())
+}
@@ -0,0 +1,117 @@
+//! Replacement inside zip-container documents (`.docx`, `.xlsx`, `.pptx`, OpenDocument), enabled
+//! with `--documents` or `documents = true` under `[mappings]`. The text parts (XML, relationships)
+//! are rewritten and the container is repacked; every other entry is copied byte for byte.
+//!
+//! Only plain zip archives are handled (see [`crate::zip`]): encrypted entries are copied as they
+//! are and Zip64 archives leave the document untouched. Word processors may split a name across
+//! formatting runs (`<w:t>acme</w:t><w:t>-app`), which no textual replacement can see.
+use std::path::Path;
+
+use crate::zip;
+
+/// Extensions of the zip-based office formats.
+const EXTENSIONS: &[&str] = &[
+    "docx", "docm", "dotx", "xlsx", "xlsm", "xltx", "pptx", "pptm", "potx", "odt", "ods", "odp", "odg",
+];
+
+/// Largest text part that is unpacked; larger ones are copied as they are.
+const MAX_PART_SIZE: u32 = 64 * 1024 * 1024;
+
+/// Entries rewritten inside a document; everything else is copied as is.
+const TEXT_PARTS: &[&str] = &["xml", "rels", "txt", "xhtml", "html"];
+
+pub fn is_document(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| EXTENSIONS.iter().any(|x| x.eq_ignore_ascii_case(e)))
+}
+
+/// The document with `mappings` applied to its text parts, or `None` if nothing changed or the
+/// container cannot be rewritten safely.
+pub fn replace(bytes: &[u8], mappings: &[(String, String)]) -> Option<Vec<u8>> {
+    let (mut entries, archive_comment) = zip::read(bytes)?;
+    let mut changed = false;
+    for entry in &mut entries {
+        let is_text = std::str::from_utf8(&entry.name)
+            .ok()
+            .and_then(|n| n.rsplit_once('.'))
+            .is_some_and(|(_, ext)| TEXT_PARTS.contains(&ext.to_ascii_lowercase().as_str()));
+        if !is_text {
+            continue;
+        }
+        let Some(text) = entry.contents(MAX_PART_SIZE).and_then(|plain| String::from_utf8(plain).ok()) else {
+            continue;
+        };
+        let replaced = crate::apply_mappings(&text, mappings);
+        if replaced == text {
+            continue;
+        }
+        entry.set_contents(replaced.as_bytes()).ok()?;
+        changed = true;
+    }
+    if !changed {
+        return None;
+    }
+    let mut writer = zip::Writer::new(Vec::new());
+    for entry in &entries {
+        writer.add(entry).ok()?;
+    }
+    writer.finish(&archive_comment).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn documents_are_repacked_with_consistent_records() {
+        let part = |name: &str, text: &str, deflate: bool| {
+            zip::Entry::new(name, text.as_bytes(), deflate, (0, 0x21), 20, 0).unwrap()
+        };
+        let mut writer = zip::Writer::new(Vec::new());
+        writer.add(&part("word/document.xml", "<w:t>acme-app and acme-app</w:t>", true)).unwrap();
+        writer.add(&part("docProps/app.xml", "<Company>acme-app</Company>", false)).unwrap();
+        writer.add(&part("media/logo.bin", "acme-app", false)).unwrap();
+        let container = writer.finish(b"").unwrap();
+
+        let mappings = vec![("acme-app".to_string(), "zeta".to_string())];
+        let repacked = replace(&container, &mappings).unwrap();
+
+        // Walk the central directory by hand and check each record against its local header
+        let u16_at = |at: usize| u16::from_le_bytes([repacked[at], repacked[at + 1]]) as usize;
+        let u32_at = |at: usize| u32::from_le_bytes(repacked[at..at + 4].try_into().unwrap());
+        let end = repacked.len() - 22;
+        assert_eq!(u32_at(end), 0x0605_4b50);
+        assert_eq!(u16_at(end + 10), 3);
+        let mut at = u32_at(end + 16) as usize;
+        let mut contents = BTreeMap::new();
+        for _ in 0..3 {
+            assert_eq!(u32_at(at), 0x0201_4b50);
+            let (method, crc, compressed, size) = (u16_at(at + 10), u32_at(at + 16), u32_at(at + 20), u32_at(at + 24));
+            let name_len = u16_at(at + 28);
+            let name = String::from_utf8(repacked[at + 46..at + 46 + name_len].to_vec()).unwrap();
+            let offset = u32_at(at + 42) as usize;
+            assert_eq!(u32_at(offset), 0x0403_4b50, "{}", name);
+            assert_eq!((u32_at(offset + 14), u32_at(offset + 18), u32_at(offset + 22)), (crc, compressed, size));
+            let start = offset + 30 + u16_at(offset + 26) + u16_at(offset + 28);
+            let data = &repacked[start..start + compressed as usize];
+            let plain = match method {
+                0 => data.to_vec(),
+                _ => {
+                    let mut out = Vec::new();
+                    std::io::Read::read_to_end(&mut flate2::read::DeflateDecoder::new(data), &mut out).unwrap();
+                    out
+                }
+            };
+            assert_eq!((plain.len() as u32, crc32fast::hash(&plain)), (size, crc), "{}", name);
+            contents.insert(name, String::from_utf8(plain).unwrap());
+            at += 46 + name_len + u16_at(at + 30) + u16_at(at + 32);
+        }
+        assert_eq!(contents["word/document.xml"], "<w:t>zeta and zeta</w:t>");
+        assert_eq!(contents["docProps/app.xml"], "<Company>zeta</Company>");
+        assert_eq!(contents["media/logo.bin"], "acme-app");
+        // Parts are only unpacked up to a size limit
+        assert!(part("big.xml", "acme-app", true).contents(4).is_none());
+    }
+}
@@ -0,0 +1,115 @@
+//! Template inheritance: a template names the base it specializes in its manifest, so an
+//! organization can keep one base template and many thin variants of it:
+//!
+//! ```toml
+//! [extends]
+//! url = "https://github.com/acme/service-base"
+//! ref = "v2"   # branch, tag or full commit SHA; the default branch if unset
+//! ```
+//!
+//! A base may extend another template in turn. The file trees are layered with the most specific
+//! template on top: a file of the template replaces the base's file at the same path. Manifests
+//! are merged table by table, so a variant that sets only `default` in `[variables.database]`
+//! keeps the base's prompt and help; any other value the variant sets wins over the base's.
+use std::fs;
+use std::path::Path;
+
+use walkdir::WalkDir;
+
+use crate::manifest::{ExtendsSpec, MANIFEST_FILE};
+
+/// Longest chain of bases followed before giving up.
+const MAX_DEPTH: usize = 8;
+
+/// Layers the bases of the template in `root` under its files and writes the merged manifest;
+/// returns the bases, most specific first, as `url@ref`. Templates without `[extends]` are left
+/// alone.
+pub fn resolve(root: &Path, tmpdir: Option<&Path>, use_cache: bool) -> anyhow::Result<Vec<String>> {
+    let mut manifest = read_manifest(root)?;
+    let mut next = take_extends(&mut manifest, root)?;
+    let mut chain: Vec<String> = Vec::new();
+    while let Some(spec) = next {
+        let label = format!("{}@{}", spec.url, spec.git_ref.as_deref().unwrap_or("HEAD"));
+        if chain.contains(&label) {
+            anyhow::bail!("Template inheritance loops back to {}", label);
+        }
+        if chain.len() == MAX_DEPTH {
+            anyhow::bail!("Template inheritance is deeper than {} levels at {}", MAX_DEPTH, label);
+        }
+        println!("Extends {}", label);
+        let base = crate::make_tempdir("liscaf-base-", tmpdir)?;
+        crate::cache::checkout_ref(&spec.url, spec.git_ref.as_deref(), base.path(), use_cache)
+            .map_err(|e| anyhow::anyhow!("Failed to fetch base template {}: {}", label, e))?;
+        let mut base_manifest = read_manifest(base.path())?;
+        next = take_extends(&mut base_manifest, base.path())?;
+        layer_under(base.path(), root)?;
+        merge_tables(&mut manifest, base_manifest);
+        chain.push(label);
+    }
+    if !chain.is_empty() {
+        let merged = toml::to_string(&manifest)
+            .map_err(|e| anyhow::anyhow!("Failed to write the merged manifest: {}", e))?;
+        fs::write(root.join(MANIFEST_FILE), merged)?;
+    }
+    Ok(chain)
+}
+
+fn read_manifest(root: &Path) -> anyhow::Result<toml::Table> {
+    let path = root.join(MANIFEST_FILE);
+    if !path.exists() {
+        return Ok(toml::Table::new());
+    }
+    let content = fs::read_to_string(&path)?;
+    toml::from_str(&content).map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Removes `[extends]` from `manifest` and returns it.
+fn take_extends(manifest: &mut toml::Table, root: &Path) -> anyhow::Result<Option<ExtendsSpec>> {
+    let Some(value) = manifest.remove("extends") else {
+        return Ok(None);
+    };
+    let spec: ExtendsSpec = value.try_into().map_err(|e| {
+        anyhow::anyhow!("Invalid [extends] in {}: {}", root.join(MANIFEST_FILE).display(), e)
+    })?;
+    if spec.url.trim().is_empty() {
+        anyhow::bail!("[extends] in {} needs a url", root.join(MANIFEST_FILE).display());
+    }
+    Ok(Some(spec))
+}
+
+/// Copies the files of `base` that `root` does not have (the manifest is merged separately).
+fn layer_under(base: &Path, root: &Path) -> anyhow::Result<()> {
+    let mut walker = WalkDir::new(base).min_depth(1).into_iter();
+    while let Some(entry) = walker.next() {
+        let entry = entry?;
+        let rel = entry.path().strip_prefix(base)?;
+        if rel == Path::new(MANIFEST_FILE) || rel.components().any(|c| c.as_os_str() == ".git") {
+            continue;
+        }
+        let target = root.join(rel);
+        if entry.file_type().is_dir() {
+            if target.exists() && !target.is_dir() {
+                // The template replaced the whole directory with a file
+                walker.skip_current_dir();
+            } else {
+                fs::create_dir_all(&target)?;
+            }
+        } else if entry.file_type().is_file() && !target.exists() {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Adds what `base` has and `ours` lacks, descending into tables both have.
+pub fn merge_tables(ours: &mut toml::Table, base: toml::Table) {
+    for (key, value) in base {
+        match (ours.get_mut(&key), value) {
+            (Some(toml::Value::Table(ours)), toml::Value::Table(base)) => merge_tables(ours, base),
+            (Some(_), _) => {}
+            (None, value) => {
+                ours.insert(key, value);
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! External subcommands: like cargo and git, `liscaf foo` runs a `liscaf-foo` executable from
+//! PATH when `foo` is not a built-in command.
+//!
+//! The command gets its remaining arguments plus context in the environment: `LISCAF` (this
+//! binary), `LISCAF_VERSION`, and for a scaffolded project (found by walking up from the current
+//! directory to `.scaffold.json`) `LISCAF_PROJECT_DIR` and `LISCAF_METADATA`. `LISCAF_CONTEXT`
+//! carries all of it as JSON, including the recorded answers.
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const PREFIX: &str = "liscaf-";
+
+/// Version of the `LISCAF_CONTEXT` JSON layout.
+const CONTEXT_VERSION: u32 = 1;
+
+pub fn run(args: &[String], proxy: Option<&str>) -> anyhow::Result<()> {
+    let Some((name, rest)) = args.split_first() else {
+        anyhow::bail!("no command given");
+    };
+    let Some(program) = find(name) else {
+        let available = list();
+        anyhow::bail!(
+            "no such command: `{}` (no built-in command and no `{}{}` on PATH; installed external commands: {})",
+            name,
+            PREFIX,
+            name,
+            if available.is_empty() { "none".to_string() } else { available.join(", ") }
+        );
+    };
+
+    let exe = env::current_exe()?;
+    let project_dir = env::current_dir().ok().and_then(|cwd| find_project(&cwd));
+    let metadata = project_dir
+        .as_ref()
+        .and_then(|dir| std::fs::read_to_string(dir.join(".scaffold.json")).ok())
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok());
+    let context = serde_json::json!({
+        "version": CONTEXT_VERSION,
+        "liscaf": exe,
+        "liscaf_version": env!("CARGO_PKG_VERSION"),
+        "command": name,
+        "args": rest,
+        "project_dir": project_dir,
+        "metadata": metadata,
+    });
+
+    let mut command = Command::new(&program);
+    command
+        .args(rest)
+        .env("LISCAF", &exe)
+        .env("LISCAF_VERSION", env!("CARGO_PKG_VERSION"))
+        .env("LISCAF_CONTEXT", context.to_string());
+    if let Some(ref dir) = project_dir {
+        command
+            .env("LISCAF_PROJECT_DIR", dir)
+            .env("LISCAF_METADATA", dir.join(".scaffold.json"));
+    }
+    if let Some(proxy) = proxy {
+        command.env("LISCAF_PROXY", proxy);
+    }
+    let status = command
+        .status()
+        .map_err(|e| anyhow::anyhow!("Failed to run {}: {}", program.display(), e))?;
+    if !status.success() {
+        crate::logfile::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+/// Whether `liscaf <name>` has a `liscaf-<name>` executable to run.
+pub fn exists(name: &str) -> bool {
+    find(name).is_some()
+}
+
+/// The `liscaf-<name>` executable on PATH, if any.
+fn find(name: &str) -> Option<PathBuf> {
+    on_path(&format!("{}{}", PREFIX, name))
+}
+
+/// The executable `program` on PATH, if any.
+pub fn on_path(program: &str) -> Option<PathBuf> {
+    let file = format!("{}{}", program, env::consts::EXE_SUFFIX);
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(&file))
+        .find(|path| is_executable(path))
+}
+
+/// Names of all external commands on PATH, without the prefix.
+fn list() -> Vec<String> {
+    let Some(paths) = env::var_os("PATH") else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = env::split_paths(&paths)
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.filter_map(|e| e.ok()))
+        .filter(|entry| is_executable(&entry.path()))
+        .filter_map(|entry| {
+            let file = entry.file_name().to_string_lossy().into_owned();
+            let name = file.strip_prefix(PREFIX)?;
+            let name = name.strip_suffix(env::consts::EXE_SUFFIX).unwrap_or(name);
+            (!name.is_empty()).then(|| name.to_string())
+        })
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// The nearest directory at or above `start` that contains `.scaffold.json`.
+fn find_project(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| dir.join(".scaffold.json").is_file())
+        .map(Path::to_path_buf)
+}
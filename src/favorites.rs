@@ -0,0 +1,102 @@
+//! `liscaf fav add|remove|list`: favorite templates, stored as `[[favorites]]` in the user
+//! configuration. Favorites come first in the template picker and are found by their short name
+//! without loading any registry (`liscaf new rust-api payments-service`).
+use std::fs;
+
+use toml_edit::{value, ArrayOfTables, DocumentMut, Item, Table};
+
+use crate::config::{self, FavoriteConfig};
+use crate::TemplateEntry;
+
+impl FavoriteConfig {
+    pub fn entry(&self) -> TemplateEntry {
+        TemplateEntry {
+            name: self.name.clone(),
+            label: self.name.clone(),
+            url: self.url.clone(),
+            commit: self.commit.clone(),
+        }
+    }
+}
+
+/// The favorite called `name` (case-insensitive).
+pub fn find<'c>(favorites: &'c [FavoriteConfig], name: &str) -> Option<&'c FavoriteConfig> {
+    favorites.iter().find(|f| f.name.eq_ignore_ascii_case(name))
+}
+
+/// Adds the template (or replaces the favorite with the same short name).
+pub fn add(short_name: &str, entry: &TemplateEntry) -> anyhow::Result<()> {
+    let (path, mut doc) = load_document()?;
+    let favorites = favorites_table(&mut doc)?;
+    remove_named(favorites, short_name);
+    let mut table = Table::new();
+    table.insert("name", value(short_name));
+    table.insert("url", value(&entry.url));
+    if let Some(ref commit) = entry.commit {
+        table.insert("commit", value(commit));
+    }
+    favorites.push(table);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, doc.to_string())?;
+    println!("Added favorite '{}': {}", short_name, entry.url);
+    Ok(())
+}
+
+pub fn remove(short_name: &str) -> anyhow::Result<()> {
+    let (path, mut doc) = load_document()?;
+    let favorites = favorites_table(&mut doc)?;
+    if !remove_named(favorites, short_name) {
+        anyhow::bail!("No favorite named '{}'", short_name);
+    }
+    if favorites.is_empty() {
+        doc.remove("favorites");
+    }
+    fs::write(&path, doc.to_string())?;
+    println!("Removed favorite '{}'", short_name);
+    Ok(())
+}
+
+pub fn list(favorites: &[FavoriteConfig]) {
+    if favorites.is_empty() {
+        println!("No favorites (add one with `liscaf fav add <template>`)");
+        return;
+    }
+    let width = favorites.iter().map(|f| f.name.chars().count()).max().unwrap_or(0);
+    for favorite in favorites {
+        let mut line = format!("{:<width$}  {}", favorite.name, favorite.url, width = width);
+        if let Some(ref commit) = favorite.commit {
+            line.push_str(&format!(" @ {}", &commit[..commit.len().min(12)]));
+        }
+        println!("{}", line);
+    }
+}
+
+fn load_document() -> anyhow::Result<(std::path::PathBuf, DocumentMut)> {
+    let path = config::config_path()
+        .ok_or_else(|| anyhow::anyhow!("No configuration file location (set HOME or LISCAF_CONFIG)"))?;
+    let content = if path.is_file() { fs::read_to_string(&path)? } else { String::new() };
+    let doc = content
+        .parse::<DocumentMut>()
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))?;
+    Ok((path, doc))
+}
+
+fn favorites_table(doc: &mut DocumentMut) -> anyhow::Result<&mut ArrayOfTables> {
+    doc.entry("favorites")
+        .or_insert_with(|| Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+        .ok_or_else(|| anyhow::anyhow!("`favorites` in the configuration must be [[favorites]] entries"))
+}
+
+/// Removes favorites with this name; whether there was one.
+fn remove_named(favorites: &mut ArrayOfTables, short_name: &str) -> bool {
+    let before = favorites.len();
+    favorites.retain(|t| {
+        !t.get("name")
+            .and_then(|n| n.as_str())
+            .is_some_and(|n| n.eq_ignore_ascii_case(short_name))
+    });
+    favorites.len() != before
+}
@@ -0,0 +1,191 @@
+//! `--only` / `--exclude` globs limiting which files token replacement and renaming touch, plus
+//! the size limit above which files are not read at all, vendored/build directories that are
+//! not walked, and the depth, hidden-file and symlink bounds of the walks.
+//!
+//! Patterns are matched against paths relative to the project root with `/` separators
+//! (`src/**`, `docs/legal/**`); a pattern without `/` matches the file name at any depth (`*.md`).
+use std::ffi::OsStr;
+use std::path::Path;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::scaffold_fs::WalkOptions;
+
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    only: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    max_file_size: Option<u64>,
+    skip_dirs: Vec<String>,
+    walk: WalkOptions,
+    documents: bool,
+    structured: crate::structured::Mode,
+    lockfiles: bool,
+    fs_compat: Option<crate::fscompat::FsCompat>,
+}
+
+/// Dependency and build output directories that templates sometimes contain by accident.
+pub const DEFAULT_SKIP_DIRS: &[&str] = &["node_modules", "target", ".venv", "dist", "vendor"];
+
+/// Files above this size are skipped unless `--max-file-size` says otherwise.
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+impl PathFilter {
+    pub fn new(only: &[String], exclude: &[String]) -> anyhow::Result<Self> {
+        Ok(PathFilter {
+            only: build(only)?,
+            exclude: build(exclude)?,
+            max_file_size: Some(DEFAULT_MAX_FILE_SIZE),
+            skip_dirs: DEFAULT_SKIP_DIRS.iter().map(|d| d.to_string()).collect(),
+            walk: WalkOptions::default(),
+            documents: false,
+            structured: crate::structured::Mode::Off,
+            lockfiles: false,
+            fs_compat: None,
+        })
+    }
+
+    /// Depth, hidden-entry and symlink bounds of the replacement and rename walks.
+    pub fn with_walk_options(mut self, walk: WalkOptions) -> Self {
+        self.walk = walk;
+        self
+    }
+
+    pub fn walk_options(&self) -> &WalkOptions {
+        &self.walk
+    }
+
+    /// Also replace inside zip-container documents such as `.docx` and `.xlsx`.
+    pub fn with_documents(mut self, documents: bool) -> Self {
+        self.documents = documents;
+        self
+    }
+
+    pub fn documents(&self) -> bool {
+        self.documents
+    }
+
+    /// Replace inside lockfiles too instead of leaving them to be regenerated.
+    pub fn with_lockfiles(mut self, lockfiles: bool) -> Self {
+        self.lockfiles = lockfiles;
+        self
+    }
+
+    pub fn lockfiles(&self) -> bool {
+        self.lockfiles
+    }
+
+    /// Rewrite names the target filesystem would reject while renaming paths (`--fs-compat`).
+    pub fn with_fs_compat(mut self, fs_compat: Option<crate::fscompat::FsCompat>) -> Self {
+        self.fs_compat = fs_compat;
+        self
+    }
+
+    pub fn fs_compat(&self) -> Option<crate::fscompat::FsCompat> {
+        self.fs_compat
+    }
+
+    /// Replace only in string values (or also keys) of JSON, YAML and TOML files.
+    pub fn with_structured(mut self, mode: crate::structured::Mode) -> Self {
+        self.structured = mode;
+        self
+    }
+
+    pub fn structured(&self) -> crate::structured::Mode {
+        self.structured
+    }
+
+    /// Replaces the default skipped directory names.
+    pub fn with_skip_dirs(mut self, dirs: Vec<String>) -> Self {
+        self.skip_dirs = dirs;
+        self
+    }
+
+    /// Whether a directory with this name is left out of the walk entirely.
+    pub fn skips_dir(&self, name: &OsStr) -> bool {
+        self.skip_dirs.iter().any(|d| OsStr::new(d) == name)
+    }
+
+    /// `0` reads files of any size.
+    pub fn with_max_file_size(mut self, max: u64) -> Self {
+        self.max_file_size = (max > 0).then_some(max);
+        self
+    }
+
+    /// Whether a file of `len` bytes is too large to be read for replacement.
+    pub fn exceeds_size_limit(&self, len: u64) -> bool {
+        self.max_file_size.is_some_and(|max| len > max)
+    }
+
+    /// Whether `path` (below `base`) may be modified.
+    pub fn allows(&self, base: &Path, path: &Path) -> bool {
+        let Ok(rel) = path.strip_prefix(base) else {
+            return true;
+        };
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        if rel.is_empty() {
+            return true;
+        }
+        if let Some(only) = &self.only {
+            if !only.is_match(&rel) {
+                return false;
+            }
+        }
+        match &self.exclude {
+            Some(exclude) => !exclude.is_match(&rel),
+            None => true,
+        }
+    }
+}
+
+fn build(patterns: &[String]) -> anyhow::Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = pattern.trim_start_matches("./");
+        let pattern = if pattern.contains('/') {
+            pattern.to_string()
+        } else {
+            format!("**/{}", pattern)
+        };
+        let glob = GlobBuilder::new(&pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Invalid glob '{}': {}", pattern, e))?;
+        builder.add(glob);
+    }
+    Ok(Some(builder.build()?))
+}
+
+/// Parses `5MB`, `512K`, `1g` or plain bytes (binary units).
+pub fn parse_size(raw: &str) -> Result<u64, String> {
+    let raw = raw.trim();
+    let split = raw
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(raw.len());
+    let (digits, unit) = raw.split_at(split);
+    let number: u64 = digits
+        .parse()
+        .map_err(|_| format!("invalid size '{}'", raw))?;
+    let factor: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1024,
+        "m" | "mb" | "mib" => 1024 * 1024,
+        "g" | "gb" | "gib" => 1024 * 1024 * 1024,
+        other => return Err(format!("unknown size unit '{}' (use K, M or G)", other)),
+    };
+    number
+        .checked_mul(factor)
+        .ok_or_else(|| format!("size '{}' is too large", raw))
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / MB)
+    } else {
+        format!("{} KB", bytes.div_ceil(1024))
+    }
+}
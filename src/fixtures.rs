@@ -0,0 +1,227 @@
+//! `liscaf test [template]`: renders a template once per fixture in `tests/*.answers.toml` and
+//! checks the result — CI for template authors. With `test_fixtures = true` in the manifest,
+//! fixtures are template-only and never end up in generated projects.
+//!
+//! ```toml
+//! name = "payments-service"        # project name; defaults to the file name
+//! profile = "minimal"              # optional answer profile
+//!
+//! [variables]
+//! database = "postgres"
+//!
+//! [expect]
+//! exists = ["src/main.rs", "migrations"]
+//! missing = ["docker-compose.yml"]
+//! contains = { "Cargo.toml" = ['name = "payments-service"'] }
+//! omits = { "README.md" = ["acme"] }
+//! run = ["cargo build"]            # must succeed in the project
+//! no_residue = true                # as --assert-no-residue
+//! ```
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{run_scaffold, ScaffoldOptions};
+
+pub const FIXTURE_DIR: &str = "tests";
+const FIXTURE_SUFFIX: &str = ".answers.toml";
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Fixture {
+    name: Option<String>,
+    profile: Option<String>,
+    variables: BTreeMap<String, String>,
+    expect: Expect,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Expect {
+    exists: Vec<String>,
+    missing: Vec<String>,
+    contains: BTreeMap<String, Vec<String>>,
+    omits: BTreeMap<String, Vec<String>>,
+    run: Vec<String>,
+    no_residue: bool,
+}
+
+pub struct TestOptions<'a> {
+    pub template_base: &'a str,
+    /// Run only these fixtures (file names without `.answers.toml`).
+    pub only: &'a [String],
+    /// Keep the rendered projects for inspection.
+    pub keep: bool,
+    pub tmpdir: Option<&'a Path>,
+}
+
+/// The fixture files of a template, sorted.
+pub fn fixture_files(template: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(template.join(FIXTURE_DIR)) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && fixture_name(p).is_some())
+        .collect();
+    files.sort();
+    files
+}
+
+fn fixture_name(path: &Path) -> Option<&str> {
+    path.file_name()?.to_str()?.strip_suffix(FIXTURE_SUFFIX)
+}
+
+pub fn run_tests(template: &str, options: &TestOptions) -> anyhow::Result<()> {
+    let workdir = crate::make_tempdir("liscaf-test-", options.tmpdir)?;
+    let local = Path::new(template);
+    let dir = if local.is_dir() {
+        local.to_path_buf()
+    } else {
+        let url = crate::normalize_repo_url(template);
+        if !crate::is_supported_repo_url(&url) {
+            anyhow::bail!("Template is neither a directory nor a repository URL: {}", template);
+        }
+        let checkout = workdir.path().join("template");
+        crate::cache::checkout(&url, None, &checkout, true)?;
+        checkout
+    };
+
+    let mut fixtures = fixture_files(&dir);
+    if !options.only.is_empty() {
+        fixtures.retain(|p| fixture_name(p).is_some_and(|n| options.only.iter().any(|o| o == n)));
+    }
+    if fixtures.is_empty() {
+        anyhow::bail!(
+            "No fixtures found in {} (expected {}/*{})",
+            dir.display(),
+            FIXTURE_DIR,
+            FIXTURE_SUFFIX
+        );
+    }
+
+    let mut results = Vec::new();
+    for (i, path) in fixtures.iter().enumerate() {
+        let name = fixture_name(path).unwrap_or_default();
+        println!("\n=== [{}/{}] {} ===", i + 1, fixtures.len(), name);
+        let outcome = run_fixture(&dir, path, name, &workdir.path().join("out"), options);
+        match outcome {
+            Ok(ref failures) => {
+                for failure in failures {
+                    println!("FAIL: {}", failure);
+                }
+            }
+            Err(ref e) => println!("ERROR: {}", e),
+        }
+        results.push((name, outcome));
+    }
+
+    let failed = results
+        .iter()
+        .filter(|(_, o)| !matches!(o, Ok(f) if f.is_empty()))
+        .count();
+    println!(
+        "\nTest summary: {} passed, {} failed",
+        results.len() - failed,
+        failed
+    );
+    let width = results.iter().map(|(n, _)| n.len()).max().unwrap_or(0);
+    for (name, outcome) in &results {
+        match outcome {
+            Ok(failures) if failures.is_empty() => println!("  PASS    {}", name),
+            Ok(failures) => println!(
+                "  FAILED  {:width$}  {} failed check(s), first: {}",
+                name,
+                failures.len(),
+                failures[0],
+                width = width
+            ),
+            Err(e) => println!("  FAILED  {:width$}  {}", name, e, width = width),
+        }
+    }
+
+    if options.keep {
+        let kept = workdir.keep();
+        println!("Rendered projects kept in {}", kept.join("out").display());
+    }
+    if failed > 0 {
+        anyhow::bail!("{} of {} fixtures failed", failed, results.len());
+    }
+    Ok(())
+}
+
+/// Renders one fixture and returns the failed checks.
+fn run_fixture(
+    template: &Path,
+    path: &Path,
+    name: &str,
+    out: &Path,
+    options: &TestOptions,
+) -> anyhow::Result<Vec<String>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let fixture: Fixture = toml::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))?;
+    let project_name = fixture.name.clone().unwrap_or_else(|| name.to_string());
+    let destination = out.join(name).join(&project_name);
+
+    let scaffold_options = ScaffoldOptions {
+        assume_yes: true,
+        output_dir: Some(destination),
+        workspace_aware: true,
+        vars: fixture.variables.clone().into_iter().collect(),
+        profile: fixture.profile.clone(),
+        no_name_check: true,
+        assert_no_residue: fixture.expect.no_residue,
+        local_template: Some(template.to_path_buf()),
+        tmpdir: options.tmpdir.map(Path::to_path_buf),
+        ..Default::default()
+    };
+    let project = run_scaffold(
+        &template.display().to_string(),
+        &project_name,
+        options.template_base,
+        &scaffold_options,
+    )?
+    .ok_or_else(|| anyhow::anyhow!("Scaffolding produced no project"))?;
+    Ok(check(&project, &fixture.expect))
+}
+
+fn check(project: &Path, expect: &Expect) -> Vec<String> {
+    let mut failures = Vec::new();
+    for rel in &expect.exists {
+        if !project.join(rel).exists() {
+            failures.push(format!("{} does not exist", rel));
+        }
+    }
+    for rel in &expect.missing {
+        if project.join(rel).exists() {
+            failures.push(format!("{} exists", rel));
+        }
+    }
+    for (checks, should_contain) in [(&expect.contains, true), (&expect.omits, false)] {
+        for (rel, needles) in checks {
+            let Ok(content) = fs::read_to_string(project.join(rel)) else {
+                failures.push(format!("{} cannot be read", rel));
+                continue;
+            };
+            for needle in needles {
+                match (content.contains(needle.as_str()), should_contain) {
+                    (false, true) => failures.push(format!("{} does not contain '{}'", rel, needle)),
+                    (true, false) => failures.push(format!("{} contains '{}'", rel, needle)),
+                    _ => {}
+                }
+            }
+        }
+    }
+    for command in &expect.run {
+        println!("RUN: {}", command);
+        match crate::logfile::status(crate::shell_command(command).current_dir(project)) {
+            Ok(status) if status.success() => {}
+            Ok(status) => failures.push(format!("'{}' failed with code {}", command, status.code().unwrap_or(-1))),
+            Err(e) => failures.push(format!("'{}' could not be run: {}", command, e)),
+        }
+    }
+    failures
+}
@@ -0,0 +1,58 @@
+//! `--format`: runs formatters on the scaffolded tree before the initial commit. Replacing names
+//! can shift alignment, and generated manifests should start out canonically formatted.
+use std::path::Path;
+
+/// Formatters used when the template declares none: the files that mark the ecosystem, the
+/// tool that must be on PATH, and the command.
+const DETECTED: &[(&[&str], &str, &str)] = &[
+    (&["Cargo.toml"], "cargo", "cargo fmt --all"),
+    (&["go.mod"], "gofmt", "gofmt -w ."),
+    (
+        &[".prettierrc", ".prettierrc.json", ".prettierrc.yaml", "prettier.config.js"],
+        "prettier",
+        "prettier --write .",
+    ),
+];
+
+/// The template's formatters, else those of the ecosystems found in `root` whose tool is
+/// installed.
+pub fn commands(root: &Path, declared: &[String]) -> Vec<String> {
+    if !declared.is_empty() {
+        return declared.to_vec();
+    }
+    DETECTED
+        .iter()
+        .filter(|(markers, _, _)| markers.iter().any(|m| root.join(m).is_file()))
+        .filter(|(_, tool, _)| crate::external::on_path(tool).is_some())
+        .map(|(_, _, command)| command.to_string())
+        .collect()
+}
+
+/// Runs each command in `root`. Missing tools and failures are reported without aborting.
+pub fn run(root: &Path, commands: &[String], dry_run: bool) {
+    if commands.is_empty() {
+        println!("No formatters for this project");
+        return;
+    }
+    for command in commands {
+        let program = command.split_whitespace().next().unwrap_or_default();
+        if crate::external::on_path(program).is_none() {
+            println!("WARN: skipping formatter '{}': {} not found", command, program);
+            continue;
+        }
+        if dry_run {
+            println!("DRY FORMAT: {}", command);
+            continue;
+        }
+        println!("FORMAT: {}", command);
+        match crate::logfile::status(crate::shell_command(command).current_dir(root)) {
+            Ok(status) if status.success() => {}
+            Ok(status) => println!(
+                "Warning: formatter '{}' failed with code {}",
+                command,
+                status.code().unwrap_or(-1)
+            ),
+            Err(e) => println!("Warning: failed to run formatter '{}': {}", command, e),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! `liscaf add <fragment> <name>`: renders a generator-style fragment (a module, handler or
+//! page) from a template into an existing project.
+//!
+//! Fragments are declared in the template manifest and live in `.liscaf/fragments/<fragment>`
+//! by default, so they never end up in scaffolded projects themselves.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::manifest;
+use crate::placeholders::{self, RenderContext};
+use crate::tokenizer::TokenizerRules;
+use crate::variables;
+
+pub struct AddRequest<'a> {
+    pub fragment: &'a str,
+    pub name: &'a str,
+    pub project_dir: &'a Path,
+    pub template_source: Option<&'a str>,
+    pub vars: &'a [(String, String)],
+    pub dry_run: bool,
+    pub assume_yes: bool,
+    pub tmpdir: Option<&'a Path>,
+}
+
+pub fn run_add(req: &AddRequest) -> anyhow::Result<()> {
+    let metadata = read_scaffold_metadata(req.project_dir);
+    let repo_url = match req.template_source {
+        Some(source) => crate::normalize_repo_url(source),
+        None => metadata
+            .as_ref()
+            .and_then(|m| m.get("template_repo_url"))
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No template known for {}; pass --from <repo-url>",
+                    req.project_dir.display()
+                )
+            })?,
+    };
+    println!("Adding fragment '{}' as '{}' from {}", req.fragment, req.name, repo_url);
+
+    let tmpdir = crate::make_tempdir("liscaf-add-", req.tmpdir)?;
+    let template_dir = tmpdir.path().join("template");
+    if Path::new(&repo_url).is_dir() {
+        crate::copy_dir_recursive(Path::new(&repo_url), &template_dir)?;
+    } else {
+        if !crate::is_supported_repo_url(&repo_url) {
+            anyhow::bail!("Template repo URL is not supported: {}", repo_url);
+        }
+        crate::cache::checkout(&repo_url, None, &template_dir, true)?;
+    }
+    crate::extends::resolve(&template_dir, req.tmpdir, true)?;
+
+    let manifest = manifest::load(&template_dir)?;
+    let spec = manifest.fragments.get(req.fragment).ok_or_else(|| {
+        let available: Vec<&str> = manifest.fragments.keys().map(String::as_str).collect();
+        anyhow::anyhow!(
+            "Template has no fragment '{}' (available: {})",
+            req.fragment,
+            if available.is_empty() { "none".to_string() } else { available.join(", ") }
+        )
+    })?;
+    if let Some(ref description) = spec.description {
+        println!("{}", description);
+    }
+
+    let rules = TokenizerRules::default().with_acronyms(&manifest.mappings.acronyms);
+    let mut vars: BTreeMap<String, String> = variables::resolve_variables(
+        req.name,
+        &spec.variables,
+        req.vars,
+        req.assume_yes,
+        None,
+        &rules,
+    )?;
+    if let Some(project) = metadata
+        .as_ref()
+        .and_then(|m| m.get("project_name"))
+        .and_then(|v| v.as_str())
+    {
+        placeholders::insert_with_case_variants(&mut vars, "project", project, &rules);
+    }
+    let ctx = RenderContext::new(vars)
+        .with_delimiters(manifest.delimiters())
+        .with_tokenizer(&rules);
+
+    let source_rel = spec
+        .source
+        .clone()
+        .unwrap_or_else(|| format!(".liscaf/fragments/{}", req.fragment));
+    let source = template_dir.join(checked_relative(&source_rel)?);
+    if !source.is_dir() {
+        anyhow::bail!("Fragment source directory not found in template: {}", source_rel);
+    }
+
+    let staging = tmpdir.path().join("fragment");
+    crate::copy_dir_recursive(&source, &staging)?;
+    let filter = crate::filter::PathFilter::new(&[], &[])?;
+    let real = crate::scaffold_fs::RealFs;
+    placeholders::render_in_files(&real, &staging, &ctx, &filter, false)?;
+    placeholders::render_paths(&real, &staging, &ctx, &filter, false)?;
+
+    let destination = placeholders::render(spec.destination.as_deref().unwrap_or("."), &ctx);
+    let dest = req.project_dir.join(checked_relative(&destination)?);
+    if !req.dry_run {
+        fs::create_dir_all(&dest)?;
+    }
+    crate::merge_into_dest(
+        &crate::scaffold_fs::RealFs,
+        &staging,
+        &dest,
+        &Default::default(),
+        &crate::cache::cache_dir(),
+        req.dry_run,
+    )?;
+    println!("Fragment '{}' added", req.fragment);
+    Ok(())
+}
+
+fn read_scaffold_metadata(project_dir: &Path) -> Option<serde_json::Value> {
+    let content = fs::read_to_string(project_dir.join(".scaffold.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Rejects absolute paths and `..` so fragments cannot write outside the project.
+fn checked_relative(path: &str) -> anyhow::Result<PathBuf> {
+    let p = Path::new(path);
+    if p.components()
+        .any(|c| matches!(c, Component::ParentDir | Component::RootDir | Component::Prefix(_)))
+    {
+        anyhow::bail!("Fragment path must stay inside the project: {}", path);
+    }
+    Ok(p.to_path_buf())
+}
@@ -0,0 +1,84 @@
+//! `--fs-compat fat|ntfs|posix`: file and directory names in the project are checked against
+//! the naming rules of the filesystem it will live on, and names the filesystem would reject are
+//! rewritten while paths are renamed — for projects on USB drives, network shares or Windows
+//! paths mounted in WSL.
+//!
+//! FAT (vfat, exFAT) and NTFS share the Windows rules: none of `< > : " / \ | ? *` or control
+//! characters, no trailing dot or space, and no device names such as `CON` or `LPT1`, with or
+//! without an extension. POSIX filesystems only reject NUL.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsCompat {
+    Fat,
+    Ntfs,
+    Posix,
+}
+
+const WINDOWS_FORBIDDEN: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+const WINDOWS_DEVICES: &[&str] = &["CON", "PRN", "AUX", "NUL", "CONIN$", "CONOUT$"];
+
+pub fn parse(raw: &str) -> Result<FsCompat, String> {
+    match raw.to_ascii_lowercase().as_str() {
+        "fat" | "vfat" | "exfat" => Ok(FsCompat::Fat),
+        "ntfs" => Ok(FsCompat::Ntfs),
+        "posix" => Ok(FsCompat::Posix),
+        other => Err(format!("unknown filesystem '{}' (use fat, ntfs or posix)", other)),
+    }
+}
+
+impl FsCompat {
+    pub fn label(self) -> &'static str {
+        match self {
+            FsCompat::Fat => "FAT",
+            FsCompat::Ntfs => "NTFS",
+            FsCompat::Posix => "POSIX",
+        }
+    }
+
+    fn windows_rules(self) -> bool {
+        matches!(self, FsCompat::Fat | FsCompat::Ntfs)
+    }
+
+    /// `name` made valid on this filesystem; unchanged if it already is.
+    pub fn sanitize(self, name: &str) -> String {
+        if !self.windows_rules() {
+            return name.replace('\0', "_");
+        }
+        let mut clean: String = name
+            .chars()
+            .map(|c| {
+                if c.is_control() || WINDOWS_FORBIDDEN.contains(&c) {
+                    '_'
+                } else {
+                    c
+                }
+            })
+            .collect();
+        let trimmed = clean.trim_end_matches(['.', ' ']).len();
+        if trimmed < clean.len() {
+            clean.truncate(trimmed);
+            clean.push('_');
+        }
+        let (stem, extension) = match clean.find('.') {
+            Some(at) => clean.split_at(at),
+            None => (clean.as_str(), ""),
+        };
+        if is_device(stem.trim_end()) {
+            clean = format!("{}_{}", stem, extension);
+        }
+        clean
+    }
+}
+
+fn is_device(stem: &str) -> bool {
+    let upper = stem.to_ascii_uppercase();
+    if WINDOWS_DEVICES.contains(&upper.as_str()) {
+        return true;
+    }
+    let mut chars = upper.chars();
+    let prefix: String = chars.by_ref().take(3).collect();
+    let digit = chars.next();
+    (prefix == "COM" || prefix == "LPT")
+        && digit.is_some_and(|d| d.is_ascii_digit() || "¹²³".contains(d))
+        && chars.next().is_none()
+}
@@ -0,0 +1,187 @@
+//! Template discovery from a GitHub organization: `--templates gh-org:myorg?topic=liscaf-template`
+//! lists the organization's repositories (optionally only those with a topic) via the GitHub API,
+//! so no repositories.yaml needs to be maintained.
+//!
+//! `GITHUB_TOKEN` or `GH_TOKEN` is sent when set (private repositories, higher rate limit), else
+//! a token stored in the OS keychain. When the API refuses the token or the anonymous rate limit
+//! runs out, liscaf asks for a token on a terminal and offers to store it.
+//! `GITHUB_API_URL` points at a GitHub Enterprise Server API instead of api.github.com.
+use crate::keychain;
+use crate::net::{self, Failure};
+use crate::TemplateEntry;
+
+pub const SOURCE_PREFIX: &str = "gh-org:";
+
+const DEFAULT_API_BASE: &str = "https://api.github.com";
+const PER_PAGE: usize = 100;
+
+#[derive(Debug, serde::Deserialize)]
+struct Repo {
+    name: String,
+    clone_url: String,
+    description: Option<String>,
+    #[serde(default)]
+    topics: Vec<String>,
+    #[serde(default)]
+    archived: bool,
+}
+
+/// `myorg?topic=liscaf-template` -> (`myorg`, `Some("liscaf-template")`)
+fn parse_spec(spec: &str) -> anyhow::Result<(String, Option<String>)> {
+    let (org, query) = spec.split_once('?').unwrap_or((spec, ""));
+    let org = org.trim().trim_matches('/');
+    if org.is_empty() {
+        anyhow::bail!("Missing organization in '{}{}'", SOURCE_PREFIX, spec);
+    }
+    let mut topic = None;
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        match pair.split_once('=') {
+            Some(("topic", value)) if !value.is_empty() => topic = Some(value.to_string()),
+            _ => anyhow::bail!("Unsupported query '{}' (expected topic=<name>)", pair),
+        }
+    }
+    Ok((org.to_string(), topic))
+}
+
+fn api_base() -> String {
+    std::env::var("GITHUB_API_URL")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| DEFAULT_API_BASE.to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+fn token() -> Option<String> {
+    ["GITHUB_TOKEN", "GH_TOKEN"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+        .or_else(|| keychain::get(&keychain::github_account(&api_base())))
+}
+
+/// A token typed in after the API turned `rejected` (or no token) away.
+fn ask_token(rejected: Option<&str>) -> Option<String> {
+    let account = keychain::github_account(&api_base());
+    if rejected.is_some()
+        && rejected == keychain::get(&account).as_deref()
+        && keychain::forget(&account).unwrap_or(false)
+    {
+        println!("KEYCHAIN: Removed the rejected token '{}'", account);
+    }
+    keychain::prompt_token(&format!("the GitHub API ({})", api_base()), &account)
+}
+
+pub fn load_org_templates(spec: &str) -> anyhow::Result<Vec<TemplateEntry>> {
+    let (org, topic) = parse_spec(spec)?;
+    let api = api_base();
+    // Organizations first; the same name may also be a user account
+    let repos = match list_repos(&format!("{}/orgs/{}/repos", api, org)) {
+        Ok(Some(repos)) => repos,
+        Ok(None) => list_repos(&format!("{}/users/{}/repos", api, org))?
+            .ok_or_else(|| anyhow::anyhow!("GitHub organization or user '{}' not found", org))?,
+        Err(e) => return Err(e),
+    };
+
+    let entries: Vec<TemplateEntry> = repos
+        .into_iter()
+        .filter(|r| !r.archived)
+        .filter(|r| topic.as_ref().is_none_or(|t| r.topics.iter().any(|rt| rt == t)))
+        .map(|r| TemplateEntry {
+            name: r.name.clone(),
+            label: match r.description.filter(|d| !d.trim().is_empty()) {
+                Some(description) => format!("{} — {}", r.name, description.trim()),
+                None => r.name,
+            },
+            url: r.clone_url,
+            commit: None,
+        })
+        .collect();
+    if entries.is_empty() {
+        match topic {
+            Some(topic) => println!("Warning: no repositories in {} have the topic '{}'", org, topic),
+            None => println!("Warning: no repositories found in {}", org),
+        }
+    }
+    Ok(entries)
+}
+
+/// All pages of a repository listing; `None` if the account does not exist.
+fn list_repos(base_url: &str) -> anyhow::Result<Option<Vec<Repo>>> {
+    let agent = net::agent();
+    let mut token = token();
+    let mut repos = Vec::new();
+    let mut page = 1;
+    loop {
+        let url = format!("{}?per_page={}&page={}&type=all", base_url, PER_PAGE, page);
+        let response = net::with_retries(&format!("Fetching {}", url), || {
+            let mut request = agent
+                .get(&url)
+                .header("Accept", "application/vnd.github+json")
+                .header("X-GitHub-Api-Version", "2022-11-28");
+            if let Some(token) = &token {
+                request = request.header("Authorization", format!("Bearer {}", token));
+            }
+            request
+                .config()
+                .http_status_as_error(false)
+                .build()
+                .call()
+                .map_err(|e| Failure::Transient(anyhow::anyhow!("GitHub API error: {}", e)))
+        })?;
+
+        let status = response.status().as_u16();
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        if status == 404 {
+            return Ok(None);
+        }
+        let rate_limited =
+            (status == 403 || status == 429) && header("x-ratelimit-remaining").as_deref() == Some("0");
+        if status == 401 || (rate_limited && token.is_none()) {
+            if status == 401 {
+                println!("Warning: the GitHub API rejected the token");
+            }
+            if let Some(new_token) = ask_token(token.as_deref()) {
+                token = Some(new_token);
+                continue;
+            }
+        }
+        if rate_limited {
+            let reset = header("x-ratelimit-reset")
+                .and_then(|r| r.parse::<i64>().ok())
+                .and_then(|r| chrono::DateTime::from_timestamp(r, 0))
+                .map(|t| t.with_timezone(&chrono::Local).format("%H:%M").to_string())
+                .unwrap_or_else(|| "later".to_string());
+            let hint = if token.is_some() {
+                ""
+            } else {
+                "; set GITHUB_TOKEN for a higher limit"
+            };
+            anyhow::bail!("GitHub API rate limit exceeded (resets at {}){}", reset, hint);
+        }
+        if status >= 400 {
+            anyhow::bail!("GitHub API returned status {} for {}", status, url);
+        }
+        if let Some(remaining) = header("x-ratelimit-remaining").and_then(|r| r.parse::<u32>().ok()) {
+            if remaining < 5 {
+                println!("Warning: only {} GitHub API requests left in this rate-limit window", remaining);
+            }
+        }
+
+        let body = response.into_body().read_to_string()?;
+        let page_repos: Vec<Repo> = serde_json::from_str(&body)
+            .map_err(|e| anyhow::anyhow!("Unexpected GitHub API response: {}", e))?;
+        let done = page_repos.len() < PER_PAGE;
+        repos.extend(page_repos);
+        if done {
+            break;
+        }
+        page += 1;
+    }
+    Ok(Some(repos))
+}
@@ -0,0 +1,78 @@
+//! Git hooks shipped by a template: files in `.hooks/` are copied into `.git/hooks`, and a
+//! `.pre-commit-config.yaml` is activated with `pre-commit install`. Templates opt in with
+//! `install_hooks = true`; `--no-hooks` skips it.
+use std::fs;
+use std::path::Path;
+
+const HOOKS_DIR: &str = ".hooks";
+const PRE_COMMIT_CONFIG: &str = ".pre-commit-config.yaml";
+
+/// Installs the hooks of the project at `root`, which must be a git repository. Problems are
+/// reported without aborting the scaffold.
+pub fn install(root: &Path) {
+    let git_hooks = root.join(".git").join("hooks");
+    if !root.join(".git").is_dir() {
+        println!("WARN: not installing git hooks: {} is not a git repository", root.display());
+        return;
+    }
+    let hooks_dir = root.join(HOOKS_DIR);
+    if hooks_dir.is_dir() {
+        if let Err(e) = copy_hooks(&hooks_dir, &git_hooks) {
+            println!("Warning: failed to install hooks from {}: {}", HOOKS_DIR, e);
+        }
+    }
+    if root.join(PRE_COMMIT_CONFIG).is_file() {
+        if crate::external::on_path("pre-commit").is_none() {
+            println!(
+                "WARN: {} found but pre-commit is not installed; run `pre-commit install` later",
+                PRE_COMMIT_CONFIG
+            );
+            return;
+        }
+        println!("HOOKS: pre-commit install");
+        let mut install = std::process::Command::new("pre-commit");
+        let status = crate::logfile::status(install.arg("install").current_dir(root));
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(status) => println!(
+                "Warning: pre-commit install failed with code {}",
+                status.code().unwrap_or(-1)
+            ),
+            Err(e) => println!("Warning: failed to run pre-commit install: {}", e),
+        }
+    }
+}
+
+/// Whether the template at `root` ships hooks.
+pub fn present(root: &Path) -> bool {
+    root.join(HOOKS_DIR).is_dir() || root.join(PRE_COMMIT_CONFIG).is_file()
+}
+
+fn copy_hooks(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    let mut entries: Vec<_> = fs::read_dir(from)?.filter_map(|e| e.ok()).collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let target = to.join(entry.file_name());
+        fs::copy(entry.path(), &target)?;
+        make_executable(&target)?;
+        println!("HOOKS: {}", target.display());
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o755);
+    fs::set_permissions(path, permissions)
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
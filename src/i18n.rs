@@ -0,0 +1,123 @@
+//! Translated prompts and messages. Messages are written in English in the code and looked up in
+//! the catalog of the selected language: `--lang`, else the first of `LC_ALL`, `LC_MESSAGES` and
+//! `LANG` that is set. Catalogs live in `src/locales/<lang>.toml` as
+//! `"English message" = "translation"`, with `{}` standing for the same arguments in the same
+//! order. A message without a translation stays English.
+//!
+//! Status lines (`ADD:`, `RENAME:`, `WARN:`, ...) and `--porcelain` records are not translated,
+//! since scripts read them.
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/// Languages with a catalog, besides English.
+const CATALOGS: &[(&str, &str)] = &[("de", include_str!("locales/de.toml"))];
+
+static CATALOG: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// `--lang`: `en` or a language with a catalog.
+pub fn parse(raw: &str) -> Result<String, String> {
+    let lang = language(raw);
+    if lang == "en" || CATALOGS.iter().any(|(code, _)| *code == lang) {
+        Ok(lang)
+    } else {
+        let known: Vec<&str> = CATALOGS.iter().map(|(code, _)| *code).collect();
+        Err(format!("no translation for '{}' (use en or {})", raw, known.join(", ")))
+    }
+}
+
+/// `de` for `de_DE.UTF-8`, `de-AT` or `de`.
+fn language(locale: &str) -> String {
+    locale
+        .split(['_', '-', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Selects the language; call once at startup. Without a call, everything stays English.
+pub fn configure(lang: Option<&str>) -> anyhow::Result<()> {
+    let lang = match lang {
+        Some(lang) => language(lang),
+        None => ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .find_map(|key| std::env::var(key).ok().filter(|v| !v.is_empty()))
+            .map(|locale| language(&locale))
+            .unwrap_or_default(),
+    };
+    let catalog = match CATALOGS.iter().find(|(code, _)| *code == lang) {
+        Some((code, source)) => {
+            load(source).map_err(|e| anyhow::anyhow!("Broken catalog for '{}': {}", code, e))?
+        }
+        None => HashMap::new(),
+    };
+    let _ = CATALOG.set(catalog);
+    Ok(())
+}
+
+fn load(source: &str) -> Result<HashMap<String, String>, toml::de::Error> {
+    toml::from_str(source)
+}
+
+/// The translation of `message`, or `message` itself.
+pub fn text(message: &str) -> &str {
+    match CATALOG.get().and_then(|catalog| catalog.get(message)) {
+        Some(translated) => translated,
+        None => message,
+    }
+}
+
+/// `message` with each `{}` replaced by the next argument.
+pub fn format(message: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(message.len());
+    let mut args = args.iter();
+    let mut parts = message.split("{}");
+    if let Some(first) = parts.next() {
+        out.push_str(first);
+    }
+    for part in parts {
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+/// A translated message, formatted with `{}` placeholders when arguments are given.
+macro_rules! tr {
+    ($message:literal) => {
+        $crate::i18n::text($message)
+    };
+    ($message:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::format(
+            $crate::i18n::text($message),
+            &[$(&$arg as &dyn std::fmt::Display),+],
+        )
+    };
+}
+pub(crate) use tr;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalogs_keep_the_placeholders_of_each_message() {
+        for (lang, source) in CATALOGS {
+            let catalog: HashMap<String, String> = toml::from_str(source).unwrap();
+            for (message, translated) in &catalog {
+                assert_eq!(
+                    message.matches("{}").count(),
+                    translated.matches("{}").count(),
+                    "{}: {}",
+                    lang,
+                    message
+                );
+            }
+        }
+        assert_eq!(parse("de_DE.UTF-8").unwrap(), "de");
+        assert!(parse("xx").is_err());
+        assert_eq!(format("'{}' in {}", &[&"a", &2]), "'a' in 2");
+    }
+}
@@ -0,0 +1,172 @@
+//! `liscaf info <template>`: what a template is about before scaffolding from it — description,
+//! variables, required tools, last commit and the start of its README.
+use std::fs;
+use std::path::Path;
+
+use crate::manifest::{self, Manifest};
+use crate::registry::{self, Registry};
+use crate::TemplateEntry;
+
+/// README lines shown when the terminal size is unknown (`LINES` unset).
+const DEFAULT_PREVIEW_LINES: usize = 20;
+
+pub fn run_info(
+    template: &str,
+    registries: &[Registry],
+    tmpdir: Option<&Path>,
+) -> anyhow::Result<()> {
+    let local = Path::new(template);
+    let (entry, dir, revision, _tmp) = if local.is_dir() {
+        let entry = TemplateEntry {
+            name: template.to_string(),
+            label: template.to_string(),
+            url: local.display().to_string(),
+            commit: None,
+        };
+        (entry, local.to_path_buf(), None, None)
+    } else {
+        let entry = resolve(template, registries)?;
+        let tmp = crate::make_tempdir("liscaf-info-", tmpdir)?;
+        let dir = tmp.path().to_path_buf();
+        let revision = crate::cache::checkout(&entry.url, entry.commit.as_deref(), &dir, true)?;
+        crate::extends::resolve(&dir, tmpdir, true)?;
+        (entry, dir, revision, Some(tmp))
+    };
+    let manifest = manifest::load(&dir)?;
+
+    println!();
+    println!("Template:    {}", entry.label);
+    println!("URL:         {}", entry.url);
+    if let Some(revision) = revision {
+        println!(
+            "Commit:      {}{} ({})",
+            &revision.commit[..revision.commit.len().min(12)],
+            if entry.commit.is_some() { ", pinned" } else { "" },
+            revision.committed_at
+        );
+    }
+    if let Some(ref description) = manifest.description {
+        println!("Description: {}", description);
+    }
+    if !manifest.requires.is_empty() {
+        println!("Requires:    {}", manifest.requires.join(", "));
+    }
+    print_variables(&manifest);
+    if !manifest.profiles.is_empty() {
+        let names: Vec<&str> = manifest.profiles.keys().map(String::as_str).collect();
+        println!("Profiles:    {}", names.join(", "));
+    }
+    if !manifest.fragments.is_empty() {
+        println!("Fragments:");
+        for (name, spec) in &manifest.fragments {
+            match spec.description {
+                Some(ref description) => println!("  {} — {}", name, description),
+                None => println!("  {}", name),
+            }
+        }
+    }
+    print_readme(&dir);
+    Ok(())
+}
+
+/// A repository URL, or the name or label of a template in one of the registries.
+pub fn resolve(template: &str, registries: &[Registry]) -> anyhow::Result<TemplateEntry> {
+    let url = crate::normalize_repo_url(template);
+    if crate::is_supported_repo_url(&url) {
+        return Ok(TemplateEntry {
+            name: url.clone(),
+            label: url.clone(),
+            url,
+            commit: None,
+        });
+    }
+    let templates = registry::load_templates(registries)?;
+    let wanted = template.to_lowercase();
+    let names: Vec<String> = templates.iter().map(|t| t.name.clone()).collect();
+    templates
+        .into_iter()
+        .find(|t| t.name.to_lowercase() == wanted || t.label.to_lowercase() == wanted)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No template named '{}' (available: {})",
+                template,
+                if names.is_empty() { "none".to_string() } else { names.join(", ") }
+            )
+        })
+}
+
+fn print_variables(manifest: &Manifest) {
+    if manifest.variables.is_empty() {
+        return;
+    }
+    println!("Variables:");
+    for (name, spec) in &manifest.variables {
+        let mut line = format!("  {}", name);
+        if let Some(ref prompt) = spec.prompt {
+            line.push_str(&format!(" — {}", prompt));
+        }
+        if let Some(ref default) = spec.default {
+            line.push_str(&format!(" (default: {})", default));
+        }
+        println!("{}", line);
+    }
+}
+
+/// Fetches the template and prints its README rendered as markdown, for the template picker.
+pub fn show_readme(entry: &TemplateEntry, tmpdir: Option<&Path>) -> anyhow::Result<()> {
+    let tmp = crate::make_tempdir("liscaf-info-", tmpdir)?;
+    crate::cache::checkout(&entry.url, entry.commit.as_deref(), tmp.path(), true)?;
+    let Some(path) = find_readme(tmp.path()) else {
+        println!("No README in template");
+        return Ok(());
+    };
+    let content = fs::read_to_string(&path)?;
+    println!();
+    termimad::MadSkin::default().print_text(&content);
+    println!();
+    Ok(())
+}
+
+/// The first screenful of README.md (or README, README.txt, ...).
+fn print_readme(dir: &Path) {
+    let Some(path) = find_readme(dir) else {
+        println!("No README in template");
+        return;
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return;
+    };
+    let rows = std::env::var("LINES")
+        .ok()
+        .and_then(|l| l.parse::<usize>().ok())
+        .map(|l| l.saturating_sub(2).max(5))
+        .unwrap_or(DEFAULT_PREVIEW_LINES);
+    let lines: Vec<&str> = content.lines().collect();
+    println!();
+    println!("--- {} ---", path.file_name().unwrap_or_default().to_string_lossy());
+    for line in lines.iter().take(rows) {
+        println!("{}", line);
+    }
+    if lines.len() > rows {
+        println!("... ({} more lines)", lines.len() - rows);
+    }
+}
+
+pub fn find_readme(dir: &Path) -> Option<std::path::PathBuf> {
+    let mut candidates: Vec<_> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .filter(|p| {
+            p.file_stem()
+                .is_some_and(|s| s.to_string_lossy().eq_ignore_ascii_case("readme"))
+        })
+        .collect();
+    // Prefer README.md over README.txt, README.rst, ...
+    candidates.sort_by_key(|p| {
+        let ext = p.extension().map(|e| e.to_string_lossy().to_lowercase());
+        (ext.as_deref() != Some("md"), p.clone())
+    });
+    candidates.into_iter().next()
+}
@@ -0,0 +1,113 @@
+//! Tokens kept in the OS keychain (macOS Keychain, Windows Credential Manager, the Secret Service
+//! on Linux). When a registry or the GitHub API turns liscaf away and a token is typed in, liscaf
+//! offers to store it, and later runs read it from there instead of needing an environment
+//! variable every time.
+//!
+//! Entries belong to the service `liscaf`, with accounts such as `registry:internal` or
+//! `github:api.github.com`. Keychain support is the default `keychain` cargo feature.
+use inquire::{Confirm, Password, PasswordDisplayMode};
+
+use crate::i18n::tr;
+use crate::prompt::Ask;
+
+/// Account of the registry named `name`.
+pub fn registry_account(name: &str) -> String {
+    format!("registry:{}", name)
+}
+
+/// Account of the GitHub API at `api_base`.
+pub fn github_account(api_base: &str) -> String {
+    let host = api_base
+        .split_once("://")
+        .map_or(api_base, |(_, rest)| rest)
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    format!("github:{}", host)
+}
+
+/// The stored token for `account`, if there is one. Keychain errors count as no token: headless
+/// machines and CI often have no keychain at all.
+pub fn get(account: &str) -> Option<String> {
+    store::get(account).ok().flatten().filter(|t| !t.is_empty())
+}
+
+/// Asks for a token for `what` (only on a terminal) and offers to store it under `account`.
+pub fn prompt_token(what: &str, account: &str) -> Option<String> {
+    if !crate::prompt::interactive() {
+        return None;
+    }
+    let token = Password::new(&tr!("Token for {}:", what))
+        .without_confirmation()
+        .with_display_mode(PasswordDisplayMode::Hidden)
+        .with_help_message(tr!("leave empty to skip"))
+        .ask()
+        .ok()?;
+    let token = token.trim().to_string();
+    if token.is_empty() {
+        return None;
+    }
+    if store::AVAILABLE
+        && Confirm::new("Store it in the OS keychain for later runs?")
+            .with_default(true)
+            .ask()
+            .unwrap_or(false)
+    {
+        match store::set(account, &token) {
+            Ok(()) => println!("KEYCHAIN: Stored the token as '{}'", account),
+            Err(e) => println!("Warning: could not store the token in the OS keychain: {}", e),
+        }
+    }
+    Some(token)
+}
+
+/// Removes the token stored for `account`; `false` if there was none.
+pub fn forget(account: &str) -> anyhow::Result<bool> {
+    store::delete(account)
+}
+
+#[cfg(feature = "keychain")]
+mod store {
+    use keyring::{Entry, Error};
+
+    const SERVICE: &str = "liscaf";
+
+    pub const AVAILABLE: bool = true;
+
+    pub fn get(account: &str) -> anyhow::Result<Option<String>> {
+        match Entry::new(SERVICE, account)?.get_password() {
+            Ok(token) => Ok(Some(token)),
+            Err(Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn set(account: &str, token: &str) -> anyhow::Result<()> {
+        Ok(Entry::new(SERVICE, account)?.set_password(token)?)
+    }
+
+    pub fn delete(account: &str) -> anyhow::Result<bool> {
+        match Entry::new(SERVICE, account)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(Error::NoEntry) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(not(feature = "keychain"))]
+mod store {
+    pub const AVAILABLE: bool = false;
+
+    pub fn get(_account: &str) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+
+    pub fn set(_account: &str, _token: &str) -> anyhow::Result<()> {
+        anyhow::bail!("this liscaf build has no keychain support (enable the `keychain` feature)")
+    }
+
+    pub fn delete(_account: &str) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+}
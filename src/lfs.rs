@@ -0,0 +1,78 @@
+//! Git LFS: templates whose `.gitattributes` route files through the `lfs` filter get their LFS
+//! objects fetched at checkout, and new projects get the LFS hooks, so assets are not copied as
+//! pointer stubs.
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use walkdir::WalkDir;
+
+/// Whether any `.gitattributes` below `root` (outside `.git`) uses `filter=lfs`.
+pub fn uses_lfs(root: &Path) -> bool {
+    WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.file_name() == ".gitattributes")
+        .any(|e| fs::read_to_string(e.path()).is_ok_and(|content| content.contains("filter=lfs")))
+}
+
+fn installed() -> bool {
+    Command::new("git")
+        .args(["lfs", "version"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+/// Replaces LFS pointer files in the clone at `repo` with their content; `git_env` points git
+/// at a repository stored elsewhere. Fails when the template uses LFS and git-lfs is missing,
+/// rather than scaffolding pointer stubs.
+pub fn pull(repo: &Path, git_env: &[(&str, &Path)]) -> anyhow::Result<()> {
+    if !uses_lfs(repo) {
+        return Ok(());
+    }
+    if !installed() {
+        anyhow::bail!(
+            "The template stores files in Git LFS, but git-lfs is not installed; install it (https://git-lfs.com) and try again"
+        );
+    }
+    println!("Fetching Git LFS objects");
+    crate::net::with_retries("Fetching Git LFS objects", || {
+        let output = crate::net::git()
+            .args(["lfs", "pull"])
+            .envs(git_env.iter().copied())
+            .current_dir(repo)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .map_err(|e| {
+                crate::net::Failure::Permanent(anyhow::anyhow!("Failed to run git lfs: {}", e))
+            })?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(crate::git_failure("git lfs pull", &output))
+        }
+    })
+}
+
+/// Sets up LFS in the new repository at `root` before its first commit, so LFS-tracked files
+/// are committed as LFS objects.
+pub fn install(root: &Path) {
+    if !uses_lfs(root) {
+        return;
+    }
+    let status = Command::new("git")
+        .args(["lfs", "install", "--local"])
+        .current_dir(root)
+        .stdout(Stdio::null())
+        .status();
+    match status {
+        Ok(status) if status.success() => println!("Set up Git LFS in the new repository"),
+        _ => println!(
+            "Warning: `git lfs install` failed; LFS-tracked files are committed as regular files"
+        ),
+    }
+}
@@ -0,0 +1,3894 @@
+//! Simple scaffolder: clones a repo, replaces template tokens, and initializes a new git repo.
+//!
+//! Usage:
+//!   liscaf new <new-project-name> [repo-url]
+//!   liscaf <new-project-name> [repo-url]   (same as `new`)
+//!
+//! Templates can be selected from a repositories.yaml/.yml list by providing a
+//! templates source (folder, repo, or http base URL).
+//!
+//! As a library, [`scaffold`] and [`scaffold_async`] run a non-interactive scaffold and report
+//! [`progress::Event`]s while it runs.
+//!
+mod api;
+mod archive;
+mod batch;
+mod cache;
+mod color;
+mod condition;
+mod conditional;
+mod conflicts;
+mod config;
+mod copyright;
+mod destination;
+mod diskspace;
+mod doctor;
+mod documents;
+mod extends;
+mod external;
+mod favorites;
+mod filter;
+mod fixtures;
+mod format;
+mod fscompat;
+mod fragment;
+mod github;
+mod hooks;
+mod i18n;
+mod info;
+mod keychain;
+mod lfs;
+mod license;
+mod lockfiles;
+mod logfile;
+mod manifest;
+mod names;
+mod net;
+mod notify;
+mod mappings;
+mod merge_hashes;
+mod permissions;
+mod placeholders;
+mod plugin;
+mod porcelain;
+mod postprocess;
+pub mod progress;
+mod prompt;
+mod protocol;
+mod rebrand;
+mod recent;
+mod registry;
+mod residue;
+mod scaffold_fs;
+mod serve;
+mod structured;
+pub mod tokenizer;
+mod timings;
+mod tools;
+mod upgrade;
+mod validate;
+mod variables;
+mod workspace;
+mod zip;
+
+pub use api::{scaffold, scaffold_async, ScaffoldRequest, ScaffoldTask};
+pub use tokenizer::{
+    generate_variant_mappings, split_name_to_tokens, DigitHandling, TokenizerRules, VARIANT_NAMES,
+};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Instant;
+
+use clap::{Parser, Subcommand};
+use inquire::{Confirm, Select, Text};
+use walkdir::WalkDir;
+
+use color::status;
+use i18n::tr;
+use merge_hashes::MergeHashes;
+use prompt::Ask;
+use scaffold_fs::{MemoryFs, OverlayFs, RealFs, ScaffoldFs, WalkOptions};
+use tokenizer::validate_variant_names;
+
+/// Templates source used when neither `--templates` nor `LISCAF_TEMPLATES` is given.
+const DEFAULT_TEMPLATES_SOURCE: &str = "github.com/yoktobit/liscaf-assets";
+
+/// Simple scaffolder: clones a repo, replaces template tokens, and initializes a new git repo.
+#[derive(Parser, Debug)]
+#[command(name = "liscaf", about = "Simple scaffolder using inquire", after_help = prompt::HELP)]
+struct Args {
+    /// Without a command, a wizard walks through scaffolding a new project
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+    /// Proxy for registry fetches and git (default: HTTPS_PROXY/HTTP_PROXY, honoring NO_PROXY)
+    #[arg(long, global = true, env = "LISCAF_PROXY", value_name = "URL")]
+    proxy: Option<String>,
+    /// Print stable tab-separated records (ADD, CONFLICT, RENAME, DONE) on stdout for scripts; other output goes to stderr
+    #[arg(long, global = true)]
+    porcelain: bool,
+    /// Color status lines and diffs: auto (when stdout is a terminal and NO_COLOR is unset), always or never
+    #[arg(long, global = true, value_name = "WHEN", value_parser = color::parse, default_value = "auto")]
+    color: color::ColorChoice,
+    /// Also write the full output, the commands run and their exit codes to this file
+    #[arg(long, global = true, value_name = "PATH", env = "LISCAF_LOG_FILE")]
+    log_file: Option<PathBuf>,
+    /// Language of prompts and messages (en, de); defaults to LC_ALL, LC_MESSAGES or LANG
+    #[arg(long, global = true, value_name = "LANG", value_parser = i18n::parse)]
+    lang: Option<String>,
+    /// Stop (or take the default, see --on-prompt-timeout) when a prompt gets no answer for this many seconds
+    #[arg(long, global = true, value_name = "SECS", env = "LISCAF_PROMPT_TIMEOUT")]
+    prompt_timeout: Option<u64>,
+    /// What an unanswered prompt does after --prompt-timeout: abort (default) or take its default answer
+    #[arg(long, global = true, value_name = "ACTION", value_parser = prompt::parse_on_timeout)]
+    on_prompt_timeout: Option<prompt::OnTimeout>,
+}
+
+#[derive(Subcommand, Debug)]
+enum CliCommand {
+    /// Scaffold a new project from a template repo (`liscaf <name> [url]` works as well)
+    #[command(visible_alias = "scaffold")]
+    New(ScaffoldArgs),
+    /// Re-apply the project's template (from .scaffold.json), merging changes with conflict markers
+    Update(UpdateArgs),
+    /// Show the template changes since the project's pinned commit and move the pin forward
+    UpgradeTemplate(UpgradeTemplateArgs),
+    /// List the templates of all registries
+    List(ListArgs),
+    /// Rename an existing project in place: rewrite the old name's variants in contents and paths
+    #[command(visible_alias = "replace")]
+    Rename(RenameArgs),
+    /// Render a fragment (module, handler, page, ...) from a template into an existing project
+    Add(AddArgs),
+    /// Scaffold every project listed in a plan file (plan.toml)
+    Batch(BatchArgs),
+    /// Rename a product across many repositories and directories listed in a file
+    Rebrand(RebrandArgs),
+    /// Check git, network access to the templates source, credentials, temp and cache directories
+    Doctor(DoctorArgs),
+    /// Show a template's description, variables, required tools, last commit and README
+    Info(InfoArgs),
+    /// Serve JSON-RPC for editor integrations (list templates, variables, plan, execute)
+    Serve(ServeArgs),
+    /// Inspect and clean the local template cache
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+    /// Check a template directory: manifest, plugins, fragments and the template name
+    Validate(ValidateArgs),
+    /// Render a template once per fixture (tests/*.answers.toml) and check the results
+    Test(TestArgs),
+    /// Manage favorite templates, listed first in the picker and usable by short name
+    Fav {
+        #[command(subcommand)]
+        command: FavCommand,
+    },
+    /// Any other command runs a `liscaf-<command>` executable from PATH
+    #[command(external_subcommand)]
+    External(Vec<String>),
+}
+
+#[derive(Subcommand, Debug)]
+enum FavCommand {
+    /// Add a template (registry name or repository URL) to the favorites
+    Add {
+        template: String,
+        /// Short name for the favorite (defaults to the template's name)
+        #[arg(long = "name", value_name = "NAME")]
+        name: Option<String>,
+        /// Templates source (folder with repositories.yaml/.yml, git repo, HTTP base URL, or gh-org:<org>?topic=<topic>)
+        #[arg(
+            long = "templates",
+            env = "LISCAF_TEMPLATES",
+            value_name = "PATH_OR_URL",
+            default_value = DEFAULT_TEMPLATES_SOURCE
+        )]
+        templates_source: String,
+    },
+    /// Remove a favorite
+    Remove { name: String },
+    /// List the favorites
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheCommand {
+    /// List cached templates with ref, size and last-used time
+    List,
+    /// Remove cached templates (all, or those whose URL contains FILTER)
+    Clean {
+        filter: Option<String>,
+    },
+    /// Remove least recently used templates until the cache fits the given size
+    Prune {
+        /// Maximum cache size, e.g. 2G or 500MB
+        #[arg(long = "max-size", value_name = "SIZE", value_parser = filter::parse_size)]
+        max_size: u64,
+    },
+}
+
+#[derive(Parser, Debug)]
+struct DoctorArgs {
+    /// Templates source to check (folder with repositories.yaml/.yml, git repo, or HTTP base URL)
+    #[arg(
+        long = "templates",
+        env = "LISCAF_TEMPLATES",
+        value_name = "PATH_OR_URL",
+        default_value = DEFAULT_TEMPLATES_SOURCE
+    )]
+    templates_source: String,
+    /// Directory for temporary clones and transforms (defaults to the system temp dir)
+    #[arg(long = "tmpdir", env = "LISCAF_TMPDIR", value_name = "PATH")]
+    tmpdir: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+struct ServeArgs {
+    /// Speak JSON-RPC over stdin/stdout (the only transport)
+    #[arg(long)]
+    stdio: bool,
+    /// Default templates source for template lookups (folder with repositories.yaml/.yml, git repo, HTTP base URL, or gh-org:<org>?topic=<topic>)
+    #[arg(
+        long = "templates",
+        env = "LISCAF_TEMPLATES",
+        value_name = "PATH_OR_URL",
+        default_value = DEFAULT_TEMPLATES_SOURCE
+    )]
+    templates_source: String,
+    /// Directory for temporary clones and transforms (defaults to the system temp dir)
+    #[arg(long = "tmpdir", env = "LISCAF_TMPDIR", value_name = "PATH")]
+    tmpdir: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+struct InfoArgs {
+    /// Template name from the registries, repo URL, or local template directory
+    template: String,
+    /// Templates source (folder with repositories.yaml/.yml, git repo, HTTP base URL, or gh-org:<org>?topic=<topic>)
+    #[arg(
+        long = "templates",
+        env = "LISCAF_TEMPLATES",
+        value_name = "PATH_OR_URL",
+        default_value = DEFAULT_TEMPLATES_SOURCE
+    )]
+    templates_source: String,
+    /// Directory for temporary clones (defaults to the system temp dir)
+    #[arg(long = "tmpdir", env = "LISCAF_TMPDIR", value_name = "PATH")]
+    tmpdir: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+struct BatchArgs {
+    /// Plan file listing the projects to generate
+    plan: PathBuf,
+    /// If set, show planned changes but don't write files or initialize git
+    #[arg(long)]
+    dry_run: bool,
+    /// Directory for temporary clones and transforms (defaults to the system temp dir)
+    #[arg(long = "tmpdir", env = "LISCAF_TMPDIR", value_name = "PATH")]
+    tmpdir: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+struct RebrandArgs {
+    /// File listing one directory (relative to the file) or repository URL per line
+    list: PathBuf,
+    /// Current name (e.g. acme-app)
+    from: String,
+    /// New name (e.g. globex-app)
+    to: String,
+    /// Create this branch in every git repository and commit the changes on it
+    #[arg(long, value_name = "NAME")]
+    branch: Option<String>,
+    /// Commit message for --branch (default: "Rename <from> to <to>")
+    #[arg(long, short = 'm', value_name = "MESSAGE", requires = "branch")]
+    message: Option<String>,
+    /// Directory repository URLs are cloned into
+    #[arg(long, value_name = "PATH", default_value = "rebrand")]
+    workdir: PathBuf,
+    /// If set, show planned changes but don't write files or commit
+    #[arg(long)]
+    dry_run: bool,
+    /// Variant forms not to replace (comma-separated, e.g. concatlower,concatupper)
+    #[arg(long = "skip-variant", value_name = "VARIANTS", value_delimiter = ',')]
+    skip_variants: Vec<String>,
+    /// Keep this acronym in one token and spell it as given in camel/Pascal forms (repeatable, e.g. HTTP, OAuth2)
+    #[arg(long = "acronym", value_name = "WORD")]
+    acronyms: Vec<String>,
+    /// Only replace/rename within paths matching this glob (repeatable, e.g. 'src/**')
+    #[arg(long = "only", value_name = "GLOB")]
+    only: Vec<String>,
+    /// Never replace/rename within paths matching this glob (repeatable, e.g. 'docs/legal/**')
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude: Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+struct AddArgs {
+    /// Fragment declared in the template manifest (e.g. api-endpoint)
+    fragment: String,
+    /// Name for the generated component (e.g. users)
+    name: String,
+    /// Template repo URL or local directory (defaults to the project's .scaffold.json template)
+    #[arg(long = "from", value_name = "REPO_URL")]
+    from: Option<String>,
+    /// Project directory (defaults to current directory)
+    #[arg(long = "path", value_name = "PATH")]
+    path: Option<PathBuf>,
+    /// Set a fragment variable (repeatable)
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = variables::parse_key_value)]
+    vars: Vec<(String, String)>,
+    /// If set, show planned changes but don't write files
+    #[arg(long)]
+    dry_run: bool,
+    /// Assume yes to all prompts (non-interactive)
+    #[arg(short = 'y', long = "yes")]
+    yes: bool,
+    /// Directory for temporary clones and transforms (defaults to the system temp dir)
+    #[arg(long = "tmpdir", env = "LISCAF_TMPDIR", value_name = "PATH")]
+    tmpdir: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+struct UpdateArgs {
+    /// Project directory (defaults to current directory)
+    #[arg(long = "path", value_name = "PATH")]
+    path: Option<PathBuf>,
+    /// Template commit to update to instead of the latest
+    #[arg(long, value_name = "SHA")]
+    commit: Option<String>,
+    /// Set a template variable (repeatable), overriding the recorded answer
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = variables::parse_key_value)]
+    vars: Vec<(String, String)>,
+    /// If set, show planned changes but don't write files
+    #[arg(long)]
+    dry_run: bool,
+    /// Assume yes to all prompts (non-interactive)
+    #[arg(short = 'y', long = "yes")]
+    yes: bool,
+    /// Directory for temporary clones and transforms (defaults to the system temp dir)
+    #[arg(long = "tmpdir", env = "LISCAF_TMPDIR", value_name = "PATH")]
+    tmpdir: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+struct UpgradeTemplateArgs {
+    /// Project directory (defaults to current directory)
+    #[arg(long = "path", value_name = "PATH")]
+    path: Option<PathBuf>,
+    /// Template revision (commit, tag or branch) to move to instead of the latest commit
+    #[arg(long, value_name = "REV")]
+    to: Option<String>,
+    /// Update the project to the new revision right away, as `liscaf update --commit` does
+    #[arg(long)]
+    update: bool,
+    /// Show the changes without moving the pin
+    #[arg(long)]
+    dry_run: bool,
+    /// Assume yes to all prompts (non-interactive)
+    #[arg(short = 'y', long = "yes")]
+    yes: bool,
+    /// Directory for temporary clones and transforms (defaults to the system temp dir)
+    #[arg(long = "tmpdir", env = "LISCAF_TMPDIR", value_name = "PATH")]
+    tmpdir: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+struct ListArgs {
+    /// Templates source (folder with repositories.yaml/.yml, git repo, HTTP base URL, or gh-org:<org>?topic=<topic>)
+    #[arg(
+        long = "templates",
+        env = "LISCAF_TEMPLATES",
+        value_name = "PATH_OR_URL",
+        default_value = DEFAULT_TEMPLATES_SOURCE
+    )]
+    templates_source: String,
+}
+
+#[derive(Parser, Debug)]
+struct ValidateArgs {
+    /// Template directory (defaults to current directory)
+    path: Option<PathBuf>,
+    /// Name the template uses, replaced by the project name
+    #[arg(long = "base", value_name = "NAME", default_value = "acme-app")]
+    template_base: String,
+}
+
+#[derive(Parser, Debug)]
+struct TestArgs {
+    /// Template directory or repository URL (defaults to current directory)
+    template: Option<String>,
+    /// Name the template uses, replaced by the project name
+    #[arg(long = "base", value_name = "NAME", default_value = "acme-app")]
+    template_base: String,
+    /// Run only this fixture (repeatable; file name without .answers.toml)
+    #[arg(long = "fixture", value_name = "NAME")]
+    fixtures: Vec<String>,
+    /// Keep the rendered projects and print where they are
+    #[arg(long)]
+    keep: bool,
+    /// Directory for temporary clones and rendered projects (defaults to the system temp dir)
+    #[arg(long = "tmpdir", env = "LISCAF_TMPDIR", value_name = "PATH")]
+    tmpdir: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+struct RenameArgs {
+    /// Current name to replace (e.g. acme-app or myOtherSentence)
+    from: String,
+    /// New name to substitute (e.g. payments-service or newProjectSentence)
+    to: String,
+    /// Target directory (defaults to current directory)
+    #[arg(long = "path", value_name = "PATH")]
+    path: Option<PathBuf>,
+    /// If set, show planned changes but don't write files
+    #[arg(long)]
+    dry_run: bool,
+    /// Print a unified diff of every rewritten file
+    #[arg(long)]
+    diff: bool,
+    /// Variant forms not to replace (comma-separated, e.g. concatlower,concatupper)
+    #[arg(long = "skip-variant", value_name = "VARIANTS", value_delimiter = ',')]
+    skip_variants: Vec<String>,
+    /// Only replace/rename within paths matching this glob (repeatable, e.g. 'src/**')
+    #[arg(long = "only", value_name = "GLOB")]
+    only: Vec<String>,
+    /// Never replace/rename within paths matching this glob (repeatable, e.g. 'docs/legal/**')
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude: Vec<String>,
+    /// Skip files larger than this when replacing (e.g. 10MB; 0 for no limit)
+    #[arg(long = "max-file-size", value_name = "SIZE", value_parser = filter::parse_size)]
+    max_file_size: Option<u64>,
+    /// Also skip directories with this name during replacement (repeatable)
+    #[arg(long = "skip-dir", value_name = "NAME")]
+    skip_dirs: Vec<String>,
+    /// Walk node_modules, target, .venv, dist and vendor too
+    #[arg(long)]
+    no_default_skip_dirs: bool,
+    /// Keep this acronym in one token and spell it as given in camel/Pascal forms (repeatable, e.g. HTTP, OAuth2)
+    #[arg(long = "acronym", value_name = "WORD")]
+    acronyms: Vec<String>,
+    /// Replace in file contents only; keep file and directory names
+    #[arg(long, conflicts_with = "no_content")]
+    no_rename: bool,
+    /// Rename files and directories only; leave file contents unchanged
+    #[arg(long)]
+    no_content: bool,
+    /// Walk at most this many directory levels below the root when replacing, renaming and merging
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+    /// Walk files and directories whose name starts with '.'
+    #[arg(long, value_name = "BOOL", default_value_t = true, action = clap::ArgAction::Set)]
+    include_hidden: bool,
+    /// Follow symlinks to files and directories instead of leaving them as they are
+    #[arg(long)]
+    follow_symlinks: bool,
+    /// Fail, listing the matches, if any variant of the old name is left in contents or paths afterwards
+    #[arg(long)]
+    assert_no_residue: bool,
+    /// Replace inside lockfiles (Cargo.lock, package-lock.json, ...) instead of leaving them for regeneration
+    #[arg(long)]
+    replace_in_lockfiles: bool,
+    /// Rewrite file and directory names the target filesystem would reject (fat, ntfs or posix)
+    #[arg(long, value_name = "FS", value_parser = fscompat::parse)]
+    fs_compat: Option<fscompat::FsCompat>,
+    /// Regenerate lockfiles that still name the template without asking
+    #[arg(long, conflicts_with = "replace_in_lockfiles")]
+    regenerate_lockfiles: bool,
+    /// Extra mapping applied with the generated ones (repeatable): OLD=>NEW, or regex:PATTERN=>REPLACEMENT with $1 for groups
+    #[arg(long = "map", value_name = "OLD=>NEW", value_parser = mappings::parse_map)]
+    maps: Vec<(String, String)>,
+    /// Read old/new pairs from this TOML file, added to the generated mappings or (with generated = false) used instead
+    #[arg(long = "mappings", value_name = "FILE")]
+    mappings_file: Option<PathBuf>,
+    /// Write the mappings to this TOML file for review and reuse with --mappings, then stop without changing anything
+    #[arg(long, value_name = "FILE")]
+    export_mappings: Option<PathBuf>,
+    /// Also replace inside zip-container documents (.docx, .xlsx, .pptx, .odt, ...)
+    #[arg(long)]
+    documents: bool,
+    /// In JSON, YAML and TOML files replace only inside string values, never in keys or YAML anchors
+    #[arg(long)]
+    structured: bool,
+    /// With --structured, replace in keys and YAML anchors/aliases as well
+    #[arg(long)]
+    structured_keys: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(after_help = prompt::HELP)]
+struct ScaffoldArgs {
+    /// New project name (used to replace template tokens), or a template from the registries followed by the project name
+    new_name: String,
+
+    /// Git repo URL (HTTPS or SSH). Examples: https://github.com/owner/repo or git@github.com:owner/repo.git. After a template name: the project name
+    repo_url: Option<String>,
+    /// Templates source (folder with repositories.yaml/.yml, git repo, HTTP base URL, or gh-org:<org>?topic=<topic>)
+    #[arg(
+        long = "templates",
+        env = "LISCAF_TEMPLATES",
+        value_name = "PATH_OR_URL",
+        default_value = DEFAULT_TEMPLATES_SOURCE
+    )]
+    templates_source: String,
+    /// If set, show planned changes but don't write files or initialize git
+    #[arg(long)]
+    dry_run: bool,
+    /// Assume yes to all prompts (non-interactive)
+    #[arg(short = 'y', long = "yes")]
+    yes: bool,
+    /// Merge scaffold output into an existing directory instead of creating a new one
+    #[arg(long = "into", value_name = "PATH")]
+    into: Option<PathBuf>,
+    /// With --into, merge into the destination root even if it is a Cargo/pnpm/npm workspace
+    #[arg(long)]
+    no_workspace: bool,
+    /// Set a template variable (repeatable), e.g. --var module=billing
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = variables::parse_key_value)]
+    vars: Vec<(String, String)>,
+    /// Remove .gitkeep files from directories that contain other files
+    #[arg(long)]
+    strip_gitkeep: bool,
+    /// Run the template's bootstrap commands (e.g. npm install) without asking
+    #[arg(long, conflicts_with = "no_bootstrap")]
+    bootstrap: bool,
+    /// Never run the template's bootstrap commands
+    #[arg(long)]
+    no_bootstrap: bool,
+    /// Keep files the template's own .gitignore excludes
+    #[arg(long)]
+    no_ignore: bool,
+    /// Directory for temporary clones and transforms (defaults to the system temp dir)
+    #[arg(long = "tmpdir", env = "LISCAF_TMPDIR", value_name = "PATH")]
+    tmpdir: Option<PathBuf>,
+    /// Clone the template directly instead of using the local template cache
+    #[arg(long)]
+    no_cache: bool,
+    /// Use this exact template commit (full SHA) instead of the default branch
+    #[arg(long, value_name = "SHA")]
+    commit: Option<String>,
+    /// Don't check the project name against crates.io / npm naming rules and existing packages
+    #[arg(long)]
+    no_name_check: bool,
+    /// Ecosystem-aware manifest post-processing modes (comma-separated: cargo, npm, go, helm, reset)
+    #[arg(long, value_name = "MODES", value_delimiter = ',')]
+    postprocess: Vec<String>,
+    /// Review, drop or adjust the generated variant mappings before replacing
+    #[arg(long)]
+    edit_mappings: bool,
+    /// Variant forms not to replace (comma-separated, e.g. concatlower,concatupper)
+    #[arg(long = "skip-variant", value_name = "VARIANTS", value_delimiter = ',')]
+    skip_variants: Vec<String>,
+    /// Only replace/rename within paths matching this glob (repeatable, e.g. 'src/**')
+    #[arg(long = "only", value_name = "GLOB")]
+    only: Vec<String>,
+    /// Never replace/rename within paths matching this glob (repeatable, e.g. 'docs/legal/**')
+    #[arg(long = "exclude", value_name = "GLOB")]
+    exclude: Vec<String>,
+    /// Skip files larger than this when replacing (e.g. 10MB; 0 for no limit)
+    #[arg(long = "max-file-size", value_name = "SIZE", value_parser = filter::parse_size)]
+    max_file_size: Option<u64>,
+    /// Also skip directories with this name during replacement (repeatable)
+    #[arg(long = "skip-dir", value_name = "NAME")]
+    skip_dirs: Vec<String>,
+    /// Walk node_modules, target, .venv, dist and vendor too
+    #[arg(long)]
+    no_default_skip_dirs: bool,
+    /// Keep this acronym in one token and spell it as given in camel/Pascal forms (repeatable, e.g. HTTP, OAuth2)
+    #[arg(long = "acronym", value_name = "WORD")]
+    acronyms: Vec<String>,
+    /// Replace in file contents only; keep file and directory names
+    #[arg(long, conflicts_with = "no_content")]
+    no_rename: bool,
+    /// Rename files and directories only; leave file contents unchanged
+    #[arg(long)]
+    no_content: bool,
+    /// Walk at most this many directory levels below the root when replacing, renaming and merging
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+    /// Walk files and directories whose name starts with '.'
+    #[arg(long, value_name = "BOOL", default_value_t = true, action = clap::ArgAction::Set)]
+    include_hidden: bool,
+    /// Follow symlinks to files and directories instead of leaving them as they are
+    #[arg(long)]
+    follow_symlinks: bool,
+    /// Fail, listing the matches, if any variant of the old name is left in contents or paths afterwards
+    #[arg(long)]
+    assert_no_residue: bool,
+    /// Replace inside lockfiles (Cargo.lock, package-lock.json, ...) instead of leaving them for regeneration
+    #[arg(long)]
+    replace_in_lockfiles: bool,
+    /// Rewrite file and directory names the target filesystem would reject (fat, ntfs or posix)
+    #[arg(long, value_name = "FS", value_parser = fscompat::parse)]
+    fs_compat: Option<fscompat::FsCompat>,
+    /// Regenerate lockfiles that still name the template without asking
+    #[arg(long, conflicts_with = "replace_in_lockfiles")]
+    regenerate_lockfiles: bool,
+    /// Extra mapping applied with the generated ones (repeatable): OLD=>NEW, or regex:PATTERN=>REPLACEMENT with $1 for groups
+    #[arg(long = "map", value_name = "OLD=>NEW", value_parser = mappings::parse_map)]
+    maps: Vec<(String, String)>,
+    /// Read old/new pairs from this TOML file, added to the generated mappings or (with generated = false) used instead
+    #[arg(long = "mappings", value_name = "FILE")]
+    mappings_file: Option<PathBuf>,
+    /// Write the mappings to this TOML file for review and reuse with --mappings, then stop without changing anything
+    #[arg(long, value_name = "FILE")]
+    export_mappings: Option<PathBuf>,
+    /// Also replace inside zip-container documents (.docx, .xlsx, .pptx, .odt, ...)
+    #[arg(long)]
+    documents: bool,
+    /// In JSON, YAML and TOML files replace only inside string values, never in keys or YAML anchors
+    #[arg(long)]
+    structured: bool,
+    /// With --structured, replace in keys and YAML anchors/aliases as well
+    #[arg(long)]
+    structured_keys: bool,
+    /// Byte-identical output across runs and machines: timestamps from SOURCE_DATE_EPOCH or the template commit, seeded generators, nothing from git config
+    #[arg(long)]
+    reproducible: bool,
+    /// Answer the template's variables from this profile of its manifest, without prompting
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+    /// Open the new project in the configured editor (config `editor`, else VISUAL or EDITOR)
+    #[arg(long)]
+    open: bool,
+    /// Run formatters before the initial commit: the template's, else cargo fmt, gofmt or prettier where the project uses them
+    #[arg(long)]
+    format: bool,
+    /// Leave copyright notices as they are instead of updating their year and holder
+    #[arg(long)]
+    keep_copyright: bool,
+    /// Don't install the template's git hooks (.hooks/, .pre-commit-config.yaml)
+    #[arg(long)]
+    no_hooks: bool,
+    /// When running as root, give the created files to the owner of the destination directory
+    #[arg(long)]
+    preserve_owner: bool,
+    /// Write the project as an archive (.tar.gz, .tgz or .zip) instead of a directory
+    #[arg(long, value_name = "FILE", value_parser = archive::parse_path, conflicts_with = "into")]
+    archive: Option<PathBuf>,
+    /// Don't initialize a git repository or create the initial commit
+    #[arg(long)]
+    no_git: bool,
+    /// Scaffold even if tools the template requires are missing or too old
+    #[arg(long)]
+    no_tool_check: bool,
+    /// Print how long clone, manifest, replacement, renaming, merge and git init took, with file throughput
+    #[arg(long)]
+    timings: bool,
+}
+
+/// Runs the command-line interface.
+pub fn run() -> anyhow::Result<()> {
+    let args = parse_args(std::env::args_os().collect()).unwrap_or_else(|e| e.exit());
+    i18n::configure(args.lang.as_deref())?;
+    if args.porcelain {
+        porcelain::enable()?;
+    }
+    color::configure(args.color);
+    let _capture = args.log_file.as_deref().map(logfile::start).transpose()?;
+    let result = run_command(args);
+    if let Err(ref e) = result {
+        logfile::note(&format!("Error: {:?}", e));
+    }
+    result
+}
+
+/// Parses the command line. `liscaf <name> [url]` without a `liscaf-<name>` command is parsed
+/// again as `liscaf new <name> [url]`, so global flags after the name (`--porcelain`,
+/// `--color never`) apply to the scaffold.
+fn parse_args(argv: Vec<std::ffi::OsString>) -> Result<Args, clap::Error> {
+    let args = Args::try_parse_from(&argv)?;
+    let Some(CliCommand::External(external_args)) = &args.command else {
+        return Ok(args);
+    };
+    if external::exists(&external_args[0]) {
+        return Ok(args);
+    }
+    let (globals, rest) = argv.split_at(argv.len() - external_args.len());
+    Args::try_parse_from(
+        globals
+            .iter()
+            .cloned()
+            .chain(std::iter::once("new".into()))
+            .chain(rest.iter().cloned()),
+    )
+}
+
+fn run_command(args: Args) -> anyhow::Result<()> {
+    let mut config = config::load()?;
+    net::configure(args.proxy.as_deref(), &config.network)?;
+    config::apply_remote(&mut config)?;
+    prompt::configure(args.prompt_timeout, args.on_prompt_timeout, &config.prompts)?;
+    protocol::configure(&config);
+    cache::configure(&config.cache)?;
+    conflicts::configure(&config.merge);
+    notify::configure(&config.webhooks);
+
+    let Some(command) = args.command else {
+        return run_wizard(&config);
+    };
+    match command {
+        CliCommand::New(scaffold_args) => run_scaffold_command(scaffold_args, &config)?,
+        CliCommand::Update(update_args) => run_update_command(update_args)?,
+        CliCommand::UpgradeTemplate(upgrade_args) => {
+            let project_dir = match upgrade_args.path {
+                Some(path) => path,
+                None => std::env::current_dir()?,
+            };
+            upgrade::run_upgrade(&upgrade::UpgradeRequest {
+                project_dir: &project_dir,
+                to: upgrade_args.to.as_deref(),
+                update: upgrade_args.update,
+                dry_run: upgrade_args.dry_run,
+                assume_yes: upgrade_args.yes,
+                tmpdir: upgrade_args.tmpdir.as_deref(),
+            })?
+        }
+        CliCommand::List(list_args) => {
+            let registries = registry::registries(&list_args.templates_source, &config.registries);
+            registry::print_templates(&registry::load_templates(&registries)?);
+        }
+        CliCommand::Rename(rename_args) => run_rename_command(rename_args)?,
+        CliCommand::Add(add_args) => run_add_command(add_args)?,
+        CliCommand::Batch(batch_args) => batch::run_batch(
+            &batch_args.plan,
+            batch_args.dry_run,
+            batch_args.tmpdir.as_deref(),
+        )?,
+        CliCommand::Rebrand(rebrand_args) => rebrand::run_rebrand(
+            &rebrand_args.list,
+            &rebrand::RebrandOptions {
+                from: &rebrand_args.from,
+                to: &rebrand_args.to,
+                branch: rebrand_args.branch.as_deref(),
+                message: rebrand_args.message.as_deref(),
+                workdir: &rebrand_args.workdir,
+                skip_variants: &rebrand_args.skip_variants,
+                acronyms: &rebrand_args.acronyms,
+                only: &rebrand_args.only,
+                exclude: &rebrand_args.exclude,
+                dry_run: rebrand_args.dry_run,
+            },
+        )?,
+        CliCommand::Doctor(doctor_args) => {
+            let registries = registry::registries(&doctor_args.templates_source, &config.registries);
+            doctor::run_doctor(&registries, doctor_args.tmpdir.as_deref())?
+        }
+        CliCommand::Info(info_args) => {
+            let registries = registry::registries(&info_args.templates_source, &config.registries);
+            info::run_info(&info_args.template, &registries, info_args.tmpdir.as_deref())?
+        }
+        CliCommand::Serve(serve_args) => {
+            if !serve_args.stdio {
+                anyhow::bail!("liscaf serve needs a transport; pass --stdio");
+            }
+            serve::run(&serve_args.templates_source, &config, serve_args.tmpdir.as_deref())?
+        }
+        CliCommand::Cache { command } => match command {
+            CacheCommand::List => cache::list()?,
+            CacheCommand::Clean { filter } => cache::clean(filter.as_deref())?,
+            CacheCommand::Prune { max_size } => cache::prune(max_size)?,
+        },
+        CliCommand::Validate(validate_args) => {
+            let dir = match validate_args.path {
+                Some(path) => path,
+                None => std::env::current_dir()?,
+            };
+            validate::run_validate(&dir, &validate_args.template_base)?
+        }
+        CliCommand::Test(test_args) => fixtures::run_tests(
+            test_args.template.as_deref().unwrap_or("."),
+            &fixtures::TestOptions {
+                template_base: &test_args.template_base,
+                only: &test_args.fixtures,
+                keep: test_args.keep,
+                tmpdir: test_args.tmpdir.as_deref(),
+            },
+        )?,
+        CliCommand::Fav { command } => match command {
+            FavCommand::Add {
+                template,
+                name,
+                templates_source,
+            } => {
+                let registries = registry::registries(&templates_source, &config.registries);
+                let entry = info::resolve(&template, &registries)?;
+                let short_name = name.unwrap_or_else(|| {
+                    if entry.name == entry.url {
+                        template.clone()
+                    } else {
+                        entry.name.clone()
+                    }
+                });
+                favorites::add(&short_name, &entry)?
+            }
+            FavCommand::Remove { name } => favorites::remove(&name)?,
+            FavCommand::List => favorites::list(&config.favorites),
+        },
+        CliCommand::External(external_args) => {
+            external::run(&external_args, args.proxy.as_deref())?
+        }
+    }
+
+    Ok(())
+}
+
+fn run_rename_command(args: RenameArgs) -> anyhow::Result<()> {
+    let base = match args.path {
+        Some(path) => path,
+        None => std::env::current_dir()?,
+    };
+
+    if !base.exists() {
+        anyhow::bail!("Target path does not exist: {}", base.display());
+    }
+    if !base.is_dir() {
+        anyhow::bail!("Target path is not a directory: {}", base.display());
+    }
+
+    let rules = TokenizerRules::default().with_acronyms(&args.acronyms);
+    let from_tokens = split_name_to_tokens(&args.from, &rules);
+    let to_tokens = split_name_to_tokens(&args.to, &rules);
+    validate_variant_names(&args.skip_variants)?;
+    let filter = filter::PathFilter::new(&args.only, &args.exclude)?
+        .with_max_file_size(args.max_file_size.unwrap_or(filter::DEFAULT_MAX_FILE_SIZE))
+        .with_skip_dirs(skip_dir_names(args.no_default_skip_dirs, &[], &args.skip_dirs))
+        .with_walk_options(WalkOptions {
+            max_depth: args.max_depth,
+            include_hidden: args.include_hidden,
+            follow_symlinks: args.follow_symlinks,
+        })
+        .with_documents(args.documents)
+        .with_structured(structured::Mode::new(args.structured, args.structured_keys))
+        .with_lockfiles(args.replace_in_lockfiles)
+        .with_fs_compat(args.fs_compat);
+    let mappings_file = args.mappings_file.as_deref().map(mappings::load_file).transpose()?;
+    let generated = generate_variant_mappings(&from_tokens, &to_tokens, &args.skip_variants, &rules);
+    let mut mappings = mappings::combine(generated.clone(), mappings_file.as_ref());
+    mappings.extend(args.maps.iter().cloned());
+
+    println!("Replacing tokens in: {}", base.display());
+    println!("Generated {} variant mappings", mappings.len());
+    for (o, n) in &mappings {
+        println!("  {} -> {}", o, n);
+    }
+    if let Some(ref path) = args.export_mappings {
+        return mappings::export(path, &mappings);
+    }
+
+    let simulated;
+    let fs: &dyn ScaffoldFs = if args.dry_run {
+        simulated = MemoryFs::snapshot(&base, &filter)?;
+        &simulated
+    } else {
+        &RealFs
+    };
+    let diffing = scaffold_fs::DiffFs { inner: fs, root: &base };
+    let fs: &dyn ScaffoldFs = if args.diff { &diffing } else { fs };
+    if args.no_content {
+        println!("Skipping replacement in file contents (--no-content)");
+    } else {
+        replace_in_files(fs, &base, &mappings, &filter, args.dry_run)?;
+    }
+    if args.no_rename {
+        println!("Skipping renames (--no-rename)");
+    } else {
+        rename_paths(fs, &base, &mappings, &filter, args.dry_run)?;
+    }
+    // The target directory itself may have been renamed
+    let renamed_base = base
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(|name| base.with_file_name(apply_mappings(name, &mappings)))
+        .filter(|renamed| !args.dry_run && !base.exists() && renamed.is_dir());
+    let root = renamed_base.as_deref().unwrap_or(&base);
+    if !args.no_content && !args.replace_in_lockfiles {
+        lockfiles::regenerate(root, &mappings, args.regenerate_lockfiles, false, args.dry_run);
+    }
+    if args.assert_no_residue {
+        if args.dry_run {
+            println!("Dry run: skipping the residue check");
+        } else {
+            assert_no_residue(root, &generated)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A favorite, an alias from the config, a registry template or a repository URL.
+fn resolve_template(
+    template: &str,
+    registries: &[registry::Registry],
+    config: &config::Config,
+) -> anyhow::Result<TemplateEntry> {
+    if let Some(favorite) = favorites::find(&config.favorites, template) {
+        return Ok(favorite.entry());
+    }
+    let alias = config
+        .aliases
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(template));
+    match alias {
+        Some((alias, target)) => {
+            let mut entry = info::resolve(target, registries)?;
+            entry.label = format!("{} ({})", alias, entry.label);
+            Ok(entry)
+        }
+        None => info::resolve(template, registries),
+    }
+}
+
+/// Plain `liscaf`: pick a template, enter a name, answer the template's variables, review the
+/// planned changes and confirm.
+fn run_wizard(config: &config::Config) -> anyhow::Result<()> {
+    println!("{}", tr!("liscaf — new project wizard (liscaf --help lists all commands)"));
+    let templates_source = std::env::var("LISCAF_TEMPLATES")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| DEFAULT_TEMPLATES_SOURCE.to_string());
+    let registries = registry::registries(&templates_source, &config.registries);
+    let entry = prompt_for_repo_url(&registries, &config.favorites, None)?;
+    let new_name = Text::new("Project name:")
+        .with_placeholder("my-cool-app")
+        .with_validator(|input: &str| {
+            Ok(if input.trim().is_empty() {
+                inquire::validator::Validation::Invalid(tr!("Enter a name").into())
+            } else {
+                inquire::validator::Validation::Valid
+            })
+        })
+        .ask()?;
+    let template_base = Text::new("Name used in the template:")
+        .with_default("acme-app")
+        .ask()?;
+
+    // Variables are asked and the plan is shown for confirmation while scaffolding
+    let options = ScaffoldOptions {
+        workspace_aware: true,
+        commit: entry.commit,
+        recent_label: Some(entry.label),
+        notify: true,
+        default_branch: config.defaults.default_branch.clone(),
+        ..Default::default()
+    };
+    let repo_url = normalize_repo_url(&entry.url);
+    run_scaffold(&repo_url, new_name.trim(), &template_base, &options)?;
+    Ok(())
+}
+
+/// Scaffolds the recorded template again with the recorded name and answers and merges the
+/// result into the project, as `--into` does.
+fn run_update_command(args: UpdateArgs) -> anyhow::Result<()> {
+    let project_dir = match args.path {
+        Some(path) => path,
+        None => std::env::current_dir()?,
+    };
+    let metadata_path = project_dir.join(".scaffold.json");
+    let content = fs::read_to_string(&metadata_path).map_err(|e| {
+        anyhow::anyhow!(
+            "{} was not scaffolded by liscaf ({}: {})",
+            project_dir.display(),
+            metadata_path.display(),
+            e
+        )
+    })?;
+    let metadata: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", metadata_path.display(), e))?;
+    let field = |key: &str| metadata.get(key).and_then(|v| v.as_str());
+    let (Some(name), Some(repo_url)) = (field("project_name"), field("template_repo_url")) else {
+        anyhow::bail!("{} has no project name or template URL", metadata_path.display());
+    };
+    let template_base = field("template_base").unwrap_or("acme-app");
+    let mut vars: Vec<(String, String)> = metadata
+        .get("answers")
+        .and_then(|a| a.as_object())
+        .into_iter()
+        .flatten()
+        .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+        .filter(|(k, _)| !args.vars.iter().any(|(key, _)| key == k))
+        .collect();
+    vars.extend(args.vars);
+    println!(
+        "Updating '{}' from {} (recorded commit: {})",
+        name,
+        repo_url,
+        field("template_commit").unwrap_or("unknown")
+    );
+    let template_renames = match field("template_commit") {
+        Some(recorded) => upgrade::renames_since(repo_url, recorded, args.commit.as_deref(), args.tmpdir.as_deref()),
+        None => Vec::new(),
+    };
+
+    let options = ScaffoldOptions {
+        dry_run: args.dry_run,
+        assume_yes: args.yes,
+        into_dir: Some(project_dir),
+        vars,
+        tmpdir: args.tmpdir,
+        commit: args.commit,
+        no_name_check: true,
+        bootstrap: BootstrapMode::Never,
+        template_renames,
+        ..Default::default()
+    };
+    // The new metadata replaces the old instead of being merged with conflict markers
+    if !args.dry_run {
+        fs::remove_file(&metadata_path)?;
+    }
+    let result = run_scaffold(repo_url, name, template_base, &options);
+    if !args.dry_run && !metadata_path.exists() {
+        fs::write(&metadata_path, &content)?;
+    }
+    result?;
+    Ok(())
+}
+
+fn run_add_command(args: AddArgs) -> anyhow::Result<()> {
+    let project_dir = match args.path {
+        Some(path) => path,
+        None => std::env::current_dir()?,
+    };
+    if !project_dir.is_dir() {
+        anyhow::bail!("Project directory does not exist: {}", project_dir.display());
+    }
+    fragment::run_add(&fragment::AddRequest {
+        fragment: &args.fragment,
+        name: &args.name,
+        project_dir: &project_dir,
+        template_source: args.from.as_deref(),
+        vars: &args.vars,
+        dry_run: args.dry_run,
+        assume_yes: args.yes,
+        tmpdir: args.tmpdir.as_deref(),
+    })
+}
+
+fn run_scaffold_command(args: ScaffoldArgs, config: &config::Config) -> anyhow::Result<()> {
+    // Ask interactively whether to keep or edit the provided values (skip if --yes)
+    let assume_yes = args.yes;
+    let registries = registry::registries(&args.templates_source, &config.registries);
+    let mut repo_url = args.repo_url.unwrap_or_default();
+    let mut commit = args.commit;
+    let mut label = None;
+    let mut new_name = args.new_name;
+    // `new <template> <name>`: a second argument that is no repository URL is the project name
+    if !repo_url.is_empty() && !is_supported_repo_url(&normalize_repo_url(&repo_url)) {
+        let entry = resolve_template(&new_name, &registries, config)?;
+        println!("Template '{}': {}", entry.label, entry.url);
+        new_name = std::mem::replace(&mut repo_url, entry.url);
+        commit = commit.or(entry.commit);
+        label = Some(entry.label);
+    }
+    if !assume_yes
+        && !Confirm::new(&tr!("Use new project name '{}' ?", new_name))
+            .with_default(true)
+            .ask()?
+    {
+        new_name = Text::new("Enter new project name:")
+            .with_placeholder("my-cool-app")
+            .ask()?;
+    }
+
+    if repo_url.is_empty() {
+        if assume_yes {
+            anyhow::bail!("repo URL must be provided when running non-interactively");
+        }
+        let entry = prompt_for_repo_url(&registries, &config.favorites, args.tmpdir.as_deref())?;
+        repo_url = entry.url;
+        commit = commit.or(entry.commit);
+        label = Some(entry.label);
+    } else if !assume_yes
+        && !Confirm::new(&tr!("Use repo URL '{}' ?", repo_url))
+            .with_default(true)
+            .ask()?
+    {
+        let entry = prompt_for_repo_url(&registries, &config.favorites, args.tmpdir.as_deref())?;
+        repo_url = entry.url;
+        commit = commit.or(entry.commit);
+        label = Some(entry.label);
+    }
+    if let Some(ref sha) = commit {
+        println!("{}", tr!("Template pinned to commit {}", sha));
+    }
+
+    // Template base name to replace (default: acme-app)
+    let mut template_base = "acme-app".to_string();
+    if !assume_yes
+        && !Confirm::new(&tr!("Replace occurrences of '{}' ?", template_base))
+            .with_default(true)
+            .ask()?
+    {
+        template_base = Text::new("Enter template base name to replace (e.g. acme-app)")
+            .with_placeholder("acme-app")
+            .ask()?;
+    }
+
+    // Where the new project goes; `./<name>` without asking under --yes
+    let output_dir = match (&args.into, &args.archive) {
+        (None, None) if !assume_yes => Some(destination::prompt(&new_name)?),
+        _ => None,
+    };
+
+    if !assume_yes {
+        let target = args.into.as_ref().or(args.archive.as_ref()).or(output_dir.as_ref());
+        let proceed_msg = if let Some(into_dir) = target {
+            tr!(
+                "Proceed to scaffold '{}'\nfrom '{}' replacing '{}'\ninto '{}' ?",
+                new_name,
+                repo_url,
+                template_base,
+                into_dir.display()
+            )
+        } else {
+            tr!(
+                "Proceed to scaffold '{}'\nfrom '{}' replacing '{}' ?",
+                new_name,
+                repo_url,
+                template_base
+            )
+        };
+
+        if !Confirm::new(&proceed_msg).with_default(true).ask()? {
+            println!("{}", tr!("Aborted by user."));
+            return Ok(());
+        }
+    }
+
+    let options = ScaffoldOptions {
+        dry_run: args.dry_run,
+        assume_yes,
+        into_dir: args.into,
+        output_dir,
+        workspace_aware: !args.no_workspace,
+        vars: args.vars,
+        strip_gitkeep: args.strip_gitkeep,
+        postprocess: args.postprocess,
+        edit_mappings: args.edit_mappings,
+        skip_variants: args.skip_variants,
+        only: args.only,
+        exclude: args.exclude,
+        max_file_size: args.max_file_size,
+        skip_dirs: args.skip_dirs,
+        no_default_skip_dirs: args.no_default_skip_dirs,
+        no_ignore: args.no_ignore,
+        tmpdir: args.tmpdir,
+        no_cache: args.no_cache,
+        commit,
+        local_template: None,
+        template_renames: Vec::new(),
+        no_name_check: args.no_name_check,
+        reproducible: args.reproducible,
+        acronyms: args.acronyms,
+        no_rename: args.no_rename,
+        no_content: args.no_content,
+        walk: WalkOptions {
+            max_depth: args.max_depth,
+            include_hidden: args.include_hidden,
+            follow_symlinks: args.follow_symlinks,
+        },
+        assert_no_residue: args.assert_no_residue,
+        replace_in_lockfiles: args.replace_in_lockfiles,
+        fs_compat: args.fs_compat,
+        regenerate_lockfiles: args.regenerate_lockfiles,
+        maps: args.maps,
+        mappings_file: args.mappings_file,
+        export_mappings: args.export_mappings,
+        documents: args.documents,
+        structured: structured::Mode::new(args.structured, args.structured_keys),
+        recent_label: Some(label.unwrap_or_else(|| repo_url.clone())),
+        notify: true,
+        default_branch: config.defaults.default_branch.clone(),
+        profile: args.profile,
+        format: args.format,
+        no_hooks: args.no_hooks,
+        preserve_owner: args.preserve_owner,
+        archive: args.archive,
+        no_git: args.no_git,
+        keep_copyright: args.keep_copyright,
+        no_tool_check: args.no_tool_check,
+        timings: args.timings,
+        bootstrap: if args.bootstrap {
+            BootstrapMode::Always
+        } else if args.no_bootstrap {
+            BootstrapMode::Never
+        } else {
+            BootstrapMode::Ask
+        },
+        network: None,
+    };
+    // Run scaffold (synchronous, prints to stdout)
+    let repo_url = normalize_repo_url(&repo_url);
+    let editor = if args.open { Some(editor_command(config.editor.as_deref())?) } else { None };
+    let project_dir = run_scaffold(&repo_url, &new_name, &template_base, &options)?;
+    if let (Some(editor), Some(dir)) = (editor, project_dir) {
+        open_in_editor(&dir, &editor)?;
+    }
+
+    Ok(())
+}
+
+/// The configured editor, else `VISUAL` or `EDITOR`.
+fn editor_command(configured: Option<&str>) -> anyhow::Result<String> {
+    configured
+        .map(String::from)
+        .or_else(|| std::env::var("VISUAL").ok())
+        .or_else(|| std::env::var("EDITOR").ok())
+        .filter(|e| !e.trim().is_empty())
+        .ok_or_else(|| {
+            anyhow::anyhow!("No editor to open the project with; set `editor` in the liscaf config, VISUAL or EDITOR")
+        })
+}
+
+/// Runs the editor command (which may carry arguments, e.g. `code -n`) on `dir`.
+fn open_in_editor(dir: &Path, editor: &str) -> anyhow::Result<()> {
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or_default();
+    println!("OPEN: {} {}", editor, dir.display());
+    let status = Command::new(program)
+        .args(words)
+        .arg(dir)
+        .status()
+        .map_err(|e| anyhow::anyhow!("Failed to run editor '{}': {}", editor, e))?;
+    if !status.success() {
+        println!("Warning: editor '{}' exited with {}", editor, status);
+    }
+    Ok(())
+}
+
+/// Copies `src` into `dest`; files that exist with other content get conflict markers. Nothing
+/// is written in dry runs.
+fn merge_into_dest(
+    fs: &dyn ScaffoldFs,
+    src: &Path,
+    dest: &Path,
+    walk: &WalkOptions,
+    hashes_dir: &Path,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    println!("Merging scaffold into {}", dest.display());
+    // Loaded from `hashes_dir` on the first file that exists on both sides (and only on filesystems with mtimes).
+    let mut hashes: Option<MergeHashes> = None;
+    let mut reused = 0;
+    let style = conflicts::style();
+    for entry in fs.walk(src, walk, &mut |_| true) {
+        let src_path = entry.path.as_path();
+        if src_path.components().any(|c| c.as_os_str() == ".git") {
+            continue;
+        }
+        let rel = match src_path.strip_prefix(src) {
+            Ok(r) if !r.as_os_str().is_empty() => r,
+            _ => continue,
+        };
+        let dest_path = dest.join(rel);
+        let key = rel.to_string_lossy().replace('\\', "/");
+
+        if entry.is_dir {
+            if dry_run {
+                println!("DRY DIR: {}", dest_path.display());
+            } else {
+                fs.create_dir_all(&dest_path)?;
+            }
+            continue;
+        }
+
+        if !entry.is_file {
+            continue;
+        }
+
+        if !fs.exists(&dest_path) {
+            if dry_run {
+                status!("DRY ADD: {}", dest_path.display());
+            } else {
+                if let Some(parent) = dest_path.parent() {
+                    fs.create_dir_all(parent)?;
+                }
+                let contents = fs.read(src_path)?;
+                fs.write(&dest_path, &contents)?;
+                fs.copy_mode(src_path, &dest_path)?;
+                record_merge_hash(fs, &mut hashes, hashes_dir, dest, &dest_path, &key, &contents);
+                status!("ADD: {}", dest_path.display());
+            }
+            progress::emit(progress::Event::FileAdded {
+                path: progress::relative(dest, &dest_path),
+            });
+            continue;
+        }
+
+        let src_bytes = fs.read(src_path)?;
+        let dest_bytes = match fs.stat(&dest_path) {
+            Some((len, _)) if len != src_bytes.len() as u64 => fs.read(&dest_path)?,
+            Some(stat) => {
+                let hashes = hashes.get_or_insert_with(|| MergeHashes::load(hashes_dir, dest));
+                let incoming = merge_hashes::hex_sha256(&src_bytes);
+                if hashes.get(&key, stat) == Some(incoming.as_str()) {
+                    reused += 1;
+                    continue;
+                }
+                let dest_bytes = fs.read(&dest_path)?;
+                if src_bytes == dest_bytes {
+                    hashes.record(&key, stat, incoming);
+                    continue;
+                }
+                dest_bytes
+            }
+            None => {
+                let dest_bytes = fs.read(&dest_path)?;
+                if src_bytes == dest_bytes {
+                    continue;
+                }
+                dest_bytes
+            }
+        };
+
+        let src_text = bytes_to_text(&src_bytes);
+        let dest_text = bytes_to_text(&dest_bytes);
+
+        match (src_text, dest_text) {
+            (Some(incoming), Some(existing)) => {
+                let merged = conflicts::merge(&existing, &incoming, &style);
+                if dry_run {
+                    status!("DRY MERGE: {}", dest_path.display());
+                } else {
+                    fs.write(&dest_path, merged.as_bytes())?;
+                    record_merge_hash(fs, &mut hashes, hashes_dir, dest, &dest_path, &key, merged.as_bytes());
+                    status!("MERGE: {}", dest_path.display());
+                }
+                progress::emit(progress::Event::Conflict {
+                    path: progress::relative(dest, &dest_path),
+                });
+            }
+            _ => {
+                let incoming_path = unique_suffixed_path(fs, &dest_path, ".liscaf-incoming");
+                let conflict_path = unique_suffixed_path(fs, &dest_path, ".liscaf-conflict");
+                let note = conflicts::binary_note(&dest_path, &incoming_path, &style);
+                if dry_run {
+                    status!(
+                        "DRY BIN CONFLICT: {} (incoming -> {})",
+                        dest_path.display(),
+                        incoming_path.display()
+                    );
+                } else {
+                    if let Some(parent) = incoming_path.parent() {
+                        fs.create_dir_all(parent)?;
+                    }
+                    fs.write(&incoming_path, &src_bytes)?;
+                    fs.write(&conflict_path, note.as_bytes())?;
+                    status!(
+                        "BIN CONFLICT: {} (incoming -> {})",
+                        dest_path.display(),
+                        incoming_path.display()
+                    );
+                }
+                progress::emit(progress::Event::Conflict {
+                    path: progress::relative(dest, &dest_path),
+                });
+            }
+        }
+    }
+    if let Some(hashes) = hashes {
+        if reused > 0 {
+            println!("{} unchanged files skipped (hashes from an earlier merge)", format_count(reused));
+        }
+        if !dry_run {
+            hashes.save();
+        }
+    }
+    Ok(())
+}
+
+/// Moves the destination's copies of files the template renamed to their new paths, so the
+/// merge updates them in place instead of adding the new path next to the old one. Files that
+/// are gone, or whose new path is already taken, stay as they are.
+fn move_renamed_files(
+    dest: &Path,
+    renames: &[(String, String)],
+    mappings: &[(String, String)],
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    for (from, to) in renames {
+        let from_path = dest.join(apply_mappings(from, mappings));
+        let to_path = dest.join(apply_mappings(to, mappings));
+        if !from_path.is_file() || to_path.exists() {
+            continue;
+        }
+        if dry_run {
+            status!("DRY MOVE: {} -> {}", from_path.display(), to_path.display());
+            continue;
+        }
+        if let Some(parent) = to_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(&from_path, &to_path)?;
+        status!("MOVE: {} -> {}", from_path.display(), to_path.display());
+        // Directories the move emptied go too
+        let mut dir = from_path.parent();
+        while let Some(d) = dir.filter(|d| *d != dest && fs::remove_dir(d).is_ok()) {
+            dir = d.parent();
+        }
+    }
+    Ok(())
+}
+
+/// Remembers the hash of a file the merge just wrote, so the next merge need not read it.
+fn record_merge_hash(
+    fs: &dyn ScaffoldFs,
+    hashes: &mut Option<MergeHashes>,
+    hashes_dir: &Path,
+    dest: &Path,
+    path: &Path,
+    key: &str,
+    contents: &[u8],
+) {
+    if let Some(stat) = fs.stat(path) {
+        hashes
+            .get_or_insert_with(|| MergeHashes::load(hashes_dir, dest))
+            .record(key, stat, merge_hashes::hex_sha256(contents));
+    }
+}
+
+fn copy_dir_recursive(src: &Path, dest: &Path) -> anyhow::Result<()> {
+    for entry in WalkDir::new(src).into_iter().filter_map(|e| e.ok()) {
+        let rel = match entry.path().strip_prefix(src) {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        if rel.components().any(|c| c.as_os_str() == ".git") {
+            continue;
+        }
+        let target = dest.join(rel);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else if entry.file_type().is_file() {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Creates a temporary directory under `parent` (created if missing) or the system temp dir.
+fn make_tempdir(prefix: &str, parent: Option<&Path>) -> anyhow::Result<tempfile::TempDir> {
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(prefix);
+    let tmpdir = match parent {
+        Some(parent) => {
+            fs::create_dir_all(parent)?;
+            builder.tempdir_in(parent)
+        }
+        None => builder.tempdir(),
+    };
+    tmpdir.map_err(|e| anyhow::anyhow!("Failed to create temporary directory: {}", e))
+}
+
+/// Renames `src` to `dest`, falling back to copy + delete when they are on different
+/// filesystems (EXDEV, e.g. a tmpfs `/tmp`).
+fn move_dir(src: &Path, dest: &Path) -> anyhow::Result<()> {
+    match fs::rename(src, dest) {
+        Ok(()) => Ok(()),
+        Err(e) if is_cross_device(&e) => {
+            println!("Temporary directory is on another filesystem; copying to {}", dest.display());
+            if let Err(copy_err) = copy_tree(src, dest) {
+                let _ = fs::remove_dir_all(dest);
+                return Err(anyhow::anyhow!(
+                    "Failed to copy scaffold to {}: {}",
+                    dest.display(),
+                    copy_err
+                ));
+            }
+            if let Err(e) = fs::remove_dir_all(src) {
+                println!("Warning: failed to remove {}: {}", src.display(), e);
+            }
+            Ok(())
+        }
+        Err(e) => Err(anyhow::anyhow!(
+            "Failed to move {} to {}: {}",
+            src.display(),
+            dest.display(),
+            e
+        )),
+    }
+}
+
+fn is_cross_device(e: &std::io::Error) -> bool {
+    // EXDEV on Unix, ERROR_NOT_SAME_DEVICE on Windows
+    e.kind() == std::io::ErrorKind::CrossesDevices
+        || (cfg!(unix) && e.raw_os_error() == Some(18))
+        || (cfg!(windows) && e.raw_os_error() == Some(17))
+}
+
+/// Copies a directory tree as is, including `.git` and symlinks.
+fn copy_tree(src: &Path, dest: &Path) -> anyhow::Result<()> {
+    for entry in WalkDir::new(src) {
+        let entry = entry?;
+        let rel = entry.path().strip_prefix(src)?;
+        let target = dest.join(rel);
+        let file_type = entry.file_type();
+        if file_type.is_dir() {
+            fs::create_dir_all(&target)?;
+        } else if file_type.is_symlink() {
+            let link = fs::read_link(entry.path())?;
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(&link, &target)?;
+            #[cfg(not(unix))]
+            {
+                let _ = link;
+                fs::copy(entry.path(), &target)?;
+            }
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+fn bytes_to_text(bytes: &[u8]) -> Option<String> {
+    if bytes.contains(&0) {
+        return None;
+    }
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+fn unique_suffixed_path(fs: &dyn ScaffoldFs, base: &Path, suffix: &str) -> PathBuf {
+    let file_name = base
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("file");
+    let candidate = base.with_file_name(format!("{}{}", file_name, suffix));
+    if !fs.exists(&candidate) {
+        return candidate;
+    }
+    let mut i = 1;
+    loop {
+        let next = base.with_file_name(format!("{}{}{}", file_name, suffix, i));
+        if !fs.exists(&next) {
+            return next;
+        }
+        i += 1;
+    }
+}
+
+/// Flags controlling a scaffold run, collected from the CLI.
+#[derive(Debug, Default)]
+struct ScaffoldOptions {
+    dry_run: bool,
+    assume_yes: bool,
+    into_dir: Option<PathBuf>,
+    /// Directory to write a new project to; defaults to `./<new-name>`.
+    output_dir: Option<PathBuf>,
+    workspace_aware: bool,
+    vars: Vec<(String, String)>,
+    strip_gitkeep: bool,
+    postprocess: Vec<String>,
+    /// Open the mapping editor without asking first.
+    edit_mappings: bool,
+    /// Variant forms (`concatlower`, ...) excluded in addition to the manifest's.
+    skip_variants: Vec<String>,
+    /// `--only` / `--exclude` globs for replacement and renaming.
+    only: Vec<String>,
+    exclude: Vec<String>,
+    /// Size limit for files read during replacement; `None` uses the default.
+    max_file_size: Option<u64>,
+    /// Extra directory names not walked during replacement; the defaults unless disabled.
+    skip_dirs: Vec<String>,
+    no_default_skip_dirs: bool,
+    /// Keep files that the template's .gitignore excludes.
+    no_ignore: bool,
+    /// Parent directory for the temporary working copy; the system temp dir if unset.
+    tmpdir: Option<PathBuf>,
+    /// Clone directly instead of going through the template cache.
+    no_cache: bool,
+    /// Exact template commit to check out instead of the default branch.
+    commit: Option<String>,
+    /// Template directory used as it is instead of a repository (`liscaf test`).
+    local_template: Option<PathBuf>,
+    /// Template files renamed since the project's recorded commit (`liscaf update`), as
+    /// template paths; moved in the destination before merging.
+    template_renames: Vec<(String, String)>,
+    /// Skip the crates.io / npm package-name check.
+    no_name_check: bool,
+    /// Fixed timestamps and seeds, no machine-specific values (`--reproducible`).
+    reproducible: bool,
+    /// Acronyms in addition to the manifest's.
+    acronyms: Vec<String>,
+    /// Skip renaming paths (`--no-rename`) or replacing in contents (`--no-content`).
+    no_rename: bool,
+    no_content: bool,
+    /// Bounds of the replacement, rename and merge walks.
+    walk: WalkOptions,
+    /// Fail if variants of the template name are left in the output (`--assert-no-residue`).
+    assert_no_residue: bool,
+    /// Replace inside lockfiles instead of regenerating them afterwards.
+    replace_in_lockfiles: bool,
+    /// Filesystem whose naming rules generated paths must follow (`--fs-compat`).
+    fs_compat: Option<fscompat::FsCompat>,
+    /// Regenerate lockfiles without asking.
+    regenerate_lockfiles: bool,
+    /// Custom mappings added to the generated ones (`--map`).
+    maps: Vec<(String, String)>,
+    /// Pairs read from a mappings file (`--mappings`).
+    mappings_file: Option<PathBuf>,
+    /// Write the mappings to this file and stop (`--export-mappings`).
+    export_mappings: Option<PathBuf>,
+    /// Replace inside zip-container documents too (`--documents`).
+    documents: bool,
+    /// Structure-aware replacement in JSON, YAML and TOML (`--structured`).
+    structured: structured::Mode,
+    /// Record the template among the recently used ones under this label (CLI runs).
+    recent_label: Option<String>,
+    /// Send the configured completion webhooks (CLI runs).
+    notify: bool,
+    /// Branch of the initial commit; git's default unless set (`main` with `reproducible`).
+    default_branch: Option<String>,
+    /// Answer profile from the template manifest (`--profile`).
+    profile: Option<String>,
+    /// Run formatters on the tree before the initial commit (`--format`).
+    format: bool,
+    /// Leave the template's git hooks uninstalled.
+    no_hooks: bool,
+    /// Hand created files to the destination's owner when running as root.
+    preserve_owner: bool,
+    /// Write the project to this archive instead of a directory (`--archive`).
+    archive: Option<PathBuf>,
+    /// Skip git init and the initial commit (`--no-git`).
+    no_git: bool,
+    /// Leave copyright notices unchanged (`--keep-copyright`).
+    keep_copyright: bool,
+    /// Skip checking the tools the template requires.
+    no_tool_check: bool,
+    /// Print how long each phase took (`--timings`).
+    timings: bool,
+    bootstrap: BootstrapMode,
+    /// Proxy, CA bundle, timeouts and retries of this run; the CLI's configured ones if unset.
+    network: Option<std::sync::Arc<net::Settings>>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum BootstrapMode {
+    /// Ask before running; skipped with --yes since it executes template-provided commands
+    #[default]
+    Ask,
+    Always,
+    Never,
+}
+
+fn run_scaffold(
+    repo_url: &str,
+    new_name: &str,
+    template_base: &str,
+    options: &ScaffoldOptions,
+) -> anyhow::Result<Option<PathBuf>> {
+    match &options.network {
+        Some(network) => net::with_settings(network, || {
+            scaffold_with_settings(repo_url, new_name, template_base, options)
+        }),
+        None => scaffold_with_settings(repo_url, new_name, template_base, options),
+    }
+}
+
+fn scaffold_with_settings(
+    repo_url: &str,
+    new_name: &str,
+    template_base: &str,
+    options: &ScaffoldOptions,
+) -> anyhow::Result<Option<PathBuf>> {
+    let dry_run = options.dry_run;
+    let assume_yes = options.assume_yes;
+    let into_dir = options.into_dir.as_deref();
+    let mut timings = timings::Timings::new(options.timings);
+
+    println!("{}", tr!("Starting scaffolding for '{}'", new_name));
+    println!("Repo URL: {}", repo_url);
+
+    if options.local_template.is_none() && !is_supported_repo_url(repo_url) {
+        anyhow::bail!("Repo URL must be HTTPS, SSH (ssh://), or SCP-like (git@host:owner/repo.git)");
+    }
+    if let Some(archive) = options.archive.as_ref().filter(|a| a.exists()) {
+        anyhow::bail!("Archive already exists: {}", archive.display());
+    }
+
+    // Create a temporary directory
+    let tmpdir = make_tempdir("liscaf-", options.tmpdir.as_deref())?;
+    let tmp_path = tmpdir.path().to_path_buf();
+    println!("Preparing template in temporary dir: {}", tmp_path.display());
+    // A previously cached copy tells the size before anything is fetched
+    if let Some(size) = cache::cached_size(repo_url, options.commit.as_deref()) {
+        diskspace::ensure(&tmp_path, size.saturating_mul(2), "template working copy")?;
+    }
+
+    // Template files without .git, unlinked from the original repository
+    let phase = Instant::now();
+    let revision = match options.local_template {
+        Some(ref dir) => {
+            copy_dir_recursive(dir, &tmp_path)?;
+            None
+        }
+        None => cache::checkout(
+            repo_url,
+            options.commit.as_deref(),
+            &tmp_path,
+            !options.no_cache,
+        )?,
+    };
+    let fixed_time = if options.reproducible {
+        Some(reproducible_time(revision.as_ref())?)
+    } else {
+        None
+    };
+    let template_commit = revision.map(|revision| revision.commit);
+    extends::resolve(&tmp_path, options.tmpdir.as_deref(), !options.no_cache)?;
+    timings.record("clone", phase);
+
+    let phase = Instant::now();
+    let manifest = manifest::load(&tmp_path)?;
+    timings.record("manifest", phase);
+    let Some(new_name) = names::validate(&manifest.name.rules, new_name, assume_yes)? else {
+        println!("{}", tr!("Aborted by user."));
+        return Ok(None);
+    };
+    let new_name = new_name.as_str();
+    if !options.no_name_check && !names::check(&tmp_path, &manifest, new_name, assume_yes)? {
+        println!("{}", tr!("Aborted by user."));
+        return Ok(None);
+    }
+    if !options.no_tool_check && !check_required_tools(&manifest, assume_yes)? {
+        println!("{}", tr!("Aborted by user."));
+        return Ok(None);
+    }
+    let plugins = plugin::load(&tmp_path, &manifest.plugins)?;
+    let rules = TokenizerRules::default()
+        .with_acronyms(&manifest.mappings.acronyms)
+        .with_acronyms(&options.acronyms);
+    // A profile answers like --var (which still wins) and leaves the rest at their defaults
+    let profile = variables::choose_profile(&manifest, options.profile.as_deref(), assume_yes)?;
+    let mut answers: Vec<(String, String)> = Vec::new();
+    if let Some((name, preset)) = profile {
+        println!("Using answer profile '{}'", name);
+        answers.extend(preset.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+    answers.extend(options.vars.iter().cloned());
+    let use_defaults = assume_yes || profile.is_some();
+    let mut vars = variables::resolve_variables(
+        new_name,
+        &manifest.variables,
+        &answers,
+        use_defaults,
+        fixed_time,
+        &rules,
+    )?;
+    variables::resolve_license(&mut vars, &manifest, &answers, use_defaults)?;
+    variables::resolve_contact(
+        &mut vars,
+        &manifest,
+        &tmp_path,
+        &answers,
+        use_defaults,
+        options.reproducible,
+    )?;
+    plugin::derive(&plugins, &mut vars)?;
+    strip_nested_git_repos(&tmp_path, dry_run)?;
+    if !options.no_ignore {
+        strip_gitignored_files(&tmp_path, dry_run)?;
+    }
+    strip_template_only_files(&tmp_path, &manifest.template_only, manifest.test_fixtures, dry_run)?;
+    conditional::apply(&tmp_path, &manifest.conditional, &vars, dry_run)?;
+
+    // Build mappings
+    let template_tokens = split_name_to_tokens(template_base, &rules);
+    let new_tokens = split_name_to_tokens(new_name, &rules);
+    println!("Template tokens: {:?}", template_tokens);
+    println!("New tokens: {:?}", new_tokens);
+    let mut skip_variants = manifest.mappings.skip_variants.clone();
+    skip_variants.extend(options.skip_variants.iter().cloned());
+    validate_variant_names(&skip_variants)?;
+    let generated = generate_variant_mappings(&template_tokens, &new_tokens, &skip_variants, &rules);
+    let mut mappings = generated.clone();
+    mappings.extend(variables::contact_mappings(&vars, &manifest));
+    for raw in &manifest.mappings.map {
+        mappings.push(mappings::parse_map(raw).map_err(anyhow::Error::msg)?);
+    }
+    let mappings_file = options.mappings_file.as_deref().map(mappings::load_file).transpose()?;
+    let mut mappings = mappings::combine(mappings, mappings_file.as_ref());
+    mappings.extend(options.maps.iter().cloned());
+    println!("Generated {} variant mappings", mappings.len());
+    for (o, n) in &mappings {
+        println!("  {} -> {}", o, n);
+    }
+    if options.edit_mappings
+        || (!assume_yes
+            && Confirm::new("Edit these mappings before replacing?")
+                .with_default(false)
+                .ask()?)
+    {
+        mappings = mappings::edit(mappings)?;
+    }
+    if let Some(ref path) = options.export_mappings {
+        mappings::export(path, &mappings)?;
+        return Ok(None);
+    }
+
+    // Replace in files
+    let filter = filter::PathFilter::new(&options.only, &options.exclude)?
+        .with_max_file_size(options.max_file_size.unwrap_or(filter::DEFAULT_MAX_FILE_SIZE))
+        .with_skip_dirs(skip_dir_names(
+            options.no_default_skip_dirs,
+            &manifest.mappings.skip_dirs,
+            &options.skip_dirs,
+        ))
+        .with_walk_options(options.walk)
+        .with_documents(options.documents || manifest.mappings.documents)
+        .with_structured(match options.structured {
+            structured::Mode::Off => structured::Mode::new(
+                manifest.mappings.structured,
+                manifest.mappings.structured_keys,
+            ),
+            mode => mode,
+        })
+        .with_lockfiles(options.replace_in_lockfiles)
+        .with_fs_compat(options.fs_compat);
+
+    let phase = Instant::now();
+    let mut summary = preflight_summary(&tmp_path, &mappings, &filter);
+    timings.record("scan", phase);
+    timings.scanned(summary.files, summary.bytes);
+    if options.no_content {
+        summary.modified = 0;
+    }
+    if options.no_rename {
+        summary.renamed = 0;
+    }
+    println!(
+        "{} files, {}, {} files will be modified, {} paths renamed",
+        format_count(summary.files),
+        filter::format_size(summary.bytes),
+        format_count(summary.modified),
+        format_count(summary.renamed)
+    );
+    if !assume_yes
+        && !dry_run
+        && !Confirm::new("Continue with these changes?")
+            .with_default(true)
+            .ask()?
+    {
+        println!("{}", tr!("Aborted by user."));
+        return Ok(None);
+    }
+    // Dry runs work on an in-memory copy from here on (replacement, renames, placeholders,
+    // copyright, license and post-processing), so they report what a real run does.
+    let simulated;
+    let fs: &dyn ScaffoldFs = if dry_run {
+        simulated = MemoryFs::snapshot(&tmp_path, &filter)?;
+        &simulated
+    } else {
+        &RealFs
+    };
+    let phase = Instant::now();
+    if options.no_content {
+        println!("Skipping replacement in file contents (--no-content)");
+    } else {
+        replace_in_files(fs, &tmp_path, &mappings, &filter, dry_run)?;
+    }
+    timings.record("replacement", phase);
+
+    // Rename paths
+    let phase = Instant::now();
+    if options.no_rename {
+        println!("Skipping renames (--no-rename)");
+    } else {
+        rename_paths(fs, &tmp_path, &mappings, &filter, dry_run)?;
+    }
+    timings.record("renaming", phase);
+
+    // Render {{ ... }} placeholders (variables and value generators)
+    let phase = Instant::now();
+    let mut render_ctx = placeholders::RenderContext::new(vars)
+        .with_delimiters(manifest.delimiters())
+        .with_tokenizer(&rules);
+    if let Some(time) = fixed_time {
+        render_ctx = render_ctx.reproducible(time, "files");
+    }
+    if manifest.renders_placeholders() {
+        placeholders::render_in_files(fs, &tmp_path, &render_ctx, &filter, dry_run)?;
+        placeholders::render_paths(fs, &tmp_path, &render_ctx, &filter, dry_run)?;
+    }
+    for (name, port) in render_ctx.assigned_ports() {
+        let label = if name.is_empty() { "port()".to_string() } else { format!("port(\"{}\")", name) };
+        println!("PORT: {} -> {}", label, port);
+    }
+    plugin::transform(&plugins, fs, &tmp_path, &render_ctx.vars, &filter, dry_run)?;
+    timings.record("placeholders", phase);
+    let next_steps = manifest
+        .next_steps
+        .as_deref()
+        .map(|message| placeholders::render(message, &render_ctx));
+
+    // Copyright notices with the current year and the new holder
+    if !(options.keep_copyright || manifest.copyright.keep) {
+        copyright::refresh(fs, &tmp_path, &filter, &render_ctx.vars, &manifest.copyright.holders, dry_run)?;
+    }
+
+    // Write the selected license (built-in `license` variable)
+    if !manifest.variables.contains_key("license") {
+        if let Some(id) = render_ctx.vars.get("license") {
+            let year = render_ctx.vars.get("year").map(String::as_str).unwrap_or_default();
+            let author = render_ctx.vars.get("author").map(String::as_str).unwrap_or_default();
+            license::apply_license(fs, &tmp_path, id, year, author, dry_run)?;
+        }
+    }
+
+    // Structural manifest edits (manifest-declared modes plus --postprocess)
+    let phase = Instant::now();
+    let mut postprocess_modes = manifest.postprocess.clone();
+    for mode in &options.postprocess {
+        if !postprocess_modes.contains(mode) {
+            postprocess_modes.push(mode.clone());
+        }
+    }
+    postprocess::run(
+        &postprocess_modes,
+        &postprocess::PostprocessContext {
+            fs,
+            root: &tmp_path,
+            manifest: &manifest,
+            new_name,
+            vars: &render_ctx.vars,
+            mappings: &mappings,
+            dry_run,
+        },
+    )?;
+    timings.record("post-processing", phase);
+
+    // Write scaffold metadata
+    let answers: BTreeMap<String, String> = manifest
+        .variables
+        .keys()
+        .map(String::as_str)
+        .chain(["license", "author_email", "repo_homepage"])
+        .filter_map(|key| Some((key.to_string(), render_ctx.vars.get(key)?.clone())))
+        .collect();
+    write_scaffold_metadata(
+        &tmp_path,
+        &ScaffoldMetadata {
+            project_name: new_name,
+            template_repo_url: repo_url,
+            template_commit: template_commit.as_deref(),
+            template_base,
+            answers: &answers,
+            generated_at: fixed_time.unwrap_or_else(chrono::Utc::now),
+        },
+        dry_run,
+    )?;
+
+    if let (Some(label), false) = (&options.recent_label, dry_run) {
+        recent::record(repo_url, label, options.commit.as_deref(), &answers);
+    }
+
+    // Keep empty directories in the new repository
+    keep_empty_dirs(&tmp_path, options.strip_gitkeep, dry_run)?;
+
+    if options.format {
+        let phase = Instant::now();
+        format::run(&tmp_path, &format::commands(&tmp_path, &manifest.formatters), dry_run);
+        timings.record("formatting", phase);
+    }
+
+    if options.assert_no_residue {
+        if dry_run {
+            println!("Dry run: skipping the residue check");
+        } else {
+            assert_no_residue(&tmp_path, &generated)?;
+        }
+    }
+
+    if let Some(dest_dir) = into_dir {
+        if !dest_dir.exists() {
+            anyhow::bail!("Destination directory does not exist: {}", dest_dir.display());
+        }
+        if !dest_dir.is_dir() {
+            anyhow::bail!("Destination is not a directory: {}", dest_dir.display());
+        }
+
+        // A package template merged into a workspace root goes into the package directory.
+        let placement = if options.workspace_aware {
+            workspace::detect(&tmp_path, dest_dir, new_name)?
+        } else {
+            None
+        };
+        let merge_dest = match placement {
+            Some(ref p) => {
+                println!("Detected {} workspace: placing package at {}", p.kind, p.member);
+                dest_dir.join(&p.member)
+            }
+            None => dest_dir.to_path_buf(),
+        };
+
+        if !dry_run {
+            let size = diskspace::dir_size(&tmp_path);
+            diskspace::ensure(&merge_dest, size.saturating_mul(2), "merged files")?;
+        }
+        let phase = Instant::now();
+        move_renamed_files(&merge_dest, &options.template_renames, &mappings, dry_run)?;
+        // Dry runs merge the simulated project, with its replacements and renames
+        let overlay = OverlayFs { upper: fs, root: &tmp_path, lower: &RealFs };
+        let merge_fs: &dyn ScaffoldFs = if dry_run { &overlay } else { &RealFs };
+        merge_into_dest(merge_fs, &tmp_path, &merge_dest, &options.walk, &cache::cache_dir(), dry_run)?;
+        if let Some(ref p) = placement {
+            workspace::register(dest_dir, p, dry_run)?;
+        }
+        if options.preserve_owner && !dry_run {
+            if let Err(e) = permissions::preserve_owner(&merge_dest, dest_dir) {
+                println!("Warning: could not change the owner of merged files: {}", e);
+            }
+        }
+        timings.record("merge", phase);
+        if dry_run {
+            println!("Dry run: skipping merge write.");
+        } else {
+            println!("{}", tr!("Merge finished"));
+            progress::emit(progress::Event::Finished {
+                project_dir: merge_dest.clone(),
+            });
+            if options.notify {
+                notify::completed(&notify::Completion {
+                    project: new_name,
+                    directory: &merge_dest,
+                    template: repo_url,
+                    template_commit: template_commit.as_deref(),
+                    merged: true,
+                });
+            }
+            regenerate_lockfiles(&merge_dest, &mappings, options, false);
+            run_mise_task_for_root(dest_dir, dry_run, assume_yes)?;
+            run_bootstrap_steps(dest_dir, &manifest.bootstrap, options.bootstrap, assume_yes);
+            if let Some(ref message) = next_steps {
+                println!("{}", message.trim_end());
+            }
+        }
+        timings.report();
+        return Ok((!dry_run).then(|| merge_dest.clone()));
+    }
+
+    // Before the initial commit, so that it includes the regenerated lockfiles
+    regenerate_lockfiles(&tmp_path, &mappings, options, dry_run);
+    if dry_run {
+        progress::added_tree(fs, &tmp_path);
+        println!("Dry run: skipping git init, commit, and moving files.");
+        println!("Temporary directory with changes: {}", tmp_path.display());
+        println!("{}", tr!("Scaffolding dry-run finished"));
+        timings.report();
+    } else {
+        if let Err(e) = permissions::normalize(&tmp_path) {
+            println!("Warning: could not set file modes: {}", e);
+        }
+        // Git init + commit
+        let phase = Instant::now();
+        if options.no_git {
+            println!("Skipping git init (--no-git)");
+        } else {
+            println!("Initializing new git repository");
+            let mut init = Command::new("git");
+            let branch = options
+                .default_branch
+                .as_deref()
+                .or(fixed_time.is_some().then_some("main"));
+            if let Some(branch) = branch {
+                init.arg("-c").arg(format!("init.defaultBranch={}", branch));
+            }
+            let init_status = logfile::status(init.arg("init").current_dir(&tmp_path));
+            if let Ok(s) = init_status {
+                if s.success() {
+                    println!("git init succeeded");
+                    lfs::install(&tmp_path);
+                    let _ = logfile::status(
+                        Command::new("git").arg("add").arg(".").current_dir(&tmp_path),
+                    );
+                    let mut commit = Command::new("git");
+                    if let Some(time) = fixed_time {
+                        reproducible_commit(&mut commit, time);
+                    }
+                    let _ = logfile::status(
+                        commit
+                            .arg("commit")
+                            .arg("-m")
+                            .arg("Initial commit from template (liscaf)")
+                            .current_dir(&tmp_path),
+                    );
+                    println!("Created initial commit");
+                } else {
+                    println!("Warning: git init failed");
+                }
+            } else {
+                println!("Warning: could not run git init (git not available?)");
+            }
+        }
+        timings.record("git init", phase);
+
+        if let Some(ref archive) = options.archive {
+            let phase = Instant::now();
+            let entries = archive::write(&tmp_path, new_name, archive, fixed_time)?;
+            timings.record("archive", phase);
+            println!("{}", tr!("Wrote scaffold archive {} ({} entries)", archive.display(), entries));
+            progress::emit(progress::Event::Finished {
+                project_dir: archive.clone(),
+            });
+            if options.notify {
+                notify::completed(&notify::Completion {
+                    project: new_name,
+                    directory: archive,
+                    template: repo_url,
+                    template_commit: template_commit.as_deref(),
+                    merged: false,
+                });
+            }
+            println!("{}", tr!("Scaffolding finished"));
+            timings.report();
+            return Ok(Some(archive.clone()));
+        }
+
+        // Move temp dir to destination
+        let phase = Instant::now();
+        let dest = match options.output_dir {
+            Some(ref dir) => dir.clone(),
+            None => std::env::current_dir()?.join(new_name),
+        };
+        if !diskspace::same_volume(&tmp_path, &dest) {
+            let size = diskspace::dir_size(&tmp_path);
+            diskspace::ensure(&dest, size.saturating_mul(2), "project")?;
+        }
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let final_dest = if dest.exists() {
+            let dest_name = dest.file_name().and_then(|s| s.to_str()).unwrap_or(new_name);
+            let dest_alt = dest.with_file_name(format!("{}_from_template", dest_name));
+            move_dir(&tmp_path, &dest_alt)?;
+            println!("{}", tr!("Wrote scaffold into {}", dest_alt.display()));
+            dest_alt
+        } else {
+            move_dir(&tmp_path, &dest)?;
+            println!("{}", tr!("Wrote scaffold into {}", dest.display()));
+            dest
+        };
+
+        timings.record("move", phase);
+        if options.preserve_owner {
+            let parent = final_dest.parent().unwrap_or(Path::new("."));
+            if let Err(e) = permissions::preserve_owner(&final_dest, parent) {
+                println!("Warning: could not change the owner of {}: {}", final_dest.display(), e);
+            }
+        }
+        progress::added_tree(&RealFs, &final_dest);
+        progress::emit(progress::Event::Finished {
+            project_dir: final_dest.clone(),
+        });
+        if options.notify {
+            notify::completed(&notify::Completion {
+                project: new_name,
+                directory: &final_dest,
+                template: repo_url,
+                template_commit: template_commit.as_deref(),
+                merged: false,
+            });
+        }
+
+        if manifest.install_hooks && hooks::present(&final_dest) {
+            if options.no_hooks {
+                println!("Skipping the template's git hooks (--no-hooks)");
+            } else {
+                hooks::install(&final_dest);
+            }
+        }
+        run_mise_task_for_root(&final_dest, dry_run, assume_yes)?;
+        run_bootstrap_steps(&final_dest, &manifest.bootstrap, options.bootstrap, assume_yes);
+
+        match next_steps {
+            Some(message) => println!("{}", message.trim_end()),
+            None => println!("{}", tr!("Scaffolding finished")),
+        }
+        timings.report();
+        return Ok(Some(final_dest));
+    }
+
+    Ok(None)
+}
+
+/// Removes the manifest, `.liscaf/`, `TEMPLATE_README.md` and manifest-listed paths, plus the
+/// `liscaf test` fixtures if the template declares them.
+fn strip_template_only_files(
+    root: &Path,
+    extra: &[String],
+    fixtures: bool,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let builtin = manifest::TEMPLATE_ONLY_FILES.iter().map(|s| s.to_string());
+    for rel in builtin.chain(extra.iter().cloned()) {
+        let rel_path = Path::new(rel.trim_matches('/'));
+        if rel_path.is_absolute()
+            || rel_path
+                .components()
+                .any(|c| matches!(c, std::path::Component::ParentDir))
+        {
+            println!("WARN: Ignoring template-only path outside the template: {}", rel);
+            continue;
+        }
+        let path = root.join(rel_path);
+        if !path.exists() {
+            continue;
+        }
+        if dry_run {
+            println!("DRY STRIP: {}", path.display());
+            continue;
+        }
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        } else {
+            fs::remove_file(&path)?;
+        }
+        println!("STRIP: {}", path.display());
+    }
+    if !fixtures {
+        return Ok(());
+    }
+    let fixtures = fixtures::fixture_files(root);
+    for path in &fixtures {
+        if dry_run {
+            println!("DRY STRIP: {}", path.display());
+        } else {
+            fs::remove_file(path)?;
+            println!("STRIP: {}", path.display());
+        }
+    }
+    // A directory that only held fixtures goes as well
+    let fixture_dir = root.join(fixtures::FIXTURE_DIR);
+    if !dry_run && !fixtures.is_empty() && fs::read_dir(&fixture_dir).is_ok_and(|mut d| d.next().is_none()) {
+        fs::remove_dir(&fixture_dir)?;
+    }
+    Ok(())
+}
+
+/// Removes files the template's own `.gitignore` files exclude (build artifacts or caches that
+/// were committed by accident). Global and user excludes are not consulted.
+fn strip_gitignored_files(root: &Path, dry_run: bool) -> anyhow::Result<()> {
+    let kept: std::collections::HashSet<PathBuf> = ignore::WalkBuilder::new(root)
+        .hidden(false)
+        .parents(false)
+        .ignore(false)
+        .git_global(false)
+        .git_exclude(false)
+        .require_git(false)
+        .filter_entry(|e| e.file_name() != ".git")
+        .build()
+        .filter_map(|e| e.ok())
+        .map(|e| e.into_path())
+        .collect();
+
+    let mut ignored = Vec::new();
+    let mut walker = WalkDir::new(root).min_depth(1).sort_by_file_name().into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if entry.file_name() == ".git" {
+            if entry.file_type().is_dir() {
+                walker.skip_current_dir();
+            }
+            continue;
+        }
+        if !kept.contains(entry.path()) {
+            if entry.file_type().is_dir() {
+                walker.skip_current_dir();
+            }
+            ignored.push(entry.into_path());
+        }
+    }
+
+    for path in ignored {
+        if dry_run {
+            println!("DRY STRIP: {} (gitignored)", path.display());
+            continue;
+        }
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        } else {
+            fs::remove_file(&path)?;
+        }
+        println!("STRIP: {} (gitignored)", path.display());
+    }
+    Ok(())
+}
+
+/// Removes `.git` directories and files (submodule links) below `root`, so vendored
+/// repositories and fixtures in a template do not end up as stray repositories in the project.
+fn strip_nested_git_repos(root: &Path, dry_run: bool) -> anyhow::Result<()> {
+    let mut found = Vec::new();
+    let mut walker = WalkDir::new(root).min_depth(1).sort_by_file_name().into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if entry.file_name() == ".git" {
+            if entry.file_type().is_dir() {
+                walker.skip_current_dir();
+            }
+            found.push(entry.into_path());
+        }
+    }
+    if found.is_empty() {
+        return Ok(());
+    }
+
+    println!(
+        "WARN: Template contains {} nested git repositories or submodules; leaving their .git out",
+        found.len()
+    );
+    for path in found {
+        if dry_run {
+            println!("DRY STRIP: {} (nested git repository)", path.display());
+            continue;
+        }
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        } else {
+            fs::remove_file(&path)?;
+        }
+        println!("STRIP: {} (nested git repository)", path.display());
+    }
+    Ok(())
+}
+
+const GITKEEP: &str = ".gitkeep";
+
+/// Git does not track empty directories: add a `.gitkeep` to every empty directory so it
+/// survives the initial commit. With `strip`, `.gitkeep` files are removed from directories
+/// that contain other entries and no longer need them.
+fn keep_empty_dirs(root: &Path, strip: bool, dry_run: bool) -> anyhow::Result<()> {
+    let mut dirs: Vec<PathBuf> = WalkDir::new(root)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir())
+        .map(|e| e.into_path())
+        .collect();
+    // Deepest first, so a parent holding only empty children is judged after them.
+    dirs.sort_by_key(|p| std::cmp::Reverse(p.components().count()));
+
+    for dir in dirs {
+        let names: Vec<_> = fs::read_dir(&dir)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name())
+            .collect();
+        let keep_path = dir.join(GITKEEP);
+        if names.is_empty() {
+            if dry_run {
+                println!("DRY KEEP: {}", keep_path.display());
+            } else {
+                fs::write(&keep_path, "")?;
+                println!("KEEP: {}", keep_path.display());
+            }
+        } else if strip && names.len() > 1 && names.iter().any(|n| n == GITKEEP) {
+            if dry_run {
+                println!("DRY STRIP: {}", keep_path.display());
+            } else {
+                fs::remove_file(&keep_path)?;
+                println!("STRIP: {}", keep_path.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The clock of `--reproducible` runs: `SOURCE_DATE_EPOCH` if set, else the template commit's
+/// committer date, else the Unix epoch.
+fn reproducible_time(revision: Option<&cache::Revision>) -> anyhow::Result<chrono::DateTime<chrono::Utc>> {
+    if let Ok(epoch) = std::env::var("SOURCE_DATE_EPOCH") {
+        return epoch
+            .trim()
+            .parse()
+            .ok()
+            .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+            .ok_or_else(|| anyhow::anyhow!("SOURCE_DATE_EPOCH is not a Unix timestamp: {}", epoch));
+    }
+    let committed = revision.and_then(|r| chrono::DateTime::parse_from_rfc3339(&r.committed_at).ok());
+    Ok(committed.map_or(chrono::DateTime::UNIX_EPOCH, |t| t.with_timezone(&chrono::Utc)))
+}
+
+/// The contents of `.scaffold.json`.
+struct ScaffoldMetadata<'a> {
+    project_name: &'a str,
+    template_repo_url: &'a str,
+    template_commit: Option<&'a str>,
+    template_base: &'a str,
+    answers: &'a BTreeMap<String, String>,
+    generated_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn write_scaffold_metadata(
+    root: &Path,
+    scaffold: &ScaffoldMetadata,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let metadata_path = root.join(".scaffold.json");
+    let mut metadata = serde_json::json!({
+        "project_name": scaffold.project_name,
+        "template_repo_url": scaffold.template_repo_url,
+        "template_base": scaffold.template_base,
+        "generator": "liscaf",
+        "generated_at": scaffold.generated_at.to_rfc3339()
+    });
+    if let Some(commit) = scaffold.template_commit {
+        metadata["template_commit"] = serde_json::Value::String(commit.to_string());
+    }
+    if !scaffold.answers.is_empty() {
+        metadata["answers"] = serde_json::json!(scaffold.answers);
+    }
+
+    let content = serde_json::to_string_pretty(&metadata)?;
+    if dry_run {
+        status!("DRY ADD: {}", metadata_path.display());
+    } else {
+        fs::write(&metadata_path, content)?;
+        status!("ADD: {}", metadata_path.display());
+    }
+
+    Ok(())
+}
+
+/// Pins what goes into the initial commit's hash besides the tree: dates, identity (unless set
+/// through the `GIT_AUTHOR_*` / `GIT_COMMITTER_*` variables) and no signature.
+fn reproducible_commit(command: &mut Command, time: chrono::DateTime<chrono::Utc>) {
+    let date = time.to_rfc3339();
+    command.args(["-c", "commit.gpgsign=false"]);
+    for (key, value) in [
+        ("GIT_AUTHOR_NAME", "liscaf"),
+        ("GIT_AUTHOR_EMAIL", "liscaf@localhost"),
+        ("GIT_COMMITTER_NAME", "liscaf"),
+        ("GIT_COMMITTER_EMAIL", "liscaf@localhost"),
+        ("GIT_AUTHOR_DATE", &date),
+        ("GIT_COMMITTER_DATE", &date),
+    ] {
+        if std::env::var_os(key).is_none() {
+            command.env(key, value);
+        }
+    }
+}
+
+fn is_supported_repo_url(repo_url: &str) -> bool {
+    let lowered = repo_url.to_lowercase();
+    if lowered.starts_with("https://") || lowered.starts_with("http://") {
+        return true;
+    }
+    if lowered.starts_with("ssh://") {
+        return true;
+    }
+    // SCP-like syntax: user@host:owner/repo(.git)
+    repo_url.contains('@') && repo_url.contains(':')
+}
+
+#[derive(Debug, Clone)]
+struct TemplateEntry {
+    /// Identifies the template across registries; the highest-priority registry wins.
+    name: String,
+    label: String,
+    url: String,
+    /// Exact commit the registry pins this template to.
+    commit: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct TemplateYamlEntry {
+    name: Option<String>,
+    label: Option<String>,
+    url: String,
+    commit: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct TemplateYamlRoot {
+    repositories: Vec<TemplateYamlEntry>,
+}
+
+fn prompt_for_repo_url(
+    registries: &[registry::Registry],
+    favorites: &[config::FavoriteConfig],
+    tmpdir: Option<&Path>,
+) -> anyhow::Result<TemplateEntry> {
+    let templates = match registry::load_templates(registries) {
+        Ok(entries) => entries,
+        Err(e) => {
+            println!("Warning: failed to load templates: {}", e);
+            Vec::new()
+        }
+    };
+
+    let manual_entry = || -> anyhow::Result<TemplateEntry> {
+        let url = Text::new("Enter repository URL (HTTPS or SSH):")
+            .with_placeholder("https://github.com/owner/repo or git@github.com:owner/repo.git")
+            .ask()?;
+        Ok(TemplateEntry {
+            name: url.clone(),
+            label: url.clone(),
+            url,
+            commit: None,
+        })
+    };
+    let recent = recent::load();
+    if templates.is_empty() && recent.is_empty() && favorites.is_empty() {
+        return manual_entry();
+    }
+
+    let manual_label = tr!("Enter URL manually").to_string();
+    let favorite_label = |f: &config::FavoriteConfig| tr!("Favorite: {}", f.name);
+    let recent_label = |r: &recent::RecentTemplate| tr!("Recently used: {}", r.label);
+    let mut options: Vec<String> = favorites.iter().map(favorite_label).collect();
+    options.extend(recent.iter().map(recent_label));
+    options.extend(templates.iter().map(|t| t.label.clone()));
+    options.push(manual_label.clone());
+
+    loop {
+        let choice = Select::new("Choose a template:", options.clone()).ask()?;
+        if choice == manual_label {
+            return manual_entry();
+        }
+        if let Some(f) = favorites.iter().find(|f| favorite_label(f) == choice) {
+            return Ok(f.entry());
+        }
+        if let Some(r) = recent.iter().find(|r| recent_label(r) == choice) {
+            return Ok(r.entry());
+        }
+
+        let selected = templates
+            .iter()
+            .find(|t| t.label == choice)
+            .cloned()
+            .unwrap_or(TemplateEntry {
+                name: choice.clone(),
+                label: choice.clone(),
+                url: choice,
+                commit: None,
+            });
+        if !Confirm::new(&tr!("Show the README of '{}' first?", selected.name))
+            .with_default(false)
+            .ask()?
+        {
+            return Ok(selected);
+        }
+        if let Err(e) = info::show_readme(&selected, tmpdir) {
+            println!("Warning: could not show the README: {}", e);
+        }
+        if Confirm::new(&tr!("Use template '{}' ?", selected.name))
+            .with_default(true)
+            .ask()?
+        {
+            return Ok(selected);
+        }
+    }
+}
+
+fn normalize_repo_url(repo_url: &str) -> String {
+    let trimmed = repo_url.trim();
+    if trimmed.is_empty() {
+        return String::new();
+    }
+    let lowered = trimmed.to_lowercase();
+    if lowered.starts_with("http://")
+        || lowered.starts_with("https://")
+        || lowered.starts_with("ssh://")
+        || (trimmed.contains('@') && trimmed.contains(':'))
+    {
+        return trimmed.to_string();
+    }
+    if trimmed.contains('/') {
+        return format!("https://{}", trimmed);
+    }
+    trimmed.to_string()
+}
+
+fn load_template_entries(
+    source: &str,
+    auth: Option<&net::Auth>,
+) -> anyhow::Result<Vec<TemplateEntry>> {
+    if let Some(spec) = source.strip_prefix(github::SOURCE_PREFIX) {
+        return github::load_org_templates(spec);
+    }
+    let content = if source.starts_with("http://") || source.starts_with("https://") {
+        load_repositories_yaml_from_http(source, auth)?
+    } else if Path::new(source).exists() {
+        load_repositories_yaml_from_path(source)?
+    } else {
+        let repo_url = normalize_repo_url(source);
+        load_repositories_yaml_from_repo(&repo_url)?
+    };
+
+    parse_template_entries_from_yaml(&content)
+}
+
+fn load_repositories_yaml_from_path(path: &str) -> anyhow::Result<String> {
+    let yaml_path = Path::new(path).join("repositories.yaml");
+    let yml_path = Path::new(path).join("repositories.yml");
+    let repo_file = if yaml_path.exists() {
+        yaml_path
+    } else if yml_path.exists() {
+        yml_path
+    } else {
+        anyhow::bail!(
+            "Neither repositories.yaml nor repositories.yml found in {}",
+            path
+        );
+    };
+    Ok(fs::read_to_string(repo_file)?)
+}
+
+fn load_repositories_yaml_from_http(
+    base_url: &str,
+    auth: Option<&net::Auth>,
+) -> anyhow::Result<String> {
+    if auth.is_some() && base_url.starts_with("http://") {
+        println!("Warning: sending registry credentials over plain HTTP to {}", base_url);
+    }
+    let mut yaml_url = base_url.to_string();
+    if !yaml_url.ends_with('/') {
+        yaml_url.push('/');
+    }
+    yaml_url.push_str("repositories.yaml");
+
+    match net::get_text(&yaml_url, auth) {
+        Ok(content) => return Ok(content),
+        Err(e) => println!("Warning: {}", e),
+    }
+
+    let mut yml_url = base_url.to_string();
+    if !yml_url.ends_with('/') {
+        yml_url.push('/');
+    }
+    yml_url.push_str("repositories.yml");
+
+    net::get_text(&yml_url, auth)
+}
+
+fn load_repositories_yaml_from_repo(repo_url: &str) -> anyhow::Result<String> {
+    if !is_supported_repo_url(repo_url) {
+        anyhow::bail!("Template source repo URL is not supported: {}", repo_url);
+    }
+
+    let tmpdir = tempfile::Builder::new()
+        .prefix("liscaf-templates-")
+        .tempdir()
+        .map_err(|e| anyhow::anyhow!(e))?;
+    let tmp_path = tmpdir.path().to_path_buf();
+
+    git_clone_shallow(repo_url, &tmp_path)?;
+    let yaml_path = tmp_path.join("repositories.yaml");
+    let yml_path = tmp_path.join("repositories.yml");
+    let repo_file = if yaml_path.exists() {
+        yaml_path
+    } else if yml_path.exists() {
+        yml_path
+    } else {
+        anyhow::bail!(
+            "Neither repositories.yaml nor repositories.yml found in template repo: {}",
+            repo_url
+        );
+    };
+    Ok(fs::read_to_string(repo_file)?)
+}
+
+/// git clone --depth 1 <url> <dest>, retrying transient failures.
+fn git_clone_shallow(repo_url: &str, dest: &Path) -> anyhow::Result<()> {
+    net::with_retries(&format!("Cloning {}", repo_url), || {
+        // A failed attempt may leave files behind; git refuses to clone into a non-empty dir
+        if dest.is_dir() {
+            for entry in fs::read_dir(dest).map_err(|e| net::Failure::Permanent(e.into()))? {
+                let path = entry.map_err(|e| net::Failure::Permanent(e.into()))?.path();
+                let removed = if path.is_dir() {
+                    fs::remove_dir_all(&path)
+                } else {
+                    fs::remove_file(&path)
+                };
+                removed.map_err(|e| net::Failure::Permanent(e.into()))?;
+            }
+        }
+        let output = net::git()
+            .arg("clone")
+            .arg("--depth")
+            .arg("1")
+            .arg(repo_url)
+            .arg(dest)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .map_err(|e| net::Failure::Permanent(anyhow::anyhow!("Failed to run git: {}", e)))?;
+        if output.status.success() {
+            return Ok(());
+        }
+        Err(git_failure("git clone", &output))
+    })
+}
+
+/// Classifies a failed git network command, describing it by the first `fatal:` line.
+fn git_failure(what: &str, output: &std::process::Output) -> net::Failure {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let detail = stderr
+        .lines()
+        .find(|l| l.starts_with("fatal:"))
+        .or_else(|| stderr.lines().rev().find(|l| !l.trim().is_empty()))
+        .unwrap_or("")
+        .trim();
+    let error = anyhow::anyhow!(
+        "{} failed with code: {}{}{}",
+        what,
+        output.status.code().unwrap_or(-1),
+        if detail.is_empty() { "" } else { ": " },
+        detail
+    );
+    if net::is_permanent_git_error(&stderr) {
+        net::Failure::Permanent(error)
+    } else {
+        net::Failure::Transient(error)
+    }
+}
+
+fn parse_template_entries_from_yaml(content: &str) -> anyhow::Result<Vec<TemplateEntry>> {
+    let entries_raw: Vec<TemplateYamlEntry> = match serde_yaml::from_str::<Vec<TemplateYamlEntry>>(content) {
+        Ok(list) => list,
+        Err(_) => {
+            let rooted = serde_yaml::from_str::<TemplateYamlRoot>(content)
+                .map_err(|e| anyhow::anyhow!("Failed to parse repositories YAML: {}", e))?;
+            rooted.repositories
+        }
+    };
+
+    let mut entries = Vec::new();
+    for raw in entries_raw {
+        let name = raw
+            .name
+            .or(raw.label)
+            .unwrap_or_else(|| raw.url.clone());
+        let label = name.clone();
+        let url = normalize_repo_url(&raw.url);
+        if let Some(ref commit) = raw.commit {
+            cache::validate_commit(commit)
+                .map_err(|e| anyhow::anyhow!("Registry entry '{}': {}", label, e))?;
+        }
+        if !url.is_empty() {
+            entries.push(TemplateEntry {
+                name,
+                label,
+                url,
+                commit: raw.commit,
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Checks the tools the manifest requires. Missing or outdated tools abort with --yes; otherwise
+/// the user decides whether to scaffold anyway. Returns false if they decline.
+fn check_required_tools(manifest: &manifest::Manifest, assume_yes: bool) -> anyhow::Result<bool> {
+    if manifest.requires.is_empty() {
+        return Ok(true);
+    }
+    let problems = tools::check(&manifest.requires, &manifest.install_hints);
+    if problems.is_empty() {
+        return Ok(true);
+    }
+    for problem in &problems {
+        println!("MISSING: {}", problem);
+    }
+    if assume_yes {
+        anyhow::bail!(
+            "{} required tool(s) missing or outdated (use --no-tool-check to scaffold anyway)",
+            problems.len()
+        );
+    }
+    Confirm::new("Required tools are missing. Scaffold anyway?")
+        .with_default(false)
+        .ask()
+}
+
+/// A command line run by the platform shell (`sh -c`, `cmd /C`).
+fn shell_command(line: &str) -> Command {
+    let mut command = if cfg!(windows) { Command::new("cmd") } else { Command::new("sh") };
+    command.arg(if cfg!(windows) { "/C" } else { "-c" }).arg(line);
+    command
+}
+
+/// Fails listing every variant of the old name still found below `root` (`--assert-no-residue`).
+fn assert_no_residue(root: &Path, variants: &[(String, String)]) -> anyhow::Result<()> {
+    let found = residue::find(root, variants);
+    if found.is_empty() {
+        println!("No residue of the old name found");
+        return Ok(());
+    }
+    for residue in &found {
+        println!("RESIDUE: {}", residue);
+    }
+    anyhow::bail!(
+        "{} leftover occurrence(s) of the old name (--assert-no-residue)",
+        found.len()
+    )
+}
+
+/// Lockfiles were left out of replacement; bring those that still name the template up to date.
+fn regenerate_lockfiles(root: &Path, mappings: &[(String, String)], options: &ScaffoldOptions, dry_run: bool) {
+    if options.replace_in_lockfiles || options.no_content {
+        return;
+    }
+    lockfiles::regenerate(root, mappings, options.regenerate_lockfiles, options.assume_yes, dry_run);
+}
+
+/// Runs the manifest's bootstrap commands in `root` with output streamed to the terminal.
+/// Failures are reported but never abort the scaffold, which is complete at this point.
+fn run_bootstrap_steps(root: &Path, steps: &[String], mode: BootstrapMode, assume_yes: bool) {
+    if steps.is_empty() || mode == BootstrapMode::Never {
+        return;
+    }
+    if mode == BootstrapMode::Ask {
+        if assume_yes {
+            println!("Skipping bootstrap commands because confirmation is required (use --bootstrap)");
+            return;
+        }
+        let prompt = tr!("Run bootstrap commands in '{}'?\n  {}", root.display(), steps.join("\n  "));
+        match Confirm::new(&prompt).with_default(true).ask() {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                println!("Warning: skipping bootstrap: {}", e);
+                return;
+            }
+        }
+    }
+
+    for step in steps {
+        println!("BOOTSTRAP: {}", step);
+        let status = logfile::status(shell_command(step).current_dir(root));
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(status) => println!(
+                "Warning: bootstrap command '{}' failed with code {}",
+                step,
+                status.code().unwrap_or(-1)
+            ),
+            Err(e) => println!("Warning: failed to run bootstrap command '{}': {}", step, e),
+        }
+    }
+}
+
+fn run_mise_task_for_root(
+    root: &Path,
+    dry_run: bool,
+    assume_yes: bool,
+) -> anyhow::Result<()> {
+    if dry_run {
+        println!("Dry run: skipping mise task execution.");
+        return Ok(());
+    }
+
+    // Trust the directory so mise can read task definitions
+    let trust_status = Command::new("mise")
+        .arg("trust")
+        .arg("--all")
+        .current_dir(root)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+
+    if let Ok(status) = trust_status {
+        if status.success() {
+            println!("Trusted directory with mise");
+        }
+    }
+
+    if !mise_task_exists(root, "liscaf-merge")? {
+        return Ok(());
+    }
+
+    if assume_yes {
+        println!("Skipping mise task 'liscaf-merge' because confirmation is required");
+        return Ok(());
+    }
+
+    let prompt = tr!("Run mise task 'liscaf-merge' in '{}' ?", root.display());
+    if Confirm::new(&prompt).with_default(true).ask()? {
+        run_mise_task(root, "liscaf-merge")?;
+    }
+
+    Ok(())
+}
+
+fn mise_task_exists(root: &Path, task: &str) -> anyhow::Result<bool> {
+    let json_output = Command::new("mise")
+        .arg("tasks")
+        .arg("--json")
+        .current_dir(root)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output();
+
+    if let Ok(output) = json_output {
+        if output.status.success() {
+            if let Ok(value) = serde_json::from_slice::<serde_json::Value>(&output.stdout) {
+                if mise_task_in_json(&value, task) {
+                    return Ok(true);
+                }
+            }
+        }
+    }
+
+    let text_output = Command::new("mise")
+        .arg("tasks")
+        .current_dir(root)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output();
+
+    if let Ok(output) = text_output {
+        if output.status.success() {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            if mise_task_in_text(&stdout, task) {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+fn mise_task_in_json(value: &serde_json::Value, task: &str) -> bool {
+    match value {
+        serde_json::Value::Array(items) => items.iter().any(|item| match item {
+            serde_json::Value::String(name) => name == task,
+            serde_json::Value::Object(obj) => {
+                obj.get("name").and_then(|v| v.as_str()) == Some(task)
+                    || obj.get("task").and_then(|v| v.as_str()) == Some(task)
+            }
+            _ => false,
+        }),
+        serde_json::Value::Object(obj) => obj.values().any(|v| mise_task_in_json(v, task)),
+        _ => false,
+    }
+}
+
+fn mise_task_in_text(output: &str, task: &str) -> bool {
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let trimmed = trimmed.trim_start_matches(&['*', '-', ' '][..]);
+        if trimmed == task
+            || trimmed.starts_with(&format!("{} ", task))
+            || trimmed.starts_with(&format!("{}:", task))
+        {
+            return true;
+        }
+    }
+    false
+}
+
+fn run_mise_task(root: &Path, task: &str) -> anyhow::Result<()> {
+    let status = logfile::status(Command::new("mise").arg("run").arg(task).current_dir(root));
+
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => anyhow::bail!("mise run {} failed with exit code {}", task, status.code().unwrap_or(-1)),
+        Err(err) => anyhow::bail!("Failed to run mise: {}", err),
+    }
+}
+
+/// Replaces mapping sources in a single left-to-right pass. At each position the longest matching
+/// source wins and replaced text is never matched again, so the result does not depend on the
+/// order of `mappings` (e.g. `acme` vs `acme-app`).
+fn apply_mappings(input: &str, mappings: &[(String, String)]) -> String {
+    let (patterns, literals): (Vec<_>, Vec<_>) = mappings
+        .iter()
+        .partition(|(o, _)| mappings::regex_source(o).is_some());
+    let mut sorted: Vec<&(String, String)> = literals
+        .into_iter()
+        .filter(|(o, _)| !o.is_empty() && input.contains(o.as_str()))
+        .collect();
+    let regex_matches = mappings::regex_matches(input, &patterns);
+    if sorted.is_empty() && regex_matches.is_empty() {
+        return input.to_string();
+    }
+    // Stable: among equal sources the first mapping wins.
+    sorted.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    let mut regex_matches = regex_matches.iter().peekable();
+    'scan: while let Some(c) = rest.chars().next() {
+        // Custom regex mappings win over the generated variants where both match
+        let at = input.len() - rest.len();
+        while regex_matches.next_if(|(start, _, _)| *start < at).is_some() {}
+        if let Some((_, end, replacement)) = regex_matches.next_if(|(start, _, _)| *start == at) {
+            out.push_str(replacement);
+            rest = &input[*end..];
+            continue 'scan;
+        }
+        for (o, n) in &sorted {
+            if rest.starts_with(o.as_str()) {
+                out.push_str(n);
+                rest = &rest[o.len()..];
+                continue 'scan;
+            }
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+#[derive(Debug, Default)]
+struct PreflightSummary {
+    files: usize,
+    bytes: u64,
+    modified: usize,
+    renamed: usize,
+}
+
+/// Counts what replacement and renaming are about to touch, without writing anything.
+fn preflight_summary(
+    base: &Path,
+    mappings: &[(String, String)],
+    filter: &filter::PathFilter,
+) -> PreflightSummary {
+    let mut summary = PreflightSummary::default();
+    let matcher = mappings::Matcher::new(mappings);
+    let walk = filter.walk_options();
+    let walker = WalkDir::new(base)
+        .min_depth(1)
+        .follow_links(walk.follow_symlinks)
+        .into_iter()
+        .filter_entry(|e| {
+            e.file_name() != ".git"
+                && walk.includes(e.depth(), Some(e.file_name()))
+                && !(e.file_type().is_dir() && filter.skips_dir(e.file_name()))
+        });
+    for entry in walker.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !filter.allows(base, path) {
+            continue;
+        }
+        if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
+            if apply_mappings(name, mappings) != name {
+                summary.renamed += 1;
+            }
+        }
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let len = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        summary.files += 1;
+        summary.bytes += len;
+        if filter.exceeds_size_limit(len) || (!filter.lockfiles() && lockfiles::is_lockfile(path)) {
+            continue;
+        }
+        let Ok(bytes) = fs::read(path) else {
+            continue;
+        };
+        if filter.documents() && documents::is_document(path) {
+            if documents::replace(&bytes, mappings).is_some() {
+                summary.modified += 1;
+            }
+            continue;
+        }
+        if !bytes.contains(&0) && matcher.is_match(&bytes) && std::str::from_utf8(&bytes).is_ok() {
+            summary.modified += 1;
+        }
+    }
+    summary
+}
+
+/// `1243` -> `1,243`
+fn format_count(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Default vendored/build directory names (unless disabled) plus manifest and CLI additions.
+fn skip_dir_names(no_defaults: bool, manifest_dirs: &[String], cli_dirs: &[String]) -> Vec<String> {
+    let mut dirs: Vec<String> = if no_defaults {
+        Vec::new()
+    } else {
+        filter::DEFAULT_SKIP_DIRS.iter().map(|d| d.to_string()).collect()
+    };
+    dirs.extend(manifest_dirs.iter().cloned());
+    dirs.extend(cli_dirs.iter().cloned());
+    dirs
+}
+
+/// Replaces tokens in the text files below `base`. Writes go to `fs` in dry runs too, so callers
+/// pass an in-memory copy there; `dry_run` only changes the wording.
+fn replace_in_files(
+    fs: &dyn ScaffoldFs,
+    base: &Path,
+    mappings: &[(String, String)],
+    filter: &filter::PathFilter,
+    dry_run: bool,
+) -> anyhow::Result<usize> {
+    println!("Replacing content inside files...");
+    let matcher = mappings::Matcher::new(mappings);
+    let mut updated = 0;
+    let entries = fs.walk(base, filter.walk_options(), &mut |e| {
+        if e.path.file_name().is_some_and(|n| n == ".git") {
+            return false;
+        }
+        let skip = e.depth > 0 && e.path.file_name().is_some_and(|n| filter.skips_dir(n));
+        if skip {
+            println!("SKIP: Not walking {}", e.path.display());
+        }
+        !skip
+    });
+    for entry in entries {
+        if entry.is_file {
+            let path = entry.path.as_path();
+            if path.components().any(|c| c.as_os_str() == ".git") {
+                continue;
+            }
+            if !filter.allows(base, path) {
+                continue;
+            }
+            if filter.exceeds_size_limit(entry.len) {
+                println!(
+                    "SKIP: Not reading large file ({}): {}",
+                    filter::format_size(entry.len),
+                    path.display()
+                );
+                continue;
+            }
+            if let Ok(buf) = fs.read(path) {
+                let replaced = if filter.documents() && documents::is_document(path) {
+                    documents::replace(&buf, mappings)
+                } else if buf.contains(&0) || !matcher.is_match(&buf) {
+                    // Most files mention no mapping; they are not decoded or copied
+                    continue;
+                } else {
+                    String::from_utf8(buf).ok().and_then(|original| {
+                        let content = if structured::applies(filter.structured(), path) {
+                            structured::replace(filter.structured(), path, &original, mappings)
+                                .map_err(|e| println!("WARN: Not replacing in {}: {}", path.display(), e))
+                                .ok()?
+                        } else {
+                            apply_mappings(&original, mappings)
+                        };
+                        (content != original).then(|| content.into_bytes())
+                    })
+                };
+                if replaced.is_some() && !filter.lockfiles() && lockfiles::is_lockfile(path) {
+                    println!("SKIP: Not replacing in lockfile (regenerated instead): {}", path.display());
+                    continue;
+                }
+                if let Some(content) = replaced {
+                    if let Err(e) = fs.write(path, &content) {
+                        println!("WARN: Failed to write file {}: {}", path.display(), e);
+                        continue;
+                    }
+                    if dry_run {
+                        println!("DRY REPL: Would update file: {}", path.display());
+                    } else {
+                        println!("REPL: Updated file: {}", path.display());
+                    }
+                    updated += 1;
+                    progress::emit(progress::Event::FileReplaced {
+                        path: progress::relative(base, path),
+                    });
+                }
+            }
+        }
+    }
+    Ok(updated)
+}
+
+/// Renames files and directories below `base`, deepest first. Like [`replace_in_files`], renames
+/// happen in `fs` in dry runs too.
+fn rename_paths(
+    fs: &dyn ScaffoldFs,
+    base: &Path,
+    mappings: &[(String, String)],
+    filter: &filter::PathFilter,
+    dry_run: bool,
+) -> anyhow::Result<usize> {
+    println!("Renaming files and directories where needed...");
+    let mut renamed = 0;
+    let mut entries: Vec<PathBuf> = fs
+        .walk(base, filter.walk_options(), &mut |e| {
+            !(e.depth > 0 && e.path.file_name().is_some_and(|n| n == ".git" || filter.skips_dir(n)))
+        })
+        .into_iter()
+        .map(|e| e.path)
+        .filter(|p| p.file_name() != Some(".git".as_ref()) && filter.allows(base, p))
+        .collect();
+    entries.sort_by_key(|p| std::cmp::Reverse(p.components().count()));
+
+    for path in entries {
+        let file_name_opt = path.file_name().and_then(|s| s.to_str()).map(|s| s.to_string());
+        if file_name_opt.is_none() { continue; }
+        let file_name = file_name_opt.unwrap();
+        let mut new_name = apply_mappings(&file_name, mappings);
+        if let Some(target) = filter.fs_compat() {
+            let compatible = target.sanitize(&new_name);
+            if compatible != new_name {
+                println!(
+                    "FSCOMPAT: '{}' is not a valid name on {}; using '{}'",
+                    new_name,
+                    target.label(),
+                    compatible
+                );
+                new_name = compatible;
+            }
+        }
+        if new_name != file_name {
+            let new_path = path.with_file_name(&new_name);
+            let final_path = if fs.exists(&new_path) {
+                let mut alt = new_path.clone();
+                let mut i = 1;
+                while fs.exists(&alt) {
+                    alt = new_path.with_file_name(format!("{}_{}", new_name, i));
+                    i += 1;
+                }
+                alt
+            } else {
+                new_path
+            };
+            if let Err(e) = fs.rename(&path, &final_path) {
+                println!("WARN: Failed to rename {} -> {}: {}", path.display(), final_path.display(), e);
+                continue;
+            }
+            if dry_run {
+                status!("DRY RENAME: {} -> {}", path.display(), final_path.display());
+            } else {
+                status!("RENAME: {} -> {}", path.display(), final_path.display());
+            }
+            renamed += 1;
+            progress::emit(progress::Event::RenamePlanned {
+                from: progress::relative(base, &path),
+                to: progress::relative(base, &final_path),
+            });
+        }
+    }
+
+    Ok(renamed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items.iter().map(|(o, n)| (o.to_string(), n.to_string())).collect()
+    }
+
+    fn rules() -> TokenizerRules {
+        TokenizerRules::default()
+    }
+
+    #[test]
+    fn longest_source_wins_regardless_of_order() {
+        let input = "acme-app uses acme";
+        let forward = pairs(&[("acme", "globex"), ("acme-app", "my-app")]);
+        let mut backward = forward.clone();
+        backward.reverse();
+        assert_eq!(apply_mappings(input, &forward), "my-app uses globex");
+        assert_eq!(apply_mappings(input, &backward), "my-app uses globex");
+    }
+
+    #[test]
+    fn replaced_text_is_not_matched_again() {
+        let mappings = pairs(&[("a", "b"), ("b", "c")]);
+        assert_eq!(apply_mappings("ab", &mappings), "bc");
+    }
+
+    #[test]
+    fn leftmost_match_wins_for_overlapping_sources() {
+        let mappings = pairs(&[("meap", "Y"), ("acme", "X")]);
+        assert_eq!(apply_mappings("acmeapp", &mappings), "Xapp");
+    }
+
+    #[test]
+    fn first_mapping_wins_for_duplicate_sources() {
+        let mappings = pairs(&[("acme", "one"), ("acme", "two")]);
+        assert_eq!(apply_mappings("acme", &mappings), "one");
+    }
+
+    #[test]
+    fn non_ascii_text_is_preserved() {
+        let mappings = pairs(&[("acme", "globex")]);
+        assert_eq!(apply_mappings("café acme ☕", &mappings), "café globex ☕");
+    }
+
+    #[test]
+    fn generated_variants_apply_deterministically() {
+        let mappings = generate_variant_mappings(
+            &split_name_to_tokens("acme-app", &rules()),
+            &split_name_to_tokens("my-cool-app", &rules()),
+            &[],
+            &rules(),
+        );
+        let input = "acme-app acme_app ACME_APP acmeapp ACMEAPP acmeApp AcmeApp Acme_App";
+        let expected =
+            "my-cool-app my_cool_app MY_COOL_APP mycoolapp MYCOOLAPP myCoolApp MyCoolApp My_Cool_App";
+        assert_eq!(apply_mappings(input, &mappings), expected);
+        let mut reversed = mappings.clone();
+        reversed.reverse();
+        assert_eq!(apply_mappings(input, &reversed), expected);
+    }
+
+    fn acme_mappings() -> Vec<(String, String)> {
+        generate_variant_mappings(
+            &split_name_to_tokens("acme-app", &rules()),
+            &split_name_to_tokens("payments", &rules()),
+            &[],
+            &rules(),
+        )
+    }
+
+    #[test]
+    fn replace_and_rename_run_against_memory_fs() {
+        let fs = MemoryFs::new()
+            .with_file("/t/acme-app/src/acme_app.rs", "pub struct AcmeApp;")
+            .with_file("/t/README.md", "# acme-app")
+            .with_file("/t/node_modules/acme-app/index.js", "acme-app");
+        let filter = filter::PathFilter::new(&[], &[]).unwrap();
+        let base = Path::new("/t");
+        replace_in_files(&fs, base, &acme_mappings(), &filter, false).unwrap();
+        rename_paths(&fs, base, &acme_mappings(), &filter, false).unwrap();
+
+        assert_eq!(
+            fs.files(),
+            vec![
+                PathBuf::from("/t/README.md"),
+                PathBuf::from("/t/node_modules/acme-app/index.js"),
+                PathBuf::from("/t/payments/src/payments.rs"),
+            ]
+        );
+        assert_eq!(fs.read(Path::new("/t/payments/src/payments.rs")).unwrap(), b"pub struct Payments;");
+        assert_eq!(fs.read(Path::new("/t/README.md")).unwrap(), b"# payments");
+        assert_eq!(fs.read(Path::new("/t/node_modules/acme-app/index.js")).unwrap(), b"acme-app");
+    }
+
+    #[test]
+    fn dry_run_steps_after_renaming_see_the_simulated_tree() {
+        let fs = MemoryFs::new()
+            .with_file("/t/Cargo.toml", "[workspace]\nmembers = [\"crates/acme-app\"]\n")
+            .with_file("/t/crates/acme-app/Cargo.toml", "[package]\nname = \"core\"\nversion = \"2.0.0\"\n")
+            .with_file("/t/LICENSE", "Copyright (c) 2019 Acme Corp\n");
+        let base = Path::new("/t");
+        let filter = filter::PathFilter::new(&[], &[]).unwrap();
+        rename_paths(&fs, base, &acme_mappings(), &filter, true).unwrap();
+        let manifest = manifest::Manifest::default();
+        let ctx = postprocess::PostprocessContext {
+            fs: &fs,
+            root: base,
+            manifest: &manifest,
+            new_name: "payments",
+            vars: &BTreeMap::new(),
+            mappings: &acme_mappings(),
+            dry_run: true,
+        };
+        postprocess::run(&["cargo".to_string()], &ctx).unwrap();
+        license::apply_license(&fs, base, "MIT", "2026", "Jane Doe", true).unwrap();
+
+        let read = |path: &str| String::from_utf8(fs.read(Path::new(path)).unwrap()).unwrap();
+        assert!(read("/t/Cargo.toml").contains("members = [\"crates/payments\"]"));
+        assert!(read("/t/crates/payments/Cargo.toml").contains("version = \"0.1.0\""));
+        assert!(read("/t/LICENSE").contains("Copyright (c) 2026 Jane Doe"));
+    }
+
+    #[test]
+    fn rename_avoids_existing_targets() {
+        let fs = MemoryFs::new()
+            .with_file("/t/acme-app.txt", "a")
+            .with_file("/t/payments.txt", "b");
+        let filter = filter::PathFilter::new(&[], &[]).unwrap();
+        rename_paths(&fs, Path::new("/t"), &acme_mappings(), &filter, false).unwrap();
+        assert_eq!(
+            fs.files(),
+            vec![PathBuf::from("/t/payments.txt"), PathBuf::from("/t/payments.txt_1")]
+        );
+    }
+
+    #[test]
+    fn fs_compat_rewrites_names_windows_rejects() {
+        let fs = MemoryFs::new()
+            .with_file("/t/acme-app: notes?.md", "a")
+            .with_file("/t/docs/con.txt", "b")
+            .with_file("/t/docs/v1.", "c")
+            .with_file("/t/docs/console.txt", "d");
+        let filter = filter::PathFilter::new(&[], &[])
+            .unwrap()
+            .with_fs_compat(Some(fscompat::FsCompat::Ntfs));
+        rename_paths(&fs, Path::new("/t"), &acme_mappings(), &filter, false).unwrap();
+        assert_eq!(
+            fs.files(),
+            vec![
+                PathBuf::from("/t/docs/con_.txt"),
+                PathBuf::from("/t/docs/console.txt"),
+                PathBuf::from("/t/docs/v1_"),
+                PathBuf::from("/t/payments_ notes_.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn extended_manifests_merge_table_by_table() {
+        let mut variant: toml::Table = toml::from_str(
+            "postprocess = [\"npm\"]\n[variables.database]\ndefault = \"postgres\"\n",
+        )
+        .unwrap();
+        let base: toml::Table = toml::from_str(
+            "postprocess = [\"cargo\"]\nrequires = [\"git\"]\n\
+             [variables.database]\nprompt = \"Database\"\ndefault = \"sqlite\"\n\
+             [variables.port]\ndefault = \"8080\"\n",
+        )
+        .unwrap();
+        extends::merge_tables(&mut variant, base);
+        let expected: toml::Table = toml::from_str(
+            "postprocess = [\"npm\"]\nrequires = [\"git\"]\n\
+             [variables.database]\nprompt = \"Database\"\ndefault = \"postgres\"\n\
+             [variables.port]\ndefault = \"8080\"\n",
+        )
+        .unwrap();
+        assert_eq!(variant, expected);
+    }
+
+    #[test]
+    fn merge_adds_new_files_and_marks_conflicts() {
+        let fs = MemoryFs::new()
+            .with_file("/src/new.txt", "new")
+            .with_file("/src/same.txt", "same")
+            .with_file("/src/README.md", "template\n")
+            .with_file("/dest/same.txt", "same")
+            .with_file("/dest/README.md", "mine\n");
+        let hashes = tempfile::tempdir().unwrap();
+        merge_into_dest(&fs, Path::new("/src"), Path::new("/dest"), &WalkOptions::default(), hashes.path(), false)
+            .unwrap();
+
+        assert_eq!(fs.read(Path::new("/dest/new.txt")).unwrap(), b"new");
+        assert_eq!(fs.read(Path::new("/dest/same.txt")).unwrap(), b"same");
+        let readme = String::from_utf8(fs.read(Path::new("/dest/README.md")).unwrap()).unwrap();
+        assert!(readme.contains("mine") && readme.contains("template") && readme.contains("<<<<<<<"));
+    }
+
+    #[test]
+    fn merge_dry_run_writes_nothing() {
+        let fs = MemoryFs::new()
+            .with_file("/src/new.txt", "new")
+            .with_file("/dest/keep.txt", "keep");
+        let hashes = tempfile::tempdir().unwrap();
+        merge_into_dest(&fs, Path::new("/src"), Path::new("/dest"), &WalkOptions::default(), hashes.path(), true)
+            .unwrap();
+        assert!(!fs.exists(Path::new("/dest/new.txt")));
+    }
+
+    #[test]
+    fn dry_run_merge_reports_the_simulated_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        let dest = dir.path().join("dest");
+        std::fs::create_dir_all(src.join("acme-app")).unwrap();
+        std::fs::create_dir_all(dest.join("zeta")).unwrap();
+        std::fs::write(src.join("acme-app/config.txt"), "name = acme-app\n").unwrap();
+        std::fs::write(src.join("acme-app/new.txt"), "new\n").unwrap();
+        std::fs::write(dest.join("zeta/config.txt"), "name = zeta\n").unwrap();
+        let filter = filter::PathFilter::new(&[], &[]).unwrap();
+        let mappings = vec![("acme-app".to_string(), "zeta".to_string())];
+        let simulated = MemoryFs::snapshot(&src, &filter).unwrap();
+        replace_in_files(&simulated, &src, &mappings, &filter, true).unwrap();
+        rename_paths(&simulated, &src, &mappings, &filter, true).unwrap();
+        let overlay = OverlayFs { upper: &simulated, root: &src, lower: &RealFs };
+
+        let events = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let sink = events.clone();
+        progress::with_sink(
+            move |e| sink.borrow_mut().push(e.clone()),
+            || merge_into_dest(&overlay, &src, &dest, &WalkOptions::default(), &dir.path().join("hashes"), true).unwrap(),
+        );
+        // The replaced config equals the existing one; only the renamed new file is added
+        assert_eq!(*events.borrow(), [progress::Event::FileAdded { path: "zeta/new.txt".to_string() }]);
+        assert!(!dest.join("zeta/new.txt").exists());
+    }
+
+    #[test]
+    fn repeated_merges_trust_recorded_hashes_until_a_file_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let hashes = dir.path().join("hashes");
+        let (src, dest) = (dir.path().join("src"), dir.path().join("dest"));
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::create_dir_all(&dest).unwrap();
+        std::fs::write(src.join("a.txt"), "same\n").unwrap();
+        let merge = || merge_into_dest(&RealFs, &src, &dest, &WalkOptions::default(), &hashes, false).unwrap();
+        merge();
+        let target = dest.join("a.txt");
+        let modified = std::fs::metadata(&target).unwrap().modified().unwrap();
+
+        // Same size and time: the recorded hash stands in for the content, which is not read
+        std::fs::write(&target, "sXme\n").unwrap();
+        std::fs::File::options().write(true).open(&target).unwrap().set_modified(modified).unwrap();
+        merge();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "sXme\n");
+
+        // A new modification time makes the merge read the file again
+        let later = modified + std::time::Duration::from_secs(5);
+        std::fs::File::options().write(true).open(&target).unwrap().set_modified(later).unwrap();
+        merge();
+        assert!(std::fs::read_to_string(&target).unwrap().contains("<<<<<<<"));
+    }
+
+    #[test]
+    fn bare_project_names_scaffold_with_global_flags_after_them() {
+        let argv = ["liscaf", "--lang", "de", "zeta", "https://github.com/o/acme-app", "--porcelain", "--color", "never"];
+        let args = parse_args(argv.iter().map(Into::into).collect()).unwrap();
+        let Some(CliCommand::New(scaffold)) = &args.command else {
+            panic!("not a scaffold: {:?}", args.command);
+        };
+        assert_eq!(scaffold.new_name, "zeta");
+        assert_eq!(scaffold.repo_url.as_deref(), Some("https://github.com/o/acme-app"));
+        assert!(args.porcelain);
+        assert_eq!(args.color, color::ColorChoice::Never);
+        assert_eq!(args.lang.as_deref(), Some("de"));
+    }
+
+    #[test]
+    fn files_the_template_renamed_are_moved_before_merging() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path();
+        std::fs::create_dir_all(dest.join("zeta/old")).unwrap();
+        std::fs::create_dir_all(dest.join("docs")).unwrap();
+        std::fs::write(dest.join("zeta/old/config.toml"), "mine\n").unwrap();
+        std::fs::write(dest.join("docs/guide.md"), "guide\n").unwrap();
+        std::fs::write(dest.join("docs/manual.md"), "manual\n").unwrap();
+        let renames = vec![
+            ("acme-app/old/config.toml".to_string(), "acme-app/new/config.toml".to_string()),
+            ("docs/guide.md".to_string(), "docs/manual.md".to_string()),
+            ("missing.md".to_string(), "found.md".to_string()),
+        ];
+        let mappings = vec![("acme-app".to_string(), "zeta".to_string())];
+
+        move_renamed_files(dest, &renames, &mappings, true).unwrap();
+        assert!(dest.join("zeta/old/config.toml").exists());
+
+        move_renamed_files(dest, &renames, &mappings, false).unwrap();
+        assert_eq!(std::fs::read_to_string(dest.join("zeta/new/config.toml")).unwrap(), "mine\n");
+        // The emptied directory goes, a file already at the new path is left alone
+        assert!(!dest.join("zeta/old").exists());
+        assert_eq!(std::fs::read_to_string(dest.join("docs/guide.md")).unwrap(), "guide\n");
+        assert_eq!(std::fs::read_to_string(dest.join("docs/manual.md")).unwrap(), "manual\n");
+        assert!(!dest.join("found.md").exists());
+    }
+
+    #[test]
+    fn templates_without_a_manifest_are_copied_byte_for_byte() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = dir.path().join("acme-app");
+        std::fs::create_dir_all(template.join("src")).unwrap();
+        let vue = "<template><h1>{{ name }}</h1><p>&copy; {{ year }} {{ author }}</p></template>\n";
+        std::fs::write(template.join("src/App.vue"), vue).unwrap();
+        std::fs::write(template.join("{{ name }}.hbs"), "{{ name }}").unwrap();
+        let options = ScaffoldOptions {
+            assume_yes: true,
+            no_git: true,
+            no_name_check: true,
+            no_tool_check: true,
+            output_dir: Some(dir.path().join("zeta")),
+            local_template: Some(template.clone()),
+            tmpdir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let project = run_scaffold(&template.display().to_string(), "zeta", "acme-app", &options)
+            .unwrap()
+            .unwrap();
+        assert_eq!(std::fs::read(project.join("src/App.vue")).unwrap(), vue.as_bytes());
+        assert_eq!(std::fs::read(project.join("{{ name }}.hbs")).unwrap(), b"{{ name }}");
+    }
+
+    #[test]
+    fn fixtures_are_stripped_only_when_the_template_declares_them() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("tests")).unwrap();
+        std::fs::write(dir.path().join("tests/parser.answers.toml"), "input = 1\n").unwrap();
+        strip_template_only_files(dir.path(), &[], false, false).unwrap();
+        assert!(dir.path().join("tests/parser.answers.toml").exists());
+        strip_template_only_files(dir.path(), &[], true, false).unwrap();
+        assert!(!dir.path().join("tests").exists());
+    }
+
+    #[test]
+    fn reproducible_generators_repeat_across_runs() {
+        let time = chrono::DateTime::from_timestamp(0, 0).unwrap();
+        let text = "{{ uuid() }} {{ random_hex(4) }} {{ port(\"web\") }} {{ now(\"%Y\") }}";
+        let render = |stream: &str| {
+            let ctx = placeholders::RenderContext::new(placeholders::builtin_variables("zeta", &rules()))
+                .reproducible(time, stream);
+            placeholders::render(text, &ctx)
+        };
+        assert_eq!(render("files"), render("files"));
+        assert_ne!(render("files"), render("defaults"));
+        assert!(render("files").ends_with(" 1970"));
+    }
+
+    #[test]
+    fn bad_generator_arguments_stay_unrendered() {
+        let ctx = placeholders::RenderContext::new(placeholders::builtin_variables("zeta", &rules()));
+        let text = "{{ now(\"%Q\") }} {{ random_hex(99999999999) }}";
+        assert_eq!(placeholders::render(text, &ctx), text);
+    }
+
+    #[cfg(feature = "plugins")]
+    #[test]
+    fn plugin_results_outside_memory_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        // derive returns 256 bytes starting 16 bytes before the end of its single page
+        std::fs::write(
+            dir.path().join("bad.wat"),
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "alloc") (param i32) (result i32) i32.const 0)
+                (func (export "derive") (param i32 i32) (result i64) i64.const 0xfff000000100))"#,
+        )
+        .unwrap();
+        let spec = manifest::PluginSpec { path: "bad.wat".to_string(), files: Vec::new() };
+        let plugins = plugin::load(dir.path(), &[spec]).unwrap();
+        let err = plugin::derive(&plugins, &mut BTreeMap::new()).unwrap_err();
+        assert!(format!("{:#}", err).contains("outside the module's memory"));
+    }
+
+    #[test]
+    fn cargo_postprocess_keeps_versions_of_crates_outside_the_scaffold() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("zeta");
+        std::fs::create_dir_all(root.join("crates/core")).unwrap();
+        std::fs::create_dir_all(dir.path().join("shared")).unwrap();
+        std::fs::write(root.join("crates/core/Cargo.toml"), "[package]\nname = \"core\"\nversion = \"2.0.0\"\n").unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"acme-app\"\nversion = \"2.0.0\"\n\n[dependencies]\n\
+             core = { path = \"crates/core\", version = \"2.0.0\" }\n\
+             shared = { path = \"../shared\", version = \"2.3\" }\n",
+        )
+        .unwrap();
+        let manifest = manifest::Manifest::default();
+        let ctx = postprocess::PostprocessContext {
+            fs: &RealFs,
+            root: &root,
+            manifest: &manifest,
+            new_name: "zeta",
+            vars: &BTreeMap::new(),
+            mappings: &[],
+            dry_run: false,
+        };
+        postprocess::run(&["cargo".to_string()], &ctx).unwrap();
+        let written = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+        assert!(written.contains("core = { path = \"crates/core\", version = \"0.1.0\" }"), "{}", written);
+        assert!(written.contains("shared = { path = \"../shared\", version = \"2.3\" }"), "{}", written);
+    }
+
+    #[test]
+    fn capital_runs_split_before_the_next_word() {
+        assert_eq!(split_name_to_tokens("HTTPServer", &rules()), ["http", "server"]);
+        assert_eq!(split_name_to_tokens("parseJSON", &rules()), ["parse", "json"]);
+        assert_eq!(split_name_to_tokens("ACME_APP", &rules()), ["acme", "app"]);
+        assert_eq!(split_name_to_tokens("base64Encode", &rules()), ["base64", "encode"]);
+        assert_eq!(split_name_to_tokens("my-CoolApp", &rules()), ["my", "cool", "app"]);
+    }
+
+    #[test]
+    fn digits_and_separators_follow_the_rules() {
+        let split = rules().with_digits(DigitHandling::Split);
+        assert_eq!(split_name_to_tokens("base64Encode", &split), ["base", "64", "encode"]);
+        let dashes = rules().with_separators(['-']);
+        assert_eq!(split_name_to_tokens("my-web_app", &dashes), ["my", "web_app"]);
+    }
+
+    #[test]
+    fn acronyms_stay_intact_in_tokens_and_variants() {
+        let rules = rules().with_acronyms(["HTTP", "OAuth2"]);
+        let tokens = split_name_to_tokens("OAuth2HTTPClient", &rules);
+        assert_eq!(tokens, ["oauth2", "http", "client"]);
+        assert_eq!(rules.join_pascal(&tokens, ""), "OAuth2HTTPClient");
+        assert_eq!(rules.join_camel(&tokens), "oauth2HTTPClient");
+
+        let mappings = generate_variant_mappings(
+            &split_name_to_tokens("acme-http", &rules),
+            &split_name_to_tokens("oauth2-proxy", &rules),
+            &[],
+            &rules,
+        );
+        let text = "AcmeHttp AcmeHTTP acmeHTTP ACME_HTTP";
+        assert_eq!(
+            apply_mappings(text, &mappings),
+            "OAuth2Proxy OAuth2Proxy oauth2Proxy OAUTH2_PROXY"
+        );
+    }
+
+    #[test]
+    fn nested_git_dirs_are_not_touched() {
+        let fs = MemoryFs::new()
+            .with_file("/t/vendor-lib/.git/acme-app.pack", "acme-app")
+            .with_file("/t/vendor-lib/.git/config", "url = acme-app")
+            .with_file("/t/vendor-lib/acme-app.txt", "acme-app");
+        let filter = filter::PathFilter::new(&[], &[]).unwrap();
+        let base = Path::new("/t");
+        replace_in_files(&fs, base, &acme_mappings(), &filter, false).unwrap();
+        rename_paths(&fs, base, &acme_mappings(), &filter, false).unwrap();
+
+        assert_eq!(
+            fs.files(),
+            vec![
+                PathBuf::from("/t/vendor-lib/.git/acme-app.pack"),
+                PathBuf::from("/t/vendor-lib/.git/config"),
+                PathBuf::from("/t/vendor-lib/payments.txt"),
+            ]
+        );
+        assert_eq!(fs.read(Path::new("/t/vendor-lib/.git/config")).unwrap(), b"url = acme-app");
+    }
+
+    #[test]
+    fn lockfiles_keep_template_names_unless_requested() {
+        let fs = MemoryFs::new()
+            .with_file("/t/Cargo.toml", "name = \"acme-app\"")
+            .with_file("/t/Cargo.lock", "name = \"acme-app\"")
+            .with_file("/t/web/package-lock.json", "{\"name\": \"acme-app\"}");
+        let filter = filter::PathFilter::new(&[], &[]).unwrap();
+        let base = Path::new("/t");
+        replace_in_files(&fs, base, &acme_mappings(), &filter, false).unwrap();
+        assert_eq!(fs.read(Path::new("/t/Cargo.toml")).unwrap(), b"name = \"payments\"");
+        assert_eq!(fs.read(Path::new("/t/Cargo.lock")).unwrap(), b"name = \"acme-app\"");
+        assert_eq!(
+            fs.read(Path::new("/t/web/package-lock.json")).unwrap(),
+            b"{\"name\": \"acme-app\"}"
+        );
+
+        let filter = filter.with_lockfiles(true);
+        replace_in_files(&fs, base, &acme_mappings(), &filter, false).unwrap();
+        assert_eq!(fs.read(Path::new("/t/Cargo.lock")).unwrap(), b"name = \"payments\"");
+    }
+
+    #[test]
+    fn walk_options_bound_replacement() {
+        let fs = MemoryFs::new()
+            .with_file("/t/acme-app.txt", "acme-app")
+            .with_file("/t/.acme-app.env", "acme-app")
+            .with_file("/t/src/deep/acme-app.txt", "acme-app");
+        let filter = filter::PathFilter::new(&[], &[]).unwrap().with_walk_options(WalkOptions {
+            max_depth: Some(2),
+            include_hidden: false,
+            follow_symlinks: false,
+        });
+        let base = Path::new("/t");
+        replace_in_files(&fs, base, &acme_mappings(), &filter, false).unwrap();
+        rename_paths(&fs, base, &acme_mappings(), &filter, false).unwrap();
+
+        assert_eq!(
+            fs.files(),
+            vec![
+                PathBuf::from("/t/.acme-app.env"),
+                PathBuf::from("/t/payments.txt"),
+                PathBuf::from("/t/src/deep/acme-app.txt"),
+            ]
+        );
+        assert_eq!(fs.read(Path::new("/t/.acme-app.env")).unwrap(), b"acme-app");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn disk_space_check_fails_early() {
+        let dir = std::env::temp_dir().join("liscaf-not-yet-created");
+        assert!(diskspace::ensure(&dir, 1, "project").is_ok());
+        let err = diskspace::ensure(&dir, u64::MAX, "project").unwrap_err();
+        assert!(err.to_string().starts_with("Not enough disk space for the project"));
+    }
+
+    #[test]
+    fn tool_requirements_compare_versions() {
+        let node = tools::Requirement::parse("node >= 20").unwrap();
+        assert_eq!(node.tool, "node");
+        assert!(node.accepts(&tools::parse_version("v20.11.1").unwrap()));
+        assert!(!node.accepts(&tools::parse_version("v18.19.0").unwrap()));
+        assert!(tools::Requirement::parse("docker").unwrap().accepts(&[]));
+        assert!(tools::Requirement::parse("go==1.22").unwrap().accepts(&[1, 22, 0]));
+        assert!(!tools::Requirement::parse("protoc < 3.20").unwrap().accepts(&[3, 20]));
+        assert!(tools::Requirement::parse("node >=").is_err());
+        assert!(tools::Requirement::parse("node 20").is_err());
+        assert_eq!(tools::parse_version("git version 2.43.0\n"), Some(vec![2, 43, 0]));
+    }
+
+    #[test]
+    fn protocol_preference_rewrites_urls() {
+        assert_eq!(
+            protocol::to_ssh("https://github.com/acme/api").unwrap(),
+            ("github.com".to_string(), "git@github.com:acme/api.git".to_string())
+        );
+        assert_eq!(
+            protocol::to_ssh("https://token@gitlab.example.com/team/app.git/").unwrap().1,
+            "git@gitlab.example.com:team/app.git"
+        );
+        assert_eq!(
+            protocol::to_https("git@github.com:acme/api.git").unwrap().1,
+            "https://github.com/acme/api.git"
+        );
+        assert_eq!(
+            protocol::to_https("ssh://git@gitlab.example.com:2222/team/app.git").unwrap().1,
+            "https://gitlab.example.com/team/app.git"
+        );
+        assert!(protocol::to_https("https://github.com/acme/api").is_none());
+        assert!(protocol::to_ssh("git@github.com:acme/api.git").is_none());
+    }
+
+    #[test]
+    fn structured_replacement_leaves_keys_and_anchors() {
+        let mappings = acme_mappings();
+        let values = structured::Mode::Values;
+        let yaml = "acme-app: &acme-app\n  name: acme-app # acme-app\n  image: \"acme-app:1\"\n  tags: [acme-app, {acme-app: acme-app}]\n  script: |\n    run acme-app\nother: *acme-app\n";
+        assert_eq!(
+            structured::replace(values, Path::new("k8s.yaml"), yaml, &mappings).unwrap(),
+            "acme-app: &acme-app\n  name: payments # acme-app\n  image: \"payments:1\"\n  tags: [payments, {acme-app: payments}]\n  script: |\n    run payments\nother: *acme-app\n"
+        );
+        assert_eq!(
+            structured::replace(structured::Mode::ValuesAndKeys, Path::new("a.yml"), "acme-app: &acme-app x\n", &mappings)
+                .unwrap(),
+            "payments: &payments x\n"
+        );
+        let json = "{\"acme-app\": {\"name\": \"acme-app\"}, // acme-app\n \"list\": [\"acme-app\"]}";
+        assert_eq!(
+            structured::replace(values, Path::new("package.json"), json, &mappings).unwrap(),
+            "{\"acme-app\": {\"name\": \"payments\"}, // acme-app\n \"list\": [\"payments\"]}"
+        );
+        let toml = "[acme-app]\nname = \"acme-app\" # acme-app\ndeps = { acme-app = '1', x = [\"acme-app\"] }\n";
+        assert_eq!(
+            structured::replace(values, Path::new("Cargo.toml"), toml, &mappings).unwrap(),
+            "[acme-app]\nname = \"payments\" # acme-app\ndeps = { acme-app = '1', x = [\"payments\"] }\n"
+        );
+        assert!(structured::replace(values, Path::new("a.json"), "{\"acme-app", &mappings).is_err());
+    }
+
+    #[test]
+    fn regex_mappings_win_over_generated_variants() {
+        let mut mappings = vec![
+            ("acme-app".to_string(), "payments".to_string()),
+            ("acme_app".to_string(), "payments".to_string()),
+        ];
+        mappings.push(mappings::parse_map(r"regex:acme[-_]?app(\d+)=>billing${1}").unwrap());
+        assert_eq!(
+            apply_mappings("acme-app acme_app2 acmeapp10 acme-app", &mappings),
+            "payments billing2 billing10 payments"
+        );
+        assert!(mappings::parse_map("regex:acme(=>x").is_err());
+        assert!(mappings::parse_map("acme").is_err());
+    }
+
+    #[test]
+    fn copyright_notices_get_current_year_and_holder() {
+        let text = "// Copyright (c) 2019-2023 Acme Corp. All rights reserved.\n\
+                    # Copyright 2020, Jane Roe <jane@acme.example>\n\
+                    const NOTICE: &str = \"Copyright 2021 Acme Corp\";\n\
+                    Copyright 2018 Third Party Ltd\n";
+        let holders = vec!["acme".to_string(), "Jane".to_string()];
+        assert_eq!(
+            copyright::refresh_text(text, "2026", Some("Globex"), &holders).unwrap(),
+            "// Copyright (c) 2026 Globex. All rights reserved.\n\
+             # Copyright 2026, Globex\n\
+             const NOTICE: &str = \"Copyright 2021 Acme Corp\";\n\
+             Copyright 2018 Third Party Ltd\n"
+        );
+        assert_eq!(
+            copyright::refresh_text("/* Copyright 2019-present Acme */", "2026", None, &[]).unwrap(),
+            "/* Copyright 2026 Acme */"
+        );
+        assert!(copyright::refresh_text("Copyright 2026 Acme", "2026", None, &[]).is_none());
+    }
+}
@@ -0,0 +1,202 @@
+//! SPDX license selection: writes LICENSE files and updates `license` fields in manifests.
+use std::path::Path;
+
+use inquire::Select;
+
+use crate::color::status;
+use crate::prompt::Ask;
+use crate::scaffold_fs::ScaffoldFs;
+
+pub const KEEP_TEMPLATE_LICENSE: &str = "Keep template license";
+
+pub const SPDX_CHOICES: &[&str] = &[
+    "MIT",
+    "Apache-2.0",
+    "MIT OR Apache-2.0",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "ISC",
+    "MPL-2.0",
+    "GPL-3.0-only",
+    "GPL-3.0-or-later",
+    "LGPL-3.0-only",
+    "Unlicense",
+];
+
+const MIT: &str = include_str!("licenses/MIT.txt");
+const APACHE_2_0: &str = include_str!("licenses/Apache-2.0.txt");
+const BSD_2_CLAUSE: &str = include_str!("licenses/BSD-2-Clause.txt");
+const BSD_3_CLAUSE: &str = include_str!("licenses/BSD-3-Clause.txt");
+const ISC: &str = include_str!("licenses/ISC.txt");
+const MPL_2_0: &str = include_str!("licenses/MPL-2.0.txt");
+const GPL_3_0: &str = include_str!("licenses/GPL-3.0.txt");
+const LGPL_3_0: &str = include_str!("licenses/LGPL-3.0.txt");
+const UNLICENSE: &str = include_str!("licenses/Unlicense.txt");
+
+/// License files a template may ship; they are replaced when a license is selected.
+const EXISTING_LICENSE_FILES: &[&str] = &[
+    "LICENSE",
+    "LICENSE.md",
+    "LICENSE.txt",
+    "LICENSE-MIT",
+    "LICENSE-APACHE",
+    "COPYING",
+    "COPYING.LESSER",
+    "UNLICENSE",
+];
+
+pub fn is_supported(id: &str) -> bool {
+    SPDX_CHOICES.contains(&id)
+}
+
+pub fn prompt_license() -> anyhow::Result<Option<String>> {
+    let keep = crate::i18n::text(KEEP_TEMPLATE_LICENSE);
+    let mut options = vec![keep];
+    options.extend_from_slice(SPDX_CHOICES);
+    let choice = Select::new("License:", options).ask()?;
+    if choice == keep {
+        return Ok(None);
+    }
+    Ok(Some(choice.to_string()))
+}
+
+/// (file name, text) pairs to write for an SPDX expression.
+fn license_files(id: &str) -> Vec<(&'static str, &'static str)> {
+    match id {
+        "MIT" => vec![("LICENSE", MIT)],
+        "Apache-2.0" => vec![("LICENSE", APACHE_2_0)],
+        "MIT OR Apache-2.0" => vec![("LICENSE-MIT", MIT), ("LICENSE-APACHE", APACHE_2_0)],
+        "BSD-2-Clause" => vec![("LICENSE", BSD_2_CLAUSE)],
+        "BSD-3-Clause" => vec![("LICENSE", BSD_3_CLAUSE)],
+        "ISC" => vec![("LICENSE", ISC)],
+        "MPL-2.0" => vec![("LICENSE", MPL_2_0)],
+        "GPL-3.0-only" | "GPL-3.0-or-later" => vec![("LICENSE", GPL_3_0)],
+        "LGPL-3.0-only" => vec![("COPYING.LESSER", LGPL_3_0), ("COPYING", GPL_3_0)],
+        "Unlicense" => vec![("LICENSE", UNLICENSE)],
+        _ => Vec::new(),
+    }
+}
+
+fn interpolate(text: &str, year: &str, author: &str) -> String {
+    text.replace("<copyright notice>", &format!("Copyright (c) {} {}", year, author))
+        .replace("<year>", year)
+        .replace("<copyright holders>", author)
+        .replace("<owner>", author)
+}
+
+/// Writes the license files for `id`; in dry runs the changes go to `fs` too.
+pub fn apply_license(
+    fs: &dyn ScaffoldFs,
+    root: &Path,
+    id: &str,
+    year: &str,
+    author: &str,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let files = license_files(id);
+    if files.is_empty() {
+        anyhow::bail!("Unsupported license '{}'", id);
+    }
+    println!("Applying license {}", id);
+
+    for name in EXISTING_LICENSE_FILES {
+        let path = root.join(name);
+        if !fs.exists(&path) {
+            continue;
+        }
+        fs.remove_file(&path)?;
+        if dry_run {
+            status!("DRY DELETE: {}", path.display());
+        } else {
+            status!("DELETE: {}", path.display());
+        }
+    }
+
+    for (name, text) in files {
+        let path = root.join(name);
+        fs.write(&path, interpolate(text, year, author).as_bytes())?;
+        if dry_run {
+            status!("DRY ADD: {}", path.display());
+        } else {
+            status!("ADD: {}", path.display());
+        }
+    }
+
+    update_manifest_license_fields(fs, root, id, dry_run)
+}
+
+fn update_manifest_license_fields(
+    fs: &dyn ScaffoldFs,
+    root: &Path,
+    id: &str,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let entries = fs.walk(root, &Default::default(), &mut |e| {
+        !e.path.file_name().is_some_and(|n| n == ".git" || n == "node_modules")
+    });
+    for entry in entries {
+        if !entry.is_file {
+            continue;
+        }
+        let path = entry.path.as_path();
+        let updated = match path.file_name().and_then(|n| n.to_str()) {
+            Some("Cargo.toml") => set_cargo_license(&fs.read_to_string(path)?, id),
+            Some("package.json") => set_package_json_license(&fs.read_to_string(path)?, id),
+            _ => None,
+        };
+        let Some(updated) = updated else {
+            continue;
+        };
+        fs.write(path, updated.as_bytes())?;
+        if dry_run {
+            println!("DRY LICENSE: Would update {}", path.display());
+        } else {
+            println!("LICENSE: Updated {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Rewrites `license = "..."` in `[package]`/`[workspace.package]`, keeping the layout.
+fn set_cargo_license(content: &str, id: &str) -> Option<String> {
+    let mut section = String::new();
+    let mut changed = false;
+    let mut out = String::with_capacity(content.len());
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            section = trimmed.trim_matches(|c| c == '[' || c == ']').trim().to_string();
+        }
+        let in_package = section == "package" || section == "workspace.package";
+        let is_license_key = trimmed
+            .strip_prefix("license")
+            .map(|rest| rest.trim_start().starts_with('='))
+            .unwrap_or(false);
+        if in_package && is_license_key {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            let newline = if line.ends_with('\n') { "\n" } else { "" };
+            let replacement = format!("{}license = \"{}\"{}", indent, id, newline);
+            changed |= replacement != line;
+            out.push_str(&replacement);
+        } else {
+            out.push_str(line);
+        }
+    }
+    changed.then_some(out)
+}
+
+/// Rewrites the string value of the first `"license"` key, leaving all other bytes untouched.
+fn set_package_json_license(content: &str, id: &str) -> Option<String> {
+    let key = content.find("\"license\"")?;
+    let after_key = key + "\"license\"".len();
+    let colon = after_key + content[after_key..].find(':')?;
+    let open = colon + 1 + content[colon + 1..].find('"')?;
+    if !content[colon + 1..open].trim().is_empty() {
+        return None;
+    }
+    let close = open + 1 + content[open + 1..].find('"')?;
+    if &content[open + 1..close] == id {
+        return None;
+    }
+    Some(format!("{}\"{}\"{}", &content[..open], id, &content[close + 1..]))
+}
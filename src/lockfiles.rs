@@ -0,0 +1,129 @@
+//! Lockfiles (`Cargo.lock`, `package-lock.json`, `yarn.lock`, ...) are left out of token
+//! replacement: renamed entries no longer match their checksums and integrity hashes. Instead the
+//! package manager regenerates each lockfile that still names the template after the run.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use inquire::Confirm;
+use walkdir::WalkDir;
+
+use crate::i18n::tr;
+use crate::prompt::Ask;
+
+/// Lockfile names and the command that brings each up to date with its manifest.
+const LOCKFILES: &[(&str, &[&str])] = &[
+    ("Cargo.lock", &["cargo", "update", "--workspace"]),
+    ("package-lock.json", &["npm", "install", "--package-lock-only", "--ignore-scripts"]),
+    ("npm-shrinkwrap.json", &["npm", "install", "--package-lock-only", "--ignore-scripts"]),
+    ("yarn.lock", &["yarn", "install", "--mode", "update-lockfile"]),
+    ("pnpm-lock.yaml", &["pnpm", "install", "--lockfile-only", "--ignore-scripts"]),
+    ("poetry.lock", &["poetry", "lock"]),
+    ("uv.lock", &["uv", "lock"]),
+    ("Pipfile.lock", &["pipenv", "lock"]),
+    ("Gemfile.lock", &["bundle", "lock"]),
+    ("composer.lock", &["composer", "update", "--lock", "--no-scripts"]),
+    ("go.sum", &["go", "mod", "tidy"]),
+];
+
+fn command_for(path: &Path) -> Option<&'static [&'static str]> {
+    let name = path.file_name()?.to_str()?;
+    LOCKFILES.iter().find(|(n, _)| *n == name).map(|(_, command)| *command)
+}
+
+pub fn is_lockfile(path: &Path) -> bool {
+    command_for(path).is_some()
+}
+
+/// Lockfiles below `root` that still contain a mapped name.
+fn stale(root: &Path, mappings: &[(String, String)]) -> Vec<PathBuf> {
+    let mut found: Vec<PathBuf> = WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0
+                || !(e.file_name() == ".git"
+                    || crate::filter::DEFAULT_SKIP_DIRS.iter().any(|d| e.file_name() == *d))
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && is_lockfile(e.path()))
+        .filter(|e| {
+            fs::read_to_string(e.path()).is_ok_and(|content| crate::mappings::occur_in(&content, mappings))
+        })
+        .map(|e| e.into_path())
+        .collect();
+    found.sort();
+    found
+}
+
+/// Regenerates the lockfiles below `root` that replacement skipped. Asks first unless `always`;
+/// with `--yes` and without `always` they are only listed, since regenerating hits the network.
+pub fn regenerate(root: &Path, mappings: &[(String, String)], always: bool, assume_yes: bool, dry_run: bool) {
+    let lockfiles = stale(root, mappings);
+    if lockfiles.is_empty() {
+        return;
+    }
+    let steps: Vec<(PathBuf, &[&str])> = lockfiles
+        .iter()
+        .filter_map(|path| Some((path.parent()?.to_path_buf(), command_for(path)?)))
+        .collect();
+    let describe = |(dir, command): &(PathBuf, &[&str])| format!("{} (in {})", command.join(" "), dir.display());
+    if dry_run {
+        for step in &steps {
+            println!("DRY LOCKFILE: Would regenerate with {}", describe(step));
+        }
+        return;
+    }
+    if !always {
+        if assume_yes {
+            for path in &lockfiles {
+                println!("LOCKFILE: {} still names the template; regenerate it (or pass --regenerate-lockfiles)", path.display());
+            }
+            return;
+        }
+        let lines: Vec<String> = steps.iter().map(describe).collect();
+        let prompt = tr!("Regenerate lockfiles that still name the template?\n  {}", lines.join("\n  "));
+        match Confirm::new(&prompt).with_default(true).ask() {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                println!("Warning: skipping lockfile regeneration: {}", e);
+                return;
+            }
+        }
+    }
+
+    for step @ (dir, command) in &steps {
+        println!("LOCKFILE: {}", describe(step));
+        match crate::logfile::status(Command::new(command[0]).args(&command[1..]).current_dir(dir)) {
+            Ok(status) if status.success() => {}
+            Ok(status) => println!(
+                "Warning: '{}' failed with code {}",
+                command.join(" "),
+                status.code().unwrap_or(-1)
+            ),
+            Err(e) => println!("Warning: failed to run '{}': {}", command.join(" "), e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_lockfiles_naming_the_template_are_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("web")).unwrap();
+        fs::create_dir_all(root.join("node_modules/dep")).unwrap();
+        fs::write(root.join("Cargo.lock"), "name = \"acme-app\"").unwrap();
+        fs::write(root.join("web/yarn.lock"), "payments@1.0.0").unwrap();
+        fs::write(root.join("node_modules/dep/package-lock.json"), "acme-app").unwrap();
+        fs::write(root.join("notes.lock"), "acme-app").unwrap();
+
+        let mappings = vec![("acme-app".to_string(), "payments".to_string())];
+        assert_eq!(stale(root, &mappings), vec![root.join("Cargo.lock")]);
+        assert!(is_lockfile(Path::new("web/pnpm-lock.yaml")));
+        assert!(!is_lockfile(Path::new("notes.lock")));
+    }
+}
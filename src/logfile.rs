@@ -0,0 +1,238 @@
+//! `--log-file <path>`: a copy of everything liscaf prints, plus the output of the commands it
+//! runs, so a scaffold that failed in CI can be diagnosed from the job's artifacts afterwards.
+//!
+//! stdout is passed through a pipe that a thread copies to the console and the log file; stderr
+//! too when it is not a terminal (with a terminal, prompts need it as it is). The log also gets
+//! lines the console does not show: the invocation, every command run with its directory and
+//! exit status, and the final error. Escape sequences (colors) are left out of the file.
+use std::path::Path;
+use std::process::{Command, ExitStatus};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// The running capture; kept here so [`exit`] can finish it from any thread.
+static CAPTURE: Mutex<Option<imp::Capture>> = Mutex::new(None);
+
+/// Starts the line of a note that only goes to the log file.
+const NOTE: u8 = 0;
+
+/// Writes `line` to the log file only.
+pub fn note(line: &str) {
+    if !ACTIVE.load(Ordering::Relaxed) {
+        return;
+    }
+    use std::io::Write;
+    // Through stdout, so the note lands in order with what was printed before it
+    let mut stdout = std::io::stdout().lock();
+    let _ = stdout.write_all(&[NOTE]);
+    let _ = writeln!(stdout, "{}", line);
+    let _ = stdout.flush();
+}
+
+/// `command.status()`, recording the command and how it ended in the log file.
+pub fn status(command: &mut Command) -> std::io::Result<ExitStatus> {
+    if ACTIVE.load(Ordering::Relaxed) {
+        let mut line = format!("RUN: {}", command.get_program().to_string_lossy());
+        for arg in command.get_args() {
+            line.push(' ');
+            line.push_str(&arg.to_string_lossy());
+        }
+        if let Some(dir) = command.get_current_dir() {
+            line.push_str(&format!(" (in {})", dir.display()));
+        }
+        note(&line);
+    }
+    let status = command.status();
+    match &status {
+        Ok(status) if !status.success() => note(&format!("RUN: exited with {}", status)),
+        Err(e) => note(&format!("RUN: could not start: {}", e)),
+        Ok(_) => {}
+    }
+    status
+}
+
+/// Copies the output to `path` until the returned guard is dropped or [`exit`] is called.
+pub fn start(path: &Path) -> anyhow::Result<Guard> {
+    let capture = imp::Capture::start(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open log file {}: {}", path.display(), e))?;
+    *CAPTURE.lock().unwrap_or_else(|e| e.into_inner()) = Some(capture);
+    ACTIVE.store(true, Ordering::Relaxed);
+    let args: Vec<String> = std::env::args().collect();
+    note(&format!(
+        "liscaf {} at {}: {}",
+        env!("CARGO_PKG_VERSION"),
+        chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        args.join(" ")
+    ));
+    Ok(Guard)
+}
+
+/// Ends the capture when dropped.
+pub struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        finish();
+    }
+}
+
+/// Restores stdout and stderr and writes the rest of the output to the file.
+fn finish() {
+    let capture = CAPTURE.lock().unwrap_or_else(|e| e.into_inner()).take();
+    drop(capture);
+}
+
+/// `std::process::exit`, after the log file got everything printed so far.
+pub fn exit(code: i32) -> ! {
+    finish();
+    std::process::exit(code)
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::fs::File;
+    use std::io::{self, IsTerminal, Read, Write};
+    use std::os::fd::{FromRawFd, RawFd};
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use std::thread::JoinHandle;
+
+    use super::{ACTIVE, NOTE};
+
+    pub struct Capture {
+        /// Captured descriptor, its original target, and the thread copying the pipe.
+        streams: Vec<(RawFd, RawFd, JoinHandle<()>)>,
+    }
+
+    impl Capture {
+        pub fn start(path: &Path) -> io::Result<Capture> {
+            let log = Arc::new(Mutex::new(File::create(path)?));
+            let mut capture = Capture { streams: Vec::new() };
+            capture.tee(libc::STDOUT_FILENO, &log)?;
+            if !io::stderr().is_terminal() {
+                capture.tee(libc::STDERR_FILENO, &log)?;
+            }
+            Ok(capture)
+        }
+
+        /// Points `fd` at a pipe whose contents go to its original target and `log`.
+        fn tee(&mut self, fd: RawFd, log: &Arc<Mutex<File>>) -> io::Result<()> {
+            io::stdout().flush()?;
+            let mut ends = [0; 2];
+            // SAFETY: descriptor plumbing on descriptors we own; each File takes one descriptor.
+            let (reader, console, saved) = unsafe {
+                if libc::pipe(ends.as_mut_ptr()) < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                let saved = libc::dup(fd);
+                let console = libc::dup(fd);
+                if saved < 0 || console < 0 || libc::dup2(ends[1], fd) < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                libc::close(ends[1]);
+                (File::from_raw_fd(ends[0]), File::from_raw_fd(console), saved)
+            };
+            let log = Arc::clone(log);
+            let thread = std::thread::spawn(move || copy(reader, console, &log));
+            self.streams.push((fd, saved, thread));
+            Ok(())
+        }
+    }
+
+    impl Drop for Capture {
+        fn drop(&mut self) {
+            let _ = io::stdout().flush();
+            ACTIVE.store(false, std::sync::atomic::Ordering::Relaxed);
+            for (fd, saved, thread) in self.streams.drain(..) {
+                // Closing the pipe's last write end lets the thread finish
+                // SAFETY: restores the descriptor duplicated in `tee`.
+                unsafe {
+                    libc::dup2(saved, fd);
+                    libc::close(saved);
+                }
+                let _ = thread.join();
+            }
+        }
+    }
+
+    fn copy(mut reader: File, mut console: File, log: &Mutex<File>) {
+        let mut buf = [0u8; 8192];
+        let mut at_line_start = true;
+        let mut in_note = false;
+        let mut escape = Escape::default();
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            };
+            let mut chunk = &buf[..n];
+            let mut log = log.lock().unwrap_or_else(|e| e.into_inner());
+            while !chunk.is_empty() {
+                if at_line_start && !in_note && chunk[0] == NOTE {
+                    in_note = true;
+                    chunk = &chunk[1..];
+                    continue;
+                }
+                let end = chunk.iter().position(|&b| b == b'\n').map_or(chunk.len(), |i| i + 1);
+                let (line, rest) = chunk.split_at(end);
+                if !in_note {
+                    let _ = console.write_all(line);
+                }
+                let _ = log.write_all(&escape.strip(line));
+                at_line_start = line.ends_with(b"\n");
+                if at_line_start {
+                    in_note = false;
+                }
+                chunk = rest;
+            }
+            let _ = log.flush();
+        }
+    }
+
+    /// Drops ANSI escape sequences, which may be split across reads.
+    #[derive(Default)]
+    struct Escape {
+        state: u8,
+    }
+
+    impl Escape {
+        const TEXT: u8 = 0;
+        const ESC: u8 = 1;
+        const CSI: u8 = 2;
+
+        fn strip(&mut self, bytes: &[u8]) -> Vec<u8> {
+            let mut out = Vec::with_capacity(bytes.len());
+            for &b in bytes {
+                self.state = match (self.state, b) {
+                    (Self::TEXT, 0x1b) => Self::ESC,
+                    (Self::TEXT, _) => {
+                        out.push(b);
+                        Self::TEXT
+                    }
+                    (Self::ESC, b'[') => Self::CSI,
+                    (Self::ESC, _) => Self::TEXT,
+                    (_, 0x40..=0x7e) => Self::TEXT,
+                    (state, _) => state,
+                };
+            }
+            out
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use std::path::Path;
+
+    pub struct Capture;
+
+    impl Capture {
+        pub fn start(_path: &Path) -> std::io::Result<Capture> {
+            Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "--log-file needs a Unix system"))
+        }
+    }
+}
@@ -0,0 +1,258 @@
+//! Template manifest (`liscaf.toml` in the template root).
+//!
+//! Every section is optional; a template without a manifest behaves like before.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub const MANIFEST_FILE: &str = "liscaf.toml";
+
+/// Template-authoring artifacts that never end up in a generated project.
+pub const TEMPLATE_ONLY_FILES: &[&str] = &[MANIFEST_FILE, ".liscaf", "TEMPLATE_README.md"];
+
+/// Built-in answers a profile may set besides the declared variables.
+const PROFILE_BUILTINS: &[&str] = &["license", "author_email", "repo_homepage"];
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct Manifest {
+    /// One-line summary shown by `liscaf info`.
+    pub description: Option<String>,
+    /// Base template this one specializes (see `extends.rs`); resolved before the manifest is
+    /// loaded for scaffolding.
+    pub extends: Option<ExtendsSpec>,
+    /// Tools the generated project needs, optionally with a minimum version, e.g.
+    /// `["node >= 20", "docker", "protoc"]`; checked before scaffolding.
+    pub requires: Vec<String>,
+    /// Install hints for required tools, shown when one is missing (overrides the built-in hints).
+    pub install_hints: BTreeMap<String, String>,
+    /// `[placeholders]`; see [`Manifest::renders_placeholders`].
+    pub placeholders: Option<PlaceholderConfig>,
+    /// Template variables, prompted for during scaffolding (`[variables.module]`).
+    pub variables: BTreeMap<String, VariableSpec>,
+    /// Named answer presets (`[profiles.minimal]`), chosen with `--profile` or offered first when
+    /// prompting; variables a profile leaves out take their defaults.
+    pub profiles: BTreeMap<String, BTreeMap<String, String>>,
+    /// Additional template-only files or directories (relative paths) to drop from the output.
+    pub template_only: Vec<String>,
+    /// `tests/*.answers.toml` are `liscaf test` fixtures and are dropped from the output; without
+    /// this they are the project's own files.
+    pub test_fixtures: bool,
+    /// Directory trees kept only when a condition on the answers holds (`[[conditional]]`).
+    pub conditional: Vec<ConditionalSpec>,
+    /// Ecosystem post-processing modes to run after replacement, e.g. `["cargo"]`.
+    pub postprocess: Vec<String>,
+    /// Options for the `npm` post-processing mode.
+    pub npm: NpmConfig,
+    /// Shell commands run in the new project after git init, e.g. `["npm install"]`.
+    pub bootstrap: Vec<String>,
+    /// Install the template's git hooks (`.hooks/` into `.git/hooks`, `pre-commit install` for
+    /// a `.pre-commit-config.yaml`) in new projects.
+    pub install_hooks: bool,
+    /// Formatter commands run by `--format` before the initial commit, e.g. `["cargo fmt"]`;
+    /// empty means the formatters of the ecosystems found in the project.
+    pub formatters: Vec<String>,
+    /// Message shown at the end instead of "Scaffolding finished", with placeholders rendered,
+    /// e.g. `"cd {{ name }} && docker compose up"`.
+    pub next_steps: Option<String>,
+    /// Generator-style fragments rendered into an existing project by `liscaf add`.
+    pub fragments: BTreeMap<String, FragmentSpec>,
+    pub mappings: MappingConfig,
+    pub copyright: CopyrightConfig,
+    pub name: NameConfig,
+    /// Sandboxed WASM modules deriving variables or transforming files (`[[plugins]]`).
+    pub plugins: Vec<PluginSpec>,
+}
+
+impl Manifest {
+    /// Whether `{{ }}` placeholders in files and names are rendered. Templates opt in with a
+    /// `[placeholders]` table or declared variables; other templates keep their Vue, Jinja or
+    /// Helm syntax as it is.
+    pub fn renders_placeholders(&self) -> bool {
+        self.placeholders.is_some() || !self.variables.is_empty()
+    }
+
+    /// Declared placeholder delimiters; empty means the default `{{ }}`.
+    pub fn delimiters(&self) -> &[(String, String)] {
+        self.placeholders.as_ref().map_or(&[], |p| &p.delimiters)
+    }
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct ExtendsSpec {
+    /// Repository of the base template.
+    pub url: String,
+    /// Branch, tag or full commit SHA; the default branch if unset.
+    #[serde(rename = "ref")]
+    pub git_ref: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct ConditionalSpec {
+    /// Globs relative to the template root, e.g. `["web-react"]`; matching directories go whole.
+    pub paths: Vec<String>,
+    /// Condition on the answers, in the `ask_if` syntax, e.g. `"frontend == 'react'"`.
+    pub when: String,
+    /// New name of the first path when the condition holds, e.g. `web`.
+    pub rename_to: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct PluginSpec {
+    /// Module inside the template, e.g. `.liscaf/plugins/headers.wasm` (`.wat` text also works).
+    pub path: String,
+    /// Files passed to the module's `transform` export, e.g. `["src/**/*.rs"]`; empty means all.
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct NameConfig {
+    /// Rules the project name must satisfy: `cargo`, `npm`, `dns-label`.
+    pub rules: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct MappingConfig {
+    /// Variant forms of the template name that are not replaced, e.g. `["concatlower"]`.
+    pub skip_variants: Vec<String>,
+    /// Directory names not walked during replacement, in addition to the defaults.
+    pub skip_dirs: Vec<String>,
+    /// Words kept in one token and spelled as given in camel and Pascal forms, e.g. `["HTTP", "OAuth2"]`.
+    pub acronyms: Vec<String>,
+    /// Placeholder email replaced by `author_email` (default `dev@acme.example`).
+    pub email: Option<String>,
+    /// Placeholder URL replaced by `repo_homepage` (default `https://acme.example`).
+    pub homepage: Option<String>,
+    /// Custom mappings applied with the generated ones, as for `--map`, e.g.
+    /// `["Acme Corp=>Example Inc", "regex:acme[-_]?app(\\d+)=>billing${1}"]`.
+    pub map: Vec<String>,
+    /// Replace inside zip-container documents (`.docx`, `.xlsx`, ...) as well.
+    pub documents: bool,
+    /// In JSON, YAML and TOML files replace only in string values, not in keys or anchors.
+    pub structured: bool,
+    /// With `structured`, replace in keys and YAML anchors as well.
+    pub structured_keys: bool,
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct FragmentSpec {
+    pub description: Option<String>,
+    /// Directory with the fragment files; defaults to `.liscaf/fragments/<fragment>`.
+    pub source: Option<String>,
+    /// Target directory inside the project, rendered with the answers, e.g. `src/api/{{ name_snake }}`.
+    pub destination: Option<String>,
+    pub variables: BTreeMap<String, VariableSpec>,
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct CopyrightConfig {
+    /// Leave copyright notices as the template has them.
+    pub keep: bool,
+    /// Holders whose notices are refreshed, e.g. `["Acme Corp"]`; empty means every notice.
+    pub holders: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct NpmConfig {
+    /// Remove `"private": true` from package.json files.
+    pub strip_private: bool,
+    /// Scripts to remove; scripts named `template:*` are always removed.
+    pub template_scripts: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct VariableSpec {
+    /// Prompt text; defaults to the variable name.
+    pub prompt: Option<String>,
+    /// Default value; may contain placeholders such as `{{ name_snake }}`.
+    pub default: Option<String>,
+    /// Help text shown below the prompt.
+    pub help: Option<String>,
+    /// Prompt position; variables without one follow in name order.
+    pub order: Option<i32>,
+    /// Section header, e.g. `"Database settings"`; variables of a group are asked together.
+    pub group: Option<String>,
+    /// Only ask when this condition on earlier answers holds, e.g. `"database == 'postgres'"`.
+    pub ask_if: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct PlaceholderConfig {
+    /// Open/close marker pairs, e.g. `[["__", "__"], ["%", "%"]]`. Defaults to `{{ }}`.
+    pub delimiters: Vec<(String, String)>,
+}
+
+pub fn load(root: &Path) -> anyhow::Result<Manifest> {
+    let path = root.join(MANIFEST_FILE);
+    if !path.exists() {
+        return Ok(Manifest::default());
+    }
+    let content = fs::read_to_string(&path)?;
+    let manifest: Manifest = toml::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))?;
+    for (open, close) in manifest.delimiters() {
+        if open.is_empty() || close.is_empty() {
+            anyhow::bail!("Placeholder delimiters in {} must not be empty", path.display());
+        }
+    }
+    for rule in &manifest.name.rules {
+        if !crate::names::RULES.contains(&rule.as_str()) {
+            anyhow::bail!(
+                "Unknown name rule '{}' in {} (expected one of: {})",
+                rule,
+                path.display(),
+                crate::names::RULES.join(", ")
+            );
+        }
+    }
+    for spec in &manifest.requires {
+        crate::tools::Requirement::parse(spec)
+            .map_err(|e| anyhow::anyhow!("{} in {}", e, path.display()))?;
+    }
+    for raw in &manifest.mappings.map {
+        crate::mappings::parse_map(raw)
+            .map_err(|e| anyhow::anyhow!("Invalid [mappings] map entry in {}: {}", path.display(), e))?;
+    }
+    for plugin in &manifest.plugins {
+        if plugin.path.is_empty() {
+            anyhow::bail!("Plugin entries in {} need a path", path.display());
+        }
+    }
+    for (profile, answers) in &manifest.profiles {
+        for key in answers.keys() {
+            if !manifest.variables.contains_key(key) && !PROFILE_BUILTINS.contains(&key.as_str()) {
+                anyhow::bail!(
+                    "Profile '{}' in {} sets '{}', which is not a template variable",
+                    profile,
+                    path.display(),
+                    key
+                );
+            }
+        }
+    }
+    let fragment_variables = manifest.fragments.values().flat_map(|f| &f.variables);
+    for (key, spec) in manifest.variables.iter().chain(fragment_variables) {
+        if let Some(ref condition) = spec.ask_if {
+            crate::condition::check(condition).map_err(|e| {
+                anyhow::anyhow!("Invalid ask_if for variable '{}' in {}: {}", key, path.display(), e)
+            })?;
+        }
+    }
+    for (i, rule) in manifest.conditional.iter().enumerate() {
+        crate::conditional::check(rule).map_err(|e| {
+            anyhow::anyhow!("Invalid [[conditional]] entry {} in {}: {}", i + 1, path.display(), e)
+        })?;
+    }
+    println!("Loaded template manifest: {}", path.display());
+    Ok(manifest)
+}
@@ -0,0 +1,271 @@
+//! Interactive review of the generated variant mappings before replacement runs, and custom
+//! mappings (`--map old=>new`, `--map 'regex:acme[-_]?app(\d+)=>billing$1'`).
+//!
+//! Regex mappings travel with the others as `("regex:<pattern>", "<replacement>")`; where one
+//! matches it wins over the generated variants.
+//!
+//! Mappings files (`--mappings`, written by `--export-mappings`) list pairs explicitly:
+//!
+//! ```toml
+//! # false: use only these pairs instead of the generated ones
+//! generated = false
+//!
+//! [[mapping]]
+//! old = "acme-app"
+//! new = "payments"
+//! ```
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use aho_corasick::AhoCorasick;
+use inquire::{MultiSelect, Select, Text};
+use regex::bytes::RegexSet;
+use regex::Regex;
+
+use crate::i18n::tr;
+use crate::prompt::{Ask, AskRaw};
+
+const DONE: &str = "Done";
+pub const REGEX_PREFIX: &str = "regex:";
+
+thread_local! {
+    static COMPILED: RefCell<HashMap<String, Regex>> = RefCell::new(HashMap::new());
+}
+
+/// Parses `old=>new` or `regex:<pattern>=><replacement>` (`$1`, `${name}` refer to groups).
+pub fn parse_map(raw: &str) -> Result<(String, String), String> {
+    let (old, new) = raw
+        .split_once("=>")
+        .ok_or_else(|| format!("expected OLD=>NEW or regex:PATTERN=>REPLACEMENT, got '{}'", raw))?;
+    if old.is_empty() || old == REGEX_PREFIX {
+        return Err(format!("empty source in mapping '{}'", raw));
+    }
+    if let Some(pattern) = old.strip_prefix(REGEX_PREFIX) {
+        Regex::new(pattern).map_err(|e| format!("invalid regex in mapping '{}': {}", raw, e))?;
+    }
+    Ok((old.to_string(), new.to_string()))
+}
+
+/// The pattern of a regex mapping source.
+pub fn regex_source(old: &str) -> Option<&str> {
+    old.strip_prefix(REGEX_PREFIX)
+}
+
+fn regex(pattern: &str) -> Option<Regex> {
+    COMPILED.with(|compiled| {
+        let mut compiled = compiled.borrow_mut();
+        if let Some(regex) = compiled.get(pattern) {
+            return Some(regex.clone());
+        }
+        let regex = Regex::new(pattern).ok()?;
+        compiled.insert(pattern.to_string(), regex.clone());
+        Some(regex)
+    })
+}
+
+/// Non-empty matches of the regex mappings in `input` as (start, end, replacement), by start;
+/// for matches at the same position the earlier mapping comes first.
+pub fn regex_matches(input: &str, mappings: &[&(String, String)]) -> Vec<(usize, usize, String)> {
+    let mut matches = Vec::new();
+    for (old, new) in mappings {
+        let Some(regex) = regex_source(old).and_then(regex) else {
+            continue;
+        };
+        for captures in regex.captures_iter(input) {
+            let whole = captures.get(0).expect("group 0 always matches");
+            if whole.is_empty() {
+                continue;
+            }
+            let mut replacement = String::new();
+            captures.expand(new, &mut replacement);
+            matches.push((whole.start(), whole.end(), replacement));
+        }
+    }
+    matches.sort_by_key(|(start, _, _)| *start);
+    matches
+}
+
+/// All mapping sources in one automaton (plus a set for the regex mappings), so a file is scanned
+/// once to learn whether any mapping applies, before it is decoded and rewritten.
+pub struct Matcher {
+    literals: Option<AhoCorasick>,
+    patterns: Option<RegexSet>,
+}
+
+impl Matcher {
+    pub fn new(mappings: &[(String, String)]) -> Self {
+        let (patterns, literals): (Vec<&str>, Vec<&str>) = mappings
+            .iter()
+            .map(|(old, _)| old.as_str())
+            .filter(|old| !old.is_empty())
+            .partition(|old| regex_source(old).is_some());
+        let patterns: Vec<&str> = patterns.into_iter().filter_map(regex_source).collect();
+        Matcher {
+            literals: (!literals.is_empty())
+                .then(|| AhoCorasick::new(&literals).ok())
+                .flatten(),
+            patterns: (!patterns.is_empty())
+                .then(|| RegexSet::new(&patterns).ok())
+                .flatten(),
+        }
+    }
+
+    pub fn is_match(&self, content: &[u8]) -> bool {
+        self.literals.as_ref().is_some_and(|ac| ac.is_match(content))
+            || self.patterns.as_ref().is_some_and(|set| set.is_match(content))
+    }
+}
+
+/// Whether any mapping applies to `content`.
+pub fn occur_in(content: &str, mappings: &[(String, String)]) -> bool {
+    mappings.iter().any(|(old, _)| match regex_source(old) {
+        Some(pattern) => regex(pattern).is_some_and(|r| r.is_match(content)),
+        None => content.contains(old.as_str()),
+    })
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MappingsFile {
+    /// Whether the pairs add to the generated mappings (`true`) or replace them.
+    pub generated: bool,
+    #[serde(rename = "mapping")]
+    pub mappings: Vec<Pair>,
+}
+
+impl Default for MappingsFile {
+    fn default() -> Self {
+        MappingsFile {
+            generated: true,
+            mappings: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pair {
+    pub old: String,
+    pub new: String,
+}
+
+pub fn load_file(path: &Path) -> anyhow::Result<MappingsFile> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read mappings file {}: {}", path.display(), e))?;
+    let file: MappingsFile = toml::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse mappings file {}: {}", path.display(), e))?;
+    for pair in &file.mappings {
+        parse_map(&format!("{}=>{}", pair.old, pair.new))
+            .map_err(|e| anyhow::anyhow!("Invalid mapping in {}: {}", path.display(), e))?;
+    }
+    Ok(file)
+}
+
+/// The mappings of a run: the generated ones plus the file's, or only the file's.
+pub fn combine(generated: Vec<(String, String)>, file: Option<&MappingsFile>) -> Vec<(String, String)> {
+    let Some(file) = file else {
+        return generated;
+    };
+    let mut mappings = if file.generated { generated } else { Vec::new() };
+    mappings.extend(file.mappings.iter().map(|p| (p.old.clone(), p.new.clone())));
+    mappings
+}
+
+/// Writes `mappings` as a mappings file that replaces the generated set when read back.
+pub fn export(path: &Path, mappings: &[(String, String)]) -> anyhow::Result<()> {
+    let file = MappingsFile {
+        generated: false,
+        mappings: mappings
+            .iter()
+            .map(|(old, new)| Pair {
+                old: old.clone(),
+                new: new.clone(),
+            })
+            .collect(),
+    };
+    let content = format!(
+        "# liscaf mappings; review, then pass with --mappings {}\n\
+         # generated = true adds these pairs to the generated variants instead of replacing them\n{}",
+        path.display(),
+        toml::to_string(&file)?
+    );
+    fs::write(path, content)
+        .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?;
+    println!("Wrote {} mappings to {}", mappings.len(), path.display());
+    Ok(())
+}
+
+/// Lets the user drop mappings (e.g. a risky concatenated-lowercase pair) and edit replacements.
+pub fn edit(mappings: Vec<(String, String)>) -> anyhow::Result<Vec<(String, String)>> {
+    let labels: Vec<String> = mappings.iter().map(|(o, n)| format!("{} -> {}", o, n)).collect();
+    let kept = MultiSelect::new("Mappings to apply:", labels)
+        .with_all_selected_by_default()
+        .with_help_message("space to toggle, enter to confirm")
+        .ask_raw()?;
+    let mut mappings: Vec<(String, String)> = kept
+        .into_iter()
+        .map(|option| mappings[option.index].clone())
+        .collect();
+
+    loop {
+        let mut choices: Vec<String> = mappings.iter().map(|(o, n)| format!("{} -> {}", o, n)).collect();
+        choices.push(crate::i18n::text(DONE).to_string());
+        let choice = Select::new("Adjust a replacement:", choices).ask_raw()?;
+        if choice.index == mappings.len() {
+            break;
+        }
+        let (original, replacement) = &mut mappings[choice.index];
+        let edited = Text::new(&tr!("Replace '{}' with:", original))
+            .with_initial_value(replacement)
+            .ask()?;
+        if edited.is_empty() {
+            println!("WARN: Empty replacement ignored for '{}'", original);
+        } else {
+            *replacement = edited;
+        }
+    }
+
+    println!("Using {} variant mappings", mappings.len());
+    for (o, n) in &mappings {
+        println!("  {} -> {}", o, n);
+    }
+    Ok(mappings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exported_mappings_read_back_as_the_only_pairs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mappings.toml");
+        let pairs = vec![
+            ("acme-app".to_string(), "payments".to_string()),
+            ("regex:acme[-_]?app(\\d+)".to_string(), "billing${1}".to_string()),
+        ];
+        export(&path, &pairs).unwrap();
+
+        let file = load_file(&path).unwrap();
+        assert!(!file.generated);
+        let generated = vec![("AcmeApp".to_string(), "Payments".to_string())];
+        assert_eq!(combine(generated.clone(), Some(&file)), pairs);
+        assert_eq!(combine(generated.clone(), None), generated);
+
+        fs::write(&path, "[[mapping]]\nold = \"acme\"\nnew = \"payments\"\n").unwrap();
+        let file = load_file(&path).unwrap();
+        assert!(file.generated);
+        assert_eq!(
+            combine(generated, Some(&file)),
+            vec![
+                ("AcmeApp".to_string(), "Payments".to_string()),
+                ("acme".to_string(), "payments".to_string()),
+            ]
+        );
+
+        fs::write(&path, "[[mapping]]\nold = \"regex:acme(\"\nnew = \"x\"\n").unwrap();
+        assert!(load_file(&path).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+//! Hashes of the files an `--into` merge left in a destination, kept in the cache directory
+//! between runs. A destination file whose size and modification time still match its entry is
+//! not read again: the recorded hash stands in for its content, so merging an unchanged template
+//! into a large project only has to hash the (freshly generated) incoming files.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct Entry {
+    size: u64,
+    modified_ns: u64,
+    sha256: String,
+}
+
+#[derive(Debug, Default)]
+pub struct MergeHashes {
+    /// Where the entries are saved; `None` keeps them in memory only.
+    file: Option<PathBuf>,
+    entries: BTreeMap<String, Entry>,
+    changed: bool,
+}
+
+impl MergeHashes {
+    /// The hashes recorded in `dir` (the cache directory) for `dest`; empty if there are none or
+    /// they cannot be read.
+    pub fn load(dir: &Path, dest: &Path) -> Self {
+        let dest = dest.canonicalize().unwrap_or_else(|_| dest.to_path_buf());
+        let file = dir
+            .join("merges")
+            .join(format!("{}.json", &hex_sha256(dest.to_string_lossy().as_bytes())[..32]));
+        let entries = fs::read_to_string(&file)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        MergeHashes {
+            file: Some(file),
+            entries,
+            changed: false,
+        }
+    }
+
+    /// The recorded hash of `rel` if the file still has the recorded size and modification time.
+    pub fn get(&self, rel: &str, stat: (u64, SystemTime)) -> Option<&str> {
+        let entry = self.entries.get(rel)?;
+        (entry.size == stat.0 && Some(entry.modified_ns) == nanos(stat.1)).then_some(entry.sha256.as_str())
+    }
+
+    pub fn record(&mut self, rel: &str, stat: (u64, SystemTime), sha256: String) {
+        let Some(modified_ns) = nanos(stat.1) else {
+            return;
+        };
+        let entry = Entry {
+            size: stat.0,
+            modified_ns,
+            sha256,
+        };
+        if self.entries.get(rel) != Some(&entry) {
+            self.entries.insert(rel.to_string(), entry);
+            self.changed = true;
+        }
+    }
+
+    /// Writes the entries back if any changed; failing to do so only costs speed next time.
+    pub fn save(&self) {
+        let Some(file) = self.file.as_ref().filter(|_| self.changed) else {
+            return;
+        };
+        let written = file
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(file, serde_json::to_string(&self.entries).unwrap_or_default()));
+        if let Err(e) = written {
+            println!("Warning: could not save merge hashes to {}: {}", file.display(), e);
+        }
+    }
+}
+
+pub fn hex_sha256(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn nanos(time: SystemTime) -> Option<u64> {
+    time.duration_since(UNIX_EPOCH).ok()?.as_nanos().try_into().ok()
+}
@@ -0,0 +1,359 @@
+//! Package-name checks before scaffolding: is the new project's name a valid crate / npm
+//! package name, and is it still free on crates.io / npm? Only ecosystems the template
+//! publishes to are checked (a `[package]` in Cargo.toml, a non-private package.json).
+//!
+//! Templates can also declare naming rules (`[name] rules = ["cargo", "dns-label"]`); names
+//! that break them are refused with a sanitized suggestion.
+//!
+//! Availability lookups are advisory: network errors only print a warning. `LISCAF_CRATES_IO_API`
+//! and `NPM_CONFIG_REGISTRY` point the lookups at mirrors.
+use std::fs;
+use std::path::Path;
+
+use inquire::{Confirm, Select, Text};
+
+use crate::manifest::Manifest;
+use crate::i18n::tr;
+use crate::net;
+use crate::prompt::Ask;
+
+const DEFAULT_CRATES_IO_API: &str = "https://crates.io/api/v1";
+const DEFAULT_NPM_REGISTRY: &str = "https://registry.npmjs.org";
+/// crates.io asks API clients to identify themselves.
+const USER_AGENT: &str = "liscaf (https://github.com/yoktobit/liscaf)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ecosystem {
+    Cargo,
+    Npm,
+}
+
+impl Ecosystem {
+    fn registry(self) -> &'static str {
+        match self {
+            Ecosystem::Cargo => "crates.io",
+            Ecosystem::Npm => "npm",
+        }
+    }
+}
+
+/// Rust keywords and names cargo refuses for new packages.
+const RESERVED_CRATE_NAMES: &[&str] = &[
+    "abstract", "alloc", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "core", "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if",
+    "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv",
+    "proc_macro", "pub", "ref", "return", "self", "static", "std", "struct", "super", "test",
+    "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual", "where",
+    "while", "yield",
+];
+
+/// Node.js built-in modules, which npm does not accept as package names.
+const NODE_BUILTINS: &[&str] = &[
+    "assert", "buffer", "child_process", "cluster", "crypto", "dgram", "dns", "events", "fs",
+    "http", "http2", "https", "net", "os", "path", "process", "querystring", "readline", "stream",
+    "string_decoder", "timers", "tls", "tty", "url", "util", "v8", "vm", "worker_threads", "zlib",
+];
+
+/// Why `name` cannot be published as a crate, if it cannot.
+pub fn crate_name_problem(name: &str) -> Option<String> {
+    if name.is_empty() {
+        return Some("it is empty".to_string());
+    }
+    if name.len() > 64 {
+        return Some("crates.io allows at most 64 characters".to_string());
+    }
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Some("it must start with an ASCII letter".to_string());
+    }
+    if let Some(c) = name.chars().find(|c| !c.is_ascii_alphanumeric() && *c != '-' && *c != '_') {
+        return Some(format!("'{}' is not allowed (only ASCII letters, digits, - and _)", c));
+    }
+    if RESERVED_CRATE_NAMES.contains(&name.replace('-', "_").as_str()) {
+        return Some("it is a Rust keyword or reserved name".to_string());
+    }
+    None
+}
+
+/// Why `name` cannot be published to npm, if it cannot.
+pub fn npm_name_problem(name: &str) -> Option<String> {
+    if name.is_empty() {
+        return Some("it is empty".to_string());
+    }
+    if name.len() > 214 {
+        return Some("npm allows at most 214 characters".to_string());
+    }
+    let bare = match name.strip_prefix('@') {
+        Some(scoped) => match scoped.split_once('/') {
+            Some((scope, bare)) if !scope.is_empty() => bare,
+            _ => return Some("a scoped name must look like @scope/name".to_string()),
+        },
+        None => name,
+    };
+    if name != name.to_lowercase() {
+        return Some("it must be lowercase".to_string());
+    }
+    if bare.starts_with('.') || bare.starts_with('_') {
+        return Some("it must not start with . or _".to_string());
+    }
+    if let Some(c) = bare
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && !matches!(c, '-' | '.' | '_' | '~'))
+    {
+        return Some(format!("'{}' is not URL-safe", c));
+    }
+    if NODE_BUILTINS.contains(&bare) || matches!(bare, "node_modules" | "favicon.ico") {
+        return Some("it is a Node.js built-in or reserved name".to_string());
+    }
+    None
+}
+
+/// Naming rules a template can require in `[name] rules`.
+pub const RULES: &[&str] = &["cargo", "npm", "dns-label"];
+
+/// Why `name` is not an RFC 1123 DNS label (Kubernetes resource names, hostnames), if it is not.
+pub fn dns_label_problem(name: &str) -> Option<String> {
+    if name.is_empty() {
+        return Some("it is empty".to_string());
+    }
+    if name.len() > 63 {
+        return Some("DNS labels allow at most 63 characters".to_string());
+    }
+    if let Some(c) = name.chars().find(|c| !c.is_ascii_lowercase() && !c.is_ascii_digit() && *c != '-') {
+        return Some(format!("'{}' is not allowed (only a-z, 0-9 and -)", c));
+    }
+    if name.starts_with('-') || name.ends_with('-') {
+        return Some("it must start and end with a letter or digit".to_string());
+    }
+    None
+}
+
+fn rule_problem(rule: &str, name: &str) -> Option<String> {
+    match rule {
+        "cargo" => crate_name_problem(name),
+        "npm" => npm_name_problem(name),
+        _ => dns_label_problem(name),
+    }
+}
+
+fn rule_limit(rule: &str) -> usize {
+    match rule {
+        "cargo" => 64,
+        "npm" => 214,
+        _ => 63,
+    }
+}
+
+/// A kebab-case name close to `name` that satisfies all `rules`: ASCII only, starting with a
+/// letter (leading number parts move to the end), reserved names get an `-app` suffix, and long
+/// names are cut at a word boundary.
+pub fn sanitize(name: &str, rules: &[String]) -> String {
+    let mut words: Vec<String> = crate::split_name_to_tokens(name, &Default::default())
+        .into_iter()
+        .map(|w| {
+            w.chars()
+                .map(fold_accent)
+                .collect::<String>()
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric())
+                .collect::<String>()
+        })
+        .filter(|w| !w.is_empty())
+        .collect();
+    if words.is_empty() {
+        words.push("project".to_string());
+    }
+    if let Some(first_alpha) = words.iter().position(|w| w.starts_with(|c: char| c.is_ascii_alphabetic())) {
+        words.rotate_left(first_alpha);
+    } else {
+        words.insert(0, "project".to_string());
+    }
+    let limit = rules.iter().map(|r| rule_limit(r)).min().unwrap_or(64);
+    let mut candidate = words.join("-");
+    while candidate.len() > limit && words.len() > 1 {
+        words.pop();
+        candidate = words.join("-");
+    }
+    candidate.truncate(limit);
+    let candidate = candidate.trim_end_matches('-').to_string();
+    if rules.iter().any(|r| rule_problem(r, &candidate).is_some()) {
+        let mut suffixed = candidate;
+        suffixed.truncate(limit - 4);
+        return format!("{}-app", suffixed.trim_end_matches('-'));
+    }
+    candidate
+}
+
+/// Common Latin letters with diacritics to their ASCII base (`é` -> `e`, `ß` -> `ss`).
+fn fold_accent(c: char) -> String {
+    let folded = match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => "a",
+        'æ' => "ae",
+        'ç' => "c",
+        'è' | 'é' | 'ê' | 'ë' => "e",
+        'ì' | 'í' | 'î' | 'ï' => "i",
+        'ñ' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => "o",
+        'œ' => "oe",
+        'ß' => "ss",
+        'ù' | 'ú' | 'û' | 'ü' => "u",
+        'ý' | 'ÿ' => "y",
+        _ => return c.to_string(),
+    };
+    folded.to_string()
+}
+
+/// Checks the new name against the template's naming rules and, if it breaks one, offers a
+/// sanitized name instead. Returns the name to use, or `None` if the user aborted.
+pub fn validate(rules: &[String], new_name: &str, assume_yes: bool) -> anyhow::Result<Option<String>> {
+    let mut name = new_name.to_string();
+    loop {
+        let package_name = crate::split_name_to_tokens(&name, &Default::default()).join("-");
+        let problems: Vec<String> = rules
+            .iter()
+            .filter_map(|rule| rule_problem(rule, &package_name).map(|p| format!("{}: {}", rule, p)))
+            .collect();
+        if problems.is_empty() {
+            return Ok(Some(name));
+        }
+        let suggestion = sanitize(&name, rules);
+        let message = format!(
+            "'{}' breaks the template's naming rules ({})",
+            package_name,
+            problems.join("; ")
+        );
+        if assume_yes {
+            anyhow::bail!("{}; use '{}' instead", message, suggestion);
+        }
+        println!("Warning: {}", message);
+        let use_suggestion = tr!("Use '{}'", suggestion);
+        let enter = tr!("Enter a different name").to_string();
+        let keep = tr!("Keep '{}' anyway", name);
+        let choice = Select::new("Project name:", vec![use_suggestion.clone(), enter.clone(), keep.clone()])
+            .ask()?;
+        if choice == use_suggestion {
+            return Ok(Some(suggestion));
+        }
+        if choice == keep {
+            return Ok(Some(name));
+        }
+        name = Text::new("Enter new project name:")
+            .with_initial_value(&suggestion)
+            .ask()?;
+        if name.trim().is_empty() {
+            return Ok(None);
+        }
+    }
+}
+
+/// Ecosystems the template publishes packages to.
+pub fn published_ecosystems(root: &Path, manifest: &Manifest) -> Vec<Ecosystem> {
+    let mut ecosystems = Vec::new();
+    if let Some(doc) = fs::read_to_string(root.join("Cargo.toml"))
+        .ok()
+        .and_then(|c| toml::from_str::<toml::Table>(&c).ok())
+    {
+        let package = doc.get("package").and_then(|p| p.as_table());
+        let publish = package.and_then(|p| p.get("publish")).and_then(|p| p.as_bool());
+        if package.is_some() && publish != Some(false) {
+            ecosystems.push(Ecosystem::Cargo);
+        }
+    }
+    if let Some(package) = fs::read_to_string(root.join("package.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+    {
+        let private = package.get("private").and_then(|p| p.as_bool()) == Some(true);
+        let scoped = package
+            .get("name")
+            .and_then(|n| n.as_str())
+            .is_some_and(|n| n.starts_with('@'));
+        // Scopes belong to their owner, so only unscoped names can collide
+        if (!private || manifest.npm.strip_private) && !scoped {
+            ecosystems.push(Ecosystem::Npm);
+        }
+    }
+    ecosystems
+}
+
+/// Whether `name` is already registered; an error if the registry could not be asked.
+fn is_taken(ecosystem: Ecosystem, name: &str) -> anyhow::Result<bool> {
+    let env_or = |key: &str, default: &str| {
+        std::env::var(key)
+            .ok()
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| default.to_string())
+            .trim_end_matches('/')
+            .to_string()
+    };
+    let url = match ecosystem {
+        Ecosystem::Cargo => {
+            format!("{}/crates/{}", env_or("LISCAF_CRATES_IO_API", DEFAULT_CRATES_IO_API), name)
+        }
+        Ecosystem::Npm => format!(
+            "{}/{}",
+            env_or("NPM_CONFIG_REGISTRY", DEFAULT_NPM_REGISTRY),
+            name.replace('/', "%2F")
+        ),
+    };
+    let response = net::agent()
+        .get(&url)
+        .header("User-Agent", USER_AGENT)
+        .config()
+        .http_status_as_error(false)
+        .build()
+        .call()?;
+    match response.status().as_u16() {
+        404 => Ok(false),
+        200..=299 => Ok(true),
+        status => anyhow::bail!("{} returned status {}", url, status),
+    }
+}
+
+/// Warns about a package name that is invalid or already taken. Returns false if the user
+/// chose not to continue.
+pub fn check(root: &Path, manifest: &Manifest, new_name: &str, assume_yes: bool) -> anyhow::Result<bool> {
+    let package_name = crate::split_name_to_tokens(new_name, &Default::default()).join("-");
+    let mut problems = Vec::new();
+    for ecosystem in published_ecosystems(root, manifest) {
+        let invalid = match ecosystem {
+            Ecosystem::Cargo => crate_name_problem(&package_name),
+            Ecosystem::Npm => npm_name_problem(&package_name),
+        };
+        if let Some(reason) = invalid {
+            problems.push(format!(
+                "'{}' is not a valid {} package name: {}",
+                package_name,
+                ecosystem.registry(),
+                reason
+            ));
+            continue;
+        }
+        match is_taken(ecosystem, &package_name) {
+            Ok(true) => problems.push(format!(
+                "'{}' is already taken on {}; the project cannot be published under this name",
+                package_name,
+                ecosystem.registry()
+            )),
+            Ok(false) => println!("Name '{}' is available on {}", package_name, ecosystem.registry()),
+            Err(e) => println!(
+                "Warning: could not check '{}' on {}: {}",
+                package_name,
+                ecosystem.registry(),
+                e
+            ),
+        }
+    }
+    if problems.is_empty() {
+        return Ok(true);
+    }
+    for problem in &problems {
+        println!("Warning: {}", problem);
+    }
+    if assume_yes {
+        return Ok(true);
+    }
+    Confirm::new("Scaffold with this name anyway?")
+        .with_default(false)
+        .with_help_message("--no-name-check skips this check")
+        .ask()
+}
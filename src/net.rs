@@ -0,0 +1,348 @@
+//! Network settings shared by registry fetches (ureq) and git operations.
+//!
+//! Proxies come from `HTTPS_PROXY`/`HTTP_PROXY`/`ALL_PROXY` and `NO_PROXY`, or from `--proxy`,
+//! which is also handed to the git commands liscaf runs (see [`git`]).
+//!
+//! An extra CA bundle (`LISCAF_CA_BUNDLE` or `network.ca_bundle` in the config) is trusted in
+//! addition to the built-in roots, and handed to git as `GIT_SSL_CAINFO`.
+//!
+//! Fetches and clones time out instead of hanging, and transient failures are retried with
+//! exponential backoff.
+//!
+//! The CLI configures the settings once at startup; a scaffold run with its own settings (library
+//! callers) uses them on its thread instead. The process environment is never changed.
+//!
+//! Registries behind authentication get a bearer token or basic auth header (see [`Auth`]).
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use ureq::tls::{Certificate, PemItem, RootCerts, TlsConfig};
+
+use crate::config::NetworkConfig;
+
+pub struct Settings {
+    proxy: Option<String>,
+    ca_bundle: Option<PathBuf>,
+    extra_roots: Vec<Certificate<'static>>,
+    connect_timeout: Duration,
+    read_timeout: Duration,
+    retries: u32,
+}
+
+const DEFAULT_CONNECT_TIMEOUT: u64 = 15;
+const DEFAULT_READ_TIMEOUT: u64 = 60;
+const DEFAULT_RETRIES: u32 = 2;
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            proxy: None,
+            ca_bundle: None,
+            extra_roots: Vec::new(),
+            connect_timeout: Duration::from_secs(DEFAULT_CONNECT_TIMEOUT),
+            read_timeout: Duration::from_secs(DEFAULT_READ_TIMEOUT),
+            retries: DEFAULT_RETRIES,
+        }
+    }
+}
+
+// Certificates are summarized; their DER bytes are of no use in debug output.
+impl std::fmt::Debug for Settings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Settings")
+            .field("proxy", &self.proxy)
+            .field("ca_bundle", &self.ca_bundle)
+            .field("extra_roots", &self.extra_roots.len())
+            .field("connect_timeout", &self.connect_timeout)
+            .field("read_timeout", &self.read_timeout)
+            .field("retries", &self.retries)
+            .finish()
+    }
+}
+
+impl Settings {
+    /// Validates `proxy` and reads the CA bundle (`LISCAF_CA_BUNDLE` before `network.ca_bundle`).
+    pub fn new(proxy: Option<&str>, network: &NetworkConfig) -> anyhow::Result<Self> {
+        let proxy = proxy.map(str::trim).filter(|p| !p.is_empty());
+        if let Some(proxy) = proxy {
+            ureq::Proxy::new(proxy).map_err(|e| anyhow::anyhow!("Invalid proxy '{}': {}", proxy, e))?;
+        }
+        let ca_bundle = std::env::var_os("LISCAF_CA_BUNDLE")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| network.ca_bundle.clone());
+        let extra_roots = match &ca_bundle {
+            Some(path) => load_pem_certificates(path)?,
+            None => Vec::new(),
+        };
+        Ok(Settings {
+            proxy: proxy.map(str::to_string),
+            ca_bundle,
+            extra_roots,
+            connect_timeout: Duration::from_secs(network.connect_timeout.unwrap_or(DEFAULT_CONNECT_TIMEOUT)),
+            read_timeout: Duration::from_secs(network.read_timeout.unwrap_or(DEFAULT_READ_TIMEOUT)),
+            retries: network.retries.unwrap_or(DEFAULT_RETRIES),
+        })
+    }
+}
+
+static DEFAULT: OnceLock<Arc<Settings>> = OnceLock::new();
+
+thread_local! {
+    static SCOPED: RefCell<Option<Arc<Settings>>> = const { RefCell::new(None) };
+}
+
+fn settings() -> Arc<Settings> {
+    SCOPED
+        .with(|s| s.borrow().clone())
+        .unwrap_or_else(|| Arc::clone(DEFAULT.get_or_init(Default::default)))
+}
+
+/// Runs `f` with `settings` in effect for the network access on this thread.
+pub fn with_settings<T>(settings: &Arc<Settings>, f: impl FnOnce() -> T) -> T {
+    let previous = SCOPED.with(|s| s.borrow_mut().replace(Arc::clone(settings)));
+    let result = f();
+    SCOPED.with(|s| *s.borrow_mut() = previous);
+    result
+}
+
+const PROXY_VARS: &[&str] = &["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"];
+
+/// Applies `--proxy` and the CA bundle; call once at startup before any network access.
+pub fn configure(proxy: Option<&str>, network: &NetworkConfig) -> anyhow::Result<()> {
+    let _ = DEFAULT.set(Arc::new(Settings::new(proxy, network)?));
+    Ok(())
+}
+
+/// `git` with the proxy, CA bundle and transfer timeout in effect set in its environment.
+pub fn git() -> Command {
+    let settings = settings();
+    let mut command = Command::new("git");
+    if let Some(proxy) = &settings.proxy {
+        for var in PROXY_VARS {
+            command.env(var, proxy);
+        }
+    }
+    if let Some(path) = &settings.ca_bundle {
+        if std::env::var_os("GIT_SSL_CAINFO").is_none() {
+            command.env("GIT_SSL_CAINFO", path);
+        }
+    }
+    // git has no read timeout; abort transfers slower than 1 KB/s for that long instead
+    if std::env::var_os("GIT_HTTP_LOW_SPEED_LIMIT").is_none() {
+        command
+            .env("GIT_HTTP_LOW_SPEED_LIMIT", "1000")
+            .env("GIT_HTTP_LOW_SPEED_TIME", settings.read_timeout.as_secs().to_string());
+    }
+    command
+}
+
+fn load_pem_certificates(path: &Path) -> anyhow::Result<Vec<Certificate<'static>>> {
+    let pem = std::fs::read(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read CA bundle {}: {}", path.display(), e))?;
+    let mut certs = Vec::new();
+    for item in ureq::tls::parse_pem(&pem) {
+        match item.map_err(|e| anyhow::anyhow!("Invalid CA bundle {}: {}", path.display(), e))? {
+            PemItem::Certificate(cert) => certs.push(cert),
+            _ => continue,
+        }
+    }
+    if certs.is_empty() {
+        anyhow::bail!("No certificates found in CA bundle {}", path.display());
+    }
+    Ok(certs)
+}
+
+/// The configured CA bundle and the number of certificates it contains.
+pub fn ca_bundle() -> Option<(PathBuf, usize)> {
+    let settings = settings();
+    let path = settings.ca_bundle.clone()?;
+    Some((path, settings.extra_roots.len()))
+}
+
+/// The proxy in effect, if any (for diagnostics and error hints).
+pub fn proxy_in_use() -> Option<String> {
+    if let Some(proxy) = &settings().proxy {
+        return Some(proxy.clone());
+    }
+    ["ALL_PROXY", "all_proxy"]
+        .iter()
+        .chain(PROXY_VARS)
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+}
+
+pub fn agent() -> ureq::Agent {
+    let settings = settings();
+    let mut config = ureq::Agent::config_builder()
+        .proxy(match &settings.proxy {
+            Some(proxy) => ureq::Proxy::new(proxy).ok(),
+            None => ureq::Proxy::try_from_env(),
+        })
+        .timeout_connect(Some(settings.connect_timeout))
+        .timeout_recv_response(Some(settings.read_timeout))
+        .timeout_recv_body(Some(settings.read_timeout));
+    if !settings.extra_roots.is_empty() {
+        let roots = webpki_root_certs::TLS_SERVER_ROOT_CERTS
+            .iter()
+            .map(|der| Certificate::from_der(der.as_ref()))
+            .chain(settings.extra_roots.iter().cloned());
+        config = config.tls_config(TlsConfig::builder().root_certs(RootCerts::from(roots)).build());
+    }
+    config.build().into()
+}
+
+/// Credentials for an authenticated registry host.
+#[derive(Clone)]
+pub enum Auth {
+    Bearer(String),
+    Basic { username: String, password: String },
+}
+
+impl Auth {
+    fn header(&self) -> String {
+        use base64::Engine;
+        match self {
+            Auth::Bearer(token) => format!("Bearer {}", token),
+            Auth::Basic { username, password } => format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password))
+            ),
+        }
+    }
+}
+
+// Never print the secret itself.
+impl std::fmt::Debug for Auth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Auth::Bearer(_) => f.write_str("Bearer(***)"),
+            Auth::Basic { username, .. } => write!(f, "Basic({}:***)", username),
+        }
+    }
+}
+
+/// GETs `url` as text, retrying transient failures. Errors name the proxy, since a missing or
+/// wrong one is the usual cause.
+pub fn get_text(url: &str, auth: Option<&Auth>) -> anyhow::Result<String> {
+    let agent = agent();
+    with_retries(&format!("Fetching {}", url), || {
+        let mut request = agent.get(url);
+        if let Some(auth) = auth {
+            request = request.header("Authorization", auth.header());
+        }
+        let response = request.call().map_err(|e| {
+            if let ureq::Error::StatusCode(code @ (401 | 403)) = e {
+                return Failure::Permanent(Unauthorized { url: url.to_string(), status: code }.into());
+            }
+            // Client errors (404, 401) will not go away by retrying
+            let transient = !matches!(e, ureq::Error::StatusCode(code) if code < 500);
+            let error = match proxy_in_use() {
+                Some(proxy) => {
+                    anyhow::anyhow!("HTTP error fetching {} (via proxy {}): {}", url, proxy, e)
+                }
+                None => anyhow::anyhow!("HTTP error fetching {}: {}", url, e),
+            };
+            if transient {
+                Failure::Transient(error)
+            } else {
+                Failure::Permanent(error)
+            }
+        })?;
+        response
+            .into_body()
+            .read_to_string()
+            .map_err(|e| Failure::Transient(anyhow::anyhow!("Failed to read {}: {}", url, e)))
+    })
+}
+
+/// A 401 or 403 response: the credentials are missing or were rejected.
+#[derive(Debug)]
+pub struct Unauthorized {
+    pub url: String,
+    pub status: u16,
+}
+
+impl std::fmt::Display for Unauthorized {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HTTP error fetching {}: authentication required (status {})", self.url, self.status)
+    }
+}
+
+impl std::error::Error for Unauthorized {}
+
+pub enum Failure {
+    /// Worth another attempt (timeouts, connection resets, 5xx).
+    Transient(anyhow::Error),
+    /// Retrying cannot help (not found, authentication).
+    Permanent(anyhow::Error),
+}
+
+/// Runs `op` up to `1 + retries` times, waiting 1s, 2s, 4s, ... between attempts.
+pub fn with_retries<T>(
+    what: &str,
+    mut op: impl FnMut() -> Result<T, Failure>,
+) -> anyhow::Result<T> {
+    let attempts = settings().retries + 1;
+    let mut delay = Duration::from_secs(1);
+    for attempt in 1..=attempts {
+        match op() {
+            Ok(value) => return Ok(value),
+            Err(Failure::Permanent(e)) => return Err(e),
+            Err(Failure::Transient(e)) if attempt == attempts => {
+                if attempts == 1 {
+                    return Err(e);
+                }
+                return Err(anyhow::anyhow!("{}: gave up after {} attempts: {}", what, attempts, e));
+            }
+            Err(Failure::Transient(e)) => {
+                println!(
+                    "Warning: {} failed (attempt {}/{}): {}; retrying in {}s",
+                    what,
+                    attempt,
+                    attempts,
+                    e,
+                    delay.as_secs()
+                );
+                std::thread::sleep(delay);
+                delay *= 2;
+            }
+        }
+    }
+    unreachable!("at least one attempt is made")
+}
+
+/// Whether git's stderr describes a failure that retrying cannot fix.
+pub fn is_permanent_git_error(stderr: &str) -> bool {
+    const PERMANENT: &[&str] = &[
+        "not found",
+        "does not exist",
+        "Authentication failed",
+        "could not read Username",
+        "Permission denied",
+        "does not appear to be a git repository",
+        "Remote branch",
+    ];
+    PERMANENT.iter().any(|p| stderr.contains(p))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn scoped_settings_reach_git_without_touching_the_environment() {
+        let proxy = "http://proxy.example:3128";
+        let settings = Arc::new(Settings::new(Some(proxy), &NetworkConfig::default()).unwrap());
+        let command = with_settings(&settings, git);
+        assert!(command
+            .get_envs()
+            .any(|(var, value)| var == "HTTPS_PROXY" && value == Some(OsStr::new(proxy))));
+        assert_eq!(with_settings(&settings, proxy_in_use).as_deref(), Some(proxy));
+        assert_ne!(std::env::var("HTTPS_PROXY").ok().as_deref(), Some(proxy));
+        assert!(!git().get_envs().any(|(_, value)| value == Some(OsStr::new(proxy))));
+    }
+}
@@ -0,0 +1,148 @@
+//! Modes and ownership of what liscaf writes. Generated files get `0666` and directories `0777`
+//! minus the process umask, like files created by any other tool; files executable in the
+//! template stay executable. Template checkouts and temporary directories otherwise leak their
+//! own modes (`0700` temp dirs, whatever the cache was created with) into projects.
+//!
+//! With `--preserve-owner`, a scaffold run as root into a directory owned by someone else hands
+//! the files it created to that owner, instead of leaving root-owned files in a user's home.
+use std::io;
+use std::path::Path;
+
+use walkdir::WalkDir;
+
+/// The process umask (`0o022` where there is none).
+#[cfg(unix)]
+pub fn umask() -> u32 {
+    use std::sync::OnceLock;
+    static UMASK: OnceLock<u32> = OnceLock::new();
+    // SAFETY: umask cannot fail; it is set back to the old value right away.
+    *UMASK.get_or_init(|| unsafe {
+        let mask = libc::umask(0o022);
+        libc::umask(mask);
+        mask as u32
+    })
+}
+
+#[cfg(not(unix))]
+pub fn umask() -> u32 {
+    0o022
+}
+
+/// The mode a generated file or directory gets.
+pub fn mode_for(is_dir: bool, executable: bool) -> u32 {
+    let base = if is_dir || executable { 0o777 } else { 0o666 };
+    base & !umask()
+}
+
+/// Gives everything below `root` (outside `.git`) the umask-derived modes.
+#[cfg(unix)]
+pub fn normalize(root: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let walker = WalkDir::new(root).into_iter().filter_entry(|e| e.file_name() != ".git");
+    for entry in walker.filter_map(|e| e.ok()) {
+        let file_type = entry.file_type();
+        if file_type.is_symlink() {
+            continue;
+        }
+        let current = entry.metadata()?.permissions().mode();
+        let mode = mode_for(file_type.is_dir(), current & 0o111 != 0);
+        if current & 0o777 != mode {
+            std::fs::set_permissions(entry.path(), std::fs::Permissions::from_mode(mode))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn normalize(_root: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Sets the mode of the new file `to` for a copy of `from`.
+#[cfg(unix)]
+pub fn copy_mode(from: &Path, to: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let executable = std::fs::metadata(from)?.permissions().mode() & 0o111 != 0;
+    std::fs::set_permissions(to, std::fs::Permissions::from_mode(mode_for(false, executable)))
+}
+
+#[cfg(not(unix))]
+pub fn copy_mode(_from: &Path, _to: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// When running as root, gives the entries below `root` that root owns to the owner of `owner_of`
+/// (if that is another user); returns how many were changed.
+#[cfg(unix)]
+pub fn preserve_owner(root: &Path, owner_of: &Path) -> io::Result<usize> {
+    use std::os::unix::fs::MetadataExt;
+
+    // SAFETY: geteuid cannot fail.
+    let euid = unsafe { libc::geteuid() };
+    let owner = std::fs::metadata(owner_of)?;
+    if euid != 0 || owner.uid() == euid {
+        return Ok(0);
+    }
+    let mut changed = 0;
+    for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
+        if entry.path_is_symlink() || entry.metadata()?.uid() != euid {
+            continue;
+        }
+        std::os::unix::fs::chown(entry.path(), Some(owner.uid()), Some(owner.gid()))?;
+        changed += 1;
+    }
+    if changed > 0 {
+        println!(
+            "OWNER: Gave {} files and directories to {}:{} (owner of {})",
+            changed,
+            owner.uid(),
+            owner.gid(),
+            owner_of.display()
+        );
+    }
+    Ok(changed)
+}
+
+#[cfg(not(unix))]
+pub fn preserve_owner(_root: &Path, _owner_of: &Path) -> io::Result<usize> {
+    Ok(0)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    fn mode(path: &Path) -> u32 {
+        fs::metadata(path).unwrap().permissions().mode() & 0o777
+    }
+
+    #[test]
+    fn normalized_trees_keep_the_executable_bit() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("zeta");
+        fs::create_dir_all(root.join("scripts")).unwrap();
+        fs::write(root.join("scripts/build.sh"), "#!/bin/sh\n").unwrap();
+        fs::write(root.join("README.md"), "# zeta\n").unwrap();
+        fs::set_permissions(root.join("scripts"), fs::Permissions::from_mode(0o700)).unwrap();
+        fs::set_permissions(root.join("scripts/build.sh"), fs::Permissions::from_mode(0o700)).unwrap();
+        fs::set_permissions(root.join("README.md"), fs::Permissions::from_mode(0o600)).unwrap();
+
+        normalize(&root).unwrap();
+        assert_eq!(mode(&root.join("scripts")), 0o777 & !umask());
+        assert_eq!(mode(&root.join("scripts/build.sh")), 0o777 & !umask());
+        assert_eq!(mode(&root.join("README.md")), 0o666 & !umask());
+
+        let copy = dir.path().join("build-copy.sh");
+        fs::write(&copy, "").unwrap();
+        fs::set_permissions(&copy, fs::Permissions::from_mode(0o600)).unwrap();
+        copy_mode(&root.join("scripts/build.sh"), &copy).unwrap();
+        assert_eq!(mode(&copy) & 0o100, 0o100);
+        copy_mode(&root.join("README.md"), &copy).unwrap();
+        assert_eq!(mode(&copy) & 0o111, 0);
+    }
+}
@@ -0,0 +1,494 @@
+//! Placeholder rendering: `{{ expr }}` markers in template files are evaluated at scaffold time.
+//!
+//! An expression is either a variable name (`{{ name_snake }}`) or a generator call:
+//!   uuid()          random UUID v4
+//!   random_hex(n)   n random bytes, hex encoded (2n characters)
+//!   now()           current UTC time as RFC 3339, or now("%Y-%m-%d") with a chrono format
+//!   port("web")     a free TCP port, the same one for every use of "web" in this scaffold;
+//!                   port("web", 3000, 3999) picks from a range, port() is port("")
+//!
+//! Values can be piped through case filters: `{{ module | snake }}` (snake, kebab, camel, pascal,
+//! upper, lower). Every variable also gets `<var>_snake`, `<var>_kebab`, ... variants.
+//!
+//! With `--reproducible`, `now()` returns the fixed scaffold time, `uuid()` and `random_hex()` come
+//! from a generator seeded with the project name, and `port()` picks without probing local ports.
+//!
+//! Generators other than `port` are evaluated once per occurrence. Expressions that are not recognized are left
+//! untouched, so unrelated `{{ ... }}` syntax (GitHub Actions, Helm, Jinja) survives rendering.
+//!
+//! Templates can declare other delimiters in the manifest (`__NAME__`, `%NAME%`). Variables
+//! between those are only recognized in upper case and resolve to the lowercase variable, so
+//! `__name__` and `__version__` in Python code are left alone.
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+
+use chrono::format::{Item, StrftimeItems};
+
+use crate::color::status;
+use crate::filter::PathFilter;
+use crate::scaffold_fs::{ScaffoldFs, WalkEntry};
+use crate::tokenizer::{split_name_to_tokens, TokenizerRules};
+
+pub const DEFAULT_DELIMITERS: (&str, &str) = ("{{", "}}");
+
+/// Ports handed out by `port()` without an explicit range.
+const DEFAULT_PORT_RANGE: (u16, u16) = (20000, 29999);
+
+/// Most bytes `random_hex(n)` generates; larger counts leave the expression unrendered.
+const MAX_RANDOM_HEX_BYTES: u64 = 1024;
+
+#[derive(Debug, Clone)]
+pub struct RenderContext {
+    pub vars: BTreeMap<String, String>,
+    pub delimiters: Vec<(String, String)>,
+    /// Word splitting for the case filters.
+    pub tokenizer: TokenizerRules,
+    /// Ports assigned by `port(name)` so far, keyed by name.
+    ports: RefCell<BTreeMap<String, u16>>,
+    reproducible: Option<Reproducible>,
+}
+
+/// Clock and randomness of a `--reproducible` run.
+#[derive(Debug, Clone)]
+struct Reproducible {
+    now: chrono::DateTime<chrono::Utc>,
+    /// splitmix64 state.
+    state: Cell<u64>,
+}
+
+impl RenderContext {
+    pub fn new(vars: BTreeMap<String, String>) -> Self {
+        RenderContext {
+            vars,
+            delimiters: vec![(
+                DEFAULT_DELIMITERS.0.to_string(),
+                DEFAULT_DELIMITERS.1.to_string(),
+            )],
+            tokenizer: TokenizerRules::default(),
+            ports: RefCell::new(BTreeMap::new()),
+            reproducible: None,
+        }
+    }
+
+    /// Makes the generators deterministic: `now()` returns `now`, random values are seeded with
+    /// the `name` variable and `stream`, which keeps separate contexts from repeating values.
+    pub fn reproducible(mut self, now: chrono::DateTime<chrono::Utc>, stream: &str) -> Self {
+        // FNV-1a of the project name and the stream
+        let name = self.vars.get("name").map(String::as_str).unwrap_or_default();
+        let seed = name.bytes().chain([0]).chain(stream.bytes()).fold(0xcbf2_9ce4_8422_2325u64, |hash, b| {
+            (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+        });
+        self.reproducible = Some(Reproducible {
+            now,
+            state: Cell::new(seed),
+        });
+        self
+    }
+
+    fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        match self.reproducible {
+            Some(ref r) => r.now,
+            None => chrono::Utc::now(),
+        }
+    }
+
+    fn random_bytes(&self, len: usize) -> Option<Vec<u8>> {
+        let mut buf = vec![0u8; len];
+        match self.reproducible {
+            Some(ref r) => {
+                for chunk in buf.chunks_mut(8) {
+                    let mut z = r.state.get().wrapping_add(0x9e37_79b9_7f4a_7c15);
+                    r.state.set(z);
+                    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+                    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+                    z ^= z >> 31;
+                    chunk.copy_from_slice(&z.to_le_bytes()[..chunk.len()]);
+                }
+            }
+            None => getrandom::fill(&mut buf).ok()?,
+        }
+        Some(buf)
+    }
+
+    pub fn with_tokenizer(mut self, rules: &TokenizerRules) -> Self {
+        self.tokenizer = rules.clone();
+        self
+    }
+
+    pub fn with_delimiters(mut self, delimiters: &[(String, String)]) -> Self {
+        if !delimiters.is_empty() {
+            self.delimiters = delimiters.to_vec();
+        }
+        self
+    }
+
+    /// The variable `name`; between custom delimiters (`upper_case`) only upper-case spellings
+    /// count.
+    fn lookup(&self, name: &str, upper_case: bool) -> Option<String> {
+        if upper_case {
+            if name.chars().any(|c| c.is_ascii_lowercase()) {
+                return None;
+            }
+            return self.vars.get(&name.to_lowercase()).cloned();
+        }
+        self.vars
+            .get(name)
+            .or_else(|| self.vars.get(&name.to_lowercase()))
+            .cloned()
+    }
+
+    fn may_contain_placeholder(&self, s: &str) -> bool {
+        self.delimiters.iter().any(|(open, _)| s.contains(open.as_str()))
+    }
+
+    /// Ports assigned by `port()` during rendering, by name.
+    pub fn assigned_ports(&self) -> BTreeMap<String, u16> {
+        self.ports.borrow().clone()
+    }
+
+    /// The port for `name`, picking a free one in `range` on first use.
+    fn port(&self, name: &str, range: (u16, u16)) -> Option<u16> {
+        if let Some(port) = self.ports.borrow().get(name) {
+            return Some(*port);
+        }
+        let (low, high) = range;
+        if low == 0 || low > high {
+            return None;
+        }
+        let span = u32::from(high - low) + 1;
+        let seed: [u8; 4] = self.random_bytes(4)?.try_into().ok()?;
+        let start = u32::from_le_bytes(seed) % span;
+        let taken: Vec<u16> = self.ports.borrow().values().copied().collect();
+        // Whether a port is free depends on the machine, so reproducible runs do not check.
+        let probe = self.reproducible.is_none();
+        let port = (0..span)
+            .map(|i| low + ((start + i) % span) as u16)
+            .find(|p| {
+                !taken.contains(p) && (!probe || TcpListener::bind(("127.0.0.1", *p)).is_ok())
+            })?;
+        self.ports.borrow_mut().insert(name.to_string(), port);
+        Some(port)
+    }
+}
+
+const CASE_FILTERS: &[&str] = &["kebab", "snake", "upper", "camel", "pascal"];
+
+/// Built-in variables derived from the new project name.
+pub fn builtin_variables(new_name: &str, rules: &TokenizerRules) -> BTreeMap<String, String> {
+    let mut vars = BTreeMap::new();
+    insert_with_case_variants(&mut vars, "name", new_name, rules);
+    vars
+}
+
+/// Inserts `key` plus `key_kebab`, `key_snake`, `key_upper`, `key_camel` and `key_pascal`.
+pub fn insert_with_case_variants(
+    vars: &mut BTreeMap<String, String>,
+    key: &str,
+    value: &str,
+    rules: &TokenizerRules,
+) {
+    vars.insert(key.to_string(), value.to_string());
+    for filter in CASE_FILTERS {
+        if let Some(converted) = apply_filter(value, filter, rules) {
+            vars.insert(format!("{}_{}", key, filter), converted);
+        }
+    }
+}
+
+fn apply_filter(value: &str, filter: &str, rules: &TokenizerRules) -> Option<String> {
+    let tokens = split_name_to_tokens(value, rules);
+    let converted = match filter {
+        "kebab" => tokens.join("-"),
+        "snake" => tokens.join("_"),
+        "upper" => tokens.join("_").to_uppercase(),
+        "camel" => rules.join_camel(&tokens),
+        "pascal" => rules.join_pascal(&tokens, ""),
+        "lower" => value.to_lowercase(),
+        _ => return None,
+    };
+    Some(converted)
+}
+
+pub fn render(input: &str, ctx: &RenderContext) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    // Earliest opening delimiter first; on ties prefer the longer one.
+    while let Some((start, open, close)) = ctx
+        .delimiters
+        .iter()
+        .filter_map(|(o, c)| rest.find(o.as_str()).map(|i| (i, o.as_str(), c.as_str())))
+        .min_by_key(|(i, o, _)| (*i, std::cmp::Reverse(o.len())))
+    {
+        out.push_str(&rest[..start]);
+        let after_open = &rest[start + open.len()..];
+        let custom = (open, close) != DEFAULT_DELIMITERS;
+        let evaluated = after_open
+            .find(close)
+            .and_then(|end| evaluate(&after_open[..end], ctx, custom).map(|v| (v, end)));
+        match evaluated {
+            Some((value, end)) => {
+                out.push_str(&value);
+                rest = &after_open[end + close.len()..];
+            }
+            None => {
+                // Not a placeholder: keep the opening marker and continue right after it.
+                out.push_str(open);
+                rest = after_open;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn evaluate(expr: &str, ctx: &RenderContext, custom: bool) -> Option<String> {
+    let expr = expr.trim();
+    // Trailing `| filter` segments; a `|` inside a generator argument is not a filter.
+    if let Some(pipe) = expr.rfind('|') {
+        let filter = expr[pipe + 1..].trim();
+        if is_identifier(filter) {
+            let value = evaluate(&expr[..pipe], ctx, custom)?;
+            return apply_filter(&value, filter, &ctx.tokenizer);
+        }
+    }
+    if let Some(open) = expr.find('(') {
+        if !expr.ends_with(')') {
+            return None;
+        }
+        let func = expr[..open].trim();
+        let args = parse_args(&expr[open + 1..expr.len() - 1])?;
+        return call_generator(func, &args, ctx);
+    }
+    if is_identifier(expr) {
+        return ctx.lookup(expr, custom);
+    }
+    None
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Arg {
+    Int(u64),
+    Str(String),
+}
+
+fn parse_args(raw: &str) -> Option<Vec<Arg>> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Some(Vec::new());
+    }
+    let mut args = Vec::new();
+    for part in raw.split(',') {
+        let part = part.trim();
+        let quoted = (part.starts_with('"') && part.ends_with('"'))
+            || (part.starts_with('\'') && part.ends_with('\''));
+        if quoted && part.len() >= 2 {
+            args.push(Arg::Str(part[1..part.len() - 1].to_string()));
+        } else {
+            args.push(Arg::Int(part.parse().ok()?));
+        }
+    }
+    Some(args)
+}
+
+fn call_generator(func: &str, args: &[Arg], ctx: &RenderContext) -> Option<String> {
+    let port_range = |low: u64, high: u64| Some((u16::try_from(low).ok()?, u16::try_from(high).ok()?));
+    match (func, args) {
+        ("port", []) => ctx.port("", DEFAULT_PORT_RANGE).map(|p| p.to_string()),
+        ("port", [Arg::Str(name)]) => ctx.port(name, DEFAULT_PORT_RANGE).map(|p| p.to_string()),
+        ("port", [Arg::Int(low), Arg::Int(high)]) => {
+            ctx.port("", port_range(*low, *high)?).map(|p| p.to_string())
+        }
+        ("port", [Arg::Str(name), Arg::Int(low), Arg::Int(high)]) => {
+            ctx.port(name, port_range(*low, *high)?).map(|p| p.to_string())
+        }
+        ("uuid", []) => {
+            let bytes: [u8; 16] = ctx.random_bytes(16)?.try_into().ok()?;
+            Some(uuid::Builder::from_random_bytes(bytes).into_uuid().to_string())
+        }
+        ("random_hex", [Arg::Int(n)]) if *n <= MAX_RANDOM_HEX_BYTES => {
+            let bytes = ctx.random_bytes(*n as usize)?;
+            Some(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+        }
+        ("now", []) => Some(ctx.now().to_rfc3339()),
+        ("now", [Arg::Str(format)]) => {
+            // An invalid specifier would make the formatter panic
+            let items: Vec<Item> = StrftimeItems::new(format).collect();
+            if items.iter().any(|item| matches!(item, Item::Error)) {
+                return None;
+            }
+            Some(ctx.now().format_with_items(items.into_iter()).to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Renders the text files below `base` that `filter` allows, without walking its skipped
+/// directories or reading files above its size limit. Like replacement, writes go to `fs` in
+/// dry runs too.
+pub fn render_in_files(
+    fs: &dyn ScaffoldFs,
+    base: &Path,
+    ctx: &RenderContext,
+    filter: &PathFilter,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    println!("Rendering placeholders inside files...");
+    for entry in walk(fs, base, filter) {
+        let path = entry.path.as_path();
+        if !entry.is_file || !filter.allows(base, path) || filter.exceeds_size_limit(entry.len) {
+            continue;
+        }
+        let Ok(bytes) = fs.read(path) else {
+            continue;
+        };
+        if bytes.contains(&0) {
+            continue;
+        }
+        let Ok(content) = String::from_utf8(bytes) else {
+            continue;
+        };
+        if !ctx.may_contain_placeholder(&content) {
+            continue;
+        }
+        let rendered = render(&content, ctx);
+        if rendered == content {
+            continue;
+        }
+        if let Err(e) = fs.write(path, rendered.as_bytes()) {
+            println!("WARN: Failed to write file {}: {}", path.display(), e);
+        } else if dry_run {
+            println!("DRY RENDER: Would update file: {}", path.display());
+        } else {
+            println!("RENDER: Updated file: {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+pub fn render_paths(
+    fs: &dyn ScaffoldFs,
+    base: &Path,
+    ctx: &RenderContext,
+    filter: &PathFilter,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    println!("Rendering placeholders in file and directory names...");
+    let mut entries: Vec<PathBuf> = walk(fs, base, filter)
+        .into_iter()
+        .filter(|e| e.depth > 0 && filter.allows(base, &e.path))
+        .map(|e| e.path)
+        .collect();
+    entries.sort_by_key(|p| std::cmp::Reverse(p.components().count()));
+
+    for path in entries {
+        let Some(file_name) = path.file_name().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if !ctx.may_contain_placeholder(file_name) {
+            continue;
+        }
+        let rendered = render(file_name, ctx);
+        if rendered == file_name || rendered.is_empty() {
+            continue;
+        }
+        if rendered.starts_with('/') || rendered.split('/').any(|part| part == "..") {
+            println!("WARN: Not renaming {}: rendered name '{}' leaves the project", path.display(), rendered);
+            continue;
+        }
+        // A rendered name may contain `/` (e.g. a `package_path` variable) and create nested dirs.
+        let new_path = path.with_file_name(&rendered);
+        if fs.exists(&new_path) {
+            println!(
+                "WARN: Not renaming {} -> {}: target exists",
+                path.display(),
+                new_path.display()
+            );
+            continue;
+        }
+        if let Some(parent) = new_path.parent() {
+            fs.create_dir_all(parent)?;
+        }
+        if let Err(e) = fs.rename(&path, &new_path) {
+            println!(
+                "WARN: Failed to rename {} -> {}: {}",
+                path.display(),
+                new_path.display(),
+                e
+            );
+            continue;
+        }
+        if dry_run {
+            status!("DRY RENAME: {} -> {}", path.display(), new_path.display());
+        } else {
+            status!("RENAME: {} -> {}", path.display(), new_path.display());
+        }
+        crate::progress::emit(crate::progress::Event::RenamePlanned {
+            from: crate::progress::relative(base, &path),
+            to: crate::progress::relative(base, &new_path),
+        });
+    }
+    Ok(())
+}
+
+/// Entries below `base` within the walk bounds of `filter`, leaving out `.git` and skipped
+/// directories.
+fn walk(fs: &dyn ScaffoldFs, base: &Path, filter: &PathFilter) -> Vec<WalkEntry> {
+    fs.walk(base, filter.walk_options(), &mut |e| {
+        !(e.depth > 0 && e.path.file_name().is_some_and(|n| n == ".git" || filter.skips_dir(n)))
+    })
+    .into_iter()
+    .filter(|e| e.path.file_name() != Some(".git".as_ref()))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scaffold_fs::RealFs;
+
+    #[test]
+    fn custom_delimiters_leave_python_dunders_alone() {
+        let delimiters = [("__".to_string(), "__".to_string())];
+        let mut vars = builtin_variables("zeta", &TokenizerRules::default());
+        vars.insert("author".to_string(), "Jane".to_string());
+        vars.insert("version".to_string(), "0.1.0".to_string());
+        let ctx = RenderContext::new(vars).with_delimiters(&delimiters);
+        let python = "__author__ = \"__AUTHOR__\"\n__version__ = \"1.0\"\n\n\
+                      if __name__ == \"__main__\":\n    print(\"__NAME_SNAKE__\")\n";
+        assert_eq!(
+            render(python, &ctx),
+            "__author__ = \"Jane\"\n__version__ = \"1.0\"\n\n\
+             if __name__ == \"__main__\":\n    print(\"zeta\")\n"
+        );
+    }
+
+    #[test]
+    fn rendering_keeps_to_the_path_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::create_dir_all(root.join("node_modules/lib")).unwrap();
+        for rel in ["README.md", "docs/{{ name }}.md", "node_modules/lib/index.js"] {
+            std::fs::write(root.join(rel), "{{ name }}").unwrap();
+        }
+        let ctx = RenderContext::new(builtin_variables("zeta", &TokenizerRules::default()));
+        let filter = PathFilter::new(&[], &["docs/**".to_string()]).unwrap();
+        render_in_files(&RealFs, root, &ctx, &filter, false).unwrap();
+        render_paths(&RealFs, root, &ctx, &filter, false).unwrap();
+
+        assert_eq!(std::fs::read_to_string(root.join("README.md")).unwrap(), "zeta");
+        assert_eq!(std::fs::read_to_string(root.join("docs/{{ name }}.md")).unwrap(), "{{ name }}");
+        assert_eq!(std::fs::read_to_string(root.join("node_modules/lib/index.js")).unwrap(), "{{ name }}");
+    }
+}
@@ -0,0 +1,228 @@
+//! Template plugins: small WASM modules shipped with a template (`[[plugins]]` in the manifest)
+//! that derive variables or transform files during scaffolding.
+//!
+//! Modules run sandboxed: they may not import anything (no WASI, so no filesystem, network,
+//! clock or environment), each call gets a fresh instance with bounded memory and fuel, and they
+//! only see the data passed to them. The ABI uses JSON over linear memory:
+//!
+//! - `memory` and `alloc(len: i32) -> i32` must be exported; liscaf writes the input there.
+//! - `derive(ptr: i32, len: i32) -> i64` (optional) receives the variables as a JSON object and
+//!   returns a JSON object of variables to add or override.
+//! - `transform(ptr: i32, len: i32) -> i64` (optional) receives
+//!   `{"path": "src/main.rs", "content": "...", "vars": {...}}` and returns the new file content.
+//!
+//! Results are returned as `(ptr << 32) | len` pointing into the module's memory.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::filter::PathFilter;
+use crate::manifest::PluginSpec;
+use crate::scaffold_fs::ScaffoldFs;
+
+pub struct Plugin {
+    name: String,
+    files: PathFilter,
+    module: sandbox::Module,
+}
+
+/// Reads and compiles the declared modules. Runs before template-only files are stripped, since
+/// modules usually live in `.liscaf/`.
+pub fn load(root: &Path, specs: &[PluginSpec]) -> anyhow::Result<Vec<Plugin>> {
+    let mut plugins = Vec::new();
+    for spec in specs {
+        let path = root.join(&spec.path);
+        let bytes = fs::read(&path)
+            .map_err(|e| anyhow::anyhow!("Failed to read plugin {}: {}", spec.path, e))?;
+        let module = sandbox::Module::compile(&bytes)
+            .map_err(|e| anyhow::anyhow!("Invalid plugin {}: {}", spec.path, e))?;
+        if !module.exports("derive") && !module.exports("transform") {
+            anyhow::bail!("Plugin {} exports neither derive nor transform", spec.path);
+        }
+        let name = Path::new(&spec.path)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| spec.path.clone());
+        println!("Loaded plugin: {}", spec.path);
+        plugins.push(Plugin {
+            name,
+            files: PathFilter::new(&spec.files, &[])?,
+            module,
+        });
+    }
+    Ok(plugins)
+}
+
+/// Lets each plugin with a `derive` export add or override variables, in manifest order.
+pub fn derive(plugins: &[Plugin], vars: &mut BTreeMap<String, String>) -> anyhow::Result<()> {
+    for plugin in plugins.iter().filter(|p| p.module.exports("derive")) {
+        let input = serde_json::to_vec(&*vars)?;
+        let output = plugin
+            .module
+            .call("derive", &input)
+            .map_err(|e| anyhow::anyhow!("Plugin {} failed in derive: {:#}", plugin.name, e))?;
+        let derived: BTreeMap<String, String> = serde_json::from_slice(&output).map_err(|e| {
+            anyhow::anyhow!("Plugin {} returned invalid variables: {}", plugin.name, e)
+        })?;
+        for (key, value) in derived {
+            println!("PLUGIN: {} set {} = {}", plugin.name, key, value);
+            vars.insert(key, value);
+        }
+    }
+    Ok(())
+}
+
+/// Passes every matching text file through the plugins with a `transform` export. Walks like
+/// [`crate::replace_in_files`]: within `filter`, and in `fs` in dry runs too.
+pub fn transform(
+    plugins: &[Plugin],
+    fs: &dyn ScaffoldFs,
+    root: &Path,
+    vars: &BTreeMap<String, String>,
+    filter: &PathFilter,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let plugins: Vec<&Plugin> = plugins.iter().filter(|p| p.module.exports("transform")).collect();
+    if plugins.is_empty() {
+        return Ok(());
+    }
+    let entries = fs.walk(root, filter.walk_options(), &mut |e| {
+        e.path.file_name().is_none_or(|n| n != ".git" && (e.depth == 0 || !filter.skips_dir(n)))
+    });
+    for entry in entries {
+        let path = entry.path.as_path();
+        if !entry.is_file || !filter.allows(root, path) || filter.exceeds_size_limit(entry.len) {
+            continue;
+        }
+        let Some(mut content) = fs.read(path).ok().and_then(|b| crate::bytes_to_text(&b)) else {
+            continue;
+        };
+        let rel = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let mut changed_by = Vec::new();
+        for plugin in plugins.iter().filter(|p| p.files.allows(root, path)) {
+            let input = serde_json::to_vec(&serde_json::json!({
+                "path": rel,
+                "content": content,
+                "vars": vars,
+            }))?;
+            let output = plugin.module.call("transform", &input).map_err(|e| {
+                anyhow::anyhow!("Plugin {} failed on {}: {:#}", plugin.name, rel, e)
+            })?;
+            let output = String::from_utf8(output).map_err(|_| {
+                anyhow::anyhow!("Plugin {} returned non-UTF-8 content for {}", plugin.name, rel)
+            })?;
+            if output != content {
+                content = output;
+                changed_by.push(plugin.name.as_str());
+            }
+        }
+        if changed_by.is_empty() {
+            continue;
+        }
+        if let Err(e) = fs.write(path, content.as_bytes()) {
+            println!("WARN: Failed to write file {}: {}", path.display(), e);
+        } else if dry_run {
+            println!("DRY PLUGIN: Would update file: {} ({})", rel, changed_by.join(", "));
+        } else {
+            println!("PLUGIN: Updated file: {} ({})", rel, changed_by.join(", "));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "plugins")]
+mod sandbox {
+    use wasmtime::{Config, Engine, Instance, Store, StoreLimits, StoreLimitsBuilder};
+
+    /// Upper bound on the instructions a single call may execute (roughly a second of work).
+    const FUEL_PER_CALL: u64 = 1_000_000_000;
+
+    /// Upper bound on a module's linear memory.
+    const MAX_MEMORY: usize = 256 * 1024 * 1024;
+
+    /// Upper bound on the bytes a call may return.
+    const MAX_OUTPUT: usize = 64 * 1024 * 1024;
+
+    pub struct Module {
+        engine: Engine,
+        module: wasmtime::Module,
+    }
+
+    impl Module {
+        pub fn compile(bytes: &[u8]) -> anyhow::Result<Self> {
+            let mut config = Config::new();
+            config.consume_fuel(true);
+            let engine = Engine::new(&config)?;
+            let module = wasmtime::Module::new(&engine, bytes)?;
+            if let Some(import) = module.imports().next() {
+                anyhow::bail!(
+                    "plugins must not import anything, found {}::{}",
+                    import.module(),
+                    import.name()
+                );
+            }
+            for required in ["memory", "alloc"] {
+                if module.get_export(required).is_none() {
+                    anyhow::bail!("missing export '{}'", required);
+                }
+            }
+            Ok(Module { engine, module })
+        }
+
+        pub fn exports(&self, name: &str) -> bool {
+            self.module.get_export(name).is_some()
+        }
+
+        /// Runs `export` in a fresh instance and returns the bytes it points at.
+        pub fn call(&self, export: &str, input: &[u8]) -> anyhow::Result<Vec<u8>> {
+            let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY).build();
+            let mut store: Store<StoreLimits> = Store::new(&self.engine, limits);
+            store.limiter(|limits| limits);
+            store.set_fuel(FUEL_PER_CALL)?;
+            let instance = Instance::new(&mut store, &self.module, &[])?;
+            let memory = instance
+                .get_memory(&mut store, "memory")
+                .ok_or_else(|| anyhow::anyhow!("'memory' is not a memory export"))?;
+            let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+            let func = instance.get_typed_func::<(i32, i32), i64>(&mut store, export)?;
+
+            let len = i32::try_from(input.len())?;
+            let ptr = alloc.call(&mut store, len)?;
+            memory.write(&mut store, ptr as u32 as usize, input)?;
+            let packed = func.call(&mut store, (ptr, len))? as u64;
+            let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+            if out_len > MAX_OUTPUT {
+                anyhow::bail!("result of {} bytes exceeds the limit of {} bytes", out_len, MAX_OUTPUT);
+            }
+            // The module chose the range; only copy it when it lies within its memory
+            let output = out_ptr
+                .checked_add(out_len)
+                .and_then(|end| memory.data(&store).get(out_ptr..end))
+                .ok_or_else(|| anyhow::anyhow!("result points outside the module's memory"))?;
+            Ok(output.to_vec())
+        }
+    }
+}
+
+#[cfg(not(feature = "plugins"))]
+mod sandbox {
+    pub enum Module {}
+
+    impl Module {
+        pub fn compile(_bytes: &[u8]) -> anyhow::Result<Self> {
+            anyhow::bail!("this liscaf build has no plugin support (enable the `plugins` feature)")
+        }
+
+        pub fn exports(&self, _name: &str) -> bool {
+            match *self {}
+        }
+
+        pub fn call(&self, _export: &str, _input: &[u8]) -> anyhow::Result<Vec<u8>> {
+            match *self {}
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! `--porcelain`: stable, line-oriented records on stdout for scripts, while the human-readable
+//! output moves to stderr.
+//!
+//! Records are written from the [`crate::progress`] events. Format version 1 will not change;
+//! new information gets new record types. One record per line, fields separated by tabs, in the
+//! order the changes happen:
+//!
+//! - `VERSION\t1`: always the first line
+//! - `RENAME\t<from>\t<to>`: a template file or directory was renamed (deepest paths first)
+//! - `ADD\t<path>`: a file was created
+//! - `CONFLICT\t<path>`: an existing file differs and got conflict markers (binary files: a
+//!   `.liscaf-incoming` copy)
+//! - `DONE\t<dir>`: the project was written to this directory
+//!
+//! Paths are relative to the project directory with `/` separators, except for `DONE`. Backslash,
+//! tab, CR and LF inside fields are written as `\\`, `\t`, `\r` and `\n`.
+use std::io::Write;
+use std::sync::{Mutex, OnceLock};
+
+use crate::progress::Event;
+
+pub const VERSION: u32 = 1;
+
+static OUT: OnceLock<Mutex<Box<dyn Write + Send>>> = OnceLock::new();
+
+pub fn enable() -> anyhow::Result<()> {
+    let out: Box<dyn Write + Send> = Box::new(take_stdout()?);
+    if OUT.set(Mutex::new(out)).is_ok() {
+        record(&["VERSION", &VERSION.to_string()]);
+    }
+    Ok(())
+}
+
+pub fn enabled() -> bool {
+    OUT.get().is_some()
+}
+
+/// Writes the record for a progress event; events without a record type are left out.
+pub fn record_event(event: &Event) {
+    if !enabled() {
+        return;
+    }
+    if let Some(line) = event_line(event) {
+        write_line(&line);
+    }
+}
+
+/// The record line of a progress event, if it has a record type.
+fn event_line(event: &Event) -> Option<String> {
+    Some(match event {
+        Event::RenamePlanned { from, to } => line(&["RENAME", from, to]),
+        Event::FileAdded { path } => line(&["ADD", path]),
+        Event::Conflict { path } => line(&["CONFLICT", path]),
+        Event::Finished { project_dir } => line(&["DONE", &project_dir.display().to_string()]),
+        _ => return None,
+    })
+}
+
+fn record(fields: &[&str]) {
+    write_line(&line(fields));
+}
+
+fn write_line(line: &str) {
+    let Some(out) = OUT.get() else {
+        return;
+    };
+    let mut out = out.lock().unwrap_or_else(|e| e.into_inner());
+    let _ = writeln!(out, "{}", line);
+    let _ = out.flush();
+}
+
+fn line(fields: &[&str]) -> String {
+    let fields: Vec<String> = fields.iter().map(|f| escape(f)).collect();
+    fields.join("\t")
+}
+
+fn escape(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+}
+
+/// Splits a record into its unescaped fields.
+pub fn parse(line: &str) -> Vec<String> {
+    line.split('\t')
+        .map(|field| {
+            let mut out = String::with_capacity(field.len());
+            let mut chars = field.chars();
+            while let Some(c) = chars.next() {
+                if c != '\\' {
+                    out.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some('n') => out.push('\n'),
+                    Some(other) => out.push(other),
+                    None => out.push('\\'),
+                }
+            }
+            out
+        })
+        .collect()
+}
+
+/// Takes over stdout for machine-readable output and points file descriptor 1 at stderr, so
+/// output from the rest of liscaf cannot end up in between.
+#[cfg(unix)]
+pub fn take_stdout() -> anyhow::Result<std::fs::File> {
+    use std::os::fd::FromRawFd;
+
+    std::io::stdout().flush()?;
+    // SAFETY: plain descriptor duplication; the duplicate is owned by the returned File.
+    unsafe {
+        let fd = libc::dup(libc::STDOUT_FILENO);
+        if fd < 0 || libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(std::fs::File::from_raw_fd(fd))
+    }
+}
+
+#[cfg(not(unix))]
+pub fn take_stdout() -> anyhow::Result<std::io::Stdout> {
+    Ok(std::io::stdout())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn events_become_tab_separated_records() {
+        let rename = Event::RenamePlanned {
+            from: "acme-app/src".to_string(),
+            to: "zeta/src".to_string(),
+        };
+        assert_eq!(event_line(&rename).as_deref(), Some("RENAME\tacme-app/src\tzeta/src"));
+        let add = Event::FileAdded { path: "docs/a\tb.md".to_string() };
+        assert_eq!(event_line(&add).as_deref(), Some("ADD\tdocs/a\\tb.md"));
+        let done = Event::Finished { project_dir: PathBuf::from("/work/zeta") };
+        assert_eq!(event_line(&done).as_deref(), Some("DONE\t/work/zeta"));
+        let clone = Event::CloneStarted { url: "https://github.com/o/acme-app".to_string() };
+        assert_eq!(event_line(&clone), None);
+        assert_eq!(line(&["VERSION", &VERSION.to_string()]), "VERSION\t1");
+    }
+
+    #[test]
+    fn escaped_fields_parse_back() {
+        let fields = ["CONFLICT", "dir\\name\twith\ttabs", "line\r\nbreak", "plain"];
+        let record = line(&fields);
+        assert_eq!(record.split('\t').count(), fields.len());
+        assert!(!record.contains('\n') && !record.contains('\r'));
+        assert_eq!(parse(&record), fields);
+    }
+}
@@ -0,0 +1,164 @@
+//! `cargo` mode: package name, version reset, authors/repository and workspace paths.
+use std::path::{Component, Path, PathBuf};
+
+use toml_edit::{value, Array, DocumentMut, Item, Table, TableLike, Value};
+
+use super::PostprocessContext;
+
+const INITIAL_VERSION: &str = "0.1.0";
+
+pub fn run(ctx: &PostprocessContext) -> anyhow::Result<()> {
+    println!("Post-processing Cargo manifests...");
+    let root_manifest = ctx.root.join("Cargo.toml");
+    for path in super::find_files(ctx, "Cargo.toml") {
+        let content = ctx.fs.read_to_string(&path)?;
+        let mut doc: DocumentMut = content
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))?;
+        let dir = path.parent().unwrap_or(ctx.root);
+
+        let is_root = path == root_manifest;
+        if let Some(package) = doc.get_mut("package").and_then(Item::as_table_like_mut) {
+            if is_root {
+                let name = crate::split_name_to_tokens(ctx.new_name, &Default::default()).join("-");
+                set_value(package, "name", name.into());
+            }
+            update_package_table(package, is_root, ctx);
+        }
+        if let Some(package) = doc
+            .get_mut("workspace")
+            .and_then(|w| w.get_mut("package"))
+            .and_then(Item::as_table_like_mut)
+        {
+            update_package_table(package, is_root, ctx);
+        }
+        if let Some(workspace) = doc.get_mut("workspace").and_then(Item::as_table_mut) {
+            fix_workspace_paths(workspace, dir, ctx);
+        }
+        for deps in ["dependencies", "dev-dependencies", "build-dependencies"] {
+            if let Some(table) = doc.get_mut(deps).and_then(Item::as_table_like_mut) {
+                fix_path_dependencies(table, dir, ctx);
+            }
+        }
+        if let Some(table) = doc
+            .get_mut("workspace")
+            .and_then(|w| w.get_mut("dependencies"))
+            .and_then(Item::as_table_like_mut)
+        {
+            fix_path_dependencies(table, dir, ctx);
+        }
+
+        let updated = doc.to_string();
+        if updated != content {
+            super::write_file(ctx, &path, &updated)?;
+        }
+    }
+    Ok(())
+}
+
+/// Replaces (or inserts) a value while keeping its surrounding whitespace and comments.
+pub(super) fn set_value(table: &mut dyn TableLike, key: &str, new: Value) {
+    match table.get_mut(key) {
+        Some(Item::Value(existing)) => {
+            let decor = existing.decor().clone();
+            *existing = new;
+            *existing.decor_mut() = decor;
+        }
+        _ => {
+            table.insert(key, value(new));
+        }
+    }
+}
+
+fn update_package_table(package: &mut dyn TableLike, is_root: bool, ctx: &PostprocessContext) {
+    // `version.workspace = true` is inherited and left alone.
+    if package.get("version").map(Item::is_str).unwrap_or(false) {
+        set_value(package, "version", INITIAL_VERSION.into());
+    }
+    if package.contains_key("authors") {
+        if let Some(author) = ctx.vars.get("author") {
+            let entry = match ctx.vars.get("author_email") {
+                Some(email) => format!("{} <{}>", author, email),
+                None => author.clone(),
+            };
+            let mut authors = Array::new();
+            authors.push(entry);
+            set_value(package, "authors", authors.into());
+        }
+    }
+    // Members only get a repository if they already declare one.
+    if let Some(repository) = ctx.vars.get("repository").filter(|_| is_root || package.contains_key("repository")) {
+        set_value(package, "repository", repository.as_str().into());
+    }
+}
+
+fn fix_workspace_paths(workspace: &mut Table, dir: &Path, ctx: &PostprocessContext) {
+    for key in ["members", "default-members", "exclude"] {
+        let Some(array) = workspace.get_mut(key).and_then(Item::as_array_mut) else {
+            continue;
+        };
+        for item in array.iter_mut() {
+            let Some(member) = item.as_str() else {
+                continue;
+            };
+            if let Some(fixed) = fix_relative_path(member, dir, ctx) {
+                println!("POST: workspace {} '{}' -> '{}'", key, member, fixed);
+                let decor = item.decor().clone();
+                *item = fixed.into();
+                *item.decor_mut() = decor;
+            }
+        }
+    }
+}
+
+fn fix_path_dependencies(deps: &mut dyn TableLike, dir: &Path, ctx: &PostprocessContext) {
+    for (_, dep) in deps.iter_mut() {
+        let Some(dep) = dep.as_table_like_mut() else {
+            continue;
+        };
+        let Some(path) = dep.get("path").and_then(Item::as_str).map(str::to_string) else {
+            continue;
+        };
+        let path = match fix_relative_path(&path, dir, ctx) {
+            Some(fixed) => {
+                println!("POST: dependency path '{}' -> '{}'", path, fixed);
+                set_value(dep, "path", fixed.as_str().into());
+                fixed
+            }
+            None => path,
+        };
+        // Crates of this scaffold were reset to the initial version as well; crates outside it
+        // keep theirs.
+        if dep.get("version").map(Item::is_str).unwrap_or(false) && normalize(&dir.join(&path)).starts_with(ctx.root) {
+            set_value(dep, "version", INITIAL_VERSION.into());
+        }
+    }
+}
+
+/// `path` with `.` and `..` resolved lexically; the tree may only exist in memory.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Returns a corrected path when `rel` does not exist but its token-replaced form does.
+fn fix_relative_path(rel: &str, dir: &Path, ctx: &PostprocessContext) -> Option<String> {
+    if rel.contains('*') || ctx.fs.exists(&normalize(&dir.join(rel))) {
+        return None;
+    }
+    let replaced = crate::apply_mappings(rel, ctx.mappings);
+    if replaced != rel && ctx.fs.exists(&normalize(&dir.join(&replaced))) {
+        return Some(replaced);
+    }
+    println!("WARN: Cargo path '{}' does not exist in {}", rel, dir.display());
+    None
+}
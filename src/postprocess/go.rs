@@ -0,0 +1,109 @@
+//! `go` mode: rewrites the module path in go.mod files and every matching import path.
+//!
+//! The new path comes from the `go_module` variable, or is derived from `repository`
+//! (`https://github.com/me/app` -> `github.com/me/app`).
+use std::path::Path;
+
+use super::PostprocessContext;
+
+pub fn run(ctx: &PostprocessContext) -> anyhow::Result<()> {
+    println!("Post-processing Go module paths...");
+    let go_mod = ctx.root.join("go.mod");
+    if !ctx.fs.exists(&go_mod) {
+        println!("WARN: go mode enabled but no go.mod in the project root");
+        return Ok(());
+    }
+    let Some(old_module) = module_path(&ctx.fs.read_to_string(&go_mod)?) else {
+        println!("WARN: No module directive found in {}", go_mod.display());
+        return Ok(());
+    };
+    let Some(new_module) = new_module_path(ctx) else {
+        println!(
+            "NOTE: Set --var go_module=<path> (or --var repository=<url>) to rewrite module '{}'",
+            old_module
+        );
+        return Ok(());
+    };
+    if new_module == old_module {
+        return Ok(());
+    }
+    println!("POST: Go module {} -> {}", old_module, new_module);
+
+    let entries = ctx.fs.walk(ctx.root, &Default::default(), &mut |e| {
+        !e.path.file_name().is_some_and(|n| n == ".git" || n == "vendor")
+    });
+    for entry in entries {
+        let path = entry.path.as_path();
+        if !entry.is_file || !is_go_file(path) {
+            continue;
+        }
+        let Ok(content) = ctx.fs.read_to_string(path) else {
+            continue;
+        };
+        let updated = rewrite_module_references(&content, &old_module, &new_module);
+        if updated != content {
+            super::write_file(ctx, path, &updated)?;
+        }
+    }
+    Ok(())
+}
+
+fn is_go_file(path: &Path) -> bool {
+    let name = path.file_name().and_then(|s| s.to_str()).unwrap_or_default();
+    name.ends_with(".go") || name == "go.mod" || name == "go.work"
+}
+
+fn module_path(go_mod: &str) -> Option<String> {
+    go_mod.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix("module")?;
+        if !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let path = rest.split("//").next()?.trim().trim_matches('"');
+        (!path.is_empty()).then(|| path.to_string())
+    })
+}
+
+fn new_module_path(ctx: &PostprocessContext) -> Option<String> {
+    if let Some(module) = ctx.vars.get("go_module") {
+        return Some(module.trim().trim_end_matches('/').to_string());
+    }
+    let repository = ctx.vars.get("repository")?;
+    let trimmed = repository.trim().trim_end_matches('/').trim_end_matches(".git");
+    let without_scheme = trimmed
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(trimmed);
+    // git@github.com:owner/repo -> github.com/owner/repo
+    let without_user = without_scheme
+        .split_once('@')
+        .map(|(_, rest)| rest)
+        .unwrap_or(without_scheme);
+    Some(without_user.replacen(':', "/", 1))
+}
+
+/// Replaces the module path where it stands as a whole path or a path prefix, so
+/// `github.com/acme/app` does not match inside `github.com/acme/app-extras`.
+fn rewrite_module_references(content: &str, old: &str, new: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(idx) = rest.find(old) {
+        let before = rest[..idx].chars().next_back();
+        let after = rest[idx + old.len()..].chars().next();
+        let boundary_before = before.is_none_or(|c| !is_path_char(c));
+        let boundary_after = after.is_none_or(|c| c == '/' || !is_path_char(c));
+        out.push_str(&rest[..idx]);
+        if boundary_before && boundary_after {
+            out.push_str(new);
+        } else {
+            out.push_str(old);
+        }
+        rest = &rest[idx + old.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn is_path_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '/' | '~')
+}
@@ -0,0 +1,212 @@
+//! `helm` mode: chart names, named templates and label values in Helm charts.
+//!
+//! Edits are line-based and only touch scalar values of known keys, so comments, anchors and
+//! layout survive. A single chart is renamed to the `chart_name` variable, or the project name
+//! in kebab case; with several charts only versions are reset.
+use std::path::{Path, PathBuf};
+
+use super::PostprocessContext;
+
+const INITIAL_VERSION: &str = "0.1.0";
+
+/// Label/selector keys whose value is the chart or release name.
+const NAME_LABEL_KEYS: &[&str] = &[
+    "app",
+    "app.kubernetes.io/name",
+    "app.kubernetes.io/instance",
+    "nameOverride",
+    "fullnameOverride",
+];
+
+pub fn run(ctx: &PostprocessContext) -> anyhow::Result<()> {
+    println!("Post-processing Helm charts...");
+    let charts = find_charts(ctx);
+    if charts.is_empty() {
+        println!("WARN: helm mode enabled but no Chart.yaml found");
+        return Ok(());
+    }
+    let single = charts.len() == 1;
+    for chart_dir in charts {
+        let chart_yaml = chart_dir.join("Chart.yaml");
+        let content = ctx.fs.read_to_string(&chart_yaml)?;
+        let Some(old_name) = top_level_scalar(&content, "name") else {
+            println!("WARN: No name in {}", chart_yaml.display());
+            continue;
+        };
+        let new_name = if single {
+            ctx.vars
+                .get("chart_name")
+                .cloned()
+                .unwrap_or_else(|| crate::split_name_to_tokens(ctx.new_name, &Default::default()).join("-"))
+        } else {
+            old_name.clone()
+        };
+
+        let mut updated = set_top_level_scalar(&content, "name", &new_name);
+        updated = set_top_level_scalar(&updated, "version", INITIAL_VERSION);
+        updated = set_top_level_scalar(&updated, "appVersion", INITIAL_VERSION);
+        if updated != content {
+            super::write_file(ctx, &chart_yaml, &updated)?;
+        }
+
+        if new_name == old_name {
+            continue;
+        }
+        println!("POST: Helm chart {} -> {}", old_name, new_name);
+        for path in chart_files(ctx, &chart_dir) {
+            let Ok(content) = ctx.fs.read_to_string(&path) else {
+                continue;
+            };
+            let mut updated = replace_named_templates(&content, &old_name, &new_name);
+            if is_values_file(&path) {
+                updated = replace_label_values(&updated, &old_name, &new_name);
+            }
+            if updated != content {
+                super::write_file(ctx, &path, &updated)?;
+            }
+        }
+
+        // charts/<old-name>/ follows the chart name.
+        if chart_dir.file_name().and_then(|s| s.to_str()) == Some(old_name.as_str()) {
+            let target = chart_dir.with_file_name(&new_name);
+            if ctx.fs.exists(&target) {
+                println!("WARN: Not renaming {}: {} exists", chart_dir.display(), target.display());
+            } else {
+                ctx.fs.rename(&chart_dir, &target)?;
+                if ctx.dry_run {
+                    println!("DRY RENAME: {} -> {}", chart_dir.display(), target.display());
+                } else {
+                    println!("RENAME: {} -> {}", chart_dir.display(), target.display());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Chart directories, excluding subcharts vendored below another chart.
+fn find_charts(ctx: &PostprocessContext) -> Vec<PathBuf> {
+    let mut charts: Vec<PathBuf> = super::find_files(ctx, "Chart.yaml")
+        .into_iter()
+        .filter_map(|p| p.parent().map(Path::to_path_buf))
+        .collect();
+    charts.sort_by_key(|p| p.components().count());
+    let mut top_level: Vec<PathBuf> = Vec::new();
+    for chart in charts {
+        if !top_level.iter().any(|parent| chart.starts_with(parent)) {
+            top_level.push(chart);
+        }
+    }
+    top_level
+}
+
+fn chart_files(ctx: &PostprocessContext, chart_dir: &Path) -> Vec<PathBuf> {
+    ctx.fs
+        .walk(chart_dir, &Default::default(), &mut |e| {
+            !(e.depth > 0 && e.path.file_name().is_some_and(|n| n == "charts"))
+        })
+        .into_iter()
+        .filter(|e| e.is_file)
+        .map(|e| e.path)
+        .filter(|p| {
+            let name = p.file_name().and_then(|s| s.to_str()).unwrap_or_default();
+            name.ends_with(".yaml") || name.ends_with(".yml") || name.ends_with(".tpl")
+        })
+        .filter(|p| p.file_name().and_then(|s| s.to_str()) != Some("Chart.yaml"))
+        .collect()
+}
+
+fn is_values_file(path: &Path) -> bool {
+    let name = path.file_name().and_then(|s| s.to_str()).unwrap_or_default();
+    name.starts_with("values") && (name.ends_with(".yaml") || name.ends_with(".yml"))
+}
+
+/// A `key: value  # comment` line: indentation, key and the value's byte range in the line.
+struct ScalarLine<'a> {
+    indent: &'a str,
+    key: &'a str,
+    value: &'a str,
+    value_start: usize,
+}
+
+fn split_scalar_line(line: &str) -> Option<ScalarLine<'_>> {
+    let body = line.trim_end_matches(['\n', '\r']);
+    let indent_len = body.len() - body.trim_start().len();
+    let indent = &body[..indent_len];
+    let mut key_start = indent_len;
+    if body[key_start..].starts_with("- ") {
+        key_start += 2;
+    }
+    let rest = &body[key_start..];
+    let colon = rest.find(": ").or_else(|| rest.strip_suffix(':').map(str::len))?;
+    let key = rest[..colon].trim().trim_matches(|c| c == '"' || c == '\'');
+    let after_start = key_start + colon + 1;
+    let after = &body[after_start..];
+    let comment_at = after.find(" #").unwrap_or(after.len());
+    let raw = &after[..comment_at];
+    let value = raw.trim();
+    let value_start = after_start + (raw.len() - raw.trim_start().len());
+    Some(ScalarLine {
+        indent,
+        key,
+        value,
+        value_start,
+    })
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim_matches(|c| c == '"' || c == '\'')
+}
+
+/// Keeps the quoting style of `old` for `new`.
+fn requote(old: &str, new: &str) -> String {
+    match old.chars().next() {
+        Some(q @ ('"' | '\'')) => format!("{}{}{}", q, new, q),
+        _ => new.to_string(),
+    }
+}
+
+fn top_level_scalar(content: &str, key: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let l = split_scalar_line(line)?;
+        (l.indent.is_empty() && l.key == key && !l.value.is_empty()).then(|| unquote(l.value).to_string())
+    })
+}
+
+fn rewrite_lines(content: &str, mut f: impl FnMut(&str, &str, &str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(content.len());
+    for line in content.split_inclusive('\n') {
+        let replaced = split_scalar_line(line).and_then(|l| {
+            let new_value = f(l.indent, l.key, l.value)?;
+            let value_end = l.value_start + l.value.len();
+            Some(format!("{}{}{}", &line[..l.value_start], new_value, &line[value_end..]))
+        });
+        out.push_str(replaced.as_deref().unwrap_or(line));
+    }
+    out
+}
+
+pub(super) fn set_top_level_scalar(content: &str, key: &str, new: &str) -> String {
+    rewrite_lines(content, |indent, k, value| {
+        (indent.is_empty() && k == key && !value.is_empty()).then(|| requote(value, new))
+    })
+}
+
+/// Label and override values that are exactly the old chart name.
+fn replace_label_values(content: &str, old: &str, new: &str) -> String {
+    rewrite_lines(content, |_, key, value| {
+        (NAME_LABEL_KEYS.contains(&key) && unquote(value) == old).then(|| requote(value, new))
+    })
+}
+
+/// Named templates: `define "old.fullname"`, `include "old.labels"`, `template "old.name"`.
+fn replace_named_templates(content: &str, old: &str, new: &str) -> String {
+    let mut updated = content.to_string();
+    for keyword in ["define", "include", "template"] {
+        updated = updated.replace(
+            &format!("{} \"{}.", keyword, old),
+            &format!("{} \"{}.", keyword, new),
+        );
+    }
+    updated
+}
@@ -0,0 +1,330 @@
+//! Minimal format-preserving JSON editing.
+//!
+//! Values are located by byte span and replaced in place, so indentation, key order and
+//! unrelated formatting of the document are kept exactly as they were.
+
+/// An object member: `start` is the opening quote of the key, the value spans
+/// `value_start..value_end`.
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub key: String,
+    pub start: usize,
+    pub value_start: usize,
+    pub value_end: usize,
+}
+
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str, pos: usize) -> Self {
+        Parser {
+            src: src.as_bytes(),
+            pos,
+        }
+    }
+
+    fn error(&self, what: &str) -> anyhow::Error {
+        anyhow::anyhow!("Invalid JSON at byte {}: {}", self.pos, what)
+    }
+
+    fn skip_ws(&mut self) {
+        while self.pos < self.src.len() && self.src[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> anyhow::Result<()> {
+        self.skip_ws();
+        if self.src.get(self.pos) != Some(&byte) {
+            return Err(self.error(&format!("expected '{}'", byte as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn string(&mut self) -> anyhow::Result<String> {
+        let start = self.pos;
+        self.expect(b'"')?;
+        loop {
+            match self.src.get(self.pos) {
+                None => return Err(self.error("unterminated string")),
+                Some(b'\\') => self.pos += 2,
+                Some(b'"') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(_) => self.pos += 1,
+            }
+        }
+        let raw = std::str::from_utf8(&self.src[start..self.pos])?;
+        Ok(serde_json::from_str(raw)?)
+    }
+
+    fn array(&mut self) -> anyhow::Result<Vec<(usize, usize)>> {
+        self.expect(b'[')?;
+        let mut elements = Vec::new();
+        self.skip_ws();
+        if self.src.get(self.pos) == Some(&b']') {
+            self.pos += 1;
+            return Ok(elements);
+        }
+        loop {
+            self.skip_ws();
+            let start = self.pos;
+            self.value()?;
+            elements.push((start, self.pos));
+            self.skip_ws();
+            match self.src.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(elements);
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn value(&mut self) -> anyhow::Result<()> {
+        self.skip_ws();
+        match self.src.get(self.pos) {
+            Some(b'{') => self.object().map(|_| ()),
+            Some(b'[') => self.array().map(|_| ()),
+            Some(b'"') => self.string().map(|_| ()),
+            Some(_) => {
+                let start = self.pos;
+                while self.pos < self.src.len()
+                    && !matches!(self.src[self.pos], b',' | b'}' | b']')
+                    && !self.src[self.pos].is_ascii_whitespace()
+                {
+                    self.pos += 1;
+                }
+                if self.pos == start {
+                    return Err(self.error("expected a value"));
+                }
+                Ok(())
+            }
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn object(&mut self) -> anyhow::Result<Vec<Member>> {
+        self.expect(b'{')?;
+        let mut members = Vec::new();
+        self.skip_ws();
+        if self.src.get(self.pos) == Some(&b'}') {
+            self.pos += 1;
+            return Ok(members);
+        }
+        loop {
+            self.skip_ws();
+            let start = self.pos;
+            let key = self.string()?;
+            self.expect(b':')?;
+            self.skip_ws();
+            let value_start = self.pos;
+            self.value()?;
+            members.push(Member {
+                key,
+                start,
+                value_start,
+                value_end: self.pos,
+            });
+            self.skip_ws();
+            match self.src.get(self.pos) {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(members);
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+}
+
+/// Members of the object starting at byte `pos` (after optional whitespace).
+pub fn object_members(src: &str, pos: usize) -> anyhow::Result<Vec<Member>> {
+    Parser::new(src, pos).object()
+}
+
+/// Looks up a member by key path, e.g. `["repository", "url"]`.
+pub fn find(src: &str, path: &[&str]) -> anyhow::Result<Option<Member>> {
+    let mut pos = 0;
+    let mut found = None;
+    for key in path {
+        let trimmed = src[pos..].trim_start();
+        if !trimmed.starts_with('{') {
+            return Ok(None);
+        }
+        let members = object_members(src, pos)?;
+        match members.into_iter().find(|m| m.key == *key) {
+            Some(m) => {
+                pos = m.value_start;
+                found = Some(m);
+            }
+            None => return Ok(None),
+        }
+    }
+    Ok(found)
+}
+
+/// Replaces the value at `path` with `json` (already serialized). `None` if the path is missing.
+pub fn set(src: &str, path: &[&str], json: &str) -> anyhow::Result<Option<String>> {
+    let Some(m) = find(src, path)? else {
+        return Ok(None);
+    };
+    Ok(Some(format!(
+        "{}{}{}",
+        &src[..m.value_start],
+        json,
+        &src[m.value_end..]
+    )))
+}
+
+/// Removes the member at `path` including its separating comma. `None` if the path is missing.
+pub fn remove(src: &str, path: &[&str]) -> anyhow::Result<Option<String>> {
+    let Some((key, parent)) = path.split_last() else {
+        return Ok(None);
+    };
+    let parent_pos = if parent.is_empty() {
+        0
+    } else {
+        match find(src, parent)? {
+            Some(m) => m.value_start,
+            None => return Ok(None),
+        }
+    };
+    let members = object_members(src, parent_pos)?;
+    let Some(index) = members.iter().position(|m| m.key == *key) else {
+        return Ok(None);
+    };
+    let (cut_start, cut_end) = if index + 1 < members.len() {
+        // Up to the next key: drops this member, its comma and the next member's indentation
+        // gets reused from this one.
+        (members[index].start, members[index + 1].start)
+    } else if index > 0 {
+        // Last member: drop from the end of the previous value (including the comma).
+        (members[index - 1].value_end, members[index].value_end)
+    } else {
+        (members[index].start, members[index].value_end)
+    };
+    Ok(Some(format!("{}{}", &src[..cut_start], &src[cut_end..])))
+}
+
+/// Appends `json` to the array at `path`, copying the separator style of existing elements.
+/// `None` if the path is missing or not an array.
+pub fn append(src: &str, path: &[&str], json: &str) -> anyhow::Result<Option<String>> {
+    let Some(m) = find(src, path)? else {
+        return Ok(None);
+    };
+    if !src[m.value_start..].starts_with('[') {
+        return Ok(None);
+    }
+    let elements = Parser::new(src, m.value_start).array()?;
+    let Some(&(last_start, last_end)) = elements.last() else {
+        // `[]` (possibly with whitespace inside)
+        return Ok(Some(format!(
+            "{}[{}]{}",
+            &src[..m.value_start],
+            json,
+            &src[m.value_end..]
+        )));
+    };
+    let separator = if elements.len() >= 2 {
+        src[elements[elements.len() - 2].1..last_start].to_string()
+    } else {
+        let leading = &src[m.value_start + 1..last_start];
+        if leading.contains('\n') {
+            format!(",{}", leading)
+        } else {
+            ", ".to_string()
+        }
+    };
+    Ok(Some(format!(
+        "{}{}{}{}",
+        &src[..last_end],
+        separator,
+        json,
+        &src[last_end..]
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = r#"{
+  "name": "acme-app",
+  "description": "say \"hi\", {not} [a] key: \\",
+  "repository": { "type": "git", "url": "git+https://github.com/o/acme-app.git" },
+  "files": [],
+  "keywords": ["a",  "b"],
+  "workspaces": [
+    "packages/a",
+    "packages/b"
+  ],
+  "config": {}
+}
+"#;
+
+    #[test]
+    fn nested_paths_are_found_past_escaped_strings() {
+        let url = find(DOC, &["repository", "url"]).unwrap().unwrap();
+        assert_eq!(&DOC[url.value_start..url.value_end], "\"git+https://github.com/o/acme-app.git\"");
+        let description = find(DOC, &["description"]).unwrap().unwrap();
+        assert_eq!(description.key, "description");
+        assert!(DOC[description.value_start..description.value_end].ends_with("\\\\\""));
+    }
+
+    #[test]
+    fn missing_keys_and_paths_through_non_objects_are_none() {
+        assert!(find(DOC, &["license"]).unwrap().is_none());
+        assert!(find(DOC, &["name", "first"]).unwrap().is_none());
+        assert!(find(DOC, &["config", "port"]).unwrap().is_none());
+        assert!(set(DOC, &["license"], "\"MIT\"").unwrap().is_none());
+        assert!(remove(DOC, &["repository", "directory"]).unwrap().is_none());
+        assert!(append(DOC, &["name"], "\"x\"").unwrap().is_none());
+    }
+
+    #[test]
+    fn set_keeps_the_surrounding_formatting() {
+        let updated = set(DOC, &["repository", "url"], "\"git+https://github.com/o/zeta.git\"").unwrap().unwrap();
+        assert_eq!(updated, DOC.replace("o/acme-app.git", "o/zeta.git"));
+    }
+
+    #[test]
+    fn remove_drops_the_member_and_its_comma() {
+        let first = remove(DOC, &["name"]).unwrap().unwrap();
+        assert!(first.starts_with("{\n  \"description\""));
+        let last = remove(DOC, &["config"]).unwrap().unwrap();
+        assert!(last.ends_with("    \"packages/b\"\n  ]\n}\n"));
+        assert!(serde_json::from_str::<serde_json::Value>(&last).is_ok());
+        let nested = remove(DOC, &["repository", "type"]).unwrap().unwrap();
+        assert!(nested.contains("\"repository\": { \"url\": "));
+        let only = remove("{ \"a\": 1 }", &["a"]).unwrap().unwrap();
+        assert_eq!(only, "{  }");
+    }
+
+    #[test]
+    fn append_copies_the_separator_style() {
+        let multiline = append(DOC, &["workspaces"], "\"packages/c\"").unwrap().unwrap();
+        assert!(multiline.contains("    \"packages/b\",\n    \"packages/c\"\n  ]"));
+        let inline = append(DOC, &["keywords"], "\"c\"").unwrap().unwrap();
+        assert!(inline.contains("[\"a\",  \"b\",  \"c\"]"));
+        let empty = append(DOC, &["files"], "\"dist\"").unwrap().unwrap();
+        assert!(empty.contains("\"files\": [\"dist\"],"));
+        let single = append("{\"a\": [\n  1\n]}", &["a"], "2").unwrap().unwrap();
+        assert_eq!(single, "{\"a\": [\n  1,\n  2\n]}");
+    }
+
+    #[test]
+    fn trailing_commas_are_rejected_instead_of_guessed() {
+        assert!(find("{ \"a\": 1, }", &["a"]).is_err());
+        assert!(append("{ \"a\": [1, 2,] }", &["a"], "3").is_err());
+        assert!(object_members("{ \"a\" 1 }", 0).is_err());
+    }
+}
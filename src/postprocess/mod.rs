@@ -0,0 +1,74 @@
+//! Ecosystem-aware post-processing of manifests after token replacement.
+//!
+//! Plain string replacement gets most of a manifest right but cannot reset versions, fill in
+//! authors or repair paths that no longer exist. Each mode edits the files structurally.
+//! Files are read and written through the context's `fs`, the in-memory copy in dry runs.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::manifest::Manifest;
+use crate::scaffold_fs::ScaffoldFs;
+
+mod cargo;
+mod go;
+mod helm;
+pub mod json_edit;
+mod npm;
+mod reset;
+
+pub const MODES: &[&str] = &["cargo", "npm", "go", "helm", "reset"];
+
+pub struct PostprocessContext<'a> {
+    pub fs: &'a dyn ScaffoldFs,
+    pub root: &'a Path,
+    pub manifest: &'a Manifest,
+    pub new_name: &'a str,
+    pub vars: &'a BTreeMap<String, String>,
+    pub mappings: &'a [(String, String)],
+    pub dry_run: bool,
+}
+
+pub fn run(modes: &[String], ctx: &PostprocessContext) -> anyhow::Result<()> {
+    for mode in modes {
+        match mode.as_str() {
+            "cargo" => cargo::run(ctx)?,
+            "npm" => npm::run(ctx)?,
+            "go" => go::run(ctx)?,
+            "helm" => helm::run(ctx)?,
+            "reset" => reset::run(ctx)?,
+            other => anyhow::bail!(
+                "Unknown post-processing mode '{}' (available: {})",
+                other,
+                MODES.join(", ")
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// All files called `file_name` below the root, skipping VCS and build/dependency directories.
+fn find_files(ctx: &PostprocessContext, file_name: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = ctx
+        .fs
+        .walk(ctx.root, &Default::default(), &mut |e| {
+            !e.path
+                .file_name()
+                .is_some_and(|n| n == ".git" || n == "target" || n == "node_modules")
+        })
+        .into_iter()
+        .filter(|e| e.is_file && e.path.file_name() == Some(file_name.as_ref()))
+        .map(|e| e.path)
+        .collect();
+    files.sort();
+    files
+}
+
+fn write_file(ctx: &PostprocessContext, path: &Path, content: &str) -> anyhow::Result<()> {
+    ctx.fs.write(path, content.as_bytes())?;
+    if ctx.dry_run {
+        println!("DRY POST: Would update {}", path.display());
+    } else {
+        println!("POST: Updated {}", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,162 @@
+//! `npm` mode: package.json name, version reset, repository/bugs/homepage and template-only
+//! fields, edited in place without reformatting the file.
+use super::json_edit;
+use super::PostprocessContext;
+
+const INITIAL_VERSION: &str = "0.1.0";
+const TEMPLATE_SCRIPT_PREFIX: &str = "template:";
+
+pub fn run(ctx: &PostprocessContext) -> anyhow::Result<()> {
+    println!("Post-processing package.json files...");
+    let root_package = ctx.root.join("package.json");
+    for path in super::find_files(ctx, "package.json") {
+        let original = ctx.fs.read_to_string(&path)?;
+        let is_root = path == root_package;
+        let updated = edit_package_json(&original, is_root, ctx)
+            .map_err(|e| anyhow::anyhow!("Failed to edit {}: {}", path.display(), e))?;
+        if updated != original {
+            super::write_file(ctx, &path, &updated)?;
+        }
+    }
+    Ok(())
+}
+
+fn json_string(s: &str) -> String {
+    serde_json::Value::String(s.to_string()).to_string()
+}
+
+pub(super) fn string_at(src: &str, path: &[&str]) -> anyhow::Result<Option<String>> {
+    let Some(m) = json_edit::find(src, path)? else {
+        return Ok(None);
+    };
+    Ok(serde_json::from_str::<String>(&src[m.value_start..m.value_end]).ok())
+}
+
+fn is_object_at(src: &str, path: &[&str]) -> anyhow::Result<bool> {
+    Ok(json_edit::find(src, path)?
+        .map(|m| src[m.value_start..].starts_with('{'))
+        .unwrap_or(false))
+}
+
+/// Sets a string value if the key exists; `None`-returning edits leave `src` unchanged.
+pub(super) fn set_string(src: String, path: &[&str], value: &str) -> anyhow::Result<String> {
+    Ok(json_edit::set(&src, path, &json_string(value))?.unwrap_or(src))
+}
+
+/// `--var homepage=...`, else the built-in `repo_homepage`.
+fn homepage<'a>(ctx: &'a PostprocessContext) -> Option<&'a String> {
+    ctx.vars.get("homepage").or_else(|| ctx.vars.get("repo_homepage"))
+}
+
+fn edit_package_json(original: &str, is_root: bool, ctx: &PostprocessContext) -> anyhow::Result<String> {
+    let mut src = original.to_string();
+
+    if is_root {
+        let kebab = crate::split_name_to_tokens(ctx.new_name, &Default::default()).join("-");
+        // Keep an npm scope (`@org/name`), optionally overridden by the `npm_scope` variable.
+        let scope = ctx
+            .vars
+            .get("npm_scope")
+            .map(|s| s.trim_start_matches('@').to_string())
+            .or_else(|| {
+                string_at(&src, &["name"]).ok().flatten().and_then(|name| {
+                    name.strip_prefix('@')
+                        .and_then(|rest| rest.split_once('/'))
+                        .map(|(scope, _)| scope.to_string())
+                })
+            });
+        let name = match scope {
+            Some(scope) => format!("@{}/{}", scope, kebab),
+            None => kebab,
+        };
+        src = set_string(src, &["name"], &name)?;
+    }
+
+    if string_at(&src, &["version"])?.is_some() {
+        src = set_string(src, &["version"], INITIAL_VERSION)?;
+    }
+
+    if let Some(repository) = ctx.vars.get("repository") {
+        let repository = repository.trim_end_matches('/').trim_end_matches(".git");
+        if is_object_at(&src, &["repository"])? {
+            src = set_string(src, &["repository", "url"], &format!("git+{}.git", repository))?;
+        } else {
+            src = set_string(src, &["repository"], repository)?;
+        }
+        let issues = format!("{}/issues", repository);
+        if is_object_at(&src, &["bugs"])? {
+            src = set_string(src, &["bugs", "url"], &issues)?;
+        } else {
+            src = set_string(src, &["bugs"], &issues)?;
+        }
+        let homepage = homepage(ctx).cloned().unwrap_or_else(|| format!("{}#readme", repository));
+        src = set_string(src, &["homepage"], &homepage)?;
+    } else if let Some(homepage) = homepage(ctx) {
+        src = set_string(src, &["homepage"], homepage)?;
+    }
+
+    let npm = &ctx.manifest.npm;
+    if npm.strip_private {
+        if let Some(updated) = json_edit::remove(&src, &["private"])? {
+            src = updated;
+        }
+    }
+
+    if let Some(scripts) = json_edit::find(&src, &["scripts"])? {
+        if src[scripts.value_start..].starts_with('{') {
+            let names: Vec<String> = json_edit::object_members(&src, scripts.value_start)?
+                .into_iter()
+                .map(|m| m.key)
+                .filter(|k| k.starts_with(TEMPLATE_SCRIPT_PREFIX) || npm.template_scripts.contains(k))
+                .collect();
+            for name in names {
+                if let Some(updated) = json_edit::remove(&src, &["scripts", &name])? {
+                    println!("POST: removed template-only script '{}'", name);
+                    src = updated;
+                }
+            }
+        }
+    }
+
+    Ok(src)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::path::Path;
+
+    use super::*;
+    use crate::manifest::Manifest;
+    use crate::scaffold_fs::RealFs;
+
+    fn edit(src: &str, vars: &[(&str, &str)]) -> String {
+        let vars: BTreeMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let ctx = PostprocessContext {
+            fs: &RealFs,
+            root: Path::new("/project"),
+            manifest: &Manifest::default(),
+            new_name: "zeta",
+            vars: &vars,
+            mappings: &[],
+            dry_run: true,
+        };
+        edit_package_json(src, true, &ctx).unwrap()
+    }
+
+    #[test]
+    fn homepage_falls_back_to_the_built_in_repo_homepage() {
+        let src = "{\n  \"name\": \"acme-app\",\n  \"homepage\": \"https://acme.example\"\n}\n";
+        assert_eq!(
+            edit(src, &[("repo_homepage", "https://zeta.dev")]),
+            "{\n  \"name\": \"zeta\",\n  \"homepage\": \"https://zeta.dev\"\n}\n"
+        );
+        let with_repository = edit(
+            src,
+            &[("repository", "https://github.com/o/zeta"), ("repo_homepage", "https://zeta.dev")],
+        );
+        assert!(with_repository.contains("\"homepage\": \"https://zeta.dev\""));
+        let explicit = edit(src, &[("homepage", "https://docs.zeta.dev"), ("repo_homepage", "https://zeta.dev")]);
+        assert!(explicit.contains("\"homepage\": \"https://docs.zeta.dev\""));
+    }
+}
@@ -0,0 +1,93 @@
+//! `reset` mode: the project starts without the template's release history. Changelogs keep
+//! their preamble and get a single `## [Unreleased]` section; versions in Cargo, npm/Composer,
+//! Python and Helm manifests go back to the initial version.
+use std::path::Path;
+
+use toml_edit::{DocumentMut, Item};
+
+use super::PostprocessContext;
+
+const INITIAL_VERSION: &str = "0.1.0";
+const CHANGELOG_FILES: &[&str] = &["CHANGELOG.md", "CHANGELOG", "CHANGES.md", "HISTORY.md"];
+const DEFAULT_PREAMBLE: &str = "# Changelog\n\nAll notable changes to this project will be documented in this file.\n";
+
+pub fn run(ctx: &PostprocessContext) -> anyhow::Result<()> {
+    println!("Resetting changelogs and versions...");
+    for name in CHANGELOG_FILES {
+        for path in super::find_files(ctx, name) {
+            let content = ctx.fs.read_to_string(&path)?;
+            let updated = fresh_changelog(&content);
+            if updated != content {
+                super::write_file(ctx, &path, &updated)?;
+            }
+        }
+    }
+    for (name, edit) in [
+        ("Cargo.toml", reset_toml as fn(&str, &Path) -> anyhow::Result<String>),
+        ("pyproject.toml", reset_toml),
+        ("package.json", reset_json),
+        ("composer.json", reset_json),
+        ("Chart.yaml", reset_chart),
+    ] {
+        for path in super::find_files(ctx, name) {
+            let content = ctx.fs.read_to_string(&path)?;
+            let updated = edit(&content, &path)?;
+            if updated != content {
+                super::write_file(ctx, &path, &updated)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The changelog's introduction (everything before the first release heading) followed by an
+/// empty `Unreleased` section.
+fn fresh_changelog(content: &str) -> String {
+    let first_section = content
+        .match_indices("## ")
+        .find(|(at, _)| *at == 0 || content[..*at].ends_with('\n'))
+        .map(|(at, _)| at);
+    let preamble = content[..first_section.unwrap_or(content.len())].trim_end();
+    let preamble = if preamble.is_empty() || first_section.is_none() {
+        DEFAULT_PREAMBLE.trim_end()
+    } else {
+        preamble
+    };
+    format!("{}\n\n## [Unreleased]\n", preamble)
+}
+
+/// `version` of `[package]` / `[workspace.package]` (Cargo) and `[project]` / `[tool.poetry]`
+/// (Python); inherited and dynamic versions are left alone.
+fn reset_toml(content: &str, path: &Path) -> anyhow::Result<String> {
+    let mut doc: DocumentMut = content
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))?;
+    for table_path in [&["package"][..], &["workspace", "package"], &["project"], &["tool", "poetry"]] {
+        let mut item = Some(doc.as_item_mut());
+        for key in table_path {
+            item = item.and_then(Item::as_table_like_mut).and_then(|t| t.get_mut(key));
+        }
+        let Some(table) = item.and_then(Item::as_table_like_mut) else {
+            continue;
+        };
+        if table.get("version").map(Item::is_str).unwrap_or(false) {
+            super::cargo::set_value(table, "version", INITIAL_VERSION.into());
+        }
+    }
+    Ok(doc.to_string())
+}
+
+fn reset_json(content: &str, path: &Path) -> anyhow::Result<String> {
+    let edited = || -> anyhow::Result<String> {
+        if super::npm::string_at(content, &["version"])?.is_none() {
+            return Ok(content.to_string());
+        }
+        super::npm::set_string(content.to_string(), &["version"], INITIAL_VERSION)
+    };
+    edited().map_err(|e| anyhow::anyhow!("Failed to edit {}: {}", path.display(), e))
+}
+
+fn reset_chart(content: &str, _: &Path) -> anyhow::Result<String> {
+    let updated = super::helm::set_top_level_scalar(content, "version", INITIAL_VERSION);
+    Ok(super::helm::set_top_level_scalar(&updated, "appVersion", INITIAL_VERSION))
+}
@@ -0,0 +1,78 @@
+//! Structured progress events for library users (GUIs, TUIs) that want to show what a scaffold
+//! is doing without parsing its output.
+//!
+//! Events are delivered on the thread that runs the scaffold, to the callback passed to
+//! [`crate::scaffold`] or through the channel of [`crate::scaffold_async`]. Paths are relative
+//! to the project directory with `/` separators. `--porcelain` records are written from the same
+//! events.
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+use crate::scaffold_fs::ScaffoldFs;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Event {
+    /// The template is being fetched (from the cache or by cloning).
+    CloneStarted { url: String },
+    /// Template tokens were replaced inside a file (planned only, on dry runs).
+    FileReplaced { path: String },
+    /// A file or directory is renamed; deepest paths come first.
+    RenamePlanned { from: String, to: String },
+    /// A file was created in the destination (planned only, on dry runs).
+    FileAdded { path: String },
+    /// An existing destination file differs and got conflict markers or an incoming copy.
+    Conflict { path: String },
+    /// The project was written to this directory.
+    Finished { project_dir: PathBuf },
+}
+
+type Sink = Box<dyn FnMut(&Event)>;
+
+thread_local! {
+    static SINK: RefCell<Option<Sink>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with `sink` receiving the events emitted on this thread.
+pub(crate) fn with_sink<T>(sink: impl FnMut(&Event) + 'static, f: impl FnOnce() -> T) -> T {
+    let previous = SINK.with(|s| s.borrow_mut().replace(Box::new(sink)));
+    let result = f();
+    SINK.with(|s| *s.borrow_mut() = previous);
+    result
+}
+
+pub(crate) fn emit(event: Event) {
+    SINK.with(|s| {
+        if let Some(sink) = s.borrow_mut().as_mut() {
+            sink(&event);
+        }
+    });
+    crate::porcelain::record_event(&event);
+}
+
+/// Whether anybody listens, so callers can skip preparing events.
+pub(crate) fn active() -> bool {
+    crate::porcelain::enabled() || SINK.with(|s| s.borrow().is_some())
+}
+
+/// `FileAdded` for every file below `root` (outside `.git`), for a project created from scratch.
+pub(crate) fn added_tree(fs: &dyn ScaffoldFs, root: &Path) {
+    if !active() {
+        return;
+    }
+    let is_git = |path: &Path| path.file_name() == Some(".git".as_ref());
+    for entry in fs.walk(root, &Default::default(), &mut |e| !is_git(&e.path)) {
+        if !entry.is_dir && !is_git(&entry.path) {
+            emit(Event::FileAdded {
+                path: relative(root, &entry.path),
+            });
+        }
+    }
+}
+
+pub(crate) fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
@@ -0,0 +1,418 @@
+//! Prompts that work without a terminal. inquire needs a TTY; when stdin or stderr is not one (CI,
+//! `docker run` without `-t`, answers piped in), each question is printed on stderr and answered
+//! by a line on stdin instead. An empty line takes the default. At the end of input the prompt
+//! fails with a hint at `--yes`, so a job without answers stops rather than guessing.
+//!
+//! With `--prompt-timeout` (or `[prompts] timeout`), a prompt left unanswered that long stops the
+//! run, so liscaf started interactively by mistake in CI does not hang the job. Line prompts can
+//! take their default instead (`on_timeout = "default"`); the terminal UI holds the input until it
+//! returns, so there the run always stops.
+//!
+//! Messages and help lines are translated here (see [`crate::i18n`]); messages with arguments
+//! are translated by the caller before formatting.
+use std::fmt::Display;
+use std::io::{BufRead, IsTerminal, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use inquire::list_option::ListOption;
+use inquire::validator::{ErrorMessage, Validation};
+use inquire::error::InquireResult;
+use inquire::{Confirm, MultiSelect, Password, Select, Text};
+
+use crate::config::PromptsConfig;
+use crate::i18n::{self, tr};
+
+/// Shown by `--help`.
+pub const HELP: &str = "Prompts need a terminal on stdin and stderr. Without one, each question is \
+printed on stderr and answered by a line on stdin; an empty line takes the default. When stdin \
+runs out of answers, liscaf stops: pass --yes (and --var) to run unattended. With \
+--prompt-timeout, an unanswered prompt stops the run (or takes its default, see \
+--on-prompt-timeout) instead of waiting forever.";
+
+/// What an unanswered prompt does once `--prompt-timeout` has passed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnTimeout {
+    /// Take the default answer; without one, or in the terminal UI, stop.
+    Default,
+    Abort,
+}
+
+/// `--on-prompt-timeout` and `[prompts] on_timeout`.
+pub fn parse_on_timeout(raw: &str) -> Result<OnTimeout, String> {
+    match raw.to_ascii_lowercase().as_str() {
+        "default" => Ok(OnTimeout::Default),
+        "abort" => Ok(OnTimeout::Abort),
+        other => Err(format!("unknown timeout action '{}' (use default or abort)", other)),
+    }
+}
+
+static TIMEOUT: OnceLock<Option<(Duration, OnTimeout)>> = OnceLock::new();
+
+/// Applies `--prompt-timeout` and `--on-prompt-timeout` over `[prompts]`; call once at startup.
+/// Without a call, prompts wait indefinitely.
+pub fn configure(timeout: Option<u64>, on_timeout: Option<OnTimeout>, config: &PromptsConfig) -> anyhow::Result<()> {
+    let on_timeout = match (on_timeout, &config.on_timeout) {
+        (Some(on_timeout), _) => on_timeout,
+        (None, Some(raw)) => parse_on_timeout(raw).map_err(|e| anyhow::anyhow!("Invalid prompts.on_timeout: {}", e))?,
+        (None, None) => OnTimeout::Abort,
+    };
+    let timeout = timeout.or(config.timeout).filter(|&secs| secs > 0);
+    let _ = TIMEOUT.set(timeout.map(|secs| (Duration::from_secs(secs), on_timeout)));
+    Ok(())
+}
+
+fn timeout() -> Option<(Duration, OnTimeout)> {
+    TIMEOUT.get().copied().flatten()
+}
+
+/// Whether prompts can use the terminal UI.
+pub fn interactive() -> bool {
+    static INTERACTIVE: OnceLock<bool> = OnceLock::new();
+    *INTERACTIVE.get_or_init(|| std::io::stdin().is_terminal() && std::io::stderr().is_terminal())
+}
+
+/// `prompt()`, falling back to a line on stdin without a terminal.
+pub trait Ask {
+    type Output;
+    fn ask(self) -> anyhow::Result<Self::Output>;
+}
+
+/// `raw_prompt()`, falling back to a line on stdin without a terminal.
+pub trait AskRaw {
+    type Output;
+    fn ask_raw(self) -> anyhow::Result<Self::Output>;
+}
+
+/// The first line of `question`, without its trailing colon or question mark.
+fn short(question: &str) -> &str {
+    question.lines().next().unwrap_or_default().trim_end_matches([':', '?', ' '])
+}
+
+fn no_answer(question: &str, after: Duration) -> String {
+    tr!(
+        "No answer to \"{}\" within {} s; pass --yes to run unattended (is this running in CI?)",
+        short(question),
+        after.as_secs()
+    )
+}
+
+/// Runs a terminal UI prompt, stopping the run when it is not answered in time.
+fn on_terminal<T>(question: &str, prompt: impl FnOnce() -> InquireResult<T>) -> anyhow::Result<T> {
+    let Some((after, _)) = timeout() else {
+        return Ok(prompt()?);
+    };
+    let message = no_answer(question, after);
+    let mode = terminal::save();
+    // Dropping the sender when the prompt returns disarms the watchdog
+    let (answered, wait) = mpsc::channel::<()>();
+    std::thread::spawn(move || {
+        if wait.recv_timeout(after) == Err(RecvTimeoutError::Timeout) {
+            terminal::restore(mode);
+            eprintln!("\n{}", message);
+            crate::logfile::note(&format!("Error: {}", message));
+            crate::logfile::exit(1);
+        }
+    });
+    let answer = prompt();
+    drop(answered);
+    Ok(answer?)
+}
+
+/// The next line of stdin, waiting at most `after`. Lines are read on a thread, so a wait can
+/// time out; the thread ends with stdin.
+fn next_line(after: Duration) -> Result<std::io::Result<String>, RecvTimeoutError> {
+    static LINES: OnceLock<Mutex<Receiver<std::io::Result<String>>>> = OnceLock::new();
+    let lines = LINES.get_or_init(|| {
+        let (send, lines) = mpsc::channel();
+        std::thread::spawn(move || loop {
+            let mut line = String::new();
+            match std::io::stdin().lock().read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) => {
+                    if send.send(Ok(line)).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    let _ = send.send(Err(e));
+                    break;
+                }
+            }
+        });
+        Mutex::new(lines)
+    });
+    lines.lock().unwrap_or_else(|e| e.into_inner()).recv_timeout(after)
+}
+
+/// Prints `prompt` and reads the answer; `question` names the prompt when input has ended or the
+/// prompt timed out. `None` when it timed out and its default is to be taken.
+fn read_line(question: &str, prompt: &str, has_default: bool) -> anyhow::Result<Option<String>> {
+    let mut stderr = std::io::stderr();
+    let _ = write!(stderr, "{} ", prompt);
+    let _ = stderr.flush();
+    let line = match timeout() {
+        None => {
+            let mut line = String::new();
+            let read = std::io::stdin().lock().read_line(&mut line)?;
+            (read > 0).then_some(line)
+        }
+        Some((after, on_timeout)) => match next_line(after) {
+            Ok(line) => Some(line?),
+            Err(RecvTimeoutError::Disconnected) => None,
+            Err(RecvTimeoutError::Timeout) => {
+                let _ = writeln!(stderr);
+                if on_timeout == OnTimeout::Default && has_default {
+                    eprintln!("{}", tr!("No answer within {} s, taking the default", after.as_secs()));
+                    return Ok(None);
+                }
+                anyhow::bail!(no_answer(question, after));
+            }
+        },
+    };
+    let Some(line) = line else {
+        let _ = writeln!(stderr);
+        anyhow::bail!(tr!("No answer on stdin to \"{}\" (not a terminal); pass --yes to run unattended", short(question)));
+    };
+    let line = line.trim_end_matches(['\r', '\n']).to_string();
+    if !std::io::stdin().is_terminal() {
+        // Piped answers are not echoed; keep the transcript readable
+        let _ = writeln!(stderr, "{}", line);
+    }
+    Ok(Some(line))
+}
+
+fn invalid(message: ErrorMessage) {
+    match message {
+        ErrorMessage::Custom(message) => eprintln!("{}", message),
+        ErrorMessage::Default => eprintln!("{}", tr!("Invalid input")),
+    }
+}
+
+/// The option `answer` picks, by number (from 1) or by its text.
+fn pick<T: Display>(answer: &str, options: &[T]) -> Option<usize> {
+    match answer.parse::<usize>() {
+        Ok(n) if (1..=options.len()).contains(&n) => Some(n - 1),
+        _ => options.iter().position(|o| o.to_string() == answer),
+    }
+}
+
+fn list<T: Display>(message: &str, options: &[T], marked: &[usize]) {
+    eprintln!("{}", message);
+    for (i, option) in options.iter().enumerate() {
+        let mark = if marked.contains(&i) { "*" } else { " " };
+        eprintln!(" {}{:>2}) {}", mark, i + 1, option);
+    }
+}
+
+impl Ask for Confirm<'_> {
+    type Output = bool;
+
+    fn ask(mut self) -> anyhow::Result<bool> {
+        self.message = i18n::text(self.message);
+        self.help_message = self.help_message.map(i18n::text);
+        self.parser = &parse_bool;
+        self.formatter = &format_bool;
+        self.default_value_formatter = &format_default;
+        self.error_message = tr!("Invalid answer, try typing 'y' for yes or 'n' for no").to_string();
+        if interactive() {
+            return on_terminal(self.message, || self.prompt());
+        }
+        let hint = format!("({})", self.default.map_or_else(|| tr!("y/n").to_string(), format_default));
+        loop {
+            let prompt = format!("{} {}", self.message, hint);
+            let Some(answer) = read_line(self.message, &prompt, self.default.is_some())? else {
+                return Ok(self.default.unwrap_or_default());
+            };
+            match (answer.trim(), self.default) {
+                ("", Some(default)) => return Ok(default),
+                (answer, _) => match (self.parser)(answer) {
+                    Ok(value) => return Ok(value),
+                    Err(()) => eprintln!("{}", self.error_message),
+                },
+            }
+        }
+    }
+}
+
+impl Ask for Text<'_, '_> {
+    type Output = String;
+
+    fn ask(mut self) -> anyhow::Result<String> {
+        self.message = i18n::text(self.message);
+        self.help_message = self.help_message.map(i18n::text);
+        if interactive() {
+            return on_terminal(self.message, || self.prompt());
+        }
+        let default = self.default.or(self.initial_value);
+        let prompt = match default {
+            Some(default) => format!("{} [{}]", self.message, default),
+            None => self.message.to_string(),
+        };
+        'ask: loop {
+            let Some(mut answer) = read_line(self.message, &prompt, default.is_some())? else {
+                return Ok(default.unwrap_or_default().to_string());
+            };
+            if answer.is_empty() {
+                answer = default.unwrap_or_default().to_string();
+            }
+            for validator in &self.validators {
+                if let Validation::Invalid(message) = validator.validate(&answer).map_err(|e| anyhow::anyhow!(e))? {
+                    invalid(message);
+                    continue 'ask;
+                }
+            }
+            return Ok(answer);
+        }
+    }
+}
+
+/// Secrets are only read in the terminal UI: a line prompt would echo piped answers.
+impl Ask for Password<'_> {
+    type Output = String;
+
+    fn ask(mut self) -> anyhow::Result<String> {
+        self.message = i18n::text(self.message);
+        self.help_message = self.help_message.map(i18n::text);
+        if !interactive() {
+            anyhow::bail!(tr!("\"{}\" needs a terminal", short(self.message)));
+        }
+        on_terminal(self.message, || self.prompt())
+    }
+}
+
+impl<T: Display> Ask for Select<'_, T> {
+    type Output = T;
+
+    fn ask(self) -> anyhow::Result<T> {
+        self.ask_raw().map(|option| option.value)
+    }
+}
+
+impl<T: Display> AskRaw for Select<'_, T> {
+    type Output = ListOption<T>;
+
+    fn ask_raw(mut self) -> anyhow::Result<ListOption<T>> {
+        self.message = i18n::text(self.message);
+        self.help_message = self.help_message.map(i18n::text);
+        if interactive() {
+            return on_terminal(self.message, || self.raw_prompt());
+        }
+        if self.options.is_empty() {
+            anyhow::bail!(tr!("Nothing to choose for '{}'", self.message));
+        }
+        let default = self.starting_cursor.min(self.options.len() - 1);
+        list(self.message, &self.options, &[default]);
+        loop {
+            let prompt = tr!("Choose 1-{} [{}]:", self.options.len(), default + 1);
+            let index = match read_line(self.message, &prompt, true)?.as_deref().map(str::trim) {
+                None | Some("") => Some(default),
+                Some(answer) => pick(answer, &self.options),
+            };
+            match index {
+                Some(index) => {
+                    let value = self.options.into_iter().nth(index).expect("picked index is in range");
+                    return Ok(ListOption::new(index, value));
+                }
+                None => eprintln!("{}", tr!("Enter a number from 1 to {}", self.options.len())),
+            }
+        }
+    }
+}
+
+impl<T: Display> AskRaw for MultiSelect<'_, T> {
+    type Output = Vec<ListOption<T>>;
+
+    fn ask_raw(mut self) -> anyhow::Result<Vec<ListOption<T>>> {
+        self.message = i18n::text(self.message);
+        self.help_message = self.help_message.map(i18n::text);
+        if interactive() {
+            return on_terminal(self.message, || self.raw_prompt());
+        }
+        let default = self.default.clone().unwrap_or_default();
+        list(self.message, &self.options, &default);
+        loop {
+            let answer = read_line(
+                self.message,
+                tr!("Choose numbers separated by spaces or commas, 'none', or an empty line for the marked ones:"),
+                true,
+            )?;
+            let picked: Option<Vec<usize>> = match answer.as_deref().map(str::trim) {
+                None | Some("") => Some(default.clone()),
+                Some(none) if none == "none" || none == tr!("none") => Some(Vec::new()),
+                Some(answer) => answer
+                    .split([',', ' '])
+                    .filter(|a| !a.is_empty())
+                    .map(|a| pick(a, &self.options))
+                    .collect(),
+            };
+            match picked {
+                Some(picked) => {
+                    return Ok(self
+                        .options
+                        .into_iter()
+                        .enumerate()
+                        .filter(|(i, _)| picked.contains(i))
+                        .map(|(i, value)| ListOption::new(i, value))
+                        .collect());
+                }
+                None => eprintln!("{}", tr!("Enter numbers from 1 to {}", self.options.len())),
+            }
+        }
+    }
+}
+
+/// Yes or no, in English or the selected language.
+fn parse_bool(answer: &str) -> Result<bool, ()> {
+    let answer = answer.trim().to_lowercase();
+    let is = |words: [&str; 2]| words.iter().any(|w| answer == *w || answer == i18n::text(w).to_lowercase());
+    if is(["y", "yes"]) {
+        Ok(true)
+    } else if is(["n", "no"]) {
+        Ok(false)
+    } else {
+        Err(())
+    }
+}
+
+fn format_bool(answer: bool) -> String {
+    if answer { tr!("Yes") } else { tr!("No") }.to_string()
+}
+
+fn format_default(default: bool) -> String {
+    if default { tr!("Y/n") } else { tr!("y/N") }.to_string()
+}
+
+/// The terminal's mode, put back when a timed-out prompt leaves it in raw mode.
+#[cfg(unix)]
+mod terminal {
+    pub struct Mode(Option<libc::termios>);
+
+    pub fn save() -> Mode {
+        let mut termios = std::mem::MaybeUninit::<libc::termios>::uninit();
+        // SAFETY: tcgetattr fills `termios` when it succeeds.
+        let saved = unsafe { libc::tcgetattr(libc::STDIN_FILENO, termios.as_mut_ptr()) == 0 };
+        Mode(saved.then(|| unsafe { termios.assume_init() }))
+    }
+
+    pub fn restore(mode: Mode) {
+        if let Some(termios) = mode.0 {
+            // SAFETY: sets the attributes read by `save`.
+            unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) };
+        }
+        // Select prompts hide the cursor
+        eprint!("\x1b[?25h");
+    }
+}
+
+#[cfg(not(unix))]
+mod terminal {
+    pub struct Mode;
+
+    pub fn save() -> Mode {
+        Mode
+    }
+
+    pub fn restore(_mode: Mode) {}
+}
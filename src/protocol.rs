@@ -0,0 +1,82 @@
+//! Clone protocol preference (`protocol = "ssh"` in the configuration): template URLs are
+//! rewritten between their HTTPS and SSH forms before git sees them, for networks where only one
+//! of the two gets through.
+use std::sync::OnceLock;
+
+use crate::config::Config;
+
+struct Preference {
+    ssh: bool,
+    /// Hosts the rewrite applies to; all when empty.
+    hosts: Vec<String>,
+}
+
+static PREFERENCE: OnceLock<Preference> = OnceLock::new();
+
+/// Applies the configured preference; call once at startup after the configuration is loaded.
+pub fn configure(config: &Config) {
+    if let Some(ref protocol) = config.protocol {
+        let _ = PREFERENCE.set(Preference {
+            ssh: protocol == "ssh",
+            hosts: config.protocol_hosts.iter().map(|h| h.to_lowercase()).collect(),
+        });
+    }
+}
+
+/// `url` in the preferred form, or unchanged without a preference, for other hosts and for URLs
+/// that are not HTTPS or SSH.
+pub fn rewrite(url: &str) -> String {
+    let Some(preference) = PREFERENCE.get() else {
+        return url.to_string();
+    };
+    let converted = if preference.ssh { to_ssh(url) } else { to_https(url) };
+    match converted {
+        Some((host, rewritten))
+            if preference.hosts.is_empty() || preference.hosts.contains(&host.to_lowercase()) =>
+        {
+            rewritten
+        }
+        _ => url.to_string(),
+    }
+}
+
+/// `https://host/owner/repo` → (`host`, `git@host:owner/repo.git`).
+pub fn to_ssh(url: &str) -> Option<(String, String)> {
+    let rest = strip_prefix_ignore_case(url, "https://")?;
+    let (host, path) = rest.split_once('/')?;
+    // Credentials in the URL belong to HTTPS; the SSH form always logs in as git
+    let host = host.rsplit('@').next().unwrap_or(host);
+    let path = path.trim_end_matches('/');
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+    let suffix = if path.ends_with(".git") { "" } else { ".git" };
+    Some((host.to_string(), format!("git@{}:{}{}", host, path, suffix)))
+}
+
+/// `git@host:owner/repo.git` or `ssh://git@host[:port]/owner/repo.git` →
+/// (`host`, `https://host/owner/repo.git`).
+pub fn to_https(url: &str) -> Option<(String, String)> {
+    let (host, path) = if let Some(rest) = strip_prefix_ignore_case(url, "ssh://") {
+        let (authority, path) = rest.split_once('/')?;
+        let host = authority.rsplit('@').next().unwrap_or(authority);
+        // The SSH port means nothing to HTTPS
+        (host.split(':').next().unwrap_or(host), path)
+    } else if url.contains("://") {
+        return None;
+    } else {
+        let (user_host, path) = url.split_once(':')?;
+        let (_, host) = user_host.split_once('@')?;
+        (host, path.trim_start_matches('/'))
+    };
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+    Some((host.to_string(), format!("https://{}/{}", host, path)))
+}
+
+fn strip_prefix_ignore_case<'u>(url: &'u str, prefix: &str) -> Option<&'u str> {
+    url.get(..prefix.len())
+        .filter(|start| start.eq_ignore_ascii_case(prefix))
+        .map(|_| &url[prefix.len()..])
+}
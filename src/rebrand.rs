@@ -0,0 +1,229 @@
+//! `liscaf rebrand <list> <old> <new>`: runs the in-place rename on every repository or directory
+//! of a list and reports the outcome of each — for when the product itself is renamed.
+//!
+//! The list names one local directory (relative to the list file) or repository URL per line;
+//! `#` starts a comment. Repositories are cloned into `--workdir`. With `--branch`, each git
+//! repository gets the changes as one commit on a new branch, ready to push.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::net::Failure;
+use crate::scaffold_fs::{MemoryFs, RealFs, ScaffoldFs};
+use crate::tokenizer::{generate_variant_mappings, split_name_to_tokens, TokenizerRules};
+use crate::{filter, is_supported_repo_url, normalize_repo_url};
+
+pub struct RebrandOptions<'a> {
+    pub from: &'a str,
+    pub to: &'a str,
+    /// Create this branch and commit the changes on it, in every git repository.
+    pub branch: Option<&'a str>,
+    pub message: Option<&'a str>,
+    /// Where repository URLs are cloned.
+    pub workdir: &'a Path,
+    pub skip_variants: &'a [String],
+    pub acronyms: &'a [String],
+    pub only: &'a [String],
+    pub exclude: &'a [String],
+    pub dry_run: bool,
+}
+
+struct Changes {
+    dir: PathBuf,
+    files: usize,
+    renames: usize,
+    /// The branch the changes were committed on.
+    committed: Option<String>,
+}
+
+pub fn run_rebrand(list: &Path, options: &RebrandOptions) -> anyhow::Result<()> {
+    let content = fs::read_to_string(list)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", list.display(), e))?;
+    let targets: Vec<&str> = content
+        .lines()
+        .map(|l| l.split('#').next().unwrap_or("").trim())
+        .filter(|l| !l.is_empty())
+        .collect();
+    if targets.is_empty() {
+        anyhow::bail!("{} lists no repositories or directories", list.display());
+    }
+    crate::tokenizer::validate_variant_names(options.skip_variants)?;
+    let base_dir = list
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+        .unwrap_or(std::env::current_dir()?);
+
+    let rules = TokenizerRules::default().with_acronyms(options.acronyms);
+    let mappings = generate_variant_mappings(
+        &split_name_to_tokens(options.from, &rules),
+        &split_name_to_tokens(options.to, &rules),
+        options.skip_variants,
+        &rules,
+    );
+    println!("Rebranding '{}' to '{}' in {} location(s)", options.from, options.to, targets.len());
+    for (o, n) in &mappings {
+        println!("  {} -> {}", o, n);
+    }
+
+    let mut results = Vec::new();
+    for (i, target) in targets.iter().enumerate() {
+        println!("\n=== [{}/{}] {} ===", i + 1, targets.len(), target);
+        let outcome = rebrand_one(target, &base_dir, &mappings, options);
+        if let Err(ref e) = outcome {
+            println!("ERROR: {}", e);
+        }
+        results.push((*target, outcome));
+    }
+
+    let failed = results.iter().filter(|(_, o)| o.is_err()).count();
+    println!(
+        "\nRebrand summary: {} succeeded, {} failed",
+        results.len() - failed,
+        failed
+    );
+    let width = results.iter().map(|(t, _)| t.len()).max().unwrap_or(0);
+    for (target, outcome) in &results {
+        match outcome {
+            Ok(changes) => {
+                let mut line = format!(
+                    "  {:7} {:width$}  {} file(s), {} rename(s)",
+                    if options.dry_run { "DRY" } else { "OK" },
+                    target,
+                    changes.files,
+                    changes.renames,
+                    width = width
+                );
+                if let Some(ref branch) = changes.committed {
+                    line.push_str(&format!(", committed on {}", branch));
+                }
+                if !changes.dir.ends_with(target) {
+                    line.push_str(&format!(" ({})", changes.dir.display()));
+                }
+                println!("{}", line);
+            }
+            Err(e) => println!("  FAILED  {:width$}  {}", target, e, width = width),
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{} of {} locations failed", failed, results.len());
+    }
+    Ok(())
+}
+
+fn rebrand_one(
+    target: &str,
+    base_dir: &Path,
+    mappings: &[(String, String)],
+    options: &RebrandOptions,
+) -> anyhow::Result<Changes> {
+    let local = base_dir.join(target);
+    let url = normalize_repo_url(target);
+    let dir = if local.is_dir() || !is_supported_repo_url(&url) {
+        if !local.is_dir() {
+            anyhow::bail!("Directory does not exist: {}", local.display());
+        }
+        local
+    } else {
+        clone(&url, options.workdir)?
+    };
+
+    let is_repo = dir.join(".git").exists();
+    let branch = options.branch.filter(|_| is_repo);
+    if options.branch.is_some() && !is_repo {
+        println!("WARN: {} is not a git repository; changes are left uncommitted", dir.display());
+    }
+    if let Some(branch) = branch {
+        if !git_output(&dir, &["status", "--porcelain"])?.is_empty() {
+            anyhow::bail!("{} has uncommitted changes; commit or stash them first", dir.display());
+        }
+        if !options.dry_run {
+            git(&dir, &["checkout", "-b", branch])?;
+        }
+    }
+
+    let filter = filter::PathFilter::new(options.only, options.exclude)?
+        .with_max_file_size(filter::DEFAULT_MAX_FILE_SIZE)
+        .with_skip_dirs(crate::skip_dir_names(false, &[], &[]));
+    let simulated;
+    let fs: &dyn ScaffoldFs = if options.dry_run {
+        simulated = MemoryFs::snapshot(&dir, &filter)?;
+        &simulated
+    } else {
+        &RealFs
+    };
+    let files = crate::replace_in_files(fs, &dir, mappings, &filter, options.dry_run)?;
+    let renames = crate::rename_paths(fs, &dir, mappings, &filter, options.dry_run)?;
+
+    let mut committed = None;
+    if let Some(branch) = branch.filter(|_| !options.dry_run && files + renames > 0) {
+        let message = options
+            .message
+            .map(String::from)
+            .unwrap_or_else(|| format!("Rename {} to {}", options.from, options.to));
+        git(&dir, &["add", "--all"])?;
+        git(&dir, &["commit", "--quiet", "-m", &message])?;
+        println!("Committed on branch {}", branch);
+        committed = Some(branch.to_string());
+    }
+    Ok(Changes {
+        dir,
+        files,
+        renames,
+        committed,
+    })
+}
+
+/// Clones `url` into `workdir/<repository name>`, or reuses an earlier clone there.
+fn clone(url: &str, workdir: &Path) -> anyhow::Result<PathBuf> {
+    let name = url
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or("repo")
+        .trim_end_matches(".git");
+    let dest = workdir.join(name);
+    if dest.join(".git").is_dir() {
+        println!("Using existing clone {}", dest.display());
+        return Ok(dest);
+    }
+    fs::create_dir_all(workdir)?;
+    let url = crate::protocol::rewrite(url);
+    println!("Cloning {} into {}", url, dest.display());
+    crate::net::with_retries(&format!("Cloning {}", url), || {
+        let _ = fs::remove_dir_all(&dest);
+        let output = crate::net::git()
+            .arg("clone")
+            .arg(&url)
+            .arg(&dest)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .map_err(|e| Failure::Permanent(anyhow::anyhow!("Failed to run git: {}", e)))?;
+        if output.status.success() {
+            return Ok(());
+        }
+        Err(crate::git_failure("git clone", &output))
+    })?;
+    Ok(dest)
+}
+
+fn git(dir: &Path, args: &[&str]) -> anyhow::Result<()> {
+    git_output(dir, args).map(drop)
+}
+
+fn git_output(dir: &Path, args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| anyhow::anyhow!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        let (Failure::Transient(e) | Failure::Permanent(e)) =
+            crate::git_failure(&format!("git {}", args[0]), &output);
+        return Err(e);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,87 @@
+//! Recently used templates (`recent.json` next to the user configuration), offered first in the
+//! template picker so repeat scaffolds skip browsing the registries.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::TemplateEntry;
+
+const RECENT_FILE: &str = "recent.json";
+/// Entries kept; older ones are dropped.
+const MAX_RECENT: usize = 10;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RecentTemplate {
+    pub url: String,
+    pub label: String,
+    pub commit: Option<String>,
+    /// FNV-1a of the answers of the last scaffold, to tell apart repeat runs with the same answers.
+    pub answers_hash: String,
+    pub last_used: String,
+}
+
+impl RecentTemplate {
+    pub fn entry(&self) -> TemplateEntry {
+        TemplateEntry {
+            name: self.label.clone(),
+            label: self.label.clone(),
+            url: self.url.clone(),
+            commit: self.commit.clone(),
+        }
+    }
+}
+
+fn recent_path() -> Option<PathBuf> {
+    Some(crate::config::config_dir()?.join(RECENT_FILE))
+}
+
+/// Recently used templates, most recent first; empty if none were recorded or the file is
+/// unreadable.
+pub fn load() -> Vec<RecentTemplate> {
+    recent_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Moves the template to the top of the list. Failures only print a warning.
+pub fn record(
+    url: &str,
+    label: &str,
+    commit: Option<&str>,
+    answers: &BTreeMap<String, String>,
+) {
+    let Some(path) = recent_path() else {
+        return;
+    };
+    let mut recent = load();
+    recent.retain(|r| r.url != url);
+    recent.insert(
+        0,
+        RecentTemplate {
+            url: url.to_string(),
+            label: label.to_string(),
+            commit: commit.map(String::from),
+            answers_hash: answers_hash(answers),
+            last_used: chrono::Utc::now().to_rfc3339(),
+        },
+    );
+    recent.truncate(MAX_RECENT);
+    let written = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| fs::write(&path, serde_json::to_string_pretty(&recent)?));
+    if let Err(e) = written {
+        println!("Warning: could not record recent template in {}: {}", path.display(), e);
+    }
+}
+
+fn answers_hash(answers: &BTreeMap<String, String>) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for (key, value) in answers {
+        for b in key.bytes().chain([0]).chain(value.bytes()).chain([0]) {
+            hash = (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
@@ -0,0 +1,175 @@
+//! Several template registries at once: the `--templates` source plus the `[[registries]]` in
+//! the user configuration. When more than one registry lists a template with the same name, the
+//! registry with the highest priority wins (ties go to the one configured first), much like
+//! cargo's source replacement, so an internal registry can override public templates.
+//!
+//! Registries served over HTTP(S) may require credentials: a bearer token or basic auth from the
+//! registry's config entry, or a token in `LISCAF_REGISTRY_<NAME>_TOKEN`. Failing those, a token
+//! stored in the OS keychain is used; when the registry still refuses, liscaf asks for a token and
+//! offers to store it there.
+use std::collections::BTreeMap;
+
+use crate::config::RegistryConfig;
+use crate::keychain;
+use crate::net::{Auth, Unauthorized};
+use crate::TemplateEntry;
+
+/// Name of the registry given by `--templates` / `LISCAF_TEMPLATES`.
+pub const DEFAULT_NAME: &str = "default";
+
+#[derive(Debug, Clone)]
+pub struct Registry {
+    pub name: String,
+    pub source: String,
+    pub priority: i32,
+    pub auth: Option<Auth>,
+}
+
+/// Configured registries, highest priority first. The `--templates` source is included with
+/// priority 0 unless a configured registry already uses it.
+pub fn registries(templates_source: &str, configured: &[RegistryConfig]) -> Vec<Registry> {
+    let mut registries: Vec<Registry> = configured
+        .iter()
+        .map(|r| Registry {
+            name: r.name.clone(),
+            source: r.source.clone(),
+            priority: r.priority,
+            auth: auth(&r.name, &r.source, Some(r)),
+        })
+        .collect();
+    if !templates_source.is_empty() && !registries.iter().any(|r| r.source == templates_source) {
+        registries.push(Registry {
+            name: DEFAULT_NAME.to_string(),
+            source: templates_source.to_string(),
+            priority: 0,
+            auth: auth(DEFAULT_NAME, templates_source, None),
+        });
+    }
+    // Stable sort: equal priorities keep their configured order.
+    registries.sort_by_key(|r| std::cmp::Reverse(r.priority));
+    registries
+}
+
+/// Credentials from the config entry, else `LISCAF_REGISTRY_<NAME>_TOKEN`, else the keychain.
+fn auth(name: &str, source: &str, config: Option<&RegistryConfig>) -> Option<Auth> {
+    let from_env = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+    let missing = |key: &str| {
+        println!("Warning: {} for registry '{}' is not set", key, name);
+        None
+    };
+    let from_keychain = || {
+        let http = source.starts_with("http://") || source.starts_with("https://");
+        http.then(|| keychain::get(&keychain::registry_account(name))).flatten()
+    };
+    if let Some(config) = config {
+        if let Some(ref token) = config.token {
+            return Some(Auth::Bearer(token.clone()));
+        }
+        if let Some(ref key) = config.token_env {
+            // The keychain stands in for an unset variable, so only warn without either
+            return from_env(key)
+                .or_else(from_keychain)
+                .or_else(|| missing(key))
+                .map(Auth::Bearer);
+        }
+        if let Some(ref username) = config.username {
+            let password = match (&config.password, &config.password_env) {
+                (Some(password), _) => Some(password.clone()),
+                (None, Some(key)) => from_env(key).or_else(|| missing(key)),
+                (None, None) => Some(String::new()),
+            };
+            return password.map(|password| Auth::Basic {
+                username: username.clone(),
+                password,
+            });
+        }
+    }
+    let key: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    std::env::var(format!("LISCAF_REGISTRY_{}_TOKEN", key))
+        .ok()
+        .filter(|v| !v.is_empty())
+        .or_else(from_keychain)
+        .map(Auth::Bearer)
+}
+
+/// Templates of `registry`; when it answers 401 or 403, asks for a token and tries once more.
+fn load_registry(registry: &Registry) -> anyhow::Result<Vec<TemplateEntry>> {
+    let error = match crate::load_template_entries(&registry.source, registry.auth.as_ref()) {
+        // Basic auth is configured, not typed in
+        Err(e) if e.downcast_ref::<Unauthorized>().is_some()
+            && !matches!(registry.auth, Some(Auth::Basic { .. })) => e,
+        result => return result,
+    };
+    let account = keychain::registry_account(&registry.name);
+    // A stored token that is refused is stale
+    if let (Some(Auth::Bearer(token)), Some(stored)) = (&registry.auth, keychain::get(&account)) {
+        if *token == stored && keychain::forget(&account).unwrap_or(false) {
+            println!("KEYCHAIN: Removed the rejected token '{}'", account);
+        }
+    }
+    match keychain::prompt_token(&format!("registry '{}' ({})", registry.name, registry.source), &account) {
+        Some(token) => crate::load_template_entries(&registry.source, Some(&Auth::Bearer(token))),
+        None => Err(error),
+    }
+}
+
+/// Templates from all registries with shadowed names removed. Registries that fail to load are
+/// reported and skipped; the error is returned only if none could be loaded.
+pub fn load_templates(registries: &[Registry]) -> anyhow::Result<Vec<TemplateEntry>> {
+    let mut resolved: Vec<TemplateEntry> = Vec::new();
+    let mut owners: BTreeMap<String, &str> = BTreeMap::new();
+    let mut last_error = None;
+    let mut loaded = 0;
+    for registry in registries {
+        let entries = match load_registry(registry) {
+            Ok(entries) => entries,
+            Err(e) => {
+                println!(
+                    "Warning: failed to load templates from registry '{}' ({}): {}",
+                    registry.name, registry.source, e
+                );
+                last_error = Some(e);
+                continue;
+            }
+        };
+        loaded += 1;
+        for mut entry in entries {
+            let key = entry.name.to_lowercase();
+            if let Some(owner) = owners.get(&key) {
+                println!(
+                    "Warning: template '{}' from registry '{}' is shadowed by registry '{}'",
+                    entry.name, registry.name, owner
+                );
+                continue;
+            }
+            owners.insert(key, &registry.name);
+            if registries.len() > 1 {
+                entry.label = format!("{} [{}]", entry.label, registry.name);
+            }
+            resolved.push(entry);
+        }
+    }
+    match last_error {
+        Some(e) if loaded == 0 => Err(e),
+        _ => Ok(resolved),
+    }
+}
+
+/// `liscaf list`: one template per line with its URL, and the pinned commit if any.
+pub fn print_templates(templates: &[TemplateEntry]) {
+    if templates.is_empty() {
+        println!("No templates found");
+        return;
+    }
+    let width = templates.iter().map(|t| t.label.chars().count()).max().unwrap_or(0);
+    for template in templates {
+        let mut line = format!("{:<width$}  {}", template.label, template.url, width = width);
+        if let Some(ref commit) = template.commit {
+            line.push_str(&format!(" @ {}", &commit[..commit.len().min(12)]));
+        }
+        println!("{}", line);
+    }
+}
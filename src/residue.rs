@@ -0,0 +1,59 @@
+//! `--assert-no-residue`: after scaffolding, every variant of the template name that was mapped
+//! must be gone from file contents and paths. Occurrences inside the new name (`acme-app` in
+//! `acme-app-v2`) do not count, nor does the scaffold metadata, which records the template.
+use std::fs;
+use std::path::Path;
+
+use walkdir::WalkDir;
+
+const METADATA_FILE: &str = ".scaffold.json";
+
+/// Leftover occurrences below `root` as `path:line: variant` (`path: variant` for names), with
+/// paths relative to `root`.
+pub fn find(root: &Path, mappings: &[(String, String)]) -> Vec<String> {
+    let mut found = Vec::new();
+    let walker = WalkDir::new(root)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git");
+    for entry in walker.filter_map(|e| e.ok()) {
+        let rel = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        let rel_display = rel.to_string_lossy().replace('\\', "/");
+        if let Some(name) = entry.file_name().to_str() {
+            for old in leftovers(name, mappings) {
+                found.push(format!("{}: {} (in the name)", rel_display, old));
+            }
+        }
+        if !entry.file_type().is_file() || entry.file_name() == METADATA_FILE {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        for (i, line) in content.lines().enumerate() {
+            for old in leftovers(line, mappings) {
+                found.push(format!("{}:{}: {}", rel_display, i + 1, old));
+            }
+        }
+    }
+    found
+}
+
+/// The mapping sources still present in `text`, outside occurrences of their replacement.
+fn leftovers<'a>(text: &str, mappings: &'a [(String, String)]) -> Vec<&'a str> {
+    mappings
+        .iter()
+        .filter(|(old, new)| {
+            let covered: Vec<(usize, usize)> = if new.contains(old.as_str()) {
+                text.match_indices(new.as_str()).map(|(at, m)| (at, at + m.len())).collect()
+            } else {
+                Vec::new()
+            };
+            text.match_indices(old.as_str()).any(|(at, m)| {
+                !covered.iter().any(|&(start, end)| start <= at && at + m.len() <= end)
+            })
+        })
+        .map(|(old, _)| old.as_str())
+        .collect()
+}
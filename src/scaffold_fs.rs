@@ -0,0 +1,462 @@
+//! The file operations of the scaffold pipeline (replacement, renaming, placeholders, copyright
+//! and license updates, post-processing and merging), behind a trait so the pipeline can run
+//! against the real filesystem or an in-memory tree (tests, dry runs).
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use walkdir::WalkDir;
+
+use crate::filter::PathFilter;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkEntry {
+    pub path: PathBuf,
+    /// 0 for the walk root.
+    pub depth: usize,
+    pub is_dir: bool,
+    pub is_file: bool,
+    pub len: u64,
+}
+
+/// Bounds of a walk (`--max-depth`, `--include-hidden`, `--follow-symlinks`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalkOptions {
+    /// Levels below the walk root; the root's children are at depth 1.
+    pub max_depth: Option<usize>,
+    /// Walk entries whose name starts with `.`.
+    pub include_hidden: bool,
+    /// Walk into symlinked directories and read symlinked files instead of skipping them.
+    pub follow_symlinks: bool,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        WalkOptions {
+            max_depth: None,
+            include_hidden: true,
+            follow_symlinks: false,
+        }
+    }
+}
+
+impl WalkOptions {
+    /// Whether an entry at `depth` named `name` is part of the walk.
+    pub fn includes(&self, depth: usize, name: Option<&OsStr>) -> bool {
+        if depth == 0 {
+            return true;
+        }
+        if self.max_depth.is_some_and(|max| depth > max) {
+            return false;
+        }
+        self.include_hidden || !name.is_some_and(|n| n.to_string_lossy().starts_with('.'))
+    }
+}
+
+pub trait ScaffoldFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+    /// Creates or truncates the file; the parent directory must exist.
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+    /// Moves a file or a directory with everything below it.
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+    fn exists(&self, path: &Path) -> bool;
+    /// Size and modification time of a file, where the filesystem tracks them.
+    fn stat(&self, path: &Path) -> Option<(u64, SystemTime)>;
+    /// Gives the new file `to` the mode a copy of `from` should have (see `permissions`).
+    fn copy_mode(&self, from: &Path, to: &Path) -> io::Result<()>;
+    /// `root` and everything below it within `options`, parents before children and siblings in
+    /// name order. Directories for which `descend` returns false are left out together with their
+    /// contents.
+    fn walk(
+        &self,
+        root: &Path,
+        options: &WalkOptions,
+        descend: &mut dyn FnMut(&WalkEntry) -> bool,
+    ) -> Vec<WalkEntry>;
+}
+
+/// The real filesystem.
+pub struct RealFs;
+
+impl ScaffoldFs for RealFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(path, contents)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn stat(&self, path: &Path) -> Option<(u64, SystemTime)> {
+        let metadata = fs::metadata(path).ok().filter(|m| m.is_file())?;
+        Some((metadata.len(), metadata.modified().ok()?))
+    }
+
+    fn copy_mode(&self, from: &Path, to: &Path) -> io::Result<()> {
+        crate::permissions::copy_mode(from, to)
+    }
+
+    fn walk(
+        &self,
+        root: &Path,
+        options: &WalkOptions,
+        descend: &mut dyn FnMut(&WalkEntry) -> bool,
+    ) -> Vec<WalkEntry> {
+        let mut entries = Vec::new();
+        let walker = WalkDir::new(root)
+            .follow_links(options.follow_symlinks)
+            .sort_by_file_name()
+            .into_iter();
+        let walker = walker.filter_entry(|e| {
+            if !options.includes(e.depth(), Some(e.file_name())) {
+                return false;
+            }
+            let entry = WalkEntry {
+                path: e.path().to_path_buf(),
+                depth: e.depth(),
+                is_dir: e.file_type().is_dir(),
+                is_file: e.file_type().is_file(),
+                len: e.metadata().map(|m| m.len()).unwrap_or(0),
+            };
+            if entry.is_dir && !descend(&entry) {
+                return false;
+            }
+            entries.push(entry);
+            true
+        });
+        walker.for_each(drop);
+        entries
+    }
+}
+
+/// Prints a unified diff of every text file rewritten through it (`--diff`), then passes the
+/// operation on.
+pub struct DiffFs<'a> {
+    pub inner: &'a dyn ScaffoldFs,
+    /// Diff headers show paths relative to this directory.
+    pub root: &'a Path,
+}
+
+impl ScaffoldFs for DiffFs<'_> {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.inner.read(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let before = self.inner.read(path).unwrap_or_default();
+        if let (Ok(old), Ok(new)) = (std::str::from_utf8(&before), std::str::from_utf8(contents)) {
+            let name = path.strip_prefix(self.root).unwrap_or(path).display().to_string();
+            let diff = similar::TextDiff::from_lines(old, new);
+            let diff = diff
+                .unified_diff()
+                .header(&format!("a/{}", name), &format!("b/{}", name))
+                .to_string();
+            print!("{}", crate::color::paint_diff(&diff));
+        }
+        self.inner.write(path, contents)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.inner.remove_file(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.inner.create_dir_all(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.inner.rename(from, to)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.inner.exists(path)
+    }
+
+    fn stat(&self, path: &Path) -> Option<(u64, SystemTime)> {
+        self.inner.stat(path)
+    }
+
+    fn copy_mode(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.inner.copy_mode(from, to)
+    }
+
+    fn walk(
+        &self,
+        root: &Path,
+        options: &WalkOptions,
+        descend: &mut dyn FnMut(&WalkEntry) -> bool,
+    ) -> Vec<WalkEntry> {
+        self.inner.walk(root, options, descend)
+    }
+}
+
+/// Serves paths below `root` from `upper` and everything else from `lower`, e.g. a dry run's
+/// simulated project over the real destination it is merged into.
+pub struct OverlayFs<'a> {
+    pub upper: &'a dyn ScaffoldFs,
+    pub root: &'a Path,
+    pub lower: &'a dyn ScaffoldFs,
+}
+
+impl OverlayFs<'_> {
+    fn layer(&self, path: &Path) -> &dyn ScaffoldFs {
+        if path.starts_with(self.root) {
+            self.upper
+        } else {
+            self.lower
+        }
+    }
+}
+
+impl ScaffoldFs for OverlayFs<'_> {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.layer(path).read(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.layer(path).write(path, contents)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.layer(path).remove_file(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.layer(path).create_dir_all(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        if from.starts_with(self.root) != to.starts_with(self.root) {
+            return Err(io::Error::other("cannot move files between layers"));
+        }
+        self.layer(from).rename(from, to)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.layer(path).exists(path)
+    }
+
+    fn stat(&self, path: &Path) -> Option<(u64, SystemTime)> {
+        self.layer(path).stat(path)
+    }
+
+    /// Modes are taken from the target's layer; a memory layer keeps none.
+    fn copy_mode(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.layer(to).copy_mode(from, to)
+    }
+
+    fn walk(
+        &self,
+        root: &Path,
+        options: &WalkOptions,
+        descend: &mut dyn FnMut(&WalkEntry) -> bool,
+    ) -> Vec<WalkEntry> {
+        self.layer(root).walk(root, options, descend)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Dir,
+    File(Vec<u8>),
+}
+
+/// An in-memory tree of absolute or relative paths.
+#[derive(Debug, Default)]
+pub struct MemoryFs {
+    nodes: RefCell<BTreeMap<PathBuf, Node>>,
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        MemoryFs::default()
+    }
+
+    /// Adds a file, creating its parent directories.
+    #[cfg(test)]
+    pub fn with_file(self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Self {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            let _ = self.create_dir_all(parent);
+        }
+        self.nodes
+            .borrow_mut()
+            .insert(path.to_path_buf(), Node::File(contents.as_ref().to_vec()));
+        self
+    }
+
+    /// Copies a directory from the real filesystem, leaving out what the walks of `filter` skip.
+    pub fn snapshot(root: &Path, filter: &PathFilter) -> io::Result<Self> {
+        let memory = MemoryFs::new();
+        let entries = RealFs.walk(root, filter.walk_options(), &mut |e| {
+            e.depth == 0 || !e.path.file_name().is_some_and(|n| filter.skips_dir(n))
+        });
+        for entry in entries {
+            if entry.is_dir {
+                memory.create_dir_all(&entry.path)?;
+            } else if entry.is_file {
+                memory.write(&entry.path, &fs::read(&entry.path)?)?;
+            }
+        }
+        Ok(memory)
+    }
+
+    /// Paths of all files, in name order.
+    #[cfg(test)]
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.nodes
+            .borrow()
+            .iter()
+            .filter(|(_, node)| matches!(node, Node::File(_)))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    fn not_found(path: &Path) -> io::Error {
+        io::Error::new(io::ErrorKind::NotFound, format!("{} not found", path.display()))
+    }
+}
+
+impl ScaffoldFs for MemoryFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        match self.nodes.borrow().get(path) {
+            Some(Node::File(contents)) => Ok(contents.clone()),
+            Some(Node::Dir) => Err(io::Error::other(format!("{} is a directory", path.display()))),
+            None => Err(Self::not_found(path)),
+        }
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mut nodes = self.nodes.borrow_mut();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            if !matches!(nodes.get(parent), Some(Node::Dir)) {
+                return Err(Self::not_found(parent));
+            }
+        }
+        if matches!(nodes.get(path), Some(Node::Dir)) {
+            return Err(io::Error::other(format!("{} is a directory", path.display())));
+        }
+        nodes.insert(path.to_path_buf(), Node::File(contents.to_vec()));
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let mut nodes = self.nodes.borrow_mut();
+        match nodes.get(path) {
+            Some(Node::File(_)) => {
+                nodes.remove(path);
+                Ok(())
+            }
+            Some(Node::Dir) => Err(io::Error::other(format!("{} is a directory", path.display()))),
+            None => Err(Self::not_found(path)),
+        }
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut nodes = self.nodes.borrow_mut();
+        for dir in path.ancestors().filter(|p| !p.as_os_str().is_empty()) {
+            match nodes.get(dir) {
+                Some(Node::File(_)) => {
+                    return Err(io::Error::other(format!("{} is a file", dir.display())));
+                }
+                Some(Node::Dir) => break,
+                None => {
+                    nodes.insert(dir.to_path_buf(), Node::Dir);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut nodes = self.nodes.borrow_mut();
+        if !nodes.contains_key(from) {
+            return Err(Self::not_found(from));
+        }
+        let moved: Vec<PathBuf> = nodes.keys().filter(|p| p.starts_with(from)).cloned().collect();
+        for path in moved {
+            let node = nodes.remove(&path).expect("key listed above");
+            let rest = path.strip_prefix(from).expect("filtered by prefix");
+            let target = if rest.as_os_str().is_empty() { to.to_path_buf() } else { to.join(rest) };
+            nodes.insert(target, node);
+        }
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.nodes.borrow().contains_key(path)
+    }
+
+    /// Memory trees keep no modification times.
+    fn stat(&self, _: &Path) -> Option<(u64, SystemTime)> {
+        None
+    }
+
+    /// Memory trees keep no modes.
+    fn copy_mode(&self, _: &Path, _: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Memory trees have no symlinks, so `follow_symlinks` does not matter.
+    fn walk(
+        &self,
+        root: &Path,
+        options: &WalkOptions,
+        descend: &mut dyn FnMut(&WalkEntry) -> bool,
+    ) -> Vec<WalkEntry> {
+        let nodes = self.nodes.borrow();
+        let mut entries = Vec::new();
+        let mut pruned: Vec<PathBuf> = Vec::new();
+        for (path, node) in nodes.range(root.to_path_buf()..) {
+            if !path.starts_with(root) {
+                break;
+            }
+            if pruned.iter().any(|p| path.starts_with(p)) {
+                continue;
+            }
+            let entry = WalkEntry {
+                path: path.clone(),
+                depth: path.strip_prefix(root).map(|r| r.components().count()).unwrap_or(0),
+                is_dir: matches!(node, Node::Dir),
+                is_file: matches!(node, Node::File(_)),
+                len: match node {
+                    Node::File(contents) => contents.len() as u64,
+                    Node::Dir => 0,
+                },
+            };
+            if !options.includes(entry.depth, path.file_name())
+                || (entry.is_dir && !descend(&entry))
+            {
+                pruned.push(path.clone());
+                continue;
+            }
+            entries.push(entry);
+        }
+        entries
+    }
+}
@@ -0,0 +1,419 @@
+//! `liscaf serve --stdio`: JSON-RPC 2.0 over stdin/stdout for editor extensions that build
+//! wizards on top of liscaf.
+//!
+//! Messages are either one JSON object per line or framed with `Content-Length` headers as in
+//! LSP; replies use the framing of the request. While serving, everything liscaf would print
+//! goes to stderr so stdout carries protocol messages only.
+//!
+//! Methods:
+//! - `initialize` → server name, version and method list
+//! - `templates/list` `{templates?}` → `[{name, label, url, commit}]`
+//! - `template/variables` `{template, templates?}` → description, requirements and variables in
+//!   prompt order
+//! - `scaffold/plan` / `scaffold/execute` `{template, name, directory?, into?, vars?, templates?}`
+//!   → `{changes, project_dir, output}`, running `liscaf --porcelain scaffold -y` (with
+//!   `--dry-run` for plans)
+//! - `shutdown` → `null`; the server exits after replying (also on end of input)
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde_json::{json, Value};
+
+use crate::config::Config;
+use crate::manifest;
+use crate::registry;
+use crate::TemplateEntry;
+
+const METHODS: &[&str] = &[
+    "initialize",
+    "templates/list",
+    "template/variables",
+    "scaffold/plan",
+    "scaffold/execute",
+    "shutdown",
+];
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Application error: the operation itself failed (unknown template, clone error, ...).
+const OPERATION_FAILED: i64 = -32000;
+
+struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+impl From<anyhow::Error> for RpcError {
+    fn from(e: anyhow::Error) -> Self {
+        RpcError::new(OPERATION_FAILED, format!("{:#}", e))
+    }
+}
+
+struct Server<'a> {
+    templates_source: &'a str,
+    config: &'a Config,
+    tmpdir: Option<&'a Path>,
+}
+
+pub fn run(
+    templates_source: &str,
+    config: &Config,
+    tmpdir: Option<&Path>,
+) -> anyhow::Result<()> {
+    let mut out = crate::porcelain::take_stdout()?;
+    let server = Server {
+        templates_source,
+        config,
+        tmpdir,
+    };
+    let mut input = BufReader::new(std::io::stdin().lock());
+    eprintln!("liscaf JSON-RPC server ready on stdio");
+    while let Some((body, framed)) = read_message(&mut input)? {
+        let (response, stop) = match serde_json::from_str::<Value>(&body) {
+            Ok(request) => server.handle(request),
+            Err(e) => (
+                Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, e.to_string()))),
+                false,
+            ),
+        };
+        if let Some(response) = response {
+            write_message(&mut out, &response.to_string(), framed)?;
+        }
+        if stop {
+            break;
+        }
+    }
+    Ok(())
+}
+
+impl Server<'_> {
+    /// The response (none for notifications) and whether to stop serving.
+    fn handle(&self, request: Value) -> (Option<Value>, bool) {
+        let id = request.get("id").cloned();
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            let error = RpcError::new(INVALID_REQUEST, "missing method");
+            return (Some(error_response(id.unwrap_or(Value::Null), error)), false);
+        };
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        let result = match method {
+            "initialize" => Ok(json!({
+                "name": "liscaf",
+                "version": env!("CARGO_PKG_VERSION"),
+                "methods": METHODS,
+            })),
+            "templates/list" => self.list_templates(&params),
+            "template/variables" => self.template_variables(&params),
+            "scaffold/plan" => self.scaffold(&params, true),
+            "scaffold/execute" => self.scaffold(&params, false),
+            "shutdown" => Ok(Value::Null),
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("unknown method '{}'", method))),
+        };
+        let stop = method == "shutdown";
+        let Some(id) = id else {
+            return (None, stop);
+        };
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => error_response(id, error),
+        };
+        (Some(response), stop)
+    }
+
+    fn registries(&self, params: &Value) -> Vec<registry::Registry> {
+        let source = str_param(params, "templates").unwrap_or(self.templates_source);
+        registry::registries(source, &self.config.registries)
+    }
+
+    fn list_templates(&self, params: &Value) -> Result<Value, RpcError> {
+        let templates = registry::load_templates(&self.registries(params))?;
+        Ok(templates.iter().map(entry_json).collect())
+    }
+
+    fn template_variables(&self, params: &Value) -> Result<Value, RpcError> {
+        let template = required_str(params, "template")?;
+        let local = Path::new(template);
+        let (entry, manifest) = if local.is_dir() {
+            (local_entry(template), manifest::load(local)?)
+        } else {
+            let entry = crate::info::resolve(template, &self.registries(params))?;
+            let tmp = crate::make_tempdir("liscaf-serve-", self.tmpdir)?;
+            crate::cache::checkout(&entry.url, entry.commit.as_deref(), tmp.path(), true)?;
+            crate::extends::resolve(tmp.path(), self.tmpdir, true)?;
+            let manifest = manifest::load(tmp.path())?;
+            (entry, manifest)
+        };
+        let variables: Vec<Value> = crate::variables::prompt_order(&manifest.variables)
+            .into_iter()
+            .map(|(name, spec)| {
+                json!({
+                    "name": name,
+                    "prompt": spec.prompt,
+                    "default": spec.default,
+                    "help": spec.help,
+                    "group": spec.group,
+                    "order": spec.order,
+                    "ask_if": spec.ask_if,
+                })
+            })
+            .collect();
+        Ok(json!({
+            "template": entry_json(&entry),
+            "description": manifest.description,
+            "requires": manifest.requires,
+            "name_rules": manifest.name.rules,
+            "variables": variables,
+            "fragments": manifest.fragments.keys().collect::<Vec<_>>(),
+        }))
+    }
+
+    /// Runs `liscaf --porcelain scaffold` as a child process, so its changes and output can be
+    /// returned instead of ending up between protocol messages.
+    fn scaffold(&self, params: &Value, dry_run: bool) -> Result<Value, RpcError> {
+        let template = required_str(params, "template")?;
+        let name = required_str(params, "name")?;
+        let entry = crate::info::resolve(template, &self.registries(params))?;
+        let vars: BTreeMap<String, String> = match params.get("vars") {
+            None | Some(Value::Null) => BTreeMap::new(),
+            Some(value) => serde_json::from_value(value.clone())
+                .map_err(|e| RpcError::new(INVALID_PARAMS, format!("vars: {}", e)))?,
+        };
+        let directory = match str_param(params, "directory") {
+            Some(dir) => PathBuf::from(dir),
+            None => std::env::current_dir().map_err(anyhow::Error::from)?,
+        };
+
+        let mut command = Command::new(std::env::current_exe().map_err(anyhow::Error::from)?);
+        command.args(["--porcelain", "scaffold", "--yes"]);
+        if let Some(ref commit) = entry.commit {
+            command.arg("--commit").arg(commit);
+        }
+        if dry_run {
+            command.arg("--dry-run");
+        }
+        if let Some(into) = str_param(params, "into") {
+            command.arg("--into").arg(into);
+        }
+        if let Some(tmpdir) = self.tmpdir {
+            command.arg("--tmpdir").arg(tmpdir);
+        }
+        for (key, value) in &vars {
+            command.arg("--var").arg(format!("{}={}", key, value));
+        }
+        // Client-supplied positionals go after `--`, so a name like `--into=/x` stays a name
+        command.arg("--").arg(name).arg(&entry.url);
+        let output = command
+            .current_dir(&directory)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(anyhow::Error::from)?;
+        let log: Vec<String> = String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .map(String::from)
+            .collect();
+        if !output.status.success() {
+            let message = log
+                .iter()
+                .find_map(|l| l.strip_prefix("Error: "))
+                .unwrap_or("scaffold failed")
+                .to_string();
+            return Err(RpcError {
+                code: OPERATION_FAILED,
+                message,
+                data: Some(json!({ "output": log, "exit_code": output.status.code() })),
+            });
+        }
+        let mut changes = Vec::new();
+        let mut project_dir = None;
+        for record in String::from_utf8_lossy(&output.stdout).lines().map(crate::porcelain::parse) {
+            match record.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+                ["ADD", path] => changes.push(json!({ "kind": "add", "path": path })),
+                ["CONFLICT", path] => changes.push(json!({ "kind": "conflict", "path": path })),
+                ["RENAME", from, to] => {
+                    changes.push(json!({ "kind": "rename", "from": from, "to": to }))
+                }
+                ["DONE", dir] => project_dir = Some(directory.join(dir)),
+                _ => {}
+            }
+        }
+        Ok(json!({ "changes": changes, "project_dir": project_dir, "output": log }))
+    }
+}
+
+fn entry_json(entry: &TemplateEntry) -> Value {
+    json!({
+        "name": entry.name,
+        "label": entry.label,
+        "url": entry.url,
+        "commit": entry.commit,
+    })
+}
+
+fn local_entry(dir: &str) -> TemplateEntry {
+    TemplateEntry {
+        name: dir.to_string(),
+        label: dir.to_string(),
+        url: dir.to_string(),
+        commit: None,
+    }
+}
+
+fn str_param<'v>(params: &'v Value, key: &str) -> Option<&'v str> {
+    params.get(key).and_then(Value::as_str)
+}
+
+fn required_str<'v>(params: &'v Value, key: &str) -> Result<&'v str, RpcError> {
+    str_param(params, key)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("missing string parameter '{}'", key)))
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    let mut body = json!({ "code": error.code, "message": error.message });
+    if let Some(data) = error.data {
+        body["data"] = data;
+    }
+    json!({ "jsonrpc": "2.0", "id": id, "error": body })
+}
+
+/// Reads the next message body; `true` if it was framed with `Content-Length`.
+fn read_message(input: &mut impl BufRead) -> anyhow::Result<Option<(String, bool)>> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let Some(length) = header_value(trimmed, "content-length") else {
+            return Ok(Some((trimmed.to_string(), false)));
+        };
+        let length: usize = length
+            .parse()
+            .map_err(|_| anyhow::anyhow!("invalid Content-Length: {}", length))?;
+        // Further headers (Content-Type) up to the blank line.
+        loop {
+            line.clear();
+            if input.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+        }
+        let mut body = vec![0; length];
+        input.read_exact(&mut body)?;
+        return Ok(Some((String::from_utf8_lossy(&body).into_owned(), true)));
+    }
+}
+
+fn header_value<'l>(line: &'l str, name: &str) -> Option<&'l str> {
+    let (key, value) = line.split_once(':')?;
+    key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+}
+
+fn write_message(out: &mut impl Write, body: &str, framed: bool) -> anyhow::Result<()> {
+    if framed {
+        write!(out, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    } else {
+        writeln!(out, "{}", body)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(config: &Config) -> Server<'_> {
+        Server {
+            templates_source: "",
+            config,
+            tmpdir: None,
+        }
+    }
+
+    #[test]
+    fn messages_are_read_as_lines_or_content_length_frames() {
+        let body = r#"{"jsonrpc":"2.0","id":2,"method":"shutdown"}"#;
+        let stream = format!(
+            "\n{{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"initialize\"}}\r\nContent-Length: {}\r\nContent-Type: application/json\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let mut input = stream.as_bytes();
+        let (first, framed) = read_message(&mut input).unwrap().unwrap();
+        assert_eq!((first.as_str(), framed), (r#"{"jsonrpc":"2.0","id":1,"method":"initialize"}"#, false));
+        assert_eq!(read_message(&mut input).unwrap(), Some((body.to_string(), true)));
+        assert_eq!(read_message(&mut input).unwrap(), None);
+
+        let mut out = Vec::new();
+        write_message(&mut out, "{}", true).unwrap();
+        write_message(&mut out, "{}", false).unwrap();
+        assert_eq!(out, b"Content-Length: 2\r\n\r\n{}{}\n");
+    }
+
+    #[test]
+    fn requests_get_results_errors_or_no_reply() {
+        let config = Config::default();
+        let server = server(&config);
+        let (reply, stop) = server.handle(json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize" }));
+        let reply = reply.unwrap();
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["result"]["methods"], json!(METHODS));
+        assert!(!stop);
+
+        let (reply, _) = server.handle(json!({ "jsonrpc": "2.0", "id": "a", "method": "templates/delete" }));
+        assert_eq!(reply.unwrap()["error"]["code"], METHOD_NOT_FOUND);
+        let (reply, _) = server.handle(json!({ "jsonrpc": "2.0", "id": 3, "method": "scaffold/plan", "params": {} }));
+        let reply = reply.unwrap();
+        assert_eq!(reply["error"]["code"], INVALID_PARAMS);
+        assert_eq!(reply["error"]["message"], "missing string parameter 'template'");
+        let (reply, _) = server.handle(json!({ "jsonrpc": "2.0", "id": 4 }));
+        assert_eq!(reply.unwrap()["error"]["code"], INVALID_REQUEST);
+
+        // Notifications get no reply, not even shutdown
+        assert_eq!(server.handle(json!({ "jsonrpc": "2.0", "method": "initialize" })), (None, false));
+        assert_eq!(server.handle(json!({ "jsonrpc": "2.0", "method": "shutdown" })), (None, true));
+    }
+
+    #[test]
+    fn variables_of_a_local_template_come_in_prompt_order() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(manifest::MANIFEST_FILE),
+            "description = \"API service\"\n\n[variables.port]\ndefault = \"8080\"\norder = 2\n\n\
+             [variables.database]\nprompt = \"Database\"\norder = 1\n\n[variables.pool]\nask_if = \"database == 'postgres'\"\norder = 3\n",
+        )
+        .unwrap();
+        let config = Config::default();
+        let template = dir.path().display().to_string();
+        let (reply, _) = server(&config).handle(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "template/variables",
+            "params": { "template": template },
+        }));
+        let result = &reply.unwrap()["result"];
+        assert_eq!(result["description"], "API service");
+        assert_eq!(result["template"]["url"], template);
+        let names: Vec<&str> = result["variables"].as_array().unwrap().iter().map(|v| v["name"].as_str().unwrap()).collect();
+        assert_eq!(names, ["database", "port", "pool"]);
+        assert_eq!(result["variables"][1]["default"], "8080");
+        assert_eq!(result["variables"][2]["ask_if"], "database == 'postgres'");
+    }
+}
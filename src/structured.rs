@@ -0,0 +1,453 @@
+//! Structure-aware replacement for JSON, YAML and TOML files (`--structured`): names are replaced
+//! in string values only, never in keys, YAML anchors and aliases (`&acme-app`, `*acme-app`) or
+//! tags, unless keys are asked for as well (`--structured-keys`).
+//!
+//! The files are scanned, not parsed into a document model, so comments, quoting and layout stay
+//! exactly as they were. A file the scanner cannot follow (an unterminated string, say) is left
+//! unchanged with a warning rather than replaced blindly.
+use std::path::Path;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Plain text replacement everywhere.
+    #[default]
+    Off,
+    Values,
+    ValuesAndKeys,
+}
+
+impl Mode {
+    /// The mode for `--structured` / `--structured-keys` (the latter implies the former).
+    pub fn new(structured: bool, keys: bool) -> Self {
+        match (structured, keys) {
+            (_, true) => Mode::ValuesAndKeys,
+            (true, false) => Mode::Values,
+            (false, false) => Mode::Off,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Value,
+    Key,
+    /// YAML anchors and aliases, which must change together or not at all.
+    Anchor,
+}
+
+type Spans = Vec<(usize, usize, Kind)>;
+
+fn format_of(path: &Path) -> Option<Format> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "json" => Some(Format::Json),
+        "yaml" | "yml" => Some(Format::Yaml),
+        "toml" => Some(Format::Toml),
+        _ => None,
+    }
+}
+
+/// Whether `path` is replaced structurally in this mode.
+pub fn applies(mode: Mode, path: &Path) -> bool {
+    mode != Mode::Off && format_of(path).is_some()
+}
+
+/// `text` with `mappings` applied to the parts `mode` allows; `Err` if the file could not be
+/// scanned.
+pub fn replace(
+    mode: Mode,
+    path: &Path,
+    text: &str,
+    mappings: &[(String, String)],
+) -> Result<String, String> {
+    let format = format_of(path).ok_or_else(|| "not a JSON, YAML or TOML file".to_string())?;
+    let mut scanner = Scanner {
+        text: text.as_bytes(),
+        at: 0,
+        spans: Vec::new(),
+    };
+    let scanned = match format {
+        Format::Json => scanner.flow(b"//", false),
+        Format::Yaml => scanner.yaml(),
+        Format::Toml => scanner.toml(),
+    };
+    scanned.ok_or_else(|| format!("could not follow the file's structure near byte {}", scanner.at))?;
+
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    for (start, end, kind) in scanner.spans {
+        if kind != Kind::Value && mode != Mode::ValuesAndKeys {
+            continue;
+        }
+        out.push_str(&text[copied..start]);
+        out.push_str(&crate::apply_mappings(&text[start..end], mappings));
+        copied = end;
+    }
+    out.push_str(&text[copied..]);
+    Ok(out)
+}
+
+struct Scanner<'t> {
+    text: &'t [u8],
+    at: usize,
+    spans: Spans,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.at).copied()
+    }
+
+    fn starts_with(&self, s: &[u8]) -> bool {
+        self.text[self.at..].starts_with(s)
+    }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\r')) {
+            self.at += 1;
+        }
+    }
+
+    fn skip_to_eol(&mut self) {
+        while !matches!(self.peek(), None | Some(b'\n')) {
+            self.at += 1;
+        }
+    }
+
+    /// The next non-space byte from `from` on.
+    fn next_on_line(&self, from: usize) -> Option<u8> {
+        self.text[from..]
+            .iter()
+            .copied()
+            .find(|b| !matches!(b, b' ' | b'\t' | b'\r'))
+    }
+
+    /// A quoted string starting at the opening quote; pushes its content span and moves past the
+    /// closing quote. Strings may span lines (YAML, TOML multi-line).
+    fn quoted(&mut self, quote: &[u8], escapes: bool, kind: Kind) -> Option<()> {
+        self.at += quote.len();
+        let start = self.at;
+        loop {
+            if self.at >= self.text.len() {
+                return None;
+            }
+            if escapes && self.peek() == Some(b'\\') {
+                self.at += 2;
+                continue;
+            }
+            if self.starts_with(quote) {
+                // YAML escapes a single quote by doubling it
+                if quote == b"'" && !escapes && self.text.get(self.at + 1) == Some(&b'\'') {
+                    self.at += 2;
+                    continue;
+                }
+                self.spans.push((start, self.at, kind));
+                self.at += quote.len();
+                return Some(());
+            }
+            self.at += 1;
+        }
+    }
+
+    /// Key if the next thing after `end` on the line is a `:`.
+    fn kind_after(&self, end: usize) -> Kind {
+        if self.next_on_line(end) == Some(b':') {
+            Kind::Key
+        } else {
+            Kind::Value
+        }
+    }
+
+    /// JSON, or a YAML flow collection: scans until the brackets opened here are balanced, or to
+    /// the end for a whole JSON document.
+    fn flow(&mut self, comment: &[u8], balanced: bool) -> Option<()> {
+        let mut depth = 0usize;
+        while let Some(c) = self.peek() {
+            match c {
+                b'{' | b'[' => {
+                    depth += 1;
+                    self.at += 1;
+                }
+                b'}' | b']' => {
+                    depth = depth.checked_sub(1)?;
+                    self.at += 1;
+                    if balanced && depth == 0 {
+                        return Some(());
+                    }
+                }
+                b'"' | b'\'' => {
+                    if c == b'\'' && comment == b"//" {
+                        return None;
+                    }
+                    let quote = [c];
+                    let before = self.spans.len();
+                    self.quoted(&quote, c == b'"', Kind::Value)?;
+                    let kind = self.kind_after(self.at);
+                    self.spans[before].2 = kind;
+                }
+                b',' | b':' | b' ' | b'\t' | b'\r' | b'\n' => self.at += 1,
+                _ if self.starts_with(comment) => self.skip_to_eol(),
+                b'/' if comment == b"//" && self.starts_with(b"/*") => {
+                    let end = self.text[self.at..].windows(2).position(|w| w == b"*/")?;
+                    self.at += end + 2;
+                }
+                b'&' | b'*' if comment == b"#" => self.word(Some(Kind::Anchor)),
+                b'!' if comment == b"#" => self.word(None),
+                _ => {
+                    // Plain scalar (YAML) or a number, true, false, null
+                    let start = self.at;
+                    while let Some(c) = self.peek() {
+                        let ends_key = c == b':'
+                            && matches!(self.text.get(self.at + 1), None | Some(b' ' | b',' | b']' | b'}' | b'\n' | b'\r'));
+                        if matches!(c, b',' | b'[' | b']' | b'{' | b'}' | b'\n') || ends_key || self.starts_with(b" #") {
+                            break;
+                        }
+                        self.at += 1;
+                    }
+                    if self.at == start {
+                        self.at += 1;
+                        continue;
+                    }
+                    let mut end = self.at;
+                    while end > start && matches!(self.text[end - 1], b' ' | b'\t' | b'\r') {
+                        end -= 1;
+                    }
+                    self.spans.push((start, end, self.kind_after(self.at)));
+                }
+            }
+        }
+        (depth == 0).then_some(())
+    }
+
+    /// An anchor or alias (`kind`), or a tag (`None`, never replaced), up to the next space.
+    fn word(&mut self, kind: Option<Kind>) {
+        let start = self.at + 1;
+        while !matches!(self.peek(), None | Some(b' ' | b'\t' | b'\r' | b'\n' | b',' | b']' | b'}')) {
+            self.at += 1;
+        }
+        if let Some(kind) = kind.filter(|_| self.at > start) {
+            self.spans.push((start, self.at, kind));
+        }
+    }
+
+    fn yaml(&mut self) -> Option<()> {
+        // Indentation of the line that opened a block scalar (`key: |`)
+        let mut block: Option<usize> = None;
+        while self.at < self.text.len() {
+            let line_start = self.at;
+            while self.peek() == Some(b' ') {
+                self.at += 1;
+            }
+            let indent = self.at - line_start;
+            let blank = matches!(self.peek(), None | Some(b'\n' | b'\r'));
+            if let Some(parent) = block {
+                if blank || indent > parent {
+                    let start = self.at;
+                    self.skip_to_eol();
+                    self.spans.push((start, self.at, Kind::Value));
+                    self.at += 1;
+                    continue;
+                }
+                block = None;
+            }
+            if blank || self.peek() == Some(b'#') || self.starts_with(b"---") || self.starts_with(b"...") || self.peek() == Some(b'%') {
+                self.skip_to_eol();
+                self.at += 1;
+                continue;
+            }
+            // Sequence entries: `- item`, `- - nested`, `- key: value`
+            while self.peek() == Some(b'-') && matches!(self.text.get(self.at + 1), None | Some(b' ' | b'\n' | b'\r')) {
+                self.at += 1;
+                self.skip_spaces();
+            }
+            self.yaml_key();
+            if self.yaml_value()? {
+                block = Some(indent);
+            }
+            self.skip_to_eol();
+            self.at += 1;
+        }
+        Some(())
+    }
+
+    /// A `key:` at the cursor, if there is one.
+    fn yaml_key(&mut self) {
+        let start = self.at;
+        match self.peek() {
+            Some(q @ (b'"' | b'\'')) => {
+                let before = self.spans.len();
+                if self.quoted(&[q], q == b'"', Kind::Key).is_some() && self.next_on_line(self.at) == Some(b':') {
+                    self.skip_spaces();
+                    self.at += 1;
+                    return;
+                }
+                self.spans.truncate(before);
+                self.at = start;
+            }
+            Some(b'[' | b'{' | b'&' | b'*' | b'!' | b'|' | b'>' | b'#') | None => {}
+            Some(_) => {
+                while let Some(c) = self.peek() {
+                    if c == b'\n' || self.starts_with(b" #") {
+                        break;
+                    }
+                    if c == b':' && matches!(self.text.get(self.at + 1), None | Some(b' ' | b'\t' | b'\n' | b'\r')) {
+                        self.spans.push((start, self.at, Kind::Key));
+                        self.at += 1;
+                        return;
+                    }
+                    self.at += 1;
+                }
+                self.at = start;
+            }
+        }
+    }
+
+    /// The value after a key or sequence marker, to the end of the line (or of its flow
+    /// collection or quoted string). True if it opens a block scalar.
+    fn yaml_value(&mut self) -> Option<bool> {
+        loop {
+            self.skip_spaces();
+            match self.peek() {
+                None | Some(b'\n' | b'#') => return Some(false),
+                Some(b'&' | b'*') => self.word(Some(Kind::Anchor)),
+                Some(b'!') => self.word(None),
+                Some(b'|' | b'>') => return Some(true),
+                Some(q @ (b'"' | b'\'')) => {
+                    self.quoted(&[q], q == b'"', Kind::Value)?;
+                    return Some(false);
+                }
+                Some(b'{' | b'[') => {
+                    self.flow(b"#", true)?;
+                    return Some(false);
+                }
+                Some(_) => {
+                    let start = self.at;
+                    while !matches!(self.peek(), None | Some(b'\n')) && !self.starts_with(b" #") {
+                        self.at += 1;
+                    }
+                    let mut end = self.at;
+                    while end > start && matches!(self.text[end - 1], b' ' | b'\t' | b'\r') {
+                        end -= 1;
+                    }
+                    self.spans.push((start, end, Kind::Value));
+                    return Some(false);
+                }
+            }
+        }
+    }
+
+    fn toml(&mut self) -> Option<()> {
+        while self.at < self.text.len() {
+            self.skip_spaces();
+            match self.peek() {
+                Some(b'\n') => self.at += 1,
+                Some(b'#') => self.skip_to_eol(),
+                Some(b'[') => {
+                    // Table header: `[a."b".c]` or `[[array]]`
+                    while self.peek() == Some(b'[') {
+                        self.at += 1;
+                    }
+                    self.toml_keys(b']')?;
+                    while self.peek() == Some(b']') {
+                        self.at += 1;
+                    }
+                    self.skip_to_eol();
+                }
+                Some(_) => {
+                    self.toml_keys(b'=')?;
+                    self.at += 1;
+                    self.toml_value()?;
+                    self.skip_to_eol();
+                }
+                None => break,
+            }
+        }
+        Some(())
+    }
+
+    /// Dotted bare or quoted keys up to `end`, which is left at the cursor.
+    fn toml_keys(&mut self, end: u8) -> Option<()> {
+        loop {
+            self.skip_spaces();
+            match self.peek()? {
+                c if c == end => return Some(()),
+                b'.' => self.at += 1,
+                b'"' => self.quoted(b"\"", true, Kind::Key)?,
+                b'\'' => self.quoted(b"'", false, Kind::Key)?,
+                c if c.is_ascii_alphanumeric() || c == b'_' || c == b'-' => {
+                    let start = self.at;
+                    while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == b'_' || c == b'-') {
+                        self.at += 1;
+                    }
+                    self.spans.push((start, self.at, Kind::Key));
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn toml_value(&mut self) -> Option<()> {
+        self.skip_spaces();
+        match self.peek()? {
+            _ if self.starts_with(b"\"\"\"") => self.quoted(b"\"\"\"", true, Kind::Value),
+            _ if self.starts_with(b"'''") => self.quoted(b"'''", false, Kind::Value),
+            b'"' => self.quoted(b"\"", true, Kind::Value),
+            b'\'' => self.quoted(b"'", false, Kind::Value),
+            b'[' => {
+                self.at += 1;
+                loop {
+                    self.skip_blank_and_comments();
+                    match self.peek()? {
+                        b']' => break,
+                        b',' => self.at += 1,
+                        _ => self.toml_value()?,
+                    }
+                }
+                self.at += 1;
+                Some(())
+            }
+            b'{' => {
+                self.at += 1;
+                loop {
+                    self.skip_spaces();
+                    match self.peek()? {
+                        b'}' => break,
+                        b',' => self.at += 1,
+                        _ => {
+                            self.toml_keys(b'=')?;
+                            self.at += 1;
+                            self.toml_value()?;
+                        }
+                    }
+                }
+                self.at += 1;
+                Some(())
+            }
+            _ => {
+                // Numbers, booleans, dates
+                while !matches!(self.peek(), None | Some(b',' | b']' | b'}' | b'#' | b'\n' | b' ' | b'\t' | b'\r')) {
+                    self.at += 1;
+                }
+                Some(())
+            }
+        }
+    }
+
+    fn skip_blank_and_comments(&mut self) {
+        loop {
+            self.skip_spaces();
+            match self.peek() {
+                Some(b'\n') => self.at += 1,
+                Some(b'#') => self.skip_to_eol(),
+                _ => return,
+            }
+        }
+    }
+}
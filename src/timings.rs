@@ -0,0 +1,76 @@
+//! `--timings`: how long each phase of a scaffold took, printed at the end, plus the throughput
+//! of the replacement scan.
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+pub struct Timings {
+    enabled: bool,
+    started: Instant,
+    phases: Vec<(&'static str, Duration)>,
+    /// Files and bytes the replacement walked, for the throughput line.
+    scanned: Option<(usize, u64)>,
+}
+
+impl Timings {
+    pub fn new(enabled: bool) -> Self {
+        Timings {
+            enabled,
+            started: Instant::now(),
+            phases: Vec::new(),
+            scanned: None,
+        }
+    }
+
+    /// Records the time since `since` under `phase`; repeated phases add up.
+    pub fn record(&mut self, phase: &'static str, since: Instant) {
+        let elapsed = since.elapsed();
+        match self.phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => self.phases.push((phase, elapsed)),
+        }
+    }
+
+    pub fn scanned(&mut self, files: usize, bytes: u64) {
+        self.scanned = Some((files, bytes));
+    }
+
+    /// Prints the report if `--timings` was given.
+    pub fn report(&self) {
+        if !self.enabled {
+            return;
+        }
+        let total = self.started.elapsed();
+        let width = self.phases.iter().map(|(name, _)| name.len()).max().unwrap_or(0).max(5);
+        println!("Timings:");
+        for (name, elapsed) in &self.phases {
+            let mut line = format!("  {:width$}  {:>9}", name, format_duration(*elapsed), width = width);
+            if let (&"replacement", Some((files, bytes))) = (name, self.scanned) {
+                let seconds = elapsed.as_secs_f64().max(0.001);
+                line.push_str(&format!(
+                    "  {} files, {} ({:.0} files/s, {}/s)",
+                    crate::format_count(files),
+                    crate::filter::format_size(bytes),
+                    files as f64 / seconds,
+                    crate::filter::format_size((bytes as f64 / seconds) as u64)
+                ));
+            }
+            println!("{}", line);
+        }
+        let measured: Duration = self.phases.iter().map(|(_, d)| *d).sum();
+        println!(
+            "  {:width$}  {:>9}",
+            "other",
+            format_duration(total.saturating_sub(measured)),
+            width = width
+        );
+        println!("  {:width$}  {:>9}", "total", format_duration(total), width = width);
+    }
+}
+
+fn format_duration(d: Duration) -> String {
+    if d.as_secs() >= 1 {
+        format!("{:.2} s", d.as_secs_f64())
+    } else {
+        format!("{:.1} ms", d.as_secs_f64() * 1000.0)
+    }
+}
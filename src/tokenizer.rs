@@ -0,0 +1,219 @@
+//! Splitting names into words and building the variant forms that replacement looks for.
+//!
+//! ```
+//! use liscaf::{generate_variant_mappings, split_name_to_tokens, TokenizerRules};
+//!
+//! let rules = TokenizerRules::default().with_acronyms(["HTTP"]);
+//! let from = split_name_to_tokens("acme-app", &rules);
+//! let to = split_name_to_tokens("HTTPGateway", &rules);
+//! assert_eq!(to, ["http", "gateway"]);
+//! let mappings = generate_variant_mappings(&from, &to, &[], &rules);
+//! assert!(mappings.contains(&("AcmeApp".to_string(), "HTTPGateway".to_string())));
+//! ```
+
+/// Names of the generated variant forms, usable with `--skip-variant`.
+pub const VARIANT_NAMES: &[&str] = &[
+    "kebab",
+    "snake",
+    "upper-snake",
+    "concatlower",
+    "concatupper",
+    "camel",
+    "pascal",
+    "pascal-snake",
+];
+
+/// How names are split into words.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TokenizerRules {
+    /// Words kept whole (`OAuth2Client` → `oauth2`, `client`) and spelled as given in camel and
+    /// Pascal forms.
+    pub acronyms: Vec<String>,
+    pub digits: DigitHandling,
+    /// Characters that separate words; empty means every character that is not a letter or digit.
+    pub separators: Vec<char>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DigitHandling {
+    /// Digits belong to the word they follow: `base64Encode` → `base64`, `encode`.
+    #[default]
+    Attach,
+    /// Digits form words of their own: `base64Encode` → `base`, `64`, `encode`.
+    Split,
+}
+
+impl TokenizerRules {
+    pub fn with_acronyms<S: Into<String>>(mut self, acronyms: impl IntoIterator<Item = S>) -> Self {
+        self.acronyms.extend(acronyms.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_digits(mut self, digits: DigitHandling) -> Self {
+        self.digits = digits;
+        self
+    }
+
+    pub fn with_separators(mut self, separators: impl IntoIterator<Item = char>) -> Self {
+        self.separators = separators.into_iter().collect();
+        self
+    }
+
+    fn is_separator(&self, c: char) -> bool {
+        if self.separators.is_empty() {
+            !c.is_alphanumeric()
+        } else {
+            self.separators.contains(&c)
+        }
+    }
+
+    /// Whether a word starts at `chars[i]`: `aB`, `1B`, the `B` of `ABc`, and letter/digit
+    /// changes with [`DigitHandling::Split`].
+    fn starts_token(&self, chars: &[char], i: usize) -> bool {
+        let Some(prev) = i.checked_sub(1).map(|p| chars[p]) else {
+            return false;
+        };
+        let c = chars[i];
+        if self.digits == DigitHandling::Split && prev.is_numeric() != c.is_numeric() {
+            return true;
+        }
+        c.is_uppercase()
+            && (prev.is_lowercase()
+                || prev.is_numeric()
+                || (prev.is_uppercase() && chars.get(i + 1).is_some_and(|n| n.is_lowercase())))
+    }
+
+    /// A word with its first letter capitalized, or the acronym's spelling (`http` → `HTTP`).
+    pub(crate) fn capitalize(&self, token: &str) -> String {
+        if let Some(acronym) = self.acronyms.iter().find(|a| a.to_lowercase() == token) {
+            return acronym.clone();
+        }
+        let mut chars = token.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    }
+
+    /// `camelCase`: the first word stays lowercase, the others are capitalized.
+    pub(crate) fn join_camel(&self, tokens: &[String]) -> String {
+        let mut out = tokens.first().cloned().unwrap_or_default();
+        for token in tokens.iter().skip(1) {
+            out.push_str(&self.capitalize(token));
+        }
+        out
+    }
+
+    /// `PascalCase` words joined with `separator`.
+    pub(crate) fn join_pascal(&self, tokens: &[String], separator: &str) -> String {
+        tokens
+            .iter()
+            .map(|t| self.capitalize(t))
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+
+    /// Every variant form of `tokens`, in [`VARIANT_NAMES`] order.
+    fn variants(&self, tokens: &[String]) -> Vec<(&'static str, String)> {
+        let upper = |separator: &str| {
+            tokens.iter().map(|s| s.to_uppercase()).collect::<Vec<_>>().join(separator)
+        };
+        vec![
+            ("kebab", tokens.join("-")),
+            ("snake", tokens.join("_")),
+            ("upper-snake", upper("_")),
+            ("concatlower", tokens.join("")),
+            ("concatupper", upper("")),
+            ("camel", self.join_camel(tokens)),
+            ("pascal", self.join_pascal(tokens, "")),
+            ("pascal-snake", self.join_pascal(tokens, "_")),
+        ]
+    }
+}
+
+/// Splits an arbitrary name like "my-cool_app" or "MyCoolApp" into lowercase words:
+/// ["my","cool","app"]. A run of capitals ends before the last one when a lowercase letter
+/// follows, so `HTTPServer` is `["http","server"]` even without acronyms.
+pub fn split_name_to_tokens(name: &str, rules: &TokenizerRules) -> Vec<String> {
+    let mut acronyms: Vec<Vec<char>> = rules
+        .acronyms
+        .iter()
+        .filter(|a| !a.is_empty())
+        .map(|a| a.chars().collect())
+        .collect();
+    acronyms.sort_by_key(|a| std::cmp::Reverse(a.len()));
+
+    let mut tokens: Vec<String> = Vec::new();
+    for part in name.split(|c| rules.is_separator(c)).filter(|s| !s.is_empty()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut current = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            if current.is_empty() || rules.starts_token(&chars, i) {
+                let rest = &chars[i..];
+                let acronym = acronyms.iter().find(|a| {
+                    rest.starts_with(a) && rest.get(a.len()).is_none_or(|next| !next.is_lowercase())
+                });
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current).to_lowercase());
+                }
+                if let Some(acronym) = acronym {
+                    tokens.push(acronym.iter().collect::<String>().to_lowercase());
+                    i += acronym.len();
+                    continue;
+                }
+            }
+            current.push(chars[i]);
+            i += 1;
+        }
+        if !current.is_empty() {
+            tokens.push(current.to_lowercase());
+        }
+    }
+    tokens
+}
+
+/// Mappings from every variant form of the template name to the same form of the new name,
+/// sorted and without the forms named in `skip`. Camel and Pascal forms spell acronyms as given
+/// (`HttpServer` → `HTTPServer`); the template name's plain spelling is mapped as well, so
+/// templates written either way are replaced.
+pub fn generate_variant_mappings(
+    orig_tokens: &[String],
+    new_tokens: &[String],
+    skip: &[String],
+    rules: &TokenizerRules,
+) -> Vec<(String, String)> {
+    let plain = TokenizerRules {
+        acronyms: Vec::new(),
+        ..rules.clone()
+    };
+    let new_variants = rules.variants(new_tokens);
+    let mut pairs = Vec::<(String, String)>::new();
+    for orig_variants in [rules.variants(orig_tokens), plain.variants(orig_tokens)] {
+        for ((name, o), (_, n)) in orig_variants.into_iter().zip(&new_variants) {
+            if skip.iter().any(|s| s == name) {
+                continue;
+            }
+            if !o.is_empty() && !n.is_empty() {
+                pairs.push((o, n.clone()));
+            }
+        }
+    }
+
+    pairs.sort_unstable();
+    pairs.dedup();
+    pairs
+}
+
+pub(crate) fn validate_variant_names(names: &[String]) -> anyhow::Result<()> {
+    for name in names {
+        if !VARIANT_NAMES.contains(&name.as_str()) {
+            anyhow::bail!(
+                "Unknown variant '{}' (expected one of: {})",
+                name,
+                VARIANT_NAMES.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
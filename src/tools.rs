@@ -0,0 +1,161 @@
+//! Tools a template's projects need (`requires = ["node >= 20", "docker"]`), checked before
+//! scaffolding so a missing or outdated tool shows up with an install hint instead of as a
+//! failing build later.
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::process::{Command, Stdio};
+
+/// Install hints for common tools; templates can add or override them with `[install_hints]`.
+const HINTS: &[(&str, &str)] = &[
+    ("cargo", "https://rustup.rs"),
+    ("docker", "https://docs.docker.com/get-docker/"),
+    ("go", "https://go.dev/dl/"),
+    ("helm", "https://helm.sh/docs/intro/install/"),
+    ("java", "https://adoptium.net"),
+    ("kubectl", "https://kubernetes.io/docs/tasks/tools/"),
+    ("node", "https://nodejs.org (or a version manager such as fnm or nvm)"),
+    ("npm", "comes with node: https://nodejs.org"),
+    ("pnpm", "npm install -g pnpm, or corepack enable"),
+    ("protoc", "https://grpc.io/docs/protoc-installation/"),
+    ("python3", "https://www.python.org/downloads/"),
+    ("rustc", "https://rustup.rs"),
+    ("yarn", "corepack enable"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirement {
+    pub tool: String,
+    /// Comparison and version, e.g. `>=` and `[20]`.
+    pub version: Option<(String, Vec<u64>)>,
+}
+
+impl Requirement {
+    /// `tool`, or `tool <op> <version>` with `>=`, `>`, `=`, `<=` or `<`.
+    pub fn parse(spec: &str) -> anyhow::Result<Self> {
+        let spec = spec.trim();
+        let split = spec.find(['<', '>', '=']);
+        let Some(at) = split else {
+            if spec.is_empty() || spec.contains(char::is_whitespace) {
+                anyhow::bail!("Invalid tool requirement '{}' (expected e.g. \"node >= 20\")", spec);
+            }
+            return Ok(Requirement {
+                tool: spec.to_string(),
+                version: None,
+            });
+        };
+        let tool = spec[..at].trim();
+        let rest = &spec[at..];
+        let op_len = rest.find(|c: char| !matches!(c, '<' | '>' | '=')).unwrap_or(rest.len());
+        let op = match &rest[..op_len] {
+            "==" => "=",
+            op @ (">=" | ">" | "=" | "<=" | "<") => op,
+            _ => anyhow::bail!("Invalid comparison in tool requirement '{}'", spec),
+        };
+        let version = parse_version(rest[op_len..].trim());
+        match version {
+            Some(version) if !tool.is_empty() => Ok(Requirement {
+                tool: tool.to_string(),
+                version: Some((op.to_string(), version)),
+            }),
+            _ => anyhow::bail!("Invalid tool requirement '{}' (expected e.g. \"node >= 20\")", spec),
+        }
+    }
+
+    /// Whether `installed` satisfies the version constraint; missing components count as 0.
+    pub fn accepts(&self, installed: &[u64]) -> bool {
+        let Some((ref op, ref wanted)) = self.version else {
+            return true;
+        };
+        let len = installed.len().max(wanted.len());
+        let at = |v: &[u64], i: usize| v.get(i).copied().unwrap_or(0);
+        let ordering = (0..len)
+            .map(|i| at(installed, i).cmp(&at(wanted, i)))
+            .find(|o| o.is_ne())
+            .unwrap_or(Ordering::Equal);
+        match op.as_str() {
+            ">=" => ordering.is_ge(),
+            ">" => ordering.is_gt(),
+            "<=" => ordering.is_le(),
+            "<" => ordering.is_lt(),
+            _ => ordering.is_eq(),
+        }
+    }
+}
+
+/// The first dotted number in `text`, e.g. `[20, 11, 1]` from `v20.11.1`.
+pub fn parse_version(text: &str) -> Option<Vec<u64>> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let digits: String = text[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    digits
+        .split('.')
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse().ok())
+        .collect()
+}
+
+/// The version `tool` reports with `--version` (or `version`, as go does).
+fn installed_version(tool: &str) -> Option<Vec<u64>> {
+    ["--version", "version"].iter().find_map(|arg| {
+        let output = Command::new(tool)
+            .arg(arg)
+            .stdin(Stdio::null())
+            .output()
+            .ok()
+            .filter(|o| o.status.success())?;
+        let text = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        parse_version(&text)
+    })
+}
+
+/// Checks every requirement and prints one line per tool; returns the unmet ones with their
+/// install hint.
+pub fn check(requires: &[String], hints: &BTreeMap<String, String>) -> Vec<String> {
+    let mut problems = Vec::new();
+    for spec in requires {
+        let Ok(requirement) = Requirement::parse(spec) else {
+            continue;
+        };
+        let hint = hints
+            .get(&requirement.tool)
+            .map(String::as_str)
+            .or_else(|| HINTS.iter().find(|(t, _)| *t == requirement.tool).map(|(_, h)| *h));
+        let with_hint = |problem: String| match hint {
+            Some(hint) => format!("{} — install: {}", problem, hint),
+            None => problem,
+        };
+        if crate::external::on_path(&requirement.tool).is_none() {
+            problems.push(with_hint(format!("{} is not installed", requirement.tool)));
+            continue;
+        }
+        let Some((ref op, ref wanted)) = requirement.version else {
+            println!("TOOL: {} found", requirement.tool);
+            continue;
+        };
+        let wanted = wanted.iter().map(u64::to_string).collect::<Vec<_>>().join(".");
+        match installed_version(&requirement.tool) {
+            Some(version) if requirement.accepts(&version) => {
+                let version = version.iter().map(u64::to_string).collect::<Vec<_>>().join(".");
+                println!("TOOL: {} {} ({} {})", requirement.tool, version, op, wanted);
+            }
+            Some(version) => {
+                let version = version.iter().map(u64::to_string).collect::<Vec<_>>().join(".");
+                problems.push(with_hint(format!(
+                    "{} {} is installed, {} {} is needed",
+                    requirement.tool, version, op, wanted
+                )));
+            }
+            None => println!(
+                "WARN: could not determine the version of {} (needs {} {})",
+                requirement.tool, op, wanted
+            ),
+        }
+    }
+    problems
+}
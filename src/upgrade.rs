@@ -0,0 +1,246 @@
+//! `liscaf upgrade-template`: moves a project's template pin (`template_commit` in
+//! `.scaffold.json`) to a newer revision after showing what changed in between — the template's
+//! commit log and the new changelog entries. With `--update` the project is updated to the new
+//! revision right away, as `liscaf update --commit <sha>` would.
+//!
+//! The same history tells `liscaf update` which files the template renamed since the recorded
+//! commit, so they are moved in the project instead of showing up next to their old copies.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use inquire::Confirm;
+
+use crate::i18n::tr;
+use crate::prompt::Ask;
+
+const CHANGELOG_FILES: &[&str] = &["CHANGELOG.md", "CHANGELOG", "CHANGES.md", "HISTORY.md"];
+
+pub struct UpgradeRequest<'a> {
+    pub project_dir: &'a Path,
+    /// Revision to move to instead of the latest commit of the default branch.
+    pub to: Option<&'a str>,
+    pub update: bool,
+    pub dry_run: bool,
+    pub assume_yes: bool,
+    pub tmpdir: Option<&'a Path>,
+}
+
+pub fn run_upgrade(req: &UpgradeRequest) -> anyhow::Result<()> {
+    let metadata_path = req.project_dir.join(".scaffold.json");
+    let content = fs::read_to_string(&metadata_path).map_err(|e| {
+        anyhow::anyhow!(
+            "{} was not scaffolded by liscaf ({}: {})",
+            req.project_dir.display(),
+            metadata_path.display(),
+            e
+        )
+    })?;
+    let metadata: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", metadata_path.display(), e))?;
+    let field = |key: &str| metadata.get(key).and_then(|v| v.as_str());
+    let Some(repo_url) = field("template_repo_url") else {
+        anyhow::bail!("{} has no template URL", metadata_path.display());
+    };
+    let Some(pinned) = field("template_commit") else {
+        anyhow::bail!(
+            "{} records no template commit; run `liscaf update` once to record one",
+            metadata_path.display()
+        );
+    };
+
+    let workdir = crate::make_tempdir("liscaf-upgrade-", req.tmpdir)?;
+    let history = clone_history(repo_url, &workdir.path().join("history"))?;
+    let target = req.to.unwrap_or("HEAD");
+    let latest = git_output(&history, &["rev-parse", "--verify", &format!("{}^{{commit}}", target)])
+        .ok_or_else(|| anyhow::anyhow!("The template has no revision '{}'", target))?;
+
+    println!("Template: {}", repo_url);
+    println!("Pinned:   {}", describe(&history, pinned));
+    println!("Target:   {}", describe(&history, &latest));
+    if latest == pinned {
+        println!("The project is already on this template revision");
+        return Ok(());
+    }
+
+    match git_output(&history, &["log", "--format=  %h %s", &format!("{}..{}", pinned, latest)]) {
+        Some(log) if !log.is_empty() => println!("\nTemplate commits since the pin:\n{}", log),
+        Some(_) => println!("\nWarning: the target is not newer than the pinned commit"),
+        None => println!(
+            "\nWarning: the pinned commit is not in the template's history (rewritten?); no log to show"
+        ),
+    }
+    let changelog = changelog_additions(&history, pinned, &latest);
+    if !changelog.is_empty() {
+        println!("\nChangelog:\n{}", changelog);
+    }
+    println!();
+
+    if req.dry_run {
+        println!("Dry run: the pin stays at {}", short(pinned));
+        return Ok(());
+    }
+    if !req.assume_yes
+        && !Confirm::new(&tr!("Upgrade the template pin to {} ?", short(&latest)))
+            .with_default(true)
+            .ask()?
+    {
+        println!("{}", tr!("Aborted by user."));
+        return Ok(());
+    }
+
+    if req.update {
+        // The update records the new commit in `.scaffold.json` itself
+        return crate::run_update_command(crate::UpdateArgs {
+            path: Some(req.project_dir.to_path_buf()),
+            commit: Some(latest),
+            vars: Vec::new(),
+            dry_run: false,
+            yes: req.assume_yes,
+            tmpdir: req.tmpdir.map(Path::to_path_buf),
+        });
+    }
+    let old = format!("\"template_commit\": \"{}\"", pinned);
+    if !content.contains(&old) {
+        anyhow::bail!("Could not find the template commit in {}", metadata_path.display());
+    }
+    fs::write(
+        &metadata_path,
+        content.replacen(&old, &format!("\"template_commit\": \"{}\"", latest), 1),
+    )?;
+    println!("Pinned the template to {}", short(&latest));
+    println!("Apply it with `liscaf update --commit {}`", latest);
+    Ok(())
+}
+
+/// Files the template renamed between the `recorded` commit and `target` (default the latest),
+/// as template paths; empty, with a warning, when the history cannot be fetched.
+pub fn renames_since(
+    repo_url: &str,
+    recorded: &str,
+    target: Option<&str>,
+    tmpdir: Option<&Path>,
+) -> Vec<(String, String)> {
+    let found = || -> anyhow::Result<Vec<(String, String)>> {
+        let workdir = crate::make_tempdir("liscaf-history-", tmpdir)?;
+        let history = clone_history(repo_url, &workdir.path().join("history"))?;
+        let range = [recorded, target.unwrap_or("HEAD")];
+        let output = Command::new("git")
+            .args(["diff", "--name-status", "-z", "-M", "--diff-filter=R"])
+            .args(range)
+            .current_dir(&history)
+            .output()?;
+        if !output.status.success() {
+            anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        // `R<score>\0<old>\0<new>\0` per rename
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let fields: Vec<&str> = stdout.split('\0').collect();
+        Ok(fields
+            .chunks(3)
+            .filter(|c| c.len() == 3 && c[0].starts_with('R'))
+            .map(|c| (c[1].to_string(), c[2].to_string()))
+            .collect())
+    };
+    match found() {
+        Ok(renames) => renames,
+        Err(e) => {
+            println!("Warning: could not detect renamed template files ({}); renamed files show up as new ones", e);
+            Vec::new()
+        }
+    }
+}
+
+/// A blobless clone of the template with its full history (file contents are fetched on demand).
+pub fn clone_history(repo_url: &str, dest: &Path) -> anyhow::Result<PathBuf> {
+    let url = crate::protocol::rewrite(repo_url);
+    println!("Fetching template history from {}", url);
+    crate::net::with_retries(&format!("Fetching {}", url), || {
+        let _ = fs::remove_dir_all(dest);
+        let output = crate::net::git()
+            .args(["clone", "--bare", "--quiet", "--filter=blob:none", &url])
+            .arg(dest)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .map_err(|e| crate::net::Failure::Permanent(anyhow::anyhow!("Failed to run git: {}", e)))?;
+        if output.status.success() {
+            return Ok(());
+        }
+        Err(crate::git_failure("git clone", &output))
+    })?;
+    Ok(dest.to_path_buf())
+}
+
+pub fn git_output(repo: &Path, args: &[&str]) -> Option<String> {
+    let out = Command::new("git")
+        .args(args)
+        .current_dir(repo)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    out.status
+        .success()
+        .then(|| String::from_utf8_lossy(&out.stdout).trim_end().to_string())
+}
+
+/// `abc12345 2024-05-01 Subject`, or just the short SHA if the commit is unknown.
+fn describe(repo: &Path, commit: &str) -> String {
+    git_output(repo, &["log", "-1", "--format=%h %cs %s", commit]).unwrap_or_else(|| short(commit).to_string())
+}
+
+fn short(commit: &str) -> &str {
+    &commit[..commit.len().min(8)]
+}
+
+/// Lines added to the template's changelog between the two revisions.
+fn changelog_additions(repo: &Path, from: &str, to: &str) -> String {
+    let mut args = vec!["diff", "--unified=0", "--no-color", from, to, "--"];
+    args.extend(CHANGELOG_FILES);
+    let Some(diff) = git_output(repo, &args) else {
+        return String::new();
+    };
+    diff.lines()
+        .filter(|l| l.starts_with('+') && !l.starts_with("+++"))
+        .map(|l| format!("  {}", &l[1..]).trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(["-c", "user.name=liscaf", "-c", "user.email=liscaf@localhost"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?}", args);
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[test]
+    fn renames_are_found_in_the_template_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("acme-app");
+        fs::create_dir_all(repo.join("src")).unwrap();
+        run_git(&repo, &["init", "--quiet"]);
+        fs::write(repo.join("src/acme_app.rs"), "pub fn run() {\n    println!(\"acme-app\");\n}\n").unwrap();
+        fs::write(repo.join("README.md"), "# acme-app\n").unwrap();
+        run_git(&repo, &["add", "."]);
+        run_git(&repo, &["commit", "--quiet", "-m", "initial"]);
+        let recorded = run_git(&repo, &["rev-parse", "HEAD"]);
+        fs::create_dir_all(repo.join("crates/core")).unwrap();
+        run_git(&repo, &["mv", "src/acme_app.rs", "crates/core/lib.rs"]);
+        fs::write(repo.join("README.md"), "# acme-app\n\nNow a workspace.\n").unwrap();
+        run_git(&repo, &["commit", "--quiet", "-am", "workspace"]);
+
+        let url = format!("file://{}", repo.display());
+        let renames = renames_since(&url, &recorded, None, Some(dir.path()));
+        assert_eq!(renames, [("src/acme_app.rs".to_string(), "crates/core/lib.rs".to_string())]);
+        assert!(renames_since(&url, &recorded, Some(&recorded), Some(dir.path())).is_empty());
+    }
+}
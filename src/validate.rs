@@ -0,0 +1,81 @@
+//! `liscaf validate [dir]`: checks a template before it is published — the manifest parses,
+//! plugins and fragments point at files that exist, and the template name occurs somewhere.
+use std::fs;
+use std::path::Path;
+
+use walkdir::WalkDir;
+
+use crate::manifest::{self, MANIFEST_FILE};
+use crate::tokenizer::{generate_variant_mappings, split_name_to_tokens, TokenizerRules};
+
+pub fn run_validate(dir: &Path, template_base: &str) -> anyhow::Result<()> {
+    if !dir.is_dir() {
+        anyhow::bail!("Template directory does not exist: {}", dir.display());
+    }
+    println!("Validating template {}", dir.display());
+    let manifest = manifest::load(dir)?;
+
+    let mut problems: Vec<String> = Vec::new();
+    if let Err(e) = crate::tokenizer::validate_variant_names(&manifest.mappings.skip_variants) {
+        problems.push(format!("{}: {}", MANIFEST_FILE, e));
+    }
+    for plugin in &manifest.plugins {
+        if !dir.join(&plugin.path).is_file() {
+            problems.push(format!("plugin module not found: {}", plugin.path));
+        }
+    }
+    for (name, spec) in &manifest.fragments {
+        let source = spec
+            .source
+            .clone()
+            .unwrap_or_else(|| format!(".liscaf/fragments/{}", name));
+        if !dir.join(&source).is_dir() {
+            problems.push(format!("fragment '{}': source directory not found: {}", name, source));
+        }
+    }
+    for path in &manifest.template_only {
+        if !dir.join(path).exists() {
+            println!("WARN: template_only entry matches nothing: {}", path);
+        }
+    }
+
+    let rules = TokenizerRules::default().with_acronyms(&manifest.mappings.acronyms);
+    let tokens = split_name_to_tokens(template_base, &rules);
+    let variants: Vec<String> = generate_variant_mappings(&tokens, &tokens, &[], &rules)
+        .into_iter()
+        .map(|(variant, _)| variant)
+        .collect();
+    if !mentions_any(dir, &variants) {
+        problems.push(format!(
+            "template name '{}' occurs in no file name or file content (pass --base if the template uses another name)",
+            template_base
+        ));
+    }
+
+    if problems.is_empty() {
+        println!("Template is valid");
+        return Ok(());
+    }
+    for problem in &problems {
+        println!("ERROR: {}", problem);
+    }
+    anyhow::bail!("{} problem(s) in template {}", problems.len(), dir.display())
+}
+
+/// Whether a path or text file below `dir` (outside `.git`) contains one of `needles`.
+fn mentions_any(dir: &Path, needles: &[String]) -> bool {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+        .filter_map(|e| e.ok())
+        .filter(|e| e.depth() > 0)
+        .any(|e| {
+            let name = e.file_name().to_string_lossy();
+            if needles.iter().any(|n| name.contains(n.as_str())) {
+                return true;
+            }
+            e.file_type().is_file()
+                && fs::read_to_string(e.path())
+                    .is_ok_and(|content| needles.iter().any(|n| content.contains(n.as_str())))
+        })
+}
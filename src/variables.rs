@@ -0,0 +1,354 @@
+//! Resolution of template variables from `--var` flags, manifest defaults and prompts.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use inquire::validator::Validation;
+use inquire::Text;
+use walkdir::WalkDir;
+
+use crate::condition;
+use crate::i18n::tr;
+use crate::license;
+use crate::manifest::{Manifest, VariableSpec};
+use crate::placeholders::{self, RenderContext};
+use crate::prompt::Ask;
+use crate::tokenizer::TokenizerRules;
+
+pub fn parse_key_value(raw: &str) -> Result<(String, String), String> {
+    let (key, value) = raw
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", raw))?;
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(format!("missing key in '{}'", raw));
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// Reads a value from the user's git configuration, e.g. `user.name`.
+pub fn git_config_value(key: &str) -> Option<String> {
+    let output = Command::new("git")
+        .arg("config")
+        .arg("--get")
+        .arg(key)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Returns the full variable set: built-ins, the declared variables and any extra `--var`
+/// values, each with its case variants.
+///
+/// Besides the name variables, `year` and `author` (from `git config user.name`) are built in.
+/// `fixed_time` is set for `--reproducible` runs: `year` and defaults are computed from it, and
+/// `author` is only taken from `--var`. Case variants are built with `rules`.
+pub fn resolve_variables(
+    new_name: &str,
+    specs: &BTreeMap<String, VariableSpec>,
+    cli_vars: &[(String, String)],
+    assume_yes: bool,
+    fixed_time: Option<chrono::DateTime<chrono::Utc>>,
+    rules: &TokenizerRules,
+) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut vars = placeholders::builtin_variables(new_name, rules);
+    let cli: BTreeMap<&str, &str> = cli_vars
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+
+    let now = fixed_time.unwrap_or_else(chrono::Utc::now);
+    vars.insert("year".to_string(), now.format("%Y").to_string());
+    if let Some(author) = fixed_time.is_none().then(|| git_config_value("user.name")).flatten() {
+        vars.insert("author".to_string(), author);
+    }
+    let mut render_ctx = RenderContext::new(vars.clone()).with_tokenizer(rules);
+    if let Some(time) = fixed_time {
+        render_ctx = render_ctx.reproducible(time, "defaults");
+    }
+
+    let mut current_group = None;
+    for (key, spec) in prompt_order(specs) {
+        render_ctx.vars = vars.clone();
+        let default = spec.default.as_deref().map(|d| placeholders::render(d, &render_ctx));
+        let skipped = match spec.ask_if {
+            Some(ref condition) => !condition::evaluate(condition, &vars)?,
+            None => false,
+        };
+        let value = if let Some(value) = cli.get(key.as_str()) {
+            value.to_string()
+        } else if skipped {
+            default.unwrap_or_default()
+        } else if assume_yes {
+            default.ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Template variable '{}' has no default; pass --var {}=<value>",
+                    key,
+                    key
+                )
+            })?
+        } else {
+            if spec.group.is_some() && spec.group != current_group {
+                println!();
+                println!("-- {} --", spec.group.as_deref().unwrap_or_default());
+                current_group = spec.group.clone();
+            }
+            let prompt = spec.prompt.clone().unwrap_or_else(|| format!("{}:", key));
+            let mut text = Text::new(&prompt);
+            if let Some(ref default) = default {
+                text = text.with_default(default);
+            }
+            if let Some(ref help) = spec.help {
+                text = text.with_help_message(help);
+            }
+            text.ask()?
+        };
+        placeholders::insert_with_case_variants(&mut vars, key, &value, rules);
+    }
+
+    for (key, value) in cli_vars {
+        if !specs.contains_key(key) {
+            placeholders::insert_with_case_variants(&mut vars, key, value, rules);
+        }
+    }
+
+    Ok(vars)
+}
+
+/// The answer profile to use: `--profile`, else (when prompting) the user's choice among the
+/// template's profiles, where "answer each question" means none.
+pub fn choose_profile<'m>(
+    manifest: &'m Manifest,
+    requested: Option<&str>,
+    assume_yes: bool,
+) -> anyhow::Result<Option<(&'m String, &'m BTreeMap<String, String>)>> {
+    if let Some(name) = requested {
+        return manifest
+            .profiles
+            .get_key_value(name)
+            .map(Some)
+            .ok_or_else(|| {
+                let available: Vec<&str> = manifest.profiles.keys().map(String::as_str).collect();
+                anyhow::anyhow!(
+                    "Template has no profile '{}' (available: {})",
+                    name,
+                    if available.is_empty() { "none".to_string() } else { available.join(", ") }
+                )
+            });
+    }
+    if assume_yes || manifest.profiles.is_empty() {
+        return Ok(None);
+    }
+    let custom = tr!("Answer each question").to_string();
+    let mut options: Vec<String> = manifest.profiles.keys().cloned().collect();
+    options.push(custom.clone());
+    let choice = inquire::Select::new("Answer profile:", options).ask()?;
+    Ok(manifest.profiles.get_key_value(&choice))
+}
+
+/// Variables in prompt order: by `order` (unset last, then by name), with the members of a
+/// group moved up to the group's first variable so each section is asked in one go.
+pub fn prompt_order(specs: &BTreeMap<String, VariableSpec>) -> Vec<(&String, &VariableSpec)> {
+    let mut sorted: Vec<(&String, &VariableSpec)> = specs.iter().collect();
+    sorted.sort_by_key(|(_, spec)| spec.order.unwrap_or(i32::MAX));
+    let mut ordered: Vec<(&String, &VariableSpec)> = Vec::with_capacity(sorted.len());
+    for (i, (key, spec)) in sorted.iter().enumerate() {
+        if ordered.iter().any(|(k, _)| k == key) {
+            continue;
+        }
+        ordered.push((key, spec));
+        if spec.group.is_some() {
+            ordered.extend(sorted[i + 1..].iter().filter(|(_, s)| s.group == spec.group));
+        }
+    }
+    ordered
+}
+
+/// Built-in `license` variable: chosen from a list of SPDX identifiers unless the template
+/// declares its own `license` variable.
+pub fn resolve_license(
+    vars: &mut BTreeMap<String, String>,
+    manifest: &Manifest,
+    cli_vars: &[(String, String)],
+    assume_yes: bool,
+) -> anyhow::Result<()> {
+    if !manifest.variables.contains_key("license") {
+        let cli_license = cli_vars.iter().rev().find(|(k, _)| k == "license").map(|(_, v)| v);
+        match cli_license {
+            Some(id) if !license::is_supported(id) => anyhow::bail!(
+                "Unsupported license '{}'; choose one of: {}",
+                id,
+                license::SPDX_CHOICES.join(", ")
+            ),
+            Some(_) => {}
+            None if assume_yes => {}
+            None => {
+                if let Some(id) = license::prompt_license()? {
+                    vars.insert("license".to_string(), id);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Placeholder contact details commonly found in templates, replaced by `author_email` and
+/// `repo_homepage` unless the manifest names others under `[mappings]`.
+pub const PLACEHOLDER_EMAIL: &str = "dev@acme.example";
+pub const PLACEHOLDER_HOMEPAGE: &str = "https://acme.example";
+
+pub fn is_email(value: &str) -> bool {
+    let Some((local, domain)) = value.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.contains('@')
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !value.chars().any(char::is_whitespace)
+}
+
+pub fn is_url(value: &str) -> bool {
+    let Some(rest) = value
+        .strip_prefix("https://")
+        .or_else(|| value.strip_prefix("http://"))
+    else {
+        return false;
+    };
+    let host = rest.split('/').next().unwrap_or_default();
+    !host.is_empty() && !value.chars().any(char::is_whitespace)
+}
+
+/// Built-in `author_email` (default: `git config user.email`) and `repo_homepage` (default:
+/// `https://github.com/<github.user>/<name>`). Each is only asked for when the template refers
+/// to it or to its placeholder, and is not a declared template variable. `reproducible` runs
+/// have no defaults from git config.
+pub fn resolve_contact(
+    vars: &mut BTreeMap<String, String>,
+    manifest: &Manifest,
+    root: &Path,
+    cli_vars: &[(String, String)],
+    assume_yes: bool,
+    reproducible: bool,
+) -> anyhow::Result<()> {
+    let cli = |key: &str| cli_vars.iter().rev().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+    let email_placeholder = manifest.mappings.email.as_deref().unwrap_or(PLACEHOLDER_EMAIL);
+    let homepage_placeholder = manifest
+        .mappings
+        .homepage
+        .as_deref()
+        .unwrap_or(PLACEHOLDER_HOMEPAGE);
+    let git_config = |key: &str| (!reproducible).then(|| git_config_value(key)).flatten();
+    let homepage_default = git_config("github.user").map(|user| {
+        let name = vars.get("name_kebab").cloned().unwrap_or_default();
+        format!("https://github.com/{}/{}", user, name)
+    });
+    let contacts = [
+        (
+            "author_email",
+            "Author email:",
+            email_placeholder,
+            git_config("user.email"),
+            is_email as fn(&str) -> bool,
+            "an email address like jane@example.com",
+        ),
+        (
+            "repo_homepage",
+            "Project homepage:",
+            homepage_placeholder,
+            homepage_default,
+            is_url as fn(&str) -> bool,
+            "an http(s):// URL",
+        ),
+    ];
+    let needles: Vec<&str> = contacts
+        .iter()
+        .flat_map(|(key, _, placeholder, ..)| [*key, *placeholder])
+        .collect();
+    let mentioned = template_mentions(root, &needles);
+    for (key, prompt, placeholder, default, valid, shape) in contacts {
+        if manifest.variables.contains_key(key) {
+            continue;
+        }
+        let value = match cli(key) {
+            Some(value) if !valid(&value) => {
+                anyhow::bail!("--var {}={} is not {}", key, value, shape)
+            }
+            Some(value) => Some(value),
+            None if !mentioned.iter().any(|m| m == key || m == placeholder) => None,
+            None if assume_yes => default,
+            None => {
+                let help = format!("replaces {} in the template", placeholder);
+                let mut text = Text::new(prompt)
+                    .with_help_message(&help)
+                    .with_validator(move |input: &str| {
+                        Ok(if input.is_empty() || valid(input) {
+                            Validation::Valid
+                        } else {
+                            Validation::Invalid(format!("Enter {}", shape).into())
+                        })
+                    });
+                if let Some(ref default) = default {
+                    text = text.with_default(default);
+                }
+                Some(text.ask()?).filter(|v| !v.is_empty())
+            }
+        };
+        if let Some(value) = value {
+            vars.insert(key.to_string(), value);
+        }
+    }
+    Ok(())
+}
+
+/// Mappings from the template's placeholder email and homepage to the resolved values.
+pub fn contact_mappings(vars: &BTreeMap<String, String>, manifest: &Manifest) -> Vec<(String, String)> {
+    let pairs = [
+        (
+            manifest.mappings.email.as_deref().unwrap_or(PLACEHOLDER_EMAIL),
+            vars.get("author_email"),
+        ),
+        (
+            manifest.mappings.homepage.as_deref().unwrap_or(PLACEHOLDER_HOMEPAGE),
+            vars.get("repo_homepage"),
+        ),
+    ];
+    pairs
+        .into_iter()
+        .filter_map(|(placeholder, value)| Some((placeholder.to_string(), value?.clone())))
+        .collect()
+}
+
+/// Which of `needles` occur in the template's text files.
+fn template_mentions(root: &Path, needles: &[&str]) -> Vec<String> {
+    let mut found: Vec<String> = Vec::new();
+    for entry in WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        if entry.metadata().map(|m| m.len()).unwrap_or(0) > crate::filter::DEFAULT_MAX_FILE_SIZE {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        for needle in needles {
+            if !found.iter().any(|f| f == needle) && content.contains(needle) {
+                found.push(needle.to_string());
+            }
+        }
+        if found.len() == needles.len() {
+            break;
+        }
+    }
+    found
+}
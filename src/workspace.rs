@@ -0,0 +1,320 @@
+//! Workspace-aware `--into`: a single-package template merged into a Cargo, pnpm or npm
+//! workspace root is placed in the workspace's package directory and registered as a member.
+use std::fs;
+use std::path::Path;
+
+use crate::color::status;
+use crate::postprocess::json_edit;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkspaceKind {
+    Cargo,
+    Pnpm,
+    Npm,
+}
+
+impl std::fmt::Display for WorkspaceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            WorkspaceKind::Cargo => "Cargo",
+            WorkspaceKind::Pnpm => "pnpm",
+            WorkspaceKind::Npm => "npm",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkspacePlacement {
+    pub kind: WorkspaceKind,
+    /// Member path relative to the workspace root, with `/` separators.
+    pub member: String,
+    /// Whether an existing member pattern (e.g. `crates/*`) already includes the new path.
+    pub covered: bool,
+}
+
+pub fn detect(
+    template_root: &Path,
+    dest: &Path,
+    new_name: &str,
+) -> anyhow::Result<Option<WorkspacePlacement>> {
+    let dir_name = crate::split_name_to_tokens(new_name, &Default::default()).join("-");
+
+    if is_cargo_package(template_root)? {
+        if let Some(patterns) = cargo_members(dest)? {
+            return Ok(Some(placement(WorkspaceKind::Cargo, &patterns, "crates", &dir_name)));
+        }
+    }
+    if template_root.join("package.json").is_file() {
+        if let Some(patterns) = pnpm_packages(dest)? {
+            return Ok(Some(placement(WorkspaceKind::Pnpm, &patterns, "packages", &dir_name)));
+        }
+        if let Some(patterns) = npm_workspaces(dest)? {
+            return Ok(Some(placement(WorkspaceKind::Npm, &patterns, "packages", &dir_name)));
+        }
+    }
+    Ok(None)
+}
+
+/// Uses the directory of the first member pattern that names one (`crates/*` or `crates/foo` ->
+/// `crates`; not `**/*`).
+fn placement(kind: WorkspaceKind, patterns: &[String], fallback: &str, dir_name: &str) -> WorkspacePlacement {
+    let parent = patterns
+        .iter()
+        .map(|p| p.trim_start_matches("./").trim_end_matches('/'))
+        .find_map(|p| {
+            p.rsplit_once('/')
+                .map(|(parent, _)| parent.to_string())
+                .filter(|parent| !parent.contains('*'))
+        })
+        .unwrap_or_else(|| fallback.to_string());
+    let member = format!("{}/{}", parent, dir_name);
+    let covered = patterns.iter().any(|p| pattern_matches(p, &member));
+    WorkspacePlacement {
+        kind,
+        member,
+        covered,
+    }
+}
+
+/// Matches `/`-separated paths where `*` is one segment and `**` any number of segments.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    fn matches(pattern: &[&str], path: &[&str]) -> bool {
+        match (pattern.first(), path.first()) {
+            (None, None) => true,
+            (Some(&"**"), _) => {
+                matches(&pattern[1..], path) || (!path.is_empty() && matches(pattern, &path[1..]))
+            }
+            (Some(p), Some(s)) if *p == "*" || p == s => matches(&pattern[1..], &path[1..]),
+            _ => false,
+        }
+    }
+    let pattern: Vec<&str> = pattern.trim_start_matches("./").split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    matches(&pattern, &path)
+}
+
+fn is_cargo_package(root: &Path) -> anyhow::Result<bool> {
+    let path = root.join("Cargo.toml");
+    if !path.is_file() {
+        return Ok(false);
+    }
+    let doc: toml::Table = toml::from_str(&fs::read_to_string(&path)?)?;
+    Ok(doc.contains_key("package") && !doc.contains_key("workspace"))
+}
+
+fn cargo_members(dest: &Path) -> anyhow::Result<Option<Vec<String>>> {
+    let path = dest.join("Cargo.toml");
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let doc: toml::Table = toml::from_str(&fs::read_to_string(&path)?)?;
+    let Some(workspace) = doc.get("workspace").and_then(|w| w.as_table()) else {
+        return Ok(None);
+    };
+    let members = workspace
+        .get("members")
+        .and_then(|m| m.as_array())
+        .map(|items| items.iter().filter_map(|i| i.as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    Ok(Some(members))
+}
+
+fn pnpm_packages(dest: &Path) -> anyhow::Result<Option<Vec<String>>> {
+    let path = dest.join("pnpm-workspace.yaml");
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let value: serde_yaml::Value = serde_yaml::from_str(&fs::read_to_string(&path)?)?;
+    let packages = value
+        .get("packages")
+        .and_then(|p| p.as_sequence())
+        .map(|items| items.iter().filter_map(|i| i.as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    Ok(Some(packages))
+}
+
+fn npm_workspaces(dest: &Path) -> anyhow::Result<Option<Vec<String>>> {
+    let path = dest.join("package.json");
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let value: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+    // "workspaces": [...] or "workspaces": { "packages": [...] }
+    let list = match value.get("workspaces") {
+        Some(serde_json::Value::Array(items)) => items,
+        Some(serde_json::Value::Object(obj)) => match obj.get("packages") {
+            Some(serde_json::Value::Array(items)) => items,
+            _ => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(list.iter().filter_map(|i| i.as_str().map(String::from)).collect()))
+}
+
+/// Adds the member to the workspace manifest unless a pattern already covers it.
+pub fn register(dest: &Path, placement: &WorkspacePlacement, dry_run: bool) -> anyhow::Result<()> {
+    if placement.covered {
+        println!(
+            "{} workspace already includes {} via a member pattern",
+            placement.kind, placement.member
+        );
+        return Ok(());
+    }
+    let (path, updated) = match placement.kind {
+        WorkspaceKind::Cargo => {
+            let path = dest.join("Cargo.toml");
+            let mut doc: toml_edit::DocumentMut = fs::read_to_string(&path)?.parse()?;
+            let workspace = doc["workspace"]
+                .as_table_mut()
+                .ok_or_else(|| anyhow::anyhow!("[workspace] in {} is not a table", path.display()))?;
+            let members = workspace
+                .entry("members")
+                .or_insert_with(|| toml_edit::value(toml_edit::Array::new()))
+                .as_array_mut()
+                .ok_or_else(|| anyhow::anyhow!("workspace.members in {} is not an array", path.display()))?;
+            push_array_item(members, &placement.member);
+            (path, doc.to_string())
+        }
+        WorkspaceKind::Pnpm => {
+            let path = dest.join("pnpm-workspace.yaml");
+            let content = fs::read_to_string(&path)?;
+            (path, append_pnpm_package(&content, &placement.member))
+        }
+        WorkspaceKind::Npm => {
+            let path = dest.join("package.json");
+            let content = fs::read_to_string(&path)?;
+            let json = serde_json::Value::String(placement.member.clone()).to_string();
+            let updated = match json_edit::append(&content, &["workspaces"], &json)? {
+                Some(updated) => updated,
+                None => json_edit::append(&content, &["workspaces", "packages"], &json)?
+                    .ok_or_else(|| anyhow::anyhow!("No workspaces array in {}", path.display()))?,
+            };
+            (path, updated)
+        }
+    };
+    if dry_run {
+        status!("DRY WORKSPACE: Would add {} to {}", placement.member, path.display());
+    } else {
+        fs::write(&path, updated)?;
+        status!("WORKSPACE: Added {} to {}", placement.member, path.display());
+    }
+    Ok(())
+}
+
+/// Appends to a TOML array, following the multi-line layout of existing items if any.
+fn push_array_item(array: &mut toml_edit::Array, item: &str) {
+    let multiline = array
+        .iter()
+        .last()
+        .map(|last| last.decor().prefix().and_then(|p| p.as_str()).unwrap_or("").contains('\n'))
+        .unwrap_or(false);
+    let prefix = array
+        .iter()
+        .last()
+        .and_then(|last| last.decor().prefix().and_then(|p| p.as_str()).map(String::from));
+    array.push(item);
+    if multiline {
+        if let (Some(last), Some(prefix)) = (array.iter_mut().last(), prefix) {
+            last.decor_mut().set_prefix(prefix);
+        }
+    }
+}
+
+/// Adds `  - "member"` after the last entry of the `packages:` list.
+fn append_pnpm_package(content: &str, member: &str) -> String {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let is_packages_key = |line: &str| {
+        let code = line.split_once(" #").map_or(line, |(code, _)| code);
+        code.trim_end() == "packages:"
+    };
+    let Some(start) = lines.iter().position(|l| is_packages_key(l)) else {
+        let sep = if content.is_empty() || content.ends_with('\n') { "" } else { "\n" };
+        return format!("{}{}packages:\n  - \"{}\"\n", content, sep, member);
+    };
+    let mut last_item = start;
+    let mut indent = "  ".to_string();
+    for (i, line) in lines.iter().enumerate().skip(start + 1) {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("- ") {
+            last_item = i;
+            indent = line[..line.len() - trimmed.len()].to_string();
+        } else if !trimmed.trim().is_empty() && !trimmed.starts_with('#') {
+            break;
+        }
+    }
+    let mut out = String::with_capacity(content.len() + member.len() + 8);
+    for (i, line) in lines.iter().enumerate() {
+        out.push_str(line);
+        if i == last_item {
+            if !line.ends_with('\n') {
+                out.push('\n');
+            }
+            out.push_str(&format!("{}- \"{}\"\n", indent, member));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(patterns: &[&str]) -> Vec<String> {
+        patterns.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn double_star_matches_any_number_of_segments() {
+        assert!(pattern_matches("crates/**", "crates/zeta"));
+        assert!(pattern_matches("crates/**", "crates/core/zeta"));
+        assert!(pattern_matches("**/zeta", "zeta"));
+        assert!(pattern_matches("./packages/*", "packages/zeta"));
+        assert!(!pattern_matches("packages/*", "packages/web/zeta"));
+        assert!(!pattern_matches("crates/**", "tools/zeta"));
+    }
+
+    #[test]
+    fn members_go_next_to_the_first_named_directory() {
+        let cargo = placement(WorkspaceKind::Cargo, &patterns(&["crates/*"]), "crates", "zeta");
+        assert_eq!((cargo.member.as_str(), cargo.covered), ("crates/zeta", true));
+        let listed = placement(WorkspaceKind::Cargo, &patterns(&["libs/core", "libs/api/"]), "crates", "zeta");
+        assert_eq!((listed.member.as_str(), listed.covered), ("libs/zeta", false));
+        let wildcard = placement(WorkspaceKind::Pnpm, &patterns(&["**/*", "apps/**"]), "packages", "zeta");
+        assert_eq!((wildcard.member.as_str(), wildcard.covered), ("apps/zeta", true));
+        let empty = placement(WorkspaceKind::Npm, &[], "packages", "zeta");
+        assert_eq!((empty.member.as_str(), empty.covered), ("packages/zeta", false));
+    }
+
+    fn push_member(toml: &str) -> String {
+        let mut doc: toml_edit::DocumentMut = toml.parse().unwrap();
+        push_array_item(doc["workspace"]["members"].as_array_mut().unwrap(), "crates/zeta");
+        doc.to_string()
+    }
+
+    #[test]
+    fn toml_arrays_keep_their_layout() {
+        assert_eq!(
+            push_member("[workspace]\nmembers = [\"crates/core\"]\n"),
+            "[workspace]\nmembers = [\"crates/core\", \"crates/zeta\"]\n"
+        );
+        assert_eq!(
+            push_member("[workspace]\nmembers = [\n    \"crates/core\",\n]\n"),
+            "[workspace]\nmembers = [\n    \"crates/core\",\n    \"crates/zeta\",\n]\n"
+        );
+        assert_eq!(push_member("[workspace]\nmembers = []\n"), "[workspace]\nmembers = [\"crates/zeta\"]\n");
+    }
+
+    #[test]
+    fn pnpm_packages_are_appended_after_the_last_entry() {
+        let yaml = "packages: # workspace members\n  # apps first\n  - \"apps/*\"\n\n  # then libraries\n    - libs/core\ncatalog:\n  react: ^18\n";
+        assert_eq!(
+            append_pnpm_package(yaml, "packages/zeta"),
+            "packages: # workspace members\n  # apps first\n  - \"apps/*\"\n\n  # then libraries\n    - libs/core\n    - \"packages/zeta\"\ncatalog:\n  react: ^18\n"
+        );
+        assert_eq!(
+            append_pnpm_package("catalog: {}", "packages/zeta"),
+            "catalog: {}\npackages:\n  - \"packages/zeta\"\n"
+        );
+    }
+}
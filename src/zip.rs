@@ -0,0 +1,281 @@
+//! The zip format as far as liscaf needs it: reading an archive into its entries, and writing
+//! entries out again, for documents (`--documents`) and `--archive`.
+//!
+//! Only plain archives: no Zip64 (so at most 65535 entries and 4 GiB) and no encryption. Entries
+//! keep their central directory record, so a document that is read and written again only differs
+//! where an entry was changed.
+use std::io::{self, Read, Write};
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+const FLAG_ENCRYPTED: u16 = 1;
+/// Sizes and CRC follow the data in a descriptor; they are written in the header here.
+const FLAG_DATA_DESCRIPTOR: u16 = 1 << 3;
+/// Names are UTF-8.
+const FLAG_UTF8: u16 = 1 << 11;
+
+/// Version 2.0: deflate and directories.
+const VERSION_NEEDED: u16 = 20;
+
+pub struct Entry {
+    /// The fixed 42 bytes of the central directory record after its signature. Lengths and the
+    /// local header offset are recomputed when writing.
+    header: [u8; 42],
+    pub name: Vec<u8>,
+    extra: Vec<u8>,
+    comment: Vec<u8>,
+    local_extra: Vec<u8>,
+    /// As stored, i.e. compressed with [`Entry::method`].
+    data: Vec<u8>,
+}
+
+impl Entry {
+    /// An entry holding `contents`, deflated unless `deflate` is false or there is nothing to
+    /// compress. `made_by` and `external_attributes` carry the host and the mode bits.
+    pub fn new(
+        name: &str,
+        contents: &[u8],
+        deflate: bool,
+        (time, date): (u16, u16),
+        made_by: u16,
+        external_attributes: u32,
+    ) -> io::Result<Entry> {
+        let mut entry = Entry {
+            header: [0; 42],
+            name: name.as_bytes().to_vec(),
+            extra: Vec::new(),
+            comment: Vec::new(),
+            local_extra: Vec::new(),
+            data: Vec::new(),
+        };
+        entry.set_u16(0, made_by);
+        entry.set_u16(2, VERSION_NEEDED);
+        entry.set_u16(4, FLAG_UTF8);
+        entry.set_u16(6, if deflate && !contents.is_empty() { DEFLATED } else { STORED });
+        entry.set_u16(8, time);
+        entry.set_u16(10, date);
+        entry.set_u32(34, external_attributes);
+        entry.set_contents(contents)?;
+        Ok(entry)
+    }
+
+    pub fn flags(&self) -> u16 {
+        u16_at(&self.header, 4)
+    }
+
+    pub fn method(&self) -> u16 {
+        u16_at(&self.header, 6)
+    }
+
+    /// Uncompressed size, as recorded.
+    pub fn size(&self) -> u32 {
+        u32_at(&self.header, 20)
+    }
+
+    /// The uncompressed contents, or `None` for encrypted entries, unknown methods, and entries
+    /// larger than `max` bytes or not matching their recorded size.
+    pub fn contents(&self, max: u32) -> Option<Vec<u8>> {
+        if self.flags() & FLAG_ENCRYPTED != 0 || self.size() > max {
+            return None;
+        }
+        let contents = match self.method() {
+            STORED => self.data.clone(),
+            DEFLATED => {
+                // The recorded size bounds the output, whatever the compressed data expands to
+                let mut out = Vec::with_capacity(self.size() as usize);
+                DeflateDecoder::new(self.data.as_slice())
+                    .take(u64::from(self.size()) + 1)
+                    .read_to_end(&mut out)
+                    .ok()?;
+                out
+            }
+            _ => return None,
+        };
+        (contents.len() == self.size() as usize).then_some(contents)
+    }
+
+    /// Replaces the contents, compressed with the entry's method.
+    pub fn set_contents(&mut self, contents: &[u8]) -> io::Result<()> {
+        self.data = match self.method() {
+            STORED => contents.to_vec(),
+            DEFLATED => {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(contents)?;
+                encoder.finish()?
+            }
+            method => return Err(io::Error::other(format!("unsupported compression method {}", method))),
+        };
+        self.set_u32(12, crc32fast::hash(contents));
+        self.set_u32(16, u32::try_from(self.data.len()).map_err(|_| too_large())?);
+        self.set_u32(20, u32::try_from(contents.len()).map_err(|_| too_large())?);
+        Ok(())
+    }
+
+    fn set_u16(&mut self, at: usize, value: u16) {
+        self.header[at..at + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn set_u32(&mut self, at: usize, value: u32) {
+        self.header[at..at + 4].copy_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+/// Entries (from the central directory) and the archive comment, or `None` if `bytes` is not a
+/// plain zip archive.
+pub fn read(bytes: &[u8]) -> Option<(Vec<Entry>, Vec<u8>)> {
+    // The end record is 22 bytes plus a comment of at most 64 KiB
+    let search_from = bytes.len().saturating_sub(22 + 0xffff);
+    let end = (search_from..=bytes.len().checked_sub(22)?)
+        .rev()
+        .find(|&at| u32_at(bytes, at) == END_OF_CENTRAL_DIRECTORY)?;
+    let count = u16_at(bytes, end + 10) as usize;
+    let mut at = u32_at(bytes, end + 16) as usize;
+    let comment_len = u16_at(bytes, end + 20) as usize;
+    let archive_comment = bytes.get(end + 22..end + 22 + comment_len)?.to_vec();
+    if count == 0xffff || at == 0xffff_ffff {
+        return None;
+    }
+
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        if u32_at(bytes.get(at..at + 46)?, 0) != CENTRAL_HEADER {
+            return None;
+        }
+        let header: [u8; 42] = bytes[at + 4..at + 46].try_into().ok()?;
+        let name_len = u16_at(&header, 24) as usize;
+        let extra_len = u16_at(&header, 26) as usize;
+        let comment_len = u16_at(&header, 28) as usize;
+        let offset = u32_at(bytes, at + 42) as usize;
+        let compressed = u32_at(&header, 16) as usize;
+        if compressed == 0xffff_ffff || offset == 0xffff_ffff {
+            return None;
+        }
+        let name = bytes.get(at + 46..at + 46 + name_len)?.to_vec();
+        let extra_end = at + 46 + name_len + extra_len;
+        let extra = bytes.get(at + 46 + name_len..extra_end)?.to_vec();
+        let comment = bytes.get(extra_end..extra_end + comment_len)?.to_vec();
+        at = extra_end + comment_len;
+
+        let local = bytes.get(offset..offset + 30)?;
+        if u32_at(local, 0) != LOCAL_HEADER {
+            return None;
+        }
+        let local_name_len = u16_at(local, 26) as usize;
+        let local_extra_len = u16_at(local, 28) as usize;
+        let data_start = offset + 30 + local_name_len + local_extra_len;
+        let local_extra = bytes.get(offset + 30 + local_name_len..data_start)?.to_vec();
+        let data = bytes.get(data_start..data_start + compressed)?.to_vec();
+        entries.push(Entry {
+            header,
+            name,
+            extra,
+            comment,
+            local_extra,
+            data,
+        });
+    }
+    Some((entries, archive_comment))
+}
+
+/// Writes entries as they come and the central directory at the end.
+pub struct Writer<W: Write> {
+    out: W,
+    offset: u64,
+    central: Vec<u8>,
+    count: usize,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(out: W) -> Self {
+        Writer {
+            out,
+            offset: 0,
+            central: Vec::new(),
+            count: 0,
+        }
+    }
+
+    pub fn add(&mut self, entry: &Entry) -> io::Result<()> {
+        let offset = u32::try_from(self.offset).map_err(|_| too_large())?;
+        let flags = entry.flags() & !FLAG_DATA_DESCRIPTOR;
+        let lengths = |extra: &[u8]| -> io::Result<[u8; 4]> {
+            let name = u16::try_from(entry.name.len()).map_err(|_| too_large())?;
+            let extra = u16::try_from(extra.len()).map_err(|_| too_large())?;
+            let mut lengths = [0; 4];
+            lengths[..2].copy_from_slice(&name.to_le_bytes());
+            lengths[2..].copy_from_slice(&extra.to_le_bytes());
+            Ok(lengths)
+        };
+
+        let mut local = Vec::with_capacity(30 + entry.name.len() + entry.local_extra.len());
+        local.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+        // Version needed, then flags; method, time, date, CRC and sizes as in the central record
+        local.extend_from_slice(&entry.header[2..4]);
+        local.extend_from_slice(&flags.to_le_bytes());
+        local.extend_from_slice(&entry.header[6..24]);
+        local.extend_from_slice(&lengths(&entry.local_extra)?);
+        local.extend_from_slice(&entry.name);
+        local.extend_from_slice(&entry.local_extra);
+        self.out.write_all(&local)?;
+        self.out.write_all(&entry.data)?;
+        self.offset += (local.len() + entry.data.len()) as u64;
+
+        let comment_len = u16::try_from(entry.comment.len()).map_err(|_| too_large())?;
+        self.central.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+        self.central.extend_from_slice(&entry.header[..4]);
+        self.central.extend_from_slice(&flags.to_le_bytes());
+        self.central.extend_from_slice(&entry.header[6..24]);
+        self.central.extend_from_slice(&lengths(&entry.extra)?);
+        self.central.extend_from_slice(&comment_len.to_le_bytes());
+        // Disk number, internal and external attributes
+        self.central.extend_from_slice(&entry.header[30..38]);
+        self.central.extend_from_slice(&offset.to_le_bytes());
+        self.central.extend_from_slice(&entry.name);
+        self.central.extend_from_slice(&entry.extra);
+        self.central.extend_from_slice(&entry.comment);
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Writes the central directory and the end record, and returns the output.
+    pub fn finish(mut self, archive_comment: &[u8]) -> io::Result<W> {
+        let count = u16::try_from(self.count).map_err(|_| too_large())?;
+        let directory_start = u32::try_from(self.offset).map_err(|_| too_large())?;
+        let directory_len = u32::try_from(self.central.len()).map_err(|_| too_large())?;
+        let comment_len = u16::try_from(archive_comment.len()).map_err(|_| too_large())?;
+        self.out.write_all(&self.central)?;
+        let mut end = Vec::with_capacity(22 + archive_comment.len());
+        end.extend_from_slice(&END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        end.extend_from_slice(&[0; 4]);
+        end.extend_from_slice(&count.to_le_bytes());
+        end.extend_from_slice(&count.to_le_bytes());
+        end.extend_from_slice(&directory_len.to_le_bytes());
+        end.extend_from_slice(&directory_start.to_le_bytes());
+        end.extend_from_slice(&comment_len.to_le_bytes());
+        end.extend_from_slice(archive_comment);
+        self.out.write_all(&end)?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+fn too_large() -> io::Error {
+    io::Error::other("too large for a zip archive without Zip64")
+}